@@ -0,0 +1,194 @@
+//! Common interface of the priority queues whose elements can be addressed after insertion.
+//!
+//! Pushing an element returns a [`Handle`] which can later be used to look at the
+//! element, to lower its key (`decrease_key`) or to remove it from the heap.
+//! This is what algorithms such as Dijkstra's or Prim's need to update the tentative
+//! distance of a vertex in place, instead of pushing duplicates.
+//!
+//! Every heap of this family is a min-heap: the element with the smallest key is on
+//! top. Wrap keys in [`std::cmp::Reverse`] to get a max-heap.
+
+use std::ops::{Index, IndexMut};
+
+/// An opaque reference to an element pushed into an [`AddressableHeap`].
+///
+/// A handle stays valid until its element is popped or removed. Using it afterwards
+/// is detected, even if the heap reused the underlying storage for another element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: usize,
+    generation: u32,
+}
+
+/// Errors that can occur when addressing an element of a heap.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeapError {
+    /// The handle doesn't refer to an element currently in the heap.
+    InvalidHandle,
+    /// `decrease_key` was given a key greater than the current one.
+    KeyIncreased,
+}
+
+/// A min-priority queue whose elements are addressable through handles.
+pub trait AddressableHeap<K: Ord, V> {
+    /// Inserts `value` with priority `key` and returns a handle to it.
+    fn push(&mut self, key: K, value: V) -> Handle;
+    /// Returns the element with the smallest key.
+    fn peek(&self) -> Option<(&K, &V)>;
+    /// Removes and returns the element with the smallest key.
+    fn pop(&mut self) -> Option<(K, V)>;
+    /// Returns the element referred to by `handle`.
+    fn get(&self, handle: Handle) -> Option<(&K, &V)>;
+    /// Lowers the key of the element referred to by `handle` to `key`.
+    fn decrease_key(&mut self, handle: Handle, key: K) -> Result<(), HeapError>;
+    /// Removes and returns the element referred to by `handle`.
+    fn remove(&mut self, handle: Handle) -> Option<(K, V)>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Storage shared by the addressable heaps: a vector of slots reused through a free
+/// list, whose generation counters let stale handles be detected.
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u32,
+    item: Option<T>,
+}
+
+impl<T> Arena<T> {
+    pub const fn new() -> Self {
+        Arena {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Stores `item` and returns its index.
+    pub fn insert(&mut self, item: T) -> usize {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            self.slots[index].item = Some(item);
+            index
+        } else {
+            self.slots.push(Slot {
+                generation: 0,
+                item: Some(item),
+            });
+            self.slots.len() - 1
+        }
+    }
+
+    /// Frees the slot at `index` and returns the item it held.
+    pub fn remove(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        let item = slot.item.take().expect("removing an empty slot");
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.len -= 1;
+        item
+    }
+
+    /// Returns the handle of the item stored at `index`.
+    pub fn handle(&self, index: usize) -> Handle {
+        Handle {
+            index,
+            generation: self.slots[index].generation,
+        }
+    }
+
+    /// Returns the index of the item referred to by `handle`, if it is still stored.
+    pub fn resolve(&self, handle: Handle) -> Option<usize> {
+        self.slots
+            .get(handle.index)
+            .filter(|slot| slot.generation == handle.generation && slot.item.is_some())
+            .map(|_| handle.index)
+    }
+
+    /// Moves every item of `other` into `self`.
+    ///
+    /// Returns, for each slot of `other`, the index its item was moved to. Empty slots
+    /// map to `usize::MAX`.
+    pub fn absorb(&mut self, other: Arena<T>) -> Vec<usize> {
+        other
+            .slots
+            .into_iter()
+            .map(|slot| slot.item.map_or(usize::MAX, |item| self.insert(item)))
+            .collect()
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.slots[index]
+            .item
+            .as_ref()
+            .expect("accessing an empty slot")
+    }
+}
+
+impl<T> IndexMut<usize> for Arena<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.slots[index]
+            .item
+            .as_mut()
+            .expect("accessing an empty slot")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_detects_stale_handles() {
+        let mut arena = Arena::new();
+        let first = arena.insert('a');
+        let handle = arena.handle(first);
+        assert_eq!(arena.resolve(handle), Some(first));
+        assert_eq!(arena.remove(first), 'a');
+        assert_eq!(arena.resolve(handle), None);
+
+        // The slot is reused, but the old handle must not see the new item
+        let second = arena.insert('b');
+        assert_eq!(second, first);
+        assert_eq!(arena.resolve(handle), None);
+        assert_eq!(arena.resolve(arena.handle(second)), Some(second));
+        assert_eq!(arena.len(), 1);
+    }
+
+    #[test]
+    fn test_arena_absorb() {
+        let mut left = Arena::new();
+        left.insert(1);
+        let mut right = Arena::new();
+        let removed = right.insert(2);
+        right.insert(3);
+        right.remove(removed);
+        let mapping = left.absorb(right);
+        assert_eq!(mapping.len(), 2);
+        assert_eq!(mapping[0], usize::MAX);
+        assert_eq!(left[mapping[1]], 3);
+        assert_eq!(left.len(), 2);
+    }
+}
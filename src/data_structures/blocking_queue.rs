@@ -0,0 +1,244 @@
+//! A bounded multi-producer, multi-consumer queue for passing values between threads.
+//!
+//! The queue is a bounded [`RingBuffer`] behind a [`Mutex`], with one [`Condvar`] for consumers
+//! waiting on an empty queue and one for producers waiting on a full queue. Closing the queue
+//! wakes everyone up: producers are refused from then on, and consumers drain what is left
+//! before they get `None`, which lets a pipeline stage shut down cleanly.
+
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::ring_buffer::{OverflowPolicy, RingBuffer, RingBufferError};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlockingQueueError<T> {
+    /// The queue is full; the value is handed back.
+    Full(T),
+    /// The queue is closed; the value is handed back.
+    Closed(T),
+    /// The queue stayed full until the timeout; the value is handed back.
+    Timeout(T),
+}
+
+#[derive(Debug)]
+struct State<T> {
+    buffer: RingBuffer<T>,
+    closed: bool,
+}
+
+#[derive(Debug)]
+pub struct BlockingQueue<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl<T> BlockingQueue<T> {
+    /// Creates a queue holding at most `capacity` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(State {
+                buffer: RingBuffer::bounded(capacity, OverflowPolicy::Reject),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lock().buffer.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().buffer.is_empty()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Pushes `value`, waiting while the queue is full. Fails only if the queue is closed.
+    pub fn push(&self, value: T) -> Result<(), BlockingQueueError<T>> {
+        let mut state = self.lock();
+        while state.buffer.is_full() && !state.closed {
+            state = self.not_full.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        self.push_locked(state, value)
+    }
+
+    /// Pushes `value` if there is room right away.
+    pub fn try_push(&self, value: T) -> Result<(), BlockingQueueError<T>> {
+        let state = self.lock();
+        if state.buffer.is_full() && !state.closed {
+            return Err(BlockingQueueError::Full(value));
+        }
+        self.push_locked(state, value)
+    }
+
+    /// Pushes `value`, waiting at most `timeout` for room.
+    pub fn push_timeout(&self, value: T, timeout: Duration) -> Result<(), BlockingQueueError<T>> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        while state.buffer.is_full() && !state.closed {
+            let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+                return Err(BlockingQueueError::Timeout(value));
+            };
+            state = self
+                .not_full
+                .wait_timeout(state, left)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        self.push_locked(state, value)
+    }
+
+    /// Pops the oldest value, waiting while the queue is empty.
+    /// Returns `None` once the queue is closed and drained.
+    pub fn pop(&self) -> Option<T> {
+        let mut state = self.lock();
+        while state.buffer.is_empty() && !state.closed {
+            state = self
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        self.pop_locked(state)
+    }
+
+    /// Pops the oldest value if there is one right away.
+    pub fn try_pop(&self) -> Option<T> {
+        self.pop_locked(self.lock())
+    }
+
+    /// Pops the oldest value, waiting at most `timeout` for one.
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        while state.buffer.is_empty() && !state.closed {
+            let left = deadline.checked_duration_since(Instant::now())?;
+            state = self
+                .not_empty
+                .wait_timeout(state, left)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        self.pop_locked(state)
+    }
+
+    /// Closes the queue: further pushes fail, and pops return `None` once the queue is drained.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    fn push_locked(
+        &self,
+        mut state: MutexGuard<'_, State<T>>,
+        value: T,
+    ) -> Result<(), BlockingQueueError<T>> {
+        if state.closed {
+            return Err(BlockingQueueError::Closed(value));
+        }
+        state
+            .buffer
+            .push_back(value)
+            .map_err(|RingBufferError::Full(value)| BlockingQueueError::Full(value))?;
+        drop(state);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn pop_locked(&self, mut state: MutexGuard<'_, State<T>>) -> Option<T> {
+        let value = state.buffer.pop_front()?;
+        drop(state);
+        self.not_full.notify_one();
+        Some(value)
+    }
+
+    /// Locks the state. A panic in another thread cannot leave the state inconsistent,
+    /// so a poisoned lock is recovered.
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn non_blocking_operations() {
+        let queue = BlockingQueue::new(2);
+        assert_eq!(queue.try_push(1), Ok(()));
+        assert_eq!(queue.try_push(2), Ok(()));
+        assert_eq!(queue.try_push(3), Err(BlockingQueueError::Full(3)));
+        assert_eq!(
+            queue.push_timeout(3, Duration::from_millis(10)),
+            Err(BlockingQueueError::Timeout(3))
+        );
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.capacity(), 2);
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.pop_timeout(Duration::from_millis(10)), Some(2));
+        assert_eq!(queue.pop_timeout(Duration::from_millis(10)), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn close_drains_then_ends() {
+        let queue = BlockingQueue::new(4);
+        queue.push('a').unwrap();
+        queue.close();
+        assert!(queue.is_closed());
+        assert_eq!(queue.push('b'), Err(BlockingQueueError::Closed('b')));
+        assert_eq!(queue.pop(), Some('a'));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 500;
+        let queue = BlockingQueue::new(8);
+        let mut received = thread::scope(|s| {
+            let mut consumers = Vec::new();
+            for _ in 0..3 {
+                consumers.push(s.spawn(|| {
+                    let mut received = Vec::new();
+                    while let Some(value) = queue.pop() {
+                        received.push(value);
+                    }
+                    received
+                }));
+            }
+            thread::scope(|s| {
+                for p in 0..PRODUCERS {
+                    let queue = &queue;
+                    s.spawn(move || {
+                        for i in 0..PER_PRODUCER {
+                            queue.push(p * PER_PRODUCER + i).unwrap();
+                        }
+                    });
+                }
+            });
+            queue.close();
+            consumers
+                .into_iter()
+                .flat_map(|consumer| consumer.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        received.sort_unstable();
+        assert_eq!(received, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,161 @@
+//! Range minimum queries in O(1) after O(n) preprocessing.
+//!
+//! The data is cut into blocks of 64 elements. Queries spanning several blocks combine a sparse
+//! table over the block minima, which has only `n / 64` entries and so takes O(n) space and time
+//! to build, with two queries inside blocks.
+//!
+//! Inside a block, the minimum of `[l, r]` is read from the stack of the Cartesian tree built
+//! left to right up to `r`: the stack holds the positions whose value is smaller than every value
+//! after them up to `r`, and the minimum of `[l, r]` is the first of them at or after `l`. The
+//! stack after each position is stored as a 64-bit mask, so this is a shift and a
+//! `trailing_zeros`.
+//!
+//! Ties are broken towards the leftmost minimum.
+//!
+//! References: [Wikipedia](https://en.wikipedia.org/wiki/Range_minimum_query),
+//! [Cartesian tree](https://en.wikipedia.org/wiki/Cartesian_tree)
+
+use super::range_minimum_query::RangeError;
+use super::sparse_table::check_range;
+
+const BLOCK: usize = u64::BITS as usize;
+
+pub struct CartesianTreeRmq<T: PartialOrd> {
+    data: Vec<T>,
+    /// `stacks[i]` has bit `j` set if position `j` of the block of `i` is on the Cartesian tree
+    /// stack after pushing position `i`.
+    stacks: Vec<u64>,
+    /// `block_table[k][b]` is the position of the minimum of the `2^k` blocks starting at block `b`.
+    block_table: Vec<Vec<usize>>,
+}
+
+impl<T: PartialOrd> CartesianTreeRmq<T> {
+    pub fn new(data: Vec<T>) -> Self {
+        let mut stacks = Vec::with_capacity(data.len());
+        let mut block_minima = Vec::with_capacity(data.len().div_ceil(BLOCK));
+        for (block_index, block) in data.chunks(BLOCK).enumerate() {
+            let offset = block_index * BLOCK;
+            let mut stack: u64 = 0;
+            for (idx, value) in block.iter().enumerate() {
+                // Pop the positions holding larger values, keeping equal ones for leftmost ties
+                while stack != 0 {
+                    let top = (u64::BITS - 1 - stack.leading_zeros()) as usize;
+                    if block[top] > *value {
+                        stack &= !(1 << top);
+                    } else {
+                        break;
+                    }
+                }
+                stack |= 1 << idx;
+                stacks.push(stack);
+            }
+            // The bottom of the final stack is the minimum of the block
+            block_minima.push(offset + stack.trailing_zeros() as usize);
+        }
+
+        let blocks = block_minima.len();
+        let mut block_table = vec![block_minima];
+        for log_len in 1..=blocks.checked_ilog2().unwrap_or(0) as usize {
+            let previous = &block_table[log_len - 1];
+            let half = 1 << (log_len - 1);
+            let row = (0..=blocks - (1 << log_len))
+                .map(|idx| leftmost_min(&data, previous[idx], previous[idx + half]))
+                .collect();
+            block_table.push(row);
+        }
+
+        Self {
+            data,
+            stacks,
+            block_table,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the position of the leftmost minimum in the range [start, end).
+    pub fn min_index(&self, start: usize, end: usize) -> Result<usize, RangeError> {
+        check_range(start, end, self.len())?;
+        let last = end - 1;
+        let (first_block, last_block) = (start / BLOCK, last / BLOCK);
+        if first_block == last_block {
+            return Ok(self.in_block(start, last));
+        }
+        let mut best = self.in_block(start, first_block * BLOCK + BLOCK - 1);
+        if first_block + 1 < last_block {
+            let (from, to) = (first_block + 1, last_block);
+            let log_len = (to - from).ilog2() as usize;
+            let row = &self.block_table[log_len];
+            let middle = leftmost_min(&self.data, row[from], row[to - (1 << log_len)]);
+            best = leftmost_min(&self.data, best, middle);
+        }
+        Ok(leftmost_min(
+            &self.data,
+            best,
+            self.in_block(last_block * BLOCK, last),
+        ))
+    }
+
+    /// Returns the leftmost minimum in the range [start, end).
+    pub fn min(&self, start: usize, end: usize) -> Result<&T, RangeError> {
+        self.min_index(start, end).map(|idx| &self.data[idx])
+    }
+
+    /// Returns the position of the minimum of [first, last], both in the same block.
+    fn in_block(&self, first: usize, last: usize) -> usize {
+        let block_start = first - first % BLOCK;
+        let stack = self.stacks[last] >> (first - block_start);
+        first + stack.trailing_zeros() as usize
+    }
+}
+
+/// Returns the position of the smaller value, `left` on ties, assuming `left < right`.
+fn leftmost_min<T: PartialOrd>(data: &[T], left: usize, right: usize) -> usize {
+    if data[right] < data[left] {
+        right
+    } else {
+        left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    #[test]
+    fn small_queries() {
+        let rmq = CartesianTreeRmq::new(vec![5, 2, 8, 2, 9, 1, 7]);
+        assert_eq!(rmq.min_index(0, 4), Ok(1));
+        assert_eq!(rmq.min_index(2, 5), Ok(3));
+        assert_eq!(rmq.min(0, 7), Ok(&1));
+        assert_eq!(rmq.min_index(6, 7), Ok(6));
+        assert_eq!(rmq.min_index(3, 2), Err(RangeError::InvalidRange));
+        assert_eq!(rmq.min_index(0, 8), Err(RangeError::IndexOutOfBound));
+        assert!(CartesianTreeRmq::<i32>::new(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn matches_naive_leftmost_minimum() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for len in [1, 63, 64, 65, 200, 1_000] {
+            // Few distinct values, so that ties are common
+            let data: Vec<u8> = (0..len).map(|_| rng.random_range(0..20)).collect();
+            let rmq = CartesianTreeRmq::new(data.clone());
+            assert_eq!(rmq.len(), len);
+            for _ in 0..2_000 {
+                let start = rng.random_range(0..len);
+                let end = rng.random_range(start + 1..=len);
+                let min = *data[start..end].iter().min().unwrap();
+                let expected = start + data[start..end].iter().position(|&v| v == min).unwrap();
+                assert_eq!(rmq.min_index(start, end), Ok(expected));
+            }
+        }
+    }
+}
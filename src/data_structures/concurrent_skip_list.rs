@@ -0,0 +1,532 @@
+//! A sorted map that many threads can read and update at once.
+//!
+//! This is the "lazy" skip list of Herlihy, Lev, Luchangco and Shavit. Searches take no node
+//! locks. An update locks only the nodes right before the key on each level, checks that they
+//! are still unmarked and still linked to the expected successors, and retries otherwise.
+//! Removal is two-step: the node is first marked, which logically removes it, and then it is
+//! unlinked level by level. A node is only visible to lookups once it is fully linked.
+//!
+//! Links are `Arc`s behind short-lived `RwLock`s, so a node unlinked while another thread is
+//! still traversing it stays alive until that thread moves on.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+
+use rand::random_range;
+
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+struct Node<K, V> {
+    /// `None` for the head sentinel, which sorts before every key.
+    key: Option<K>,
+    value: RwLock<Option<V>>,
+    next: Vec<RwLock<Link<K, V>>>,
+    /// Held while linking nodes after this one, or while marking it.
+    lock: Mutex<()>,
+    marked: AtomicBool,
+    fully_linked: AtomicBool,
+}
+
+impl<K: Ord, V> Node<K, V> {
+    fn new(key: Option<K>, value: Option<V>, height: usize) -> Self {
+        Self {
+            key,
+            value: RwLock::new(value),
+            next: (0..height).map(|_| RwLock::new(None)).collect(),
+            lock: Mutex::new(()),
+            marked: AtomicBool::new(false),
+            fully_linked: AtomicBool::new(false),
+        }
+    }
+
+    fn cmp_key<Q>(&self, key: &Q) -> Ordering
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.key
+            .as_ref()
+            .map_or(Ordering::Less, |own| own.borrow().cmp(key))
+    }
+
+    fn next(&self, level: usize) -> Link<K, V> {
+        self.next[level]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn set_next(&self, level: usize, link: Link<K, V>) {
+        *self.next[level]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = link;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_marked(&self) -> bool {
+        self.marked.load(AtomicOrdering::Acquire)
+    }
+
+    fn is_live(&self) -> bool {
+        self.fully_linked.load(AtomicOrdering::Acquire) && !self.is_marked()
+    }
+
+    fn value(&self) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.value
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// The nodes around a key on every level: the last node before it and the first node after or at it.
+struct Window<K, V> {
+    preds: Vec<Arc<Node<K, V>>>,
+    succs: Vec<Link<K, V>>,
+    /// The highest level on which the key was found.
+    found: Option<usize>,
+}
+
+/// A sorted map supporting concurrent `insert`, `remove`, `get` and range iteration through `&self`.
+///
+/// Iteration is weakly consistent: it sees every entry present for its whole duration, and may
+/// or may not see entries inserted or removed meanwhile.
+pub struct ConcurrentSkipList<K, V> {
+    head: Arc<Node<K, V>>,
+    len: AtomicUsize,
+}
+
+impl<K: Ord, V> Default for ConcurrentSkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> ConcurrentSkipList<K, V> {
+    pub fn new() -> Self {
+        Self {
+            head: Arc::new(Node::new(None, None, MAX_LEVEL)),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of entries, which may be stale while other threads update the list.
+    pub fn len(&self) -> usize {
+        self.len.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find_live(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        self.find_live(key)?.value()
+    }
+
+    /// Inserts `value` under `key`, returning the previous value of the key.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let height = random_height();
+        let mut value = Some(value);
+        loop {
+            let window = self.find(&key);
+            if let Some(level) = window.found {
+                let node = window.succs[level].clone().expect("found nodes are linked");
+                if !node.is_marked() {
+                    while !node.fully_linked.load(AtomicOrdering::Acquire) {
+                        std::hint::spin_loop();
+                    }
+                    // Marking takes the node lock, so holding it keeps a concurrent removal
+                    // from taking the value before it is replaced.
+                    let _guard = node.lock();
+                    if !node.is_marked() {
+                        let mut slot = node.value.write().unwrap_or_else(PoisonError::into_inner);
+                        return slot.replace(value.take().expect("value is only taken once"));
+                    }
+                }
+                // The node is being removed: wait for it to be unlinked
+                continue;
+            }
+
+            let guards = lock_distinct(&window.preds[..height]);
+            let valid = (0..height).all(|level| {
+                let pred = &window.preds[level];
+                let succ = &window.succs[level];
+                !pred.is_marked()
+                    && succ.as_ref().is_none_or(|succ| !succ.is_marked())
+                    && same_link(&pred.next(level), succ)
+            });
+            if !valid {
+                continue;
+            }
+
+            let node = Arc::new(Node::new(Some(key), value.take(), height));
+            for level in 0..height {
+                node.set_next(level, window.succs[level].clone());
+                window.preds[level].set_next(level, Some(Arc::clone(&node)));
+            }
+            node.fully_linked.store(true, AtomicOrdering::Release);
+            drop(guards);
+            self.len.fetch_add(1, AtomicOrdering::Relaxed);
+            return None;
+        }
+    }
+
+    /// Removes `key`, returning its value.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut victim: Option<Arc<Node<K, V>>> = None;
+        loop {
+            let window = self.find(key);
+            let victim = match &victim {
+                Some(victim) => Arc::clone(victim),
+                None => {
+                    let level = window.found?;
+                    let node = window.succs[level].clone().expect("found nodes are linked");
+                    if !node.fully_linked.load(AtomicOrdering::Acquire)
+                        || node.next.len() != level + 1
+                        || node.is_marked()
+                    {
+                        return None;
+                    }
+                    {
+                        let _guard = node.lock();
+                        if node.is_marked() {
+                            return None;
+                        }
+                        // Once marked, nobody links nodes after the victim any more
+                        node.marked.store(true, AtomicOrdering::Release);
+                    }
+                    victim = Some(Arc::clone(&node));
+                    node
+                }
+            };
+
+            let height = victim.next.len();
+            let guards = lock_distinct(&window.preds[..height]);
+            let valid = (0..height).all(|level| {
+                let pred = &window.preds[level];
+                !pred.is_marked() && same_link(&pred.next(level), &Some(Arc::clone(&victim)))
+            });
+            if !valid {
+                continue;
+            }
+
+            for level in (0..height).rev() {
+                window.preds[level].set_next(level, victim.next(level));
+            }
+            drop(guards);
+            self.len.fetch_sub(1, AtomicOrdering::Relaxed);
+            return victim
+                .value
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+        }
+    }
+
+    /// Iterates in order over clones of all the entries.
+    pub fn iter(&self) -> Iter<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            current: Arc::clone(&self.head),
+        }
+    }
+
+    /// Iterates in order over clones of the entries whose keys lie in `range`.
+    pub fn range<'a, Q, R>(&self, range: R) -> impl Iterator<Item = (K, V)> + 'a
+    where
+        K: Borrow<Q> + Clone + 'a,
+        V: Clone + 'a,
+        Q: Ord + ?Sized + 'a,
+        R: RangeBounds<Q> + 'a,
+    {
+        let current = match range.start_bound() {
+            Bound::Included(start) => Arc::clone(&self.find(start).preds[0]),
+            Bound::Excluded(start) => {
+                let window = self.find(start);
+                match &window.succs[0] {
+                    Some(succ) if succ.cmp_key(start) == Ordering::Equal => Arc::clone(succ),
+                    _ => Arc::clone(&window.preds[0]),
+                }
+            }
+            Bound::Unbounded => Arc::clone(&self.head),
+        };
+        Iter { current }.take_while(move |(key, _)| match range.end_bound() {
+            Bound::Included(end) => key.borrow() <= end,
+            Bound::Excluded(end) => key.borrow() < end,
+            Bound::Unbounded => true,
+        })
+    }
+
+    fn find_live<Q>(&self, key: &Q) -> Option<Arc<Node<K, V>>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut pred = Arc::clone(&self.head);
+        for level in (0..MAX_LEVEL).rev() {
+            while let Some(succ) = pred.next(level) {
+                match succ.cmp_key(key) {
+                    Ordering::Less => pred = succ,
+                    Ordering::Equal => return succ.is_live().then_some(succ),
+                    Ordering::Greater => break,
+                }
+            }
+        }
+        None
+    }
+
+    fn find<Q>(&self, key: &Q) -> Window<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut preds = vec![Arc::clone(&self.head); MAX_LEVEL];
+        let mut succs = vec![None; MAX_LEVEL];
+        let mut found = None;
+        let mut pred = Arc::clone(&self.head);
+        for level in (0..MAX_LEVEL).rev() {
+            let mut succ = pred.next(level);
+            while let Some(node) = succ
+                .as_ref()
+                .filter(|node| node.cmp_key(key) == Ordering::Less)
+            {
+                pred = Arc::clone(node);
+                succ = pred.next(level);
+            }
+            if found.is_none()
+                && succ
+                    .as_ref()
+                    .is_some_and(|node| node.cmp_key(key) == Ordering::Equal)
+            {
+                found = Some(level);
+            }
+            preds[level] = Arc::clone(&pred);
+            succs[level] = succ;
+        }
+        Window {
+            preds,
+            succs,
+            found,
+        }
+    }
+}
+
+impl<K, V> Drop for ConcurrentSkipList<K, V> {
+    /// Unlinks the nodes one by one, so that dropping a long list does not recurse through it.
+    fn drop(&mut self) {
+        let take = |node: &Node<K, V>, level: usize| {
+            node.next[level]
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take()
+        };
+        for level in 1..MAX_LEVEL {
+            take(&self.head, level);
+        }
+        let mut current = take(&self.head, 0);
+        while let Some(node) = current {
+            for level in 1..node.next.len() {
+                take(&node, level);
+            }
+            current = take(&node, 0);
+        }
+    }
+}
+
+/// A weakly consistent iterator over a [`ConcurrentSkipList`], yielding clones of the entries.
+pub struct Iter<K, V> {
+    current: Arc<Node<K, V>>,
+}
+
+impl<K: Ord + Clone, V: Clone> Iterator for Iter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        loop {
+            self.current = self.current.next(0)?;
+            if self.current.is_live() {
+                if let (Some(key), Some(value)) = (&self.current.key, self.current.value()) {
+                    return Some((key.clone(), value));
+                }
+            }
+        }
+    }
+}
+
+/// Locks each node once, from the lowest level up. Every thread locks in this order, which
+/// is decreasing key order, so they cannot deadlock.
+fn lock_distinct<K: Ord, V>(nodes: &[Arc<Node<K, V>>]) -> Vec<MutexGuard<'_, ()>> {
+    let mut guards = Vec::with_capacity(nodes.len());
+    for (level, node) in nodes.iter().enumerate() {
+        if level == 0 || !Arc::ptr_eq(node, &nodes[level - 1]) {
+            guards.push(node.lock());
+        }
+    }
+    guards
+}
+
+fn same_link<K, V>(a: &Link<K, V>, b: &Link<K, V>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Draws a height with a geometric distribution of ratio 1/2.
+fn random_height() -> usize {
+    let mut height = 1;
+    while height < MAX_LEVEL && random_range(0..2) == 0 {
+        height += 1;
+    }
+    height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn sequential_operations() {
+        let list = ConcurrentSkipList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.insert("b".to_string(), 2), None);
+        assert_eq!(list.insert("a".to_string(), 1), None);
+        assert_eq!(list.insert("b".to_string(), 20), Some(2));
+        assert_eq!(list.get("b"), Some(20));
+        assert!(list.contains_key("a"));
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            vec![("a".to_string(), 1), ("b".to_string(), 20)]
+        );
+        assert_eq!(list.remove("a"), Some(1));
+        assert_eq!(list.remove("a"), None);
+        assert_eq!(list.get("a"), None);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn range_queries() {
+        let list = ConcurrentSkipList::new();
+        for key in 0..20 {
+            list.insert(key, key * key);
+        }
+        let keys = |range: Vec<(i32, i32)>| range.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys(list.range(5..8).collect()), vec![5, 6, 7]);
+        assert_eq!(keys(list.range(17..).collect()), vec![17, 18, 19]);
+        assert_eq!(
+            keys(
+                list.range((Bound::Excluded(2), Bound::Included(4)))
+                    .collect()
+            ),
+            vec![3, 4]
+        );
+        assert_eq!(list.range(..=3).map(|(_, v)| v).sum::<i32>(), 14);
+    }
+
+    #[test]
+    fn concurrent_inserts_and_removes() {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 1_000;
+        let list = ConcurrentSkipList::new();
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let list = &list;
+                s.spawn(move || {
+                    for i in 0..PER_THREAD {
+                        let key = i * THREADS + t;
+                        list.insert(key, key);
+                        // Every thread also races on a shared set of keys
+                        list.insert(i % 100 + 1_000_000, t);
+                    }
+                    for i in (0..PER_THREAD).filter(|i| i % 2 == 0) {
+                        assert_eq!(list.remove(&(i * THREADS + t)), Some(i * THREADS + t));
+                    }
+                });
+            }
+        });
+        let entries: Vec<(usize, usize)> = list.iter().collect();
+        assert_eq!(entries.len(), list.len());
+        assert_eq!(list.len(), THREADS * PER_THREAD / 2 + 100);
+        assert!(entries.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(entries
+            .iter()
+            .filter(|(key, _)| *key < 1_000_000)
+            .all(|(key, value)| key == value && (key / THREADS) % 2 == 1));
+    }
+
+    #[test]
+    fn concurrent_updates_of_one_key() {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 2_000;
+        let list = ConcurrentSkipList::new();
+        let returned: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let list = &list;
+                    s.spawn(move || {
+                        let mut returned = Vec::new();
+                        for i in 0..PER_THREAD {
+                            returned.extend(list.insert(0, i * THREADS + t));
+                            if i % 2 == t % 2 {
+                                returned.extend(list.remove(&0));
+                            }
+                        }
+                        returned
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        // Every inserted value is either replaced, removed or still there, exactly once.
+        let mut values: Vec<usize> = returned.into_iter().chain(list.get(&0)).collect();
+        values.sort_unstable();
+        assert_eq!(values, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+        assert_eq!(list.len(), usize::from(list.contains_key(&0)));
+    }
+
+    #[test]
+    fn drops_long_lists() {
+        let list = ConcurrentSkipList::new();
+        for key in 0..100_000 {
+            list.insert(key, ());
+        }
+        drop(list);
+    }
+}
@@ -0,0 +1,283 @@
+//! A lazy segment tree over a huge coordinate range whose nodes are only allocated
+//! when an update or a query reaches them.
+//!
+//! This is useful when the positions are sparse, e.g. timestamps or 64-bit ids:
+//! after `q` operations on a range of length `n`, the tree holds `O(q log n)` nodes
+//! instead of `O(n)`. Every position starts with the same `initial` value, and the
+//! aggregate of an untouched segment is computed on demand with [`repeat`].
+
+use super::monoid::{repeat, Action, Monoid};
+use super::segment_tree::SegmentTreeError;
+use std::ops::Range;
+
+const NO_CHILD: usize = usize::MAX;
+
+struct Node<M: Monoid, A: Action<M>> {
+    value: M::Value,
+    lazy: A::Map,
+    left: usize,
+    right: usize,
+}
+
+pub struct DynamicSegmentTree<M: Monoid, A: Action<M>> {
+    range: Range<i64>,
+    initial: M::Value,
+    /// Arena of nodes, the root being stored at index 0.
+    nodes: Vec<Node<M, A>>,
+}
+
+impl<M: Monoid, A: Action<M>> DynamicSegmentTree<M, A> {
+    /// Creates a tree covering the positions in `range`, each one holding `initial`.
+    pub fn new(range: Range<i64>, initial: M::Value) -> Self {
+        let mut tree = DynamicSegmentTree {
+            range: range.clone(),
+            initial,
+            nodes: Vec::new(),
+        };
+        tree.allocate(Self::width(&range));
+        tree
+    }
+
+    /// Returns the number of allocated nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Combines the elements in `range`, returning the identity for an empty range.
+    pub fn query(&mut self, range: Range<i64>) -> Result<M::Value, SegmentTreeError> {
+        self.check_range(&range)?;
+        if range.is_empty() {
+            return Ok(M::identity());
+        }
+        Ok(self.query_recursive(0, self.range.clone(), &range))
+    }
+
+    /// Applies `map` to every element in `range`.
+    pub fn apply(&mut self, range: Range<i64>, map: &A::Map) -> Result<(), SegmentTreeError> {
+        self.check_range(&range)?;
+        if !range.is_empty() {
+            self.apply_recursive(0, self.range.clone(), &range, map);
+        }
+        Ok(())
+    }
+
+    /// Returns the element at position `pos`.
+    pub fn get(&mut self, pos: i64) -> Result<M::Value, SegmentTreeError> {
+        if !self.range.contains(&pos) {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        Ok(self.query_recursive(0, self.range.clone(), &(pos..pos + 1)))
+    }
+
+    /// Replaces the element at position `pos` by `val`.
+    pub fn set(&mut self, pos: i64, val: M::Value) -> Result<(), SegmentTreeError> {
+        if !self.range.contains(&pos) {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        self.set_recursive(0, self.range.clone(), pos, val);
+        Ok(())
+    }
+
+    fn check_range(&self, range: &Range<i64>) -> Result<(), SegmentTreeError> {
+        if range.start > range.end || range.start < self.range.start || range.end > self.range.end {
+            return Err(SegmentTreeError::InvalidRange);
+        }
+        Ok(())
+    }
+
+    /// Allocates a fresh node covering `len` untouched positions.
+    fn allocate(&mut self, len: u64) -> usize {
+        self.nodes.push(Node {
+            value: repeat::<M>(&self.initial, len),
+            lazy: A::identity_map(),
+            left: NO_CHILD,
+            right: NO_CHILD,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn midpoint(range: &Range<i64>) -> i64 {
+        range.start.saturating_add_unsigned(Self::width(range) / 2)
+    }
+
+    /// The number of positions in `range`, which may not fit in an `i64`
+    /// (e.g. `i64::MIN..i64::MAX`).
+    fn width(range: &Range<i64>) -> u64 {
+        if range.is_empty() {
+            0
+        } else {
+            range.end.abs_diff(range.start)
+        }
+    }
+
+    /// Makes sure both children of `node` exist and forwards its pending map to them.
+    fn push(&mut self, node: usize, range: &Range<i64>) {
+        let mid = Self::midpoint(range);
+        if self.nodes[node].left == NO_CHILD {
+            let left = self.allocate(Self::width(&(range.start..mid)));
+            self.nodes[node].left = left;
+        }
+        if self.nodes[node].right == NO_CHILD {
+            let right = self.allocate(Self::width(&(mid..range.end)));
+            self.nodes[node].right = right;
+        }
+        let map = std::mem::replace(&mut self.nodes[node].lazy, A::identity_map());
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.apply_node(left, &(range.start..mid), &map);
+        self.apply_node(right, &(mid..range.end), &map);
+    }
+
+    fn apply_node(&mut self, node: usize, range: &Range<i64>, map: &A::Map) {
+        let width = Self::width(range) as usize;
+        let current = &mut self.nodes[node];
+        current.value = A::apply(map, &current.value, width);
+        current.lazy = A::compose(map, &current.lazy);
+    }
+
+    fn pull(&mut self, node: usize) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.nodes[node].value = M::combine(&self.nodes[left].value, &self.nodes[right].value);
+    }
+
+    fn query_recursive(
+        &mut self,
+        node: usize,
+        node_range: Range<i64>,
+        query_range: &Range<i64>,
+    ) -> M::Value {
+        if node_range.end <= query_range.start || query_range.end <= node_range.start {
+            return M::identity();
+        }
+        if query_range.start <= node_range.start && node_range.end <= query_range.end {
+            return self.nodes[node].value.clone();
+        }
+        self.push(node, &node_range);
+        let mid = Self::midpoint(&node_range);
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        let left_value = self.query_recursive(left, node_range.start..mid, query_range);
+        let right_value = self.query_recursive(right, mid..node_range.end, query_range);
+        M::combine(&left_value, &right_value)
+    }
+
+    fn apply_recursive(
+        &mut self,
+        node: usize,
+        node_range: Range<i64>,
+        target_range: &Range<i64>,
+        map: &A::Map,
+    ) {
+        if node_range.end <= target_range.start || target_range.end <= node_range.start {
+            return;
+        }
+        if target_range.start <= node_range.start && node_range.end <= target_range.end {
+            self.apply_node(node, &node_range, map);
+            return;
+        }
+        self.push(node, &node_range);
+        let mid = Self::midpoint(&node_range);
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.apply_recursive(left, node_range.start..mid, target_range, map);
+        self.apply_recursive(right, mid..node_range.end, target_range, map);
+        self.pull(node);
+    }
+
+    fn set_recursive(&mut self, node: usize, node_range: Range<i64>, pos: i64, val: M::Value) {
+        if Self::width(&node_range) == 1 {
+            self.nodes[node].value = val;
+            return;
+        }
+        self.push(node, &node_range);
+        let mid = Self::midpoint(&node_range);
+        if pos < mid {
+            let left = self.nodes[node].left;
+            self.set_recursive(left, node_range.start..mid, pos, val);
+        } else {
+            let right = self.nodes[node].right;
+            self.set_recursive(right, mid..node_range.end, pos, val);
+        }
+        self.pull(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::monoid::{MaxMonoid, RangeAdd, RangeAssign, SumMonoid};
+    use super::*;
+
+    #[test]
+    fn test_huge_range_sum() {
+        let mut tree = DynamicSegmentTree::<SumMonoid<i64>, RangeAdd<i64>>::new(
+            -1_000_000_000..1_000_000_000,
+            1,
+        );
+        assert_eq!(tree.query(-1_000_000_000..1_000_000_000), Ok(2_000_000_000));
+        assert_eq!(tree.apply(-10..10, &5), Ok(()));
+        assert_eq!(tree.query(0..100), Ok(150));
+        assert_eq!(tree.set(500, -7), Ok(()));
+        assert_eq!(tree.query(0..1000), Ok(1042));
+        assert_eq!(tree.get(500), Ok(-7));
+        assert_eq!(tree.get(-10), Ok(6));
+        assert_eq!(tree.get(10), Ok(1));
+        assert!(tree.node_count() < 1000);
+    }
+
+    #[test]
+    fn test_full_i64_range() {
+        let mut tree =
+            DynamicSegmentTree::<SumMonoid<i64>, RangeAdd<i64>>::new(i64::MIN..i64::MAX, 0);
+        assert_eq!(tree.set(i64::MIN, 3), Ok(()));
+        assert_eq!(tree.set(i64::MAX - 1, 4), Ok(()));
+        assert_eq!(tree.apply(-5..5, &1), Ok(()));
+        assert_eq!(tree.query(i64::MIN..i64::MAX), Ok(17));
+        assert_eq!(tree.query(i64::MIN..0), Ok(8));
+        assert_eq!(tree.get(i64::MAX - 1), Ok(4));
+        assert_eq!(tree.get(i64::MAX - 2), Ok(0));
+        assert!(tree.node_count() < 1000);
+    }
+
+    #[test]
+    fn test_assign_max() {
+        let mut tree = DynamicSegmentTree::<MaxMonoid<i32>, RangeAssign<i32>>::new(0..1 << 30, 0);
+        assert_eq!(tree.apply(100..200, &Some(9)), Ok(()));
+        assert_eq!(tree.apply(150..160, &Some(4)), Ok(()));
+        assert_eq!(tree.query(0..1 << 30), Ok(9));
+        assert_eq!(tree.query(150..160), Ok(4));
+        assert_eq!(tree.query(200..300), Ok(0));
+        assert_eq!(tree.query(155..170), Ok(9));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut tree = DynamicSegmentTree::<SumMonoid<i64>, RangeAdd<i64>>::new(0..10, 0);
+        assert_eq!(tree.query(-1..3), Err(SegmentTreeError::InvalidRange));
+        assert_eq!(tree.apply(5..11, &1), Err(SegmentTreeError::InvalidRange));
+        assert_eq!(tree.get(10), Err(SegmentTreeError::IndexOutOfBounds));
+        assert_eq!(tree.set(-1, 0), Err(SegmentTreeError::IndexOutOfBounds));
+        assert_eq!(tree.query(4..4), Ok(0));
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        let mut naive = vec![3i64; 64];
+        let mut tree = DynamicSegmentTree::<SumMonoid<i64>, RangeAdd<i64>>::new(0..64, 3);
+        let mut seed = 7u64;
+        for step in 0..300 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let a = (seed >> 33) as usize % 64;
+            let b = (seed >> 40) as usize % 64;
+            let range = a.min(b)..a.max(b) + 1;
+            let delta = (seed >> 50) as i64 % 11 - 5;
+            if step % 2 == 0 {
+                naive[range.clone()].iter_mut().for_each(|x| *x += delta);
+                tree.apply(range.start as i64..range.end as i64, &delta)
+                    .unwrap();
+            } else {
+                let expected: i64 = naive[range.clone()].iter().sum();
+                assert_eq!(
+                    tree.query(range.start as i64..range.end as i64),
+                    Ok(expected)
+                );
+            }
+        }
+    }
+}
@@ -45,6 +45,41 @@ where
         }
     }
 
+    /// Creates a new Fenwick Tree holding the elements of `values`.
+    ///
+    /// Instead of performing `n` updates in `O(n log n)`, every node pushes its
+    /// partial sum to its direct parent, which builds the tree in `O(n)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The initial elements of the tree.
+    ///
+    /// # Returns
+    ///
+    /// A new `FenwickTree` instance.
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut data = vec![T::default(); values.len() + 1];
+        data[1..].copy_from_slice(values);
+        for idx in 1..data.len() {
+            let parent = idx + lowbit(idx);
+            if parent < data.len() {
+                let partial = data[idx];
+                data[parent] += partial;
+            }
+        }
+        FenwickTree { data }
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.data.len() - 1
+    }
+
+    /// Returns `true` if the tree holds no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Updates the tree by adding a value to the element at a specified index.
     ///
     /// This operation also propagates the update to subsequent elements in the tree.
@@ -172,6 +207,277 @@ where
     pub fn set(&mut self, index: usize, value: T) -> Result<(), FenwickTreeError> {
         self.update(index, value - self.point_query(index)?)
     }
+
+    /// Finds the smallest index whose prefix sum is greater than or equal to `target`.
+    ///
+    /// The search descends the implicit tree with binary lifting, so it runs in `O(log n)`
+    /// instead of the `O(log^2 n)` of a binary search over `prefix_query`. Every element
+    /// must be non-negative, so that prefix sums are non-decreasing.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The prefix sum to reach.
+    ///
+    /// # Returns
+    ///
+    /// `Some(index)` of the first prefix reaching `target`, or `None` if the sum of all
+    /// the elements is lower than `target`.
+    pub fn lower_bound(&self, target: T) -> Option<usize>
+    where
+        T: PartialOrd,
+    {
+        if target <= T::default() {
+            return if self.is_empty() { None } else { Some(0) };
+        }
+
+        let mut pos = 0;
+        let mut remaining = target;
+        let mut step = if self.is_empty() {
+            0
+        } else {
+            1 << self.len().ilog2()
+        };
+        while step > 0 {
+            if pos + step <= self.len() && self.data[pos + step] < remaining {
+                pos += step;
+                remaining -= self.data[pos];
+            }
+            step >>= 1;
+        }
+
+        // `pos` is the longest prefix whose sum is still below `target`
+        (pos < self.len()).then_some(pos)
+    }
+}
+
+/// A Fenwick Tree supporting range updates and point queries.
+///
+/// It stores the difference array `d[i] = a[i] - a[i - 1]` of the elements, so that adding
+/// a value to the range `[left, right]` only touches `d[left]` and `d[right + 1]`, and the
+/// value of an element is the prefix sum of the differences up to its index.
+///
+/// Range updates subtract from the internal tree, so `T` should be a signed type.
+pub struct RangeUpdateFenwickTree<T>
+where
+    T: Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign + Copy + Default,
+{
+    /// Fenwick Tree over the difference array of the elements.
+    differences: FenwickTree<T>,
+}
+
+impl<T> RangeUpdateFenwickTree<T>
+where
+    T: Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign + Copy + Default,
+{
+    /// Creates a new tree of `capacity` elements, all initialized to the default value of `T`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        RangeUpdateFenwickTree {
+            differences: FenwickTree::with_capacity(capacity),
+        }
+    }
+
+    /// Creates a new tree holding the elements of `values` in `O(n)`.
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut previous = T::default();
+        let differences: Vec<T> = values
+            .iter()
+            .map(|&value| {
+                let difference = value - previous;
+                previous = value;
+                difference
+            })
+            .collect();
+        RangeUpdateFenwickTree {
+            differences: FenwickTree::from_slice(&differences),
+        }
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.differences.len()
+    }
+
+    /// Returns `true` if the tree holds no element.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Adds `value` to every element in the range `[left, right]`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success (`Ok`) or an error (`FenwickTreeError::InvalidRange`)
+    /// if the left index is greater than the right index or the right index is out of bounds.
+    pub fn range_update(
+        &mut self,
+        left: usize,
+        right: usize,
+        value: T,
+    ) -> Result<(), FenwickTreeError> {
+        if left > right || right >= self.len() {
+            return Err(FenwickTreeError::InvalidRange);
+        }
+
+        self.differences.update(left, value)?;
+        if right + 1 < self.len() {
+            self.differences.update(right + 1, T::default() - value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Retrieves the value of the element at `index`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value at the specified index (`Ok(value)`) or an error
+    /// (`FenwickTreeError::IndexOutOfBounds`) if the index is out of bounds.
+    pub fn point_query(&self, index: usize) -> Result<T, FenwickTreeError> {
+        self.differences.prefix_query(index)
+    }
+}
+
+/// A Fenwick Tree supporting both range updates and range queries.
+///
+/// After adding `v` to the range `[l, r]`, the prefix sum up to an index `i` of that range
+/// grows by `v * (i + 1) - v * l`, which is linear in `i + 1`. The tree keeps two Fenwick
+/// Trees, one for the coefficient of `i + 1` and one for the constant term, so that
+/// `prefix_sum(i) = linear.prefix_query(i) * (i + 1) + constant.prefix_query(i)`.
+///
+/// Range updates subtract from the internal trees, so `T` should be a signed type.
+pub struct RangeFenwickTree<T>
+where
+    T: Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign + Copy + Default,
+{
+    /// Coefficients of `i + 1` in the prefix sums.
+    linear: FenwickTree<T>,
+    /// Constant terms of the prefix sums.
+    constant: FenwickTree<T>,
+}
+
+impl<T> RangeFenwickTree<T>
+where
+    T: Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign + Copy + Default,
+{
+    /// Creates a new tree of `capacity` elements, all initialized to the default value of `T`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        RangeFenwickTree {
+            linear: FenwickTree::with_capacity(capacity),
+            constant: FenwickTree::with_capacity(capacity),
+        }
+    }
+
+    /// Creates a new tree holding the elements of `values` in `O(n)`.
+    pub fn from_slice(values: &[T]) -> Self {
+        RangeFenwickTree {
+            linear: FenwickTree::with_capacity(values.len()),
+            constant: FenwickTree::from_slice(values),
+        }
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.constant.len()
+    }
+
+    /// Returns `true` if the tree holds no element.
+    pub fn is_empty(&self) -> bool {
+        self.constant.is_empty()
+    }
+
+    /// Adds `value` to every element in the range `[left, right]`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success (`Ok`) or an error (`FenwickTreeError::InvalidRange`)
+    /// if the left index is greater than the right index or the right index is out of bounds.
+    pub fn range_update(
+        &mut self,
+        left: usize,
+        right: usize,
+        value: T,
+    ) -> Result<(), FenwickTreeError> {
+        if left > right || right >= self.len() {
+            return Err(FenwickTreeError::InvalidRange);
+        }
+
+        self.linear.update(left, value)?;
+        self.constant
+            .update(left, T::default() - multiply(value, left))?;
+        if right + 1 < self.len() {
+            self.linear.update(right + 1, T::default() - value)?;
+            self.constant
+                .update(right + 1, multiply(value, right + 1))?;
+        }
+
+        Ok(())
+    }
+
+    /// Computes the sum of the elements from the start of the tree up to `index`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the prefix sum (`Ok(sum)`) or an error (`FenwickTreeError::IndexOutOfBounds`)
+    /// if the index is out of bounds.
+    pub fn prefix_query(&self, index: usize) -> Result<T, FenwickTreeError> {
+        let linear = self.linear.prefix_query(index)?;
+        let constant = self.constant.prefix_query(index)?;
+        Ok(multiply(linear, index + 1) + constant)
+    }
+
+    /// Computes the sum of the elements within the range `[left, right]`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the range sum (`Ok(sum)`) or an error (`FenwickTreeError::InvalidRange`)
+    /// if the left index is greater than the right index or the right index is out of bounds.
+    pub fn range_query(&self, left: usize, right: usize) -> Result<T, FenwickTreeError> {
+        if left > right || right >= self.len() {
+            return Err(FenwickTreeError::InvalidRange);
+        }
+
+        let right_query = self.prefix_query(right)?;
+        let left_query = if left == 0 {
+            T::default()
+        } else {
+            self.prefix_query(left - 1)?
+        };
+
+        Ok(right_query - left_query)
+    }
+
+    /// Retrieves the value of the element at `index`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value at the specified index (`Ok(value)`) or an error
+    /// (`FenwickTreeError::IndexOutOfBounds`) if the index is out of bounds.
+    pub fn point_query(&self, index: usize) -> Result<T, FenwickTreeError> {
+        if index >= self.len() {
+            return Err(FenwickTreeError::IndexOutOfBounds);
+        }
+        self.range_query(index, index)
+    }
+}
+
+/// Computes `value * times` with `O(log times)` additions.
+///
+/// This keeps the trees usable with any type that can be added, without requiring a
+/// conversion from `usize` into `T`.
+fn multiply<T>(value: T, mut times: usize) -> T
+where
+    T: Add<Output = T> + Copy + Default,
+{
+    let mut result = T::default();
+    let mut power = value;
+    while times > 0 {
+        if times & 1 == 1 {
+            result = result + power;
+        }
+        power = power + power;
+        times >>= 1;
+    }
+    result
 }
 
 /// Computes the lowest set bit (rightmost `1` bit) of a number.
@@ -191,7 +497,7 @@ where
 /// # Returns
 ///
 /// The value of the lowest set bit in `x`.
-const fn lowbit(x: usize) -> usize {
+pub(super) const fn lowbit(x: usize) -> usize {
     x & (!x + 1)
 }
 
@@ -261,4 +567,80 @@ mod tests {
             Err(FenwickTreeError::IndexOutOfBounds)
         );
     }
+
+    #[test]
+    fn test_from_slice() {
+        let values = [5, 3, -2, 6, -4, 7, -1, 2, -3, 4];
+        let from_slice = FenwickTree::from_slice(&values);
+        let mut from_updates = FenwickTree::with_capacity(values.len());
+        for (idx, &value) in values.iter().enumerate() {
+            from_updates.update(idx, value).unwrap();
+        }
+        assert_eq!(from_slice.len(), 10);
+        for idx in 0..values.len() {
+            assert_eq!(from_slice.prefix_query(idx), from_updates.prefix_query(idx));
+        }
+        assert!(FenwickTree::<i32>::from_slice(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let fenwick_tree = FenwickTree::from_slice(&[2, 0, 3, 1, 0, 4, 1]);
+        // prefix sums: 2, 2, 5, 6, 6, 10, 11
+        assert_eq!(fenwick_tree.lower_bound(0), Some(0));
+        assert_eq!(fenwick_tree.lower_bound(1), Some(0));
+        assert_eq!(fenwick_tree.lower_bound(2), Some(0));
+        assert_eq!(fenwick_tree.lower_bound(3), Some(2));
+        assert_eq!(fenwick_tree.lower_bound(6), Some(3));
+        assert_eq!(fenwick_tree.lower_bound(7), Some(5));
+        assert_eq!(fenwick_tree.lower_bound(11), Some(6));
+        assert_eq!(fenwick_tree.lower_bound(12), None);
+        assert_eq!(FenwickTree::<u32>::with_capacity(0).lower_bound(1), None);
+    }
+
+    #[test]
+    fn test_range_update_point_query() {
+        let mut fenwick_tree = RangeUpdateFenwickTree::from_slice(&[1, 2, 3, 4, 5]);
+        assert_eq!(fenwick_tree.range_update(1, 3, 10), Ok(()));
+        assert_eq!(fenwick_tree.range_update(0, 4, -1), Ok(()));
+        assert_eq!(fenwick_tree.range_update(4, 4, 7), Ok(()));
+        let values: Vec<i32> = (0..5)
+            .map(|idx| fenwick_tree.point_query(idx).unwrap())
+            .collect();
+        assert_eq!(values, vec![0, 11, 12, 13, 11]);
+        assert_eq!(
+            fenwick_tree.range_update(3, 5, 1),
+            Err(FenwickTreeError::InvalidRange)
+        );
+        assert_eq!(
+            fenwick_tree.point_query(5),
+            Err(FenwickTreeError::IndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_range_update_range_query() {
+        let mut naive = vec![4i64, -2, 7, 0, 3, 3, -8, 1];
+        let mut fenwick_tree = RangeFenwickTree::from_slice(&naive);
+        let updates = [(0, 7, 2), (2, 4, -5), (5, 5, 9), (1, 6, 3), (7, 7, -1)];
+        for &(left, right, value) in &updates {
+            assert_eq!(fenwick_tree.range_update(left, right, value), Ok(()));
+            naive[left..=right].iter_mut().for_each(|x| *x += value);
+            for l in 0..naive.len() {
+                for r in l..naive.len() {
+                    let expected: i64 = naive[l..=r].iter().sum();
+                    assert_eq!(fenwick_tree.range_query(l, r), Ok(expected));
+                }
+                assert_eq!(fenwick_tree.point_query(l), Ok(naive[l]));
+            }
+        }
+        assert_eq!(
+            fenwick_tree.range_query(3, 8),
+            Err(FenwickTreeError::InvalidRange)
+        );
+        assert_eq!(
+            fenwick_tree.prefix_query(8),
+            Err(FenwickTreeError::IndexOutOfBounds)
+        );
+    }
 }
@@ -0,0 +1,243 @@
+use super::fenwick_tree::{lowbit, FenwickTreeError};
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// A two-dimensional Fenwick Tree supporting point updates and rectangle sum queries on a grid.
+///
+/// Every row of the tree is itself a Fenwick Tree over the columns, so both updates and
+/// queries run in `O(log(rows) * log(cols))`.
+///
+/// Like [`FenwickTree`](super::FenwickTree), it uses **1-based** indexing internally but
+/// presents a **0-based** interface to the user.
+pub struct FenwickTree2D<T>
+where
+    T: Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign + Copy + Default,
+{
+    rows: usize,
+    cols: usize,
+    /// `(rows + 1) x (cols + 1)` matrix stored row by row. The first row and the
+    /// first column are unused to simplify index calculations.
+    data: Vec<T>,
+}
+
+impl<T> FenwickTree2D<T>
+where
+    T: Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign + Copy + Default,
+{
+    /// Creates a new `rows x cols` tree, with all elements initialized to the default value of `T`.
+    pub fn with_capacity(rows: usize, cols: usize) -> Self {
+        FenwickTree2D {
+            rows,
+            cols,
+            data: vec![T::default(); (rows + 1) * (cols + 1)],
+        }
+    }
+
+    /// Creates a new tree holding the elements of a rectangular grid in `O(rows * cols)`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the rows of `grid` don't all have the same length.
+    pub fn from_grid(grid: &[Vec<T>]) -> Option<Self> {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, Vec::len);
+        if grid.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+
+        let mut tree = Self::with_capacity(rows, cols);
+        for (r, row) in grid.iter().enumerate() {
+            let start = tree.offset(r + 1, 1);
+            tree.data[start..start + cols].copy_from_slice(row);
+        }
+        // Push the partial sums to the parent column, then to the parent row
+        for r in 1..=rows {
+            for c in 1..=cols {
+                let parent = c + lowbit(c);
+                if parent <= cols {
+                    let partial = tree.data[tree.offset(r, c)];
+                    let idx = tree.offset(r, parent);
+                    tree.data[idx] += partial;
+                }
+            }
+        }
+        for r in 1..=rows {
+            let parent = r + lowbit(r);
+            if parent <= rows {
+                for c in 1..=cols {
+                    let partial = tree.data[tree.offset(r, c)];
+                    let idx = tree.offset(parent, c);
+                    tree.data[idx] += partial;
+                }
+            }
+        }
+        Some(tree)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Adds `value` to the element at `(row, col)`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success (`Ok`) or an error (`FenwickTreeError::IndexOutOfBounds`)
+    /// if the position is out of bounds.
+    pub fn update(&mut self, row: usize, col: usize, value: T) -> Result<(), FenwickTreeError> {
+        if row >= self.rows || col >= self.cols {
+            return Err(FenwickTreeError::IndexOutOfBounds);
+        }
+
+        let mut r = row + 1;
+        while r <= self.rows {
+            let mut c = col + 1;
+            while c <= self.cols {
+                let idx = self.offset(r, c);
+                self.data[idx] += value;
+                c += lowbit(c);
+            }
+            r += lowbit(r);
+        }
+
+        Ok(())
+    }
+
+    /// Computes the sum of the rectangle spanning from `(0, 0)` to `(row, col)`, both included.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the sum (`Ok(sum)`) or an error (`FenwickTreeError::IndexOutOfBounds`)
+    /// if the position is out of bounds.
+    pub fn prefix_query(&self, row: usize, col: usize) -> Result<T, FenwickTreeError> {
+        if row >= self.rows || col >= self.cols {
+            return Err(FenwickTreeError::IndexOutOfBounds);
+        }
+        Ok(self.prefix_sum(row + 1, col + 1))
+    }
+
+    /// Computes the sum of the rectangle spanning from `top_left` to `bottom_right`, both included.
+    ///
+    /// Both corners are given as `(row, col)`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the sum (`Ok(sum)`) or an error (`FenwickTreeError::InvalidRange`)
+    /// if the corners are swapped or out of bounds.
+    pub fn range_query(
+        &self,
+        top_left: (usize, usize),
+        bottom_right: (usize, usize),
+    ) -> Result<T, FenwickTreeError> {
+        let ((top, left), (bottom, right)) = (top_left, bottom_right);
+        if top > bottom || left > right || bottom >= self.rows || right >= self.cols {
+            return Err(FenwickTreeError::InvalidRange);
+        }
+
+        // Inclusion-exclusion over the four prefix rectangles
+        Ok(
+            self.prefix_sum(bottom + 1, right + 1) + self.prefix_sum(top, left)
+                - self.prefix_sum(top, right + 1)
+                - self.prefix_sum(bottom + 1, left),
+        )
+    }
+
+    /// Retrieves the value of the element at `(row, col)`.
+    pub fn point_query(&self, row: usize, col: usize) -> Result<T, FenwickTreeError> {
+        if row >= self.rows || col >= self.cols {
+            return Err(FenwickTreeError::IndexOutOfBounds);
+        }
+        self.range_query((row, col), (row, col))
+    }
+
+    /// Sum of the first `rows` rows and `cols` columns, with 1-based bounds.
+    fn prefix_sum(&self, rows: usize, cols: usize) -> T {
+        let mut result = T::default();
+        let mut r = rows;
+        while r > 0 {
+            let mut c = cols;
+            while c > 0 {
+                result += self.data[self.offset(r, c)];
+                c -= lowbit(c);
+            }
+            r -= lowbit(r);
+        }
+        result
+    }
+
+    fn offset(&self, r: usize, c: usize) -> usize {
+        r * (self.cols + 1) + c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fenwick_tree_2d() {
+        let grid = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        let mut fenwick_tree = FenwickTree2D::from_grid(&grid).unwrap();
+        assert_eq!(fenwick_tree.prefix_query(2, 3), Ok(78));
+        assert_eq!(fenwick_tree.prefix_query(1, 1), Ok(14));
+        assert_eq!(fenwick_tree.range_query((1, 1), (2, 2)), Ok(34));
+        assert_eq!(fenwick_tree.range_query((0, 2), (0, 3)), Ok(7));
+        assert_eq!(fenwick_tree.point_query(2, 0), Ok(9));
+
+        assert_eq!(fenwick_tree.update(1, 2, -10), Ok(()));
+        assert_eq!(fenwick_tree.range_query((1, 1), (2, 2)), Ok(24));
+        assert_eq!(fenwick_tree.point_query(1, 2), Ok(-3));
+
+        assert_eq!(
+            fenwick_tree.update(3, 0, 1),
+            Err(FenwickTreeError::IndexOutOfBounds)
+        );
+        assert_eq!(
+            fenwick_tree.prefix_query(0, 4),
+            Err(FenwickTreeError::IndexOutOfBounds)
+        );
+        assert_eq!(
+            fenwick_tree.range_query((2, 0), (1, 0)),
+            Err(FenwickTreeError::InvalidRange)
+        );
+        assert!(FenwickTree2D::from_grid(&[vec![1], vec![2, 3]]).is_none());
+    }
+
+    #[test]
+    fn test_matches_updates() {
+        let (rows, cols) = (6, 5);
+        let grid: Vec<Vec<i64>> = (0..rows)
+            .map(|r| (0..cols).map(|c| (r * 7 + c * 3) as i64 % 5 - 2).collect())
+            .collect();
+        let from_grid = FenwickTree2D::from_grid(&grid).unwrap();
+        let mut from_updates = FenwickTree2D::with_capacity(rows, cols);
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &value) in row.iter().enumerate() {
+                from_updates.update(r, c, value).unwrap();
+            }
+        }
+        for top in 0..rows {
+            for left in 0..cols {
+                for bottom in top..rows {
+                    for right in left..cols {
+                        let expected: i64 = grid[top..=bottom]
+                            .iter()
+                            .map(|row| row[left..=right].iter().sum::<i64>())
+                            .sum();
+                        assert_eq!(
+                            from_grid.range_query((top, left), (bottom, right)),
+                            Ok(expected)
+                        );
+                        assert_eq!(
+                            from_updates.range_query((top, left), (bottom, right)),
+                            Ok(expected)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
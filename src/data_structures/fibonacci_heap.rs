@@ -0,0 +1,388 @@
+//! A Fibonacci heap: a collection of heap-ordered trees with the best known amortized
+//! bounds for a comparison-based priority queue.
+//!
+//! `push`, `peek` and `decrease_key` are `O(1)` amortized, and `pop` and `remove` are
+//! `O(log n)` amortized. Unlike the textbook heap, `meld` is not `O(1)`: the root lists are
+//! linked in `O(1)`, but every heap stores its nodes in its own arena, so the nodes of the
+//! melded heap are moved in `O(other.len())`. Work is deferred as much as possible: new
+//! elements are simply added to the list of roots, and trees are only consolidated (linked
+//! until no two roots share the same degree) when the minimum is popped. Cascading cuts
+//! keep the trees bushy enough for their size to grow exponentially with their degree, like
+//! the Fibonacci numbers.
+
+use super::addressable_heap::{AddressableHeap, Arena, Handle, HeapError};
+
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    parent: usize,
+    /// Any child, the children forming a circular doubly linked list.
+    child: usize,
+    left: usize,
+    right: usize,
+    degree: usize,
+    /// Whether the node lost a child since it became the child of its parent.
+    marked: bool,
+}
+
+pub struct FibonacciHeap<K: Ord, V> {
+    nodes: Arena<Node<K, V>>,
+    /// Root with the smallest key, the roots forming a circular doubly linked list.
+    min: usize,
+}
+
+impl<K: Ord, V> FibonacciHeap<K, V> {
+    pub const fn new() -> Self {
+        FibonacciHeap {
+            nodes: Arena::new(),
+            min: NIL,
+        }
+    }
+
+    /// Moves every element of `other` into this heap.
+    ///
+    /// The root lists are concatenated in `O(1)`, but moving the nodes of `other` into
+    /// the arena of `self` costs `O(other.len())`, so the smaller heap should be melded
+    /// into the larger one. Handles obtained from `other` are invalidated.
+    pub fn meld(&mut self, other: FibonacciHeap<K, V>) {
+        if other.min == NIL {
+            return;
+        }
+        let mapping = self.nodes.absorb(other.nodes);
+        let remap = |link: usize| if link == NIL { NIL } else { mapping[link] };
+        for &index in mapping.iter().filter(|&&index| index != NIL) {
+            let node = &mut self.nodes[index];
+            node.parent = remap(node.parent);
+            node.child = remap(node.child);
+            node.left = remap(node.left);
+            node.right = remap(node.right);
+        }
+        self.add_root(remap(other.min));
+    }
+
+    /// Adds the circular list containing `node` to the root list, updating the minimum.
+    fn add_root(&mut self, node: usize) {
+        if self.min == NIL {
+            self.min = node;
+        } else {
+            self.splice(self.min, node);
+            if self.nodes[node].key < self.nodes[self.min].key {
+                self.min = node;
+            }
+        }
+    }
+
+    /// Concatenates the circular lists containing `a` and `b`.
+    fn splice(&mut self, a: usize, b: usize) {
+        let a_right = self.nodes[a].right;
+        let b_left = self.nodes[b].left;
+        self.nodes[a].right = b;
+        self.nodes[b].left = a;
+        self.nodes[b_left].right = a_right;
+        self.nodes[a_right].left = b_left;
+    }
+
+    /// Removes `node` from its circular list, leaving it in a list of its own.
+    fn unlink(&mut self, node: usize) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        self.nodes[node].left = node;
+        self.nodes[node].right = node;
+    }
+
+    /// Makes the root `child` a child of the root `parent`.
+    fn link(&mut self, child: usize, parent: usize) {
+        self.unlink(child);
+        self.nodes[child].parent = parent;
+        self.nodes[child].marked = false;
+        let first_child = self.nodes[parent].child;
+        if first_child == NIL {
+            self.nodes[parent].child = child;
+        } else {
+            self.splice(first_child, child);
+        }
+        self.nodes[parent].degree += 1;
+    }
+
+    /// Links roots of equal degree until all the roots have distinct degrees.
+    fn consolidate(&mut self) {
+        let mut roots = vec![self.min];
+        let mut current = self.nodes[self.min].right;
+        while current != self.min {
+            roots.push(current);
+            current = self.nodes[current].right;
+        }
+
+        let mut by_degree: Vec<usize> = Vec::new();
+        for root in roots {
+            let mut tree = root;
+            let mut degree = self.nodes[tree].degree;
+            while degree < by_degree.len() && by_degree[degree] != NIL {
+                let mut other = by_degree[degree];
+                if self.nodes[other].key < self.nodes[tree].key {
+                    std::mem::swap(&mut tree, &mut other);
+                }
+                self.link(other, tree);
+                by_degree[degree] = NIL;
+                degree += 1;
+            }
+            if degree >= by_degree.len() {
+                by_degree.resize(degree + 1, NIL);
+            }
+            by_degree[degree] = tree;
+        }
+
+        self.min = NIL;
+        for root in by_degree.into_iter().filter(|&root| root != NIL) {
+            if self.min == NIL || self.nodes[root].key < self.nodes[self.min].key {
+                self.min = root;
+            }
+        }
+    }
+
+    /// Moves `node` from the children of `parent` to the root list.
+    fn cut(&mut self, node: usize, parent: usize) {
+        if self.nodes[node].right == node {
+            self.nodes[parent].child = NIL;
+        } else if self.nodes[parent].child == node {
+            self.nodes[parent].child = self.nodes[node].right;
+        }
+        self.unlink(node);
+        self.nodes[parent].degree -= 1;
+        self.nodes[node].parent = NIL;
+        self.nodes[node].marked = false;
+        self.splice(self.min, node);
+    }
+
+    /// Cuts the ancestors of `node` that already lost a child, marking the first one
+    /// that didn't.
+    fn cascading_cut(&mut self, mut node: usize) {
+        loop {
+            let parent = self.nodes[node].parent;
+            if parent == NIL {
+                break;
+            }
+            if !self.nodes[node].marked {
+                self.nodes[node].marked = true;
+                break;
+            }
+            self.cut(node, parent);
+            node = parent;
+        }
+    }
+
+    /// Moves `node` to the root list if it is not a root already.
+    fn detach(&mut self, node: usize) {
+        let parent = self.nodes[node].parent;
+        if parent != NIL {
+            self.cut(node, parent);
+            self.cascading_cut(parent);
+        }
+    }
+}
+
+impl<K: Ord, V> Default for FibonacciHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> AddressableHeap<K, V> for FibonacciHeap<K, V> {
+    fn push(&mut self, key: K, value: V) -> Handle {
+        let index = self.nodes.insert(Node {
+            key,
+            value,
+            parent: NIL,
+            child: NIL,
+            left: NIL,
+            right: NIL,
+            degree: 0,
+            marked: false,
+        });
+        self.nodes[index].left = index;
+        self.nodes[index].right = index;
+        self.add_root(index);
+        self.nodes.handle(index)
+    }
+
+    fn peek(&self) -> Option<(&K, &V)> {
+        (self.min != NIL).then(|| {
+            let node = &self.nodes[self.min];
+            (&node.key, &node.value)
+        })
+    }
+
+    fn pop(&mut self) -> Option<(K, V)> {
+        let min = self.min;
+        if min == NIL {
+            return None;
+        }
+
+        let first_child = self.nodes[min].child;
+        if first_child != NIL {
+            let mut child = first_child;
+            loop {
+                self.nodes[child].parent = NIL;
+                child = self.nodes[child].right;
+                if child == first_child {
+                    break;
+                }
+            }
+            self.splice(min, first_child);
+            self.nodes[min].child = NIL;
+        }
+
+        if self.nodes[min].right == min {
+            self.min = NIL;
+        } else {
+            self.min = self.nodes[min].right;
+            self.unlink(min);
+            self.consolidate();
+        }
+
+        let node = self.nodes.remove(min);
+        Some((node.key, node.value))
+    }
+
+    fn get(&self, handle: Handle) -> Option<(&K, &V)> {
+        self.nodes.resolve(handle).map(|index| {
+            let node = &self.nodes[index];
+            (&node.key, &node.value)
+        })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, key: K) -> Result<(), HeapError> {
+        let index = self.nodes.resolve(handle).ok_or(HeapError::InvalidHandle)?;
+        if key > self.nodes[index].key {
+            return Err(HeapError::KeyIncreased);
+        }
+        self.nodes[index].key = key;
+        let parent = self.nodes[index].parent;
+        if parent != NIL && self.nodes[index].key < self.nodes[parent].key {
+            self.detach(index);
+        }
+        if self.nodes[index].key < self.nodes[self.min].key {
+            self.min = index;
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(K, V)> {
+        let index = self.nodes.resolve(handle)?;
+        // Bring the node to the root list and pretend it is the minimum
+        self.detach(index);
+        self.min = index;
+        self.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(heap: &mut FibonacciHeap<i32, u32>) -> Vec<i32> {
+        std::iter::from_fn(|| heap.pop())
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = FibonacciHeap::new();
+        assert_eq!(heap.pop(), None);
+        for key in [7, 3, 9, 1, 4, 4, -2] {
+            heap.push(key, 0);
+        }
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some((&-2, &0)));
+        assert_eq!(drain(&mut heap), vec![-2, 1, 3, 4, 4, 7, 9]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_decrease_key_and_remove() {
+        let mut heap = FibonacciHeap::new();
+        let handles: Vec<Handle> = (0..10).map(|i| heap.push(i * 10, i as u32)).collect();
+        // Popping consolidates the roots into trees, so later operations exercise cuts
+        assert_eq!(heap.pop(), Some((0, 0)));
+        assert_eq!(heap.decrease_key(handles[7], 5), Ok(()));
+        assert_eq!(heap.peek(), Some((&5, &7)));
+        assert_eq!(
+            heap.decrease_key(handles[3], 31),
+            Err(HeapError::KeyIncreased)
+        );
+        assert_eq!(
+            heap.decrease_key(handles[0], -1),
+            Err(HeapError::InvalidHandle)
+        );
+        assert_eq!(heap.remove(handles[4]), Some((40, 4)));
+        assert_eq!(heap.remove(handles[4]), None);
+        assert_eq!(heap.get(handles[9]), Some((&90, &9)));
+        assert_eq!(drain(&mut heap), vec![5, 10, 20, 30, 50, 60, 80, 90]);
+    }
+
+    #[test]
+    fn test_meld() {
+        let mut left = FibonacciHeap::new();
+        let mut right = FibonacciHeap::new();
+        for key in [5, 1, 9] {
+            left.push(key, 0);
+        }
+        let handle = right.push(8, 1);
+        right.push(2, 1);
+        right.pop();
+        right.push(0, 1);
+        assert_eq!(right.decrease_key(handle, 3), Ok(()));
+        left.meld(right);
+        assert_eq!(left.len(), 5);
+        assert_eq!(drain(&mut left), vec![0, 1, 3, 5, 9]);
+    }
+
+    #[test]
+    fn test_random_operations() {
+        let mut heap = FibonacciHeap::new();
+        let mut naive: Vec<(i32, Handle)> = Vec::new();
+        let mut seed = 54321u64;
+        for _ in 0..3000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let key = (seed >> 40) as i32 % 1000;
+            match (seed >> 61) % 5 {
+                0 | 1 => naive.push((key, heap.push(key, 0))),
+                2 if !naive.is_empty() => {
+                    let i = (seed >> 20) as usize % naive.len();
+                    let lowered = naive[i].0 - key.abs() % 50;
+                    heap.decrease_key(naive[i].1, lowered).unwrap();
+                    naive[i].0 = lowered;
+                }
+                3 if !naive.is_empty() => {
+                    let i = (seed >> 20) as usize % naive.len();
+                    let (key, handle) = naive.swap_remove(i);
+                    assert_eq!(heap.remove(handle).map(|(k, _)| k), Some(key));
+                }
+                _ if !naive.is_empty() => {
+                    let popped = heap.pop().map(|(k, _)| k);
+                    assert_eq!(popped, naive.iter().map(|&(k, _)| k).min());
+                    // Equal keys may come out in any order, drop the one whose handle died
+                    let i = naive
+                        .iter()
+                        .position(|&(_, handle)| heap.get(handle).is_none())
+                        .unwrap();
+                    naive.swap_remove(i);
+                }
+                _ => {}
+            }
+            assert_eq!(heap.len(), naive.len());
+            assert_eq!(
+                heap.peek().map(|(&k, _)| k),
+                naive.iter().map(|&(k, _)| k).min()
+            );
+        }
+    }
+}
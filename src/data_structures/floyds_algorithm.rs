@@ -6,8 +6,8 @@
 use crate::data_structures::linked_list::LinkedList; // Import the LinkedList from linked_list.rs
 
 pub fn detect_cycle<T>(linked_list: &LinkedList<T>) -> Option<usize> {
-    let mut current = linked_list.head;
-    let mut checkpoint = linked_list.head;
+    let mut current = linked_list.head_node();
+    let mut checkpoint = linked_list.head_node();
     let mut steps_until_reset = 1;
     let mut times_reset = 0;
 
@@ -20,12 +20,10 @@ pub fn detect_cycle<T>(linked_list: &LinkedList<T>) -> Option<usize> {
         }
 
         unsafe {
-            let node_ptr = node.as_ptr();
-            let next = (*node_ptr).next;
-            current = next;
+            current = node.as_ref().next();
         }
         if current == checkpoint {
-            return Some(linked_list.length as usize);
+            return Some(linked_list.len());
         }
     }
 
@@ -33,17 +31,17 @@ pub fn detect_cycle<T>(linked_list: &LinkedList<T>) -> Option<usize> {
 }
 
 pub fn has_cycle<T>(linked_list: &LinkedList<T>) -> bool {
-    let mut slow = linked_list.head;
-    let mut fast = linked_list.head;
+    let mut slow = linked_list.head_node();
+    let mut fast = linked_list.head_node();
 
     while let (Some(slow_node), Some(fast_node)) = (slow, fast) {
         unsafe {
-            slow = slow_node.as_ref().next;
-            fast = fast_node.as_ref().next;
+            slow = slow_node.as_ref().next();
+            fast = fast_node.as_ref().next();
 
             if let Some(fast_next) = fast {
                 // fast = (*fast_next.as_ptr()).next;
-                fast = fast_next.as_ref().next;
+                fast = fast_next.as_ref().next();
             } else {
                 return false; // If fast reaches the end, there's no cycle
             }
@@ -82,9 +80,9 @@ mod tests {
 
         // Create a cycle for testing
         unsafe {
-            if let Some(mut tail) = linked_list.tail {
-                if let Some(head) = linked_list.head {
-                    tail.as_mut().next = Some(head);
+            if let Some(mut tail) = linked_list.tail_node() {
+                if let Some(head) = linked_list.head_node() {
+                    tail.as_mut().set_next(Some(head));
                 }
             }
         }
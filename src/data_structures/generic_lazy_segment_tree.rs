@@ -0,0 +1,434 @@
+//! A lazy segment tree generic over a value [`Monoid`] and an update [`Action`].
+//!
+//! Unlike [`LazySegmentTree`](super::LazySegmentTree), which only supports adding a value
+//! to a range, this tree accepts any family of maps acting on the stored monoid: range
+//! assignment, range addition, affine maps, chmin/chmax, or user-defined ones.
+//! Both range queries and range updates run in `O(log n)`.
+//!
+//! The tree also supports binary search on prefix/suffix aggregates through
+//! [`GenericLazySegmentTree::max_right`] and [`GenericLazySegmentTree::min_left`].
+
+use super::monoid::{Action, Monoid};
+use super::segment_tree::SegmentTreeError;
+use std::ops::Range;
+
+pub struct GenericLazySegmentTree<M: Monoid, A: Action<M>> {
+    len: usize,
+    /// Number of leaves, the smallest power of two not lower than `len`.
+    size: usize,
+    log: u32,
+    values: Vec<M::Value>,
+    /// Number of elements covered by each node, leaves past `len` cover nothing.
+    widths: Vec<usize>,
+    lazy: Vec<A::Map>,
+}
+
+impl<M: Monoid, A: Action<M>> GenericLazySegmentTree<M, A> {
+    /// Creates a tree of `len` elements, all equal to the identity of the monoid.
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(&vec![M::identity(); len])
+    }
+
+    /// Creates a tree holding a copy of `arr`, built in `O(n)`.
+    pub fn from_vec(arr: &[M::Value]) -> Self {
+        let len = arr.len();
+        let size = len.next_power_of_two();
+        let log = size.trailing_zeros();
+        let mut values = vec![M::identity(); 2 * size];
+        let mut widths = vec![0; 2 * size];
+        values[size..size + len].clone_from_slice(arr);
+        widths[size..size + len].fill(1);
+        for idx in (1..size).rev() {
+            values[idx] = M::combine(&values[2 * idx], &values[2 * idx + 1]);
+            widths[idx] = widths[2 * idx] + widths[2 * idx + 1];
+        }
+        GenericLazySegmentTree {
+            len,
+            size,
+            log,
+            values,
+            widths,
+            lazy: vec![A::identity_map(); size],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at position `idx`.
+    pub fn get(&mut self, idx: usize) -> Result<M::Value, SegmentTreeError> {
+        if idx >= self.len {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        let leaf = idx + self.size;
+        self.push_path(leaf);
+        Ok(self.values[leaf].clone())
+    }
+
+    /// Replaces the element at position `idx` by `val`.
+    pub fn set(&mut self, idx: usize, val: M::Value) -> Result<(), SegmentTreeError> {
+        if idx >= self.len {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        let leaf = idx + self.size;
+        self.push_path(leaf);
+        self.values[leaf] = val;
+        for level in 1..=self.log {
+            self.pull(leaf >> level);
+        }
+        Ok(())
+    }
+
+    /// Combines the elements in `range`, returning the identity for an empty range.
+    pub fn query(&mut self, range: Range<usize>) -> Result<M::Value, SegmentTreeError> {
+        self.check_range(&range)?;
+        if range.is_empty() {
+            return Ok(M::identity());
+        }
+        let (mut left, mut right) = (range.start + self.size, range.end + self.size);
+        self.push_boundaries(left, right);
+
+        let mut left_acc = M::identity();
+        let mut right_acc = M::identity();
+        while left < right {
+            if left & 1 == 1 {
+                left_acc = M::combine(&left_acc, &self.values[left]);
+                left += 1;
+            }
+            if right & 1 == 1 {
+                right -= 1;
+                right_acc = M::combine(&self.values[right], &right_acc);
+            }
+            left >>= 1;
+            right >>= 1;
+        }
+        Ok(M::combine(&left_acc, &right_acc))
+    }
+
+    /// Combines all the elements of the tree in `O(1)`.
+    pub fn query_all(&self) -> M::Value {
+        self.values[1].clone()
+    }
+
+    /// Applies `map` to every element in `range`.
+    pub fn apply(&mut self, range: Range<usize>, map: &A::Map) -> Result<(), SegmentTreeError> {
+        self.check_range(&range)?;
+        if range.is_empty() {
+            return Ok(());
+        }
+        let (left, right) = (range.start + self.size, range.end + self.size);
+        self.push_boundaries(left, right);
+
+        let (mut l, mut r) = (left, right);
+        while l < r {
+            if l & 1 == 1 {
+                self.apply_node(l, map);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                self.apply_node(r, map);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+
+        for level in 1..=self.log {
+            if ((left >> level) << level) != left {
+                self.pull(left >> level);
+            }
+            if ((right >> level) << level) != right {
+                self.pull((right - 1) >> level);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the largest `end` such that `pred(query(start..end))` holds.
+    ///
+    /// `pred` must be monotone: once it fails for some prefix, it must fail for every
+    /// longer one. `pred(identity)` must hold.
+    pub fn max_right<P>(&mut self, start: usize, pred: P) -> Result<usize, SegmentTreeError>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        if start > self.len {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        if start == self.len {
+            return Ok(self.len);
+        }
+        let mut node = start + self.size;
+        for level in (1..=self.log).rev() {
+            self.push(node >> level);
+        }
+        let mut acc = M::identity();
+        loop {
+            while node & 1 == 0 {
+                node >>= 1;
+            }
+            let candidate = M::combine(&acc, &self.values[node]);
+            if !pred(&candidate) {
+                while node < self.size {
+                    self.push(node);
+                    node *= 2;
+                    let candidate = M::combine(&acc, &self.values[node]);
+                    if pred(&candidate) {
+                        acc = candidate;
+                        node += 1;
+                    }
+                }
+                return Ok(node - self.size);
+            }
+            acc = candidate;
+            node += 1;
+            if node & node.wrapping_neg() == node {
+                return Ok(self.len);
+            }
+        }
+    }
+
+    /// Returns the smallest `start` such that `pred(query(start..end))` holds.
+    ///
+    /// `pred` must be monotone: once it fails for some suffix, it must fail for every
+    /// longer one. `pred(identity)` must hold.
+    pub fn min_left<P>(&mut self, end: usize, pred: P) -> Result<usize, SegmentTreeError>
+    where
+        P: Fn(&M::Value) -> bool,
+    {
+        if end > self.len {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        if end == 0 {
+            return Ok(0);
+        }
+        let mut node = end + self.size;
+        for level in (1..=self.log).rev() {
+            self.push((node - 1) >> level);
+        }
+        let mut acc = M::identity();
+        loop {
+            node -= 1;
+            while node > 1 && node & 1 == 1 {
+                node >>= 1;
+            }
+            let candidate = M::combine(&self.values[node], &acc);
+            if !pred(&candidate) {
+                while node < self.size {
+                    self.push(node);
+                    node = 2 * node + 1;
+                    let candidate = M::combine(&self.values[node], &acc);
+                    if pred(&candidate) {
+                        acc = candidate;
+                        node -= 1;
+                    }
+                }
+                return Ok(node + 1 - self.size);
+            }
+            acc = candidate;
+            if node & node.wrapping_neg() == node {
+                return Ok(0);
+            }
+        }
+    }
+
+    fn check_range(&self, range: &Range<usize>) -> Result<(), SegmentTreeError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(SegmentTreeError::InvalidRange);
+        }
+        Ok(())
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.values[node] = M::combine(&self.values[2 * node], &self.values[2 * node + 1]);
+    }
+
+    fn apply_node(&mut self, node: usize, map: &A::Map) {
+        self.values[node] = A::apply(map, &self.values[node], self.widths[node]);
+        if node < self.size {
+            self.lazy[node] = A::compose(map, &self.lazy[node]);
+        }
+    }
+
+    fn push(&mut self, node: usize) {
+        let map = std::mem::replace(&mut self.lazy[node], A::identity_map());
+        self.apply_node(2 * node, &map);
+        self.apply_node(2 * node + 1, &map);
+    }
+
+    /// Pushes every pending map on the path from the root down to `leaf`.
+    fn push_path(&mut self, leaf: usize) {
+        for level in (1..=self.log).rev() {
+            self.push(leaf >> level);
+        }
+    }
+
+    /// Pushes pending maps above the boundaries of the leaf range `left..right`.
+    fn push_boundaries(&mut self, left: usize, right: usize) {
+        for level in (1..=self.log).rev() {
+            if ((left >> level) << level) != left {
+                self.push(left >> level);
+            }
+            if ((right >> level) << level) != right {
+                self.push((right - 1) >> level);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::monoid::{
+        MaxMonoid, MinMonoid, RangeAdd, RangeAffine, RangeAssign, RangeChmax, RangeChmin, SumMonoid,
+    };
+    use super::*;
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn test_range_add_sum() {
+        let vec = vec![-30, 2, -4, 7, 3, -5, 6, 11, -20, 9, 14, 15, 5, 2, -8];
+        let mut tree = GenericLazySegmentTree::<SumMonoid<i64>, RangeAdd<i64>>::from_vec(&vec);
+        assert_eq!(tree.query(0..vec.len()), Ok(7));
+        assert_eq!(tree.apply(1..5, &3), Ok(()));
+        assert_eq!(tree.query(4..7), Ok(7));
+        assert_eq!(tree.query(0..vec.len()), Ok(19));
+        assert_eq!(tree.query(1..3), Ok(4));
+        assert_eq!(tree.query(3..3), Ok(0));
+        assert_eq!(tree.get(3), Ok(10));
+        assert_eq!(tree.query_all(), 19);
+    }
+
+    #[test]
+    fn test_range_assign_min() {
+        let vec = vec![5, 1, 4, 2, 8, 3];
+        let mut tree = GenericLazySegmentTree::<MinMonoid<i32>, RangeAssign<i32>>::from_vec(&vec);
+        assert_eq!(tree.query(0..6), Ok(1));
+        assert_eq!(tree.apply(0..3, &Some(7)), Ok(()));
+        assert_eq!(tree.query(0..6), Ok(2));
+        assert_eq!(tree.query(0..3), Ok(7));
+        assert_eq!(tree.set(1, 0), Ok(()));
+        assert_eq!(tree.query(0..3), Ok(0));
+        assert_eq!(tree.get(2), Ok(7));
+    }
+
+    #[test]
+    fn test_range_add_extreme_min() {
+        // Three elements, so the padding leaf is an empty segment holding the identity
+        let mut tree =
+            GenericLazySegmentTree::<MinMonoid<i64>, RangeAdd<i64>>::from_vec(&[i64::MAX, 7, 5]);
+        assert_eq!(tree.apply(0..3, &-1), Ok(()));
+        assert_eq!(tree.get(0), Ok(i64::MAX - 1));
+        assert_eq!(tree.query(0..3), Ok(4));
+        assert_eq!(tree.apply(1..3, &-10), Ok(()));
+        assert_eq!(tree.query(0..1), Ok(i64::MAX - 1));
+        assert_eq!(tree.query(1..3), Ok(-6));
+    }
+
+    #[test]
+    fn test_range_affine_sum() {
+        let mut tree =
+            GenericLazySegmentTree::<SumMonoid<i64>, RangeAffine<i64>>::from_vec(&[1, 2, 3, 4, 5]);
+        assert_eq!(tree.apply(1..4, &(2, 1)), Ok(())); // [1, 5, 7, 9, 5]
+        assert_eq!(tree.apply(0..3, &(-1, 0)), Ok(())); // [-1, -5, -7, 9, 5]
+        assert_eq!(tree.query(0..5), Ok(1));
+        assert_eq!(tree.query(1..4), Ok(-3));
+        assert_eq!(tree.get(2), Ok(-7));
+    }
+
+    #[test]
+    fn test_chmin_chmax() {
+        let vec = vec![3, 9, 1, 7, 5];
+        let mut min_tree =
+            GenericLazySegmentTree::<MaxMonoid<i32>, RangeChmin<i32>>::from_vec(&vec);
+        assert_eq!(min_tree.apply(0..5, &6), Ok(()));
+        assert_eq!(min_tree.query(0..5), Ok(6));
+        assert_eq!(min_tree.query(2..3), Ok(1));
+
+        let mut max_tree =
+            GenericLazySegmentTree::<MinMonoid<i32>, RangeChmax<i32>>::from_vec(&vec);
+        assert_eq!(max_tree.apply(1..4, &4), Ok(()));
+        assert_eq!(max_tree.query(0..5), Ok(3));
+        assert_eq!(max_tree.query(1..4), Ok(4));
+    }
+
+    #[test]
+    fn test_errors_and_empty() {
+        let mut tree = GenericLazySegmentTree::<SumMonoid<i32>, RangeAdd<i32>>::new(4);
+        assert_eq!(tree.query(2..5), Err(SegmentTreeError::InvalidRange));
+        assert_eq!(tree.apply(3..5, &1), Err(SegmentTreeError::InvalidRange));
+        assert_eq!(tree.get(4), Err(SegmentTreeError::IndexOutOfBounds));
+        assert_eq!(tree.set(4, 1), Err(SegmentTreeError::IndexOutOfBounds));
+        assert_eq!(
+            tree.max_right(5, |_| true),
+            Err(SegmentTreeError::IndexOutOfBounds)
+        );
+
+        let mut empty = GenericLazySegmentTree::<SumMonoid<i32>, RangeAdd<i32>>::new(0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.query(0..0), Ok(0));
+        assert_eq!(empty.max_right(0, |_| true), Ok(0));
+        assert_eq!(empty.min_left(0, |_| true), Ok(0));
+    }
+
+    #[test]
+    fn test_binary_search() {
+        let mut tree =
+            GenericLazySegmentTree::<SumMonoid<u32>, RangeAdd<u32>>::from_vec(&[1, 2, 3, 4, 5]);
+        // prefix sums: 1, 3, 6, 10, 15
+        assert_eq!(tree.max_right(0, |&s| s <= 6), Ok(3));
+        assert_eq!(tree.max_right(0, |&s| s <= 100), Ok(5));
+        assert_eq!(tree.max_right(2, |&s| s < 3), Ok(2));
+        assert_eq!(tree.min_left(5, |&s| s <= 9), Ok(3));
+        assert_eq!(tree.min_left(5, |&s| s <= 100), Ok(0));
+        assert_eq!(tree.min_left(2, |&s| s < 2), Ok(2));
+        tree.apply(0..5, &1).unwrap(); // [2, 3, 4, 5, 6]
+        assert_eq!(tree.max_right(0, |&s| s <= 6), Ok(2));
+        assert_eq!(tree.min_left(5, |&s| s <= 11), Ok(3));
+    }
+
+    #[quickcheck]
+    fn check_against_naive(array: Vec<u16>, ops: Vec<(u8, u8, u16, bool)>) -> TestResult {
+        if array.is_empty() {
+            return TestResult::discard();
+        }
+        let mut naive: Vec<u64> = array.iter().map(|&x| x as u64).collect();
+        let mut tree = GenericLazySegmentTree::<SumMonoid<u64>, RangeAdd<u64>>::from_vec(&naive);
+        for (a, b, val, is_update) in ops {
+            let (a, b) = (a as usize % naive.len(), b as usize % naive.len());
+            let range = a.min(b)..a.max(b) + 1;
+            if is_update {
+                tree.apply(range.clone(), &(val as u64)).unwrap();
+                naive[range].iter_mut().for_each(|x| *x += val as u64);
+            } else {
+                let expected: u64 = naive[range.clone()].iter().sum();
+                if tree.query(range).unwrap() != expected {
+                    return TestResult::failed();
+                }
+                let limit = val as u64;
+                let (mut prefix, mut end) = (0, a);
+                while end < naive.len() && prefix + naive[end] <= limit {
+                    prefix += naive[end];
+                    end += 1;
+                }
+                if tree.max_right(a, |&s| s <= limit).unwrap() != end {
+                    return TestResult::failed();
+                }
+                let (mut suffix, mut start) = (0, b + 1);
+                while start > 0 && suffix + naive[start - 1] <= limit {
+                    suffix += naive[start - 1];
+                    start -= 1;
+                }
+                if tree.min_left(b + 1, |&s| s <= limit).unwrap() != start {
+                    return TestResult::failed();
+                }
+            }
+        }
+        TestResult::passed()
+    }
+}
@@ -0,0 +1,152 @@
+use super::{Map, MIN_SLOTS};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+/// A hash map resolving collisions with separate chaining: every bucket holds the list
+/// of the entries hashing to it.
+///
+/// The number of buckets is doubled whenever the map holds more entries than buckets,
+/// which keeps the expected length of the chains constant.
+pub struct ChainedHashMap<K, V, S = RandomState> {
+    buckets: Vec<Vec<(K, V)>>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K: Hash + Eq, V> ChainedHashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Creates a map able to hold `capacity` entries without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ChainedHashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let buckets = capacity.max(MIN_SLOTS).next_power_of_two();
+        ChainedHashMap {
+            buckets: std::iter::repeat_with(Vec::new).take(buckets).collect(),
+            len: 0,
+            hasher,
+        }
+    }
+
+    fn bucket<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        self.hasher.hash_one(key) as usize & (self.buckets.len() - 1)
+    }
+
+    fn grow(&mut self) {
+        let buckets = self.buckets.len() * 2;
+        let old = std::mem::replace(
+            &mut self.buckets,
+            std::iter::repeat_with(Vec::new).take(buckets).collect(),
+        );
+        for (key, value) in old.into_iter().flatten() {
+            let bucket = self.bucket(&key);
+            self.buckets[bucket].push((key, value));
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for ChainedHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Map<K, V> for ChainedHashMap<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        if self.len + 1 > self.buckets.len() {
+            self.grow();
+        }
+        let bucket = self.bucket(&key);
+        self.buckets[bucket].push((key, value));
+        self.len += 1;
+        None
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.buckets[self.bucket(key)]
+            .iter()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.bucket(key);
+        self.buckets[bucket]
+            .iter_mut()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.bucket(key);
+        let position = self.buckets[bucket]
+            .iter()
+            .position(|(k, _)| k.borrow() == key)?;
+        self.len -= 1;
+        Some(self.buckets[bucket].swap_remove(position).1)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.buckets
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chains_stay_short() {
+        let mut map = ChainedHashMap::new();
+        for i in 0..4096u32 {
+            map.insert(i, ());
+        }
+        assert_eq!(map.capacity(), 4096);
+        let longest = map.buckets.iter().map(Vec::len).max().unwrap();
+        assert!(longest < 16, "longest chain: {longest}");
+        assert_eq!(map.remove(&17), Some(()));
+        assert_eq!(map.remove(&17), None);
+        assert_eq!(map.len(), 4095);
+    }
+}
@@ -0,0 +1,221 @@
+use super::{Map, MIN_SLOTS};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// Number of evictions after which an insertion is considered to be stuck in a cycle.
+const MAX_EVICTIONS: usize = 64;
+/// Number of stuck entries tolerated before the tables are grown.
+const STASH_LIMIT: usize = 4;
+
+/// Where an entry is stored.
+enum Location {
+    /// Table and slot.
+    Table(usize, usize),
+    /// Position in the stash.
+    Stash(usize),
+}
+
+/// A hash map using cuckoo hashing.
+///
+/// Every key has exactly one candidate slot in each of two tables, derived from two
+/// independent hashes of the key, so lookups and removals probe at most two slots.
+/// An insertion into an occupied slot evicts its occupant, which moves to its slot in
+/// the other table, possibly evicting another entry, and so on.
+///
+/// When evictions run in a cycle, the homeless entry is put in a small stash, and the
+/// tables are grown once the stash is full. Growing is skipped when the tables are
+/// already much larger than the number of entries, which can only happen with a
+/// degenerate hasher: the stash then absorbs the collisions.
+pub struct CuckooHashMap<K, V, S = RandomState> {
+    tables: [Vec<Option<(K, V)>>; 2],
+    stash: Vec<(K, V)>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K: Hash + Eq, V> CuckooHashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Creates a map able to hold `capacity` entries without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> CuckooHashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let slots = (capacity + 1).max(MIN_SLOTS).next_power_of_two();
+        CuckooHashMap {
+            tables: [Self::empty_table(slots), Self::empty_table(slots)],
+            stash: Vec::new(),
+            len: 0,
+            hasher,
+        }
+    }
+
+    fn empty_table(slots: usize) -> Vec<Option<(K, V)>> {
+        std::iter::repeat_with(|| None).take(slots).collect()
+    }
+
+    /// Slot of `key` in table `table`.
+    fn slot<Q: Hash + ?Sized>(&self, table: usize, key: &Q) -> usize {
+        let mut hasher = self.hasher.build_hasher();
+        hasher.write_u8(table as u8);
+        key.hash(&mut hasher);
+        hasher.finish() as usize & (self.tables[table].len() - 1)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<Location>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        for table in 0..2 {
+            let idx = self.slot(table, key);
+            if matches!(&self.tables[table][idx], Some((k, _)) if k.borrow() == key) {
+                return Some(Location::Table(table, idx));
+            }
+        }
+        self.stash
+            .iter()
+            .position(|(k, _)| k.borrow() == key)
+            .map(Location::Stash)
+    }
+
+    /// Places an entry whose key is known to be absent from the map.
+    fn place(&mut self, mut entry: (K, V)) {
+        let mut table = 0;
+        for _ in 0..MAX_EVICTIONS {
+            let idx = self.slot(table, &entry.0);
+            match &mut self.tables[table][idx] {
+                slot @ None => {
+                    *slot = Some(entry);
+                    return;
+                }
+                Some(resident) => std::mem::swap(resident, &mut entry),
+            }
+            table = 1 - table;
+        }
+
+        self.stash.push(entry);
+        if self.stash.len() > STASH_LIMIT && self.tables[0].len() < 4 * self.len.max(MIN_SLOTS) {
+            self.grow();
+        }
+    }
+
+    fn grow(&mut self) {
+        let slots = self.tables[0].len() * 2;
+        let old_tables = [Self::empty_table(slots), Self::empty_table(slots)];
+        let old_tables = std::mem::replace(&mut self.tables, old_tables);
+        let old_stash = std::mem::take(&mut self.stash);
+        for entry in old_tables.into_iter().flatten().flatten().chain(old_stash) {
+            self.place(entry);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for CuckooHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Map<K, V> for CuckooHashMap<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        // Cuckoo hashing degrades quickly above half of the total slots
+        if self.len + 1 > self.tables[0].len() {
+            self.grow();
+        }
+        self.len += 1;
+        self.place((key, value));
+        None
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find(key)? {
+            Location::Table(table, idx) => self.tables[table][idx].as_ref().map(|(_, v)| v),
+            Location::Stash(position) => Some(&self.stash[position].1),
+        }
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find(key)? {
+            Location::Table(table, idx) => self.tables[table][idx].as_mut().map(|(_, v)| v),
+            Location::Stash(position) => Some(&mut self.stash[position].1),
+        }
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = match self.find(key)? {
+            Location::Table(table, idx) => self.tables[table][idx].take()?,
+            Location::Stash(position) => self.stash.swap_remove(position),
+        };
+        self.len -= 1;
+        Some(removed.1)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.tables[0].len().max(self.len)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.tables
+            .iter()
+            .flatten()
+            .flatten()
+            .chain(self.stash.iter())
+            .map(|(key, value)| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookups_probe_two_slots() {
+        let mut map = CuckooHashMap::new();
+        for i in 0..1000u32 {
+            assert_eq!(map.insert(i, i.to_string()), None);
+        }
+        assert_eq!(map.len(), 1000);
+        // With a good hasher, nearly everything lives in the tables
+        assert!(map.stash.len() <= STASH_LIMIT);
+        for i in 0..1000u32 {
+            assert_eq!(map.get(&i), Some(&i.to_string()));
+        }
+        assert_eq!(map.insert(5, "five".to_string()), Some("5".to_string()));
+        assert_eq!(map.remove(&5), Some("five".to_string()));
+        assert_eq!(map.get(&5), None);
+    }
+}
@@ -0,0 +1,192 @@
+//! General purpose hash maps, one per collision resolution strategy.
+//!
+//! All of them implement the [`Map`] trait, accept any [`BuildHasher`](std::hash::BuildHasher)
+//! (defaulting to the randomly seeded [`RandomState`](std::collections::hash_map::RandomState)
+//! of the standard library), and grow automatically to keep their load factor bounded:
+//!
+//! * [`OpenAddressingHashMap`] stores entries directly in the table and probes it with a
+//!   [`LinearProbing`] or [`QuadraticProbing`] sequence. Removed entries leave tombstones.
+//! * [`RobinHoodHashMap`] uses linear probing, but lets an entry steal the slot of a
+//!   "richer" one (closer to its home slot), which keeps probe lengths short and uniform.
+//!   Removal shifts the following entries back, so no tombstone is needed.
+//! * [`CuckooHashMap`] gives every key one slot in each of two tables, so lookups probe at
+//!   most two slots. Insertion evicts the current occupant to its alternative slot.
+//! * [`ChainedHashMap`] keeps a list of entries per bucket (separate chaining).
+
+mod chaining;
+mod cuckoo;
+mod open_addressing;
+mod robin_hood;
+
+pub use self::chaining::ChainedHashMap;
+pub use self::cuckoo::CuckooHashMap;
+pub use self::open_addressing::{LinearProbing, OpenAddressingHashMap, Probing, QuadraticProbing};
+pub use self::robin_hood::RobinHoodHashMap;
+
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// Operations shared by the hash maps of this module, so that they can be used
+/// interchangeably, e.g. to compare their performance on the same workload.
+pub trait Map<K: Hash + Eq, V> {
+    /// Inserts a key-value pair, returning the previous value of `key` if there was one.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    /// Removes `key` from the map, returning its value if it was present.
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+
+    fn len(&self) -> usize;
+
+    /// Returns the number of entries the map can hold before it has to grow.
+    fn capacity(&self) -> usize;
+
+    /// Iterates over the entries of the map, in an arbitrary order.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Smallest number of slots allocated by the maps.
+const MIN_SLOTS: usize = 8;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher, Hasher};
+
+    /// Replays the same random workload on `map` and on the standard `HashMap`.
+    fn check_against_std<M: Map<u32, u64>>(mut map: M, key_range: u32) {
+        let mut expected = HashMap::new();
+        let mut seed = 42u64;
+        for step in 0..5000u64 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let key = (seed >> 33) as u32 % key_range;
+            match seed >> 62 {
+                0 | 1 => assert_eq!(map.insert(key, step), expected.insert(key, step)),
+                2 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                _ => {
+                    if let Some(value) = map.get_mut(&key) {
+                        *value += 1;
+                    }
+                    if let Some(value) = expected.get_mut(&key) {
+                        *value += 1;
+                    }
+                }
+            }
+            assert_eq!(map.get(&key), expected.get(&key));
+            assert_eq!(map.len(), expected.len());
+            assert!(map.len() <= map.capacity());
+        }
+        let mut entries: Vec<(u32, u64)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        let mut expected_entries: Vec<(u32, u64)> = expected.into_iter().collect();
+        entries.sort_unstable();
+        expected_entries.sort_unstable();
+        assert_eq!(entries, expected_entries);
+    }
+
+    /// The worst possible hash function: every key collides.
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            7
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    #[derive(Default)]
+    struct BuildConstantHasher;
+
+    impl BuildHasher for BuildConstantHasher {
+        type Hasher = ConstantHasher;
+
+        fn build_hasher(&self) -> ConstantHasher {
+            ConstantHasher
+        }
+    }
+
+    #[test]
+    fn test_all_strategies_against_std() {
+        for key_range in [16, 1000, 100_000] {
+            check_against_std(
+                OpenAddressingHashMap::<_, _, LinearProbing>::new(),
+                key_range,
+            );
+            check_against_std(
+                OpenAddressingHashMap::<_, _, QuadraticProbing>::new(),
+                key_range,
+            );
+            check_against_std(RobinHoodHashMap::new(), key_range);
+            check_against_std(CuckooHashMap::new(), key_range);
+            check_against_std(ChainedHashMap::new(), key_range);
+        }
+    }
+
+    #[test]
+    fn test_custom_hashers() {
+        type Deterministic = BuildHasherDefault<DefaultHasher>;
+        check_against_std(
+            OpenAddressingHashMap::<_, _, QuadraticProbing, _>::with_hasher(
+                Deterministic::default(),
+            ),
+            500,
+        );
+        check_against_std(RobinHoodHashMap::with_hasher(Deterministic::default()), 500);
+        check_against_std(CuckooHashMap::with_hasher(Deterministic::default()), 500);
+        check_against_std(ChainedHashMap::with_hasher(Deterministic::default()), 500);
+
+        // Degenerate hashing must stay correct, only slower
+        check_against_std(
+            OpenAddressingHashMap::<_, _, LinearProbing, _>::with_hasher(BuildConstantHasher),
+            40,
+        );
+        check_against_std(
+            OpenAddressingHashMap::<_, _, QuadraticProbing, _>::with_hasher(BuildConstantHasher),
+            40,
+        );
+        check_against_std(RobinHoodHashMap::with_hasher(BuildConstantHasher), 40);
+        check_against_std(CuckooHashMap::with_hasher(BuildConstantHasher), 40);
+        check_against_std(ChainedHashMap::with_hasher(BuildConstantHasher), 40);
+    }
+
+    #[test]
+    fn test_borrowed_keys() {
+        let mut map: RobinHoodHashMap<String, usize> = RobinHoodHashMap::new();
+        map.insert("apple".to_string(), 1);
+        map.insert("banana".to_string(), 2);
+        assert_eq!(map.get("apple"), Some(&1));
+        assert!(map.contains_key("banana"));
+        assert_eq!(map.remove("banana"), Some(2));
+        assert!(!map.contains_key("banana"));
+        assert_eq!(map.len(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+use super::{Map, MIN_SLOTS};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// A probe sequence: the offsets, relative to the home slot of a key, of the slots
+/// visited when looking for that key.
+///
+/// With a power-of-two number of slots, the sequence must visit every slot.
+pub trait Probing {
+    fn offset(step: usize) -> usize;
+}
+
+/// Visits the slots following the home slot one by one.
+///
+/// This is the most cache-friendly sequence, but keys hashing to nearby slots form long
+/// runs of occupied slots (primary clustering).
+pub struct LinearProbing;
+
+impl Probing for LinearProbing {
+    fn offset(step: usize) -> usize {
+        step
+    }
+}
+
+/// Visits the slots at triangular offsets `0, 1, 3, 6, 10, ...` from the home slot.
+///
+/// Triangular numbers reach every slot of a power-of-two table, and the growing jumps
+/// break the runs formed by linear probing.
+pub struct QuadraticProbing;
+
+impl Probing for QuadraticProbing {
+    fn offset(step: usize) -> usize {
+        step * (step + 1) / 2
+    }
+}
+
+enum Slot<K, V> {
+    Empty,
+    /// A removed entry. Lookups must probe past it, but insertions can reuse it.
+    Tombstone,
+    Occupied(K, V),
+}
+
+/// A hash map storing its entries in a single table, resolving collisions by probing
+/// the table with the sequence `P`.
+///
+/// The table is grown when occupied slots and tombstones exceed 3/4 of its size,
+/// and is rebuilt at the same size when most of those are tombstones.
+pub struct OpenAddressingHashMap<K, V, P = LinearProbing, S = RandomState> {
+    slots: Vec<Slot<K, V>>,
+    len: usize,
+    tombstones: usize,
+    hasher: S,
+    probing: PhantomData<P>,
+}
+
+impl<K: Hash + Eq, V, P: Probing> OpenAddressingHashMap<K, V, P, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Creates a map able to hold `capacity` entries without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, P: Probing, S: BuildHasher> OpenAddressingHashMap<K, V, P, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let slots = (capacity * 4 / 3 + 1).max(MIN_SLOTS).next_power_of_two();
+        OpenAddressingHashMap {
+            slots: std::iter::repeat_with(|| Slot::Empty).take(slots).collect(),
+            len: 0,
+            tombstones: 0,
+            hasher,
+            probing: PhantomData,
+        }
+    }
+
+    /// Iterates over the slots probed for a key of hash `hash`.
+    fn probe(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let mask = self.slots.len() - 1;
+        let home = hash as usize;
+        (0..self.slots.len()).map(move |step| home.wrapping_add(P::offset(step)) & mask)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        for idx in self.probe(self.hasher.hash_one(key)) {
+            match &self.slots[idx] {
+                Slot::Empty => return None,
+                Slot::Occupied(k, _) if k.borrow() == key => return Some(idx),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Rebuilds the table with `slots` slots, dropping the tombstones.
+    fn rebuild(&mut self, slots: usize) {
+        let old = std::mem::replace(
+            &mut self.slots,
+            std::iter::repeat_with(|| Slot::Empty).take(slots).collect(),
+        );
+        self.tombstones = 0;
+        for slot in old {
+            if let Slot::Occupied(key, value) = slot {
+                let idx = self
+                    .probe(self.hasher.hash_one(&key))
+                    .find(|&idx| matches!(self.slots[idx], Slot::Empty))
+                    .expect("the new table has free slots");
+                self.slots[idx] = Slot::Occupied(key, value);
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, P: Probing, S: BuildHasher + Default> Default
+    for OpenAddressingHashMap<K, V, P, S>
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V, P: Probing, S: BuildHasher> Map<K, V> for OpenAddressingHashMap<K, V, P, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(idx) = self.find(&key) {
+            if let Slot::Occupied(_, old) = &mut self.slots[idx] {
+                return Some(std::mem::replace(old, value));
+            }
+        }
+
+        if (self.len + self.tombstones + 1) * 4 > self.slots.len() * 3 {
+            // Only grow if the live entries need it, otherwise just clear the tombstones
+            let slots = if (self.len + 1) * 2 > self.slots.len() {
+                self.slots.len() * 2
+            } else {
+                self.slots.len()
+            };
+            self.rebuild(slots);
+        }
+
+        let idx = self
+            .probe(self.hasher.hash_one(&key))
+            .find(|&idx| !matches!(self.slots[idx], Slot::Occupied(..)))
+            .expect("the table has free slots");
+        if matches!(self.slots[idx], Slot::Tombstone) {
+            self.tombstones -= 1;
+        }
+        self.slots[idx] = Slot::Occupied(key, value);
+        self.len += 1;
+        None
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &self.slots[self.find(key)?] {
+            Slot::Occupied(_, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        match &mut self.slots[idx] {
+            Slot::Occupied(_, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        match std::mem::replace(&mut self.slots[idx], Slot::Tombstone) {
+            Slot::Occupied(_, value) => {
+                self.len -= 1;
+                self.tombstones += 1;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len() * 3 / 4
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.slots.iter().filter_map(|slot| match slot {
+            Slot::Occupied(key, value) => Some((key, value)),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_sequences_visit_every_slot() {
+        for slots in [8usize, 64, 1024] {
+            let mut linear: Vec<usize> = (0..slots)
+                .map(|s| LinearProbing::offset(s) % slots)
+                .collect();
+            let mut quadratic: Vec<usize> = (0..slots)
+                .map(|s| QuadraticProbing::offset(s) % slots)
+                .collect();
+            linear.sort_unstable();
+            quadratic.sort_unstable();
+            assert_eq!(linear, (0..slots).collect::<Vec<_>>());
+            assert_eq!(quadratic, (0..slots).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_tombstones_are_reused() {
+        let mut map: OpenAddressingHashMap<u32, u32> = OpenAddressingHashMap::with_capacity(4);
+        let slots = map.slots.len();
+        for round in 0..100 {
+            assert_eq!(map.insert(round, round), None);
+            assert_eq!(map.remove(&round), Some(round));
+        }
+        // Churn alone must not make the table grow
+        assert_eq!(map.slots.len(), slots);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut map: OpenAddressingHashMap<&str, i32, QuadraticProbing> =
+            OpenAddressingHashMap::new();
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("a", 2), Some(1));
+        assert_eq!(map.get("a"), Some(&2));
+        *map.get_mut("a").unwrap() += 5;
+        assert_eq!(map.get("a"), Some(&7));
+        assert_eq!(map.len(), 1);
+    }
+}
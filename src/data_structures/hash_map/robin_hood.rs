@@ -0,0 +1,239 @@
+use super::{Map, MIN_SLOTS};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// A linear probing hash map using Robin Hood hashing.
+///
+/// While probing for a free slot, an inserted entry takes the place of any entry that
+/// is closer to its own home slot, and the evicted entry continues the search instead.
+/// This bounds the variance of the probe lengths, which allows a high load factor
+/// (7/8 here), and lets lookups stop as soon as they meet an entry closer to its home
+/// than the searched key would be.
+///
+/// Removal shifts the following entries of the cluster one slot back, so the table
+/// never contains tombstones.
+pub struct RobinHoodHashMap<K, V, S = RandomState> {
+    slots: Vec<Option<Bucket<K, V>>>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K: Hash + Eq, V> RobinHoodHashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Creates a map able to hold `capacity` entries without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> RobinHoodHashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let slots = (capacity * 8 / 7 + 1).max(MIN_SLOTS).next_power_of_two();
+        RobinHoodHashMap {
+            slots: std::iter::repeat_with(|| None).take(slots).collect(),
+            len: 0,
+            hasher,
+        }
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    /// Distance between `idx` and the home slot of an entry of hash `hash`.
+    fn displacement(&self, idx: usize, hash: u64) -> usize {
+        idx.wrapping_sub(hash as usize) & self.mask()
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher.hash_one(key);
+        let mut idx = hash as usize & self.mask();
+        for distance in 0..self.slots.len() {
+            let bucket = self.slots[idx].as_ref()?;
+            if self.displacement(idx, bucket.hash) < distance {
+                // The key would have evicted this entry had it been inserted
+                return None;
+            }
+            if bucket.hash == hash && bucket.key.borrow() == key {
+                return Some(idx);
+            }
+            idx = (idx + 1) & self.mask();
+        }
+        None
+    }
+
+    /// Places a bucket whose key is known to be absent from the table.
+    fn place(&mut self, mut bucket: Bucket<K, V>) {
+        let mut idx = bucket.hash as usize & self.mask();
+        let mut distance = 0;
+        let mask = self.mask();
+        loop {
+            match &mut self.slots[idx] {
+                slot @ None => {
+                    *slot = Some(bucket);
+                    return;
+                }
+                Some(resident) => {
+                    let resident_distance = idx.wrapping_sub(resident.hash as usize) & mask;
+                    if resident_distance < distance {
+                        std::mem::swap(resident, &mut bucket);
+                        distance = resident_distance;
+                    }
+                }
+            }
+            idx = (idx + 1) & mask;
+            distance += 1;
+        }
+    }
+
+    fn grow(&mut self) {
+        let slots = self.slots.len() * 2;
+        let old = std::mem::replace(
+            &mut self.slots,
+            std::iter::repeat_with(|| None).take(slots).collect(),
+        );
+        for bucket in old.into_iter().flatten() {
+            self.place(bucket);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for RobinHoodHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Map<K, V> for RobinHoodHashMap<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(idx) = self.find(&key) {
+            let bucket = self.slots[idx].as_mut().expect("found slots are occupied");
+            return Some(std::mem::replace(&mut bucket.value, value));
+        }
+        if (self.len + 1) * 8 > self.slots.len() * 7 {
+            self.grow();
+        }
+        let hash = self.hasher.hash_one(&key);
+        self.place(Bucket { hash, key, value });
+        self.len += 1;
+        None
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.slots[idx].as_ref().map(|bucket| &bucket.value)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.slots[idx].as_mut().map(|bucket| &mut bucket.value)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut idx = self.find(key)?;
+        let removed = self.slots[idx].take()?;
+        self.len -= 1;
+
+        // Backward shift: pull the rest of the cluster one slot closer to home
+        loop {
+            let next = (idx + 1) & self.mask();
+            match &self.slots[next] {
+                Some(bucket) if self.displacement(next, bucket.hash) > 0 => {
+                    self.slots[idx] = self.slots[next].take();
+                    idx = next;
+                }
+                _ => break,
+            }
+        }
+        Some(removed.value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len() * 7 / 8
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_lengths_stay_short() {
+        let mut map = RobinHoodHashMap::with_capacity(10_000);
+        for i in 0..10_000u64 {
+            map.insert(i, i * 2);
+        }
+        let longest = (0..map.slots.len())
+            .filter_map(|idx| {
+                map.slots[idx]
+                    .as_ref()
+                    .map(|bucket| map.displacement(idx, bucket.hash))
+            })
+            .max()
+            .unwrap();
+        // Expected to be logarithmic at this load factor, leave plenty of slack
+        assert!(longest < 64, "longest probe: {longest}");
+        assert_eq!(map.get(&4321), Some(&8642));
+    }
+
+    #[test]
+    fn test_remove_shifts_back() {
+        let mut map = RobinHoodHashMap::new();
+        for i in 0..6 {
+            map.insert(i, i);
+        }
+        for i in (0..6).step_by(2) {
+            assert_eq!(map.remove(&i), Some(i));
+        }
+        for i in 0..6 {
+            assert_eq!(map.get(&i), (i % 2 == 1).then_some(&i));
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 3);
+    }
+}
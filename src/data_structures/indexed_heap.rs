@@ -0,0 +1,232 @@
+//! An indexed d-ary heap.
+//!
+//! The elements are stored in an arena and the heap itself only orders their indices,
+//! while every element remembers its current position in the heap. This allows
+//! `decrease_key` and `remove` by handle in `O(log n)`.
+//!
+//! The arity `D` defaults to 2 (a binary heap). Larger arities make the tree shallower,
+//! so `push` and `decrease_key` get cheaper while `pop` gets more expensive, which pays
+//! off for algorithms such as Dijkstra's on dense graphs.
+
+use super::addressable_heap::{AddressableHeap, Arena, Handle, HeapError};
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    /// Position of the entry in `IndexedHeap::heap`.
+    position: usize,
+}
+
+pub struct IndexedHeap<K: Ord, V, const D: usize = 2> {
+    entries: Arena<Entry<K, V>>,
+    /// Arena indices of the entries, in heap order.
+    heap: Vec<usize>,
+}
+
+impl<K: Ord, V, const D: usize> IndexedHeap<K, V, D> {
+    /// Creates an empty heap. Using an arity `D < 2` is a compile-time error.
+    pub fn new() -> Self {
+        const { assert!(D >= 2, "a heap needs an arity of at least 2") };
+        IndexedHeap {
+            entries: Arena::new(),
+            heap: Vec::new(),
+        }
+    }
+
+    fn key_at(&self, position: usize) -> &K {
+        &self.entries[self.heap[position]].key
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        let (entry_a, entry_b) = (self.heap[a], self.heap[b]);
+        self.entries[entry_a].position = a;
+        self.entries[entry_b].position = b;
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / D;
+            if self.key_at(position) < self.key_at(parent) {
+                self.swap(position, parent);
+                position = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let first_child = position * D + 1;
+            if first_child >= self.heap.len() {
+                break;
+            }
+            let last_child = (first_child + D).min(self.heap.len());
+            let smallest = (first_child + 1..last_child).fold(first_child, |best, child| {
+                if self.key_at(child) < self.key_at(best) {
+                    child
+                } else {
+                    best
+                }
+            });
+            if self.key_at(smallest) < self.key_at(position) {
+                self.swap(position, smallest);
+                position = smallest;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Removes the entry at `position` of the heap and returns it.
+    fn remove_at(&mut self, position: usize) -> (K, V) {
+        let last = self.heap.len() - 1;
+        self.swap(position, last);
+        let index = self.heap.pop().unwrap();
+        if position < self.heap.len() {
+            self.sift_down(position);
+            self.sift_up(position);
+        }
+        let entry = self.entries.remove(index);
+        (entry.key, entry.value)
+    }
+}
+
+impl<K: Ord, V, const D: usize> Default for IndexedHeap<K, V, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, const D: usize> AddressableHeap<K, V> for IndexedHeap<K, V, D> {
+    fn push(&mut self, key: K, value: V) -> Handle {
+        let position = self.heap.len();
+        let index = self.entries.insert(Entry {
+            key,
+            value,
+            position,
+        });
+        self.heap.push(index);
+        self.sift_up(position);
+        self.entries.handle(index)
+    }
+
+    fn peek(&self) -> Option<(&K, &V)> {
+        self.heap.first().map(|&index| {
+            let entry = &self.entries[index];
+            (&entry.key, &entry.value)
+        })
+    }
+
+    fn pop(&mut self) -> Option<(K, V)> {
+        if self.heap.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    fn get(&self, handle: Handle) -> Option<(&K, &V)> {
+        self.entries.resolve(handle).map(|index| {
+            let entry = &self.entries[index];
+            (&entry.key, &entry.value)
+        })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, key: K) -> Result<(), HeapError> {
+        let index = self
+            .entries
+            .resolve(handle)
+            .ok_or(HeapError::InvalidHandle)?;
+        if key > self.entries[index].key {
+            return Err(HeapError::KeyIncreased);
+        }
+        self.entries[index].key = key;
+        self.sift_up(self.entries[index].position);
+        Ok(())
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(K, V)> {
+        let index = self.entries.resolve(handle)?;
+        Some(self.remove_at(self.entries[index].position))
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain<const D: usize>(heap: &mut IndexedHeap<i32, char, D>) -> Vec<(i32, char)> {
+        std::iter::from_fn(|| heap.pop()).collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap: IndexedHeap<i32, char> = IndexedHeap::new();
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+        for (key, value) in [(5, 'a'), (3, 'b'), (8, 'c'), (1, 'd'), (9, 'e')] {
+            heap.push(key, value);
+        }
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.peek(), Some((&1, &'d')));
+        assert_eq!(
+            drain(&mut heap),
+            vec![(1, 'd'), (3, 'b'), (5, 'a'), (8, 'c'), (9, 'e')]
+        );
+    }
+
+    #[test]
+    fn test_decrease_key_and_remove() {
+        let mut heap: IndexedHeap<i32, char, 4> = IndexedHeap::new();
+        let handles: Vec<Handle> = "abcdefgh"
+            .chars()
+            .enumerate()
+            .map(|(i, c)| heap.push(10 * i as i32, c))
+            .collect();
+        assert_eq!(heap.decrease_key(handles[6], -1), Ok(()));
+        assert_eq!(heap.peek(), Some((&-1, &'g')));
+        assert_eq!(
+            heap.decrease_key(handles[2], 100),
+            Err(HeapError::KeyIncreased)
+        );
+        assert_eq!(heap.remove(handles[0]), Some((0, 'a')));
+        assert_eq!(heap.remove(handles[0]), None);
+        assert_eq!(
+            heap.decrease_key(handles[0], -5),
+            Err(HeapError::InvalidHandle)
+        );
+        assert_eq!(heap.get(handles[3]), Some((&30, &'d')));
+        assert_eq!(heap.remove(handles[7]), Some((70, 'h')));
+        assert_eq!(
+            drain(&mut heap),
+            vec![
+                (-1, 'g'),
+                (10, 'b'),
+                (20, 'c'),
+                (30, 'd'),
+                (40, 'e'),
+                (50, 'f')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sorts_like_std() {
+        let mut keys: Vec<i32> = (0..200).map(|i| (i * 7919) % 211 - 100).collect();
+        let mut heap: IndexedHeap<i32, (), 3> = IndexedHeap::default();
+        let handles: Vec<Handle> = keys.iter().map(|&k| heap.push(k, ())).collect();
+        for (i, handle) in handles.iter().enumerate().step_by(3) {
+            keys[i] -= 50;
+            heap.decrease_key(*handle, keys[i]).unwrap();
+        }
+        keys.sort_unstable();
+        let popped: Vec<i32> = std::iter::from_fn(|| heap.pop()).map(|(k, ())| k).collect();
+        assert_eq!(popped, keys);
+    }
+}
@@ -0,0 +1,239 @@
+//! A set of values stored as disjoint, merged intervals.
+//!
+//! The set keeps half-open intervals `[start, end)` in a `BTreeMap` from start to end, with no
+//! two intervals overlapping or touching: inserting an interval merges it with its neighbours,
+//! and removing one trims or splits the intervals it covers. Each update takes
+//! `O((k + 1) log n)` for `k` intervals merged or removed.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T: Ord + Clone> {
+    intervals: BTreeMap<T, T>,
+}
+
+impl<T: Ord + Clone> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet {
+            intervals: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of disjoint intervals.
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.interval_at(value).is_some()
+    }
+
+    /// Returns the interval of the set containing `value`.
+    pub fn interval_at(&self, value: &T) -> Option<Range<T>> {
+        let (start, end) = self.intervals.range(..=value).next_back()?;
+        (value < end).then(|| start.clone()..end.clone())
+    }
+
+    /// Adds all the values of `range` to the set.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        let Range { mut start, mut end } = range;
+        // Absorb an interval starting before and reaching the new one
+        if let Some((before_start, before_end)) = self.intervals.range(..=&start).next_back() {
+            if *before_end >= start {
+                start = before_start.clone();
+                end = end.max(before_end.clone());
+            }
+        }
+        // Absorb the intervals starting inside or right at the end of the new one
+        let absorbed: Vec<T> = self
+            .intervals
+            .range(&start..=&end)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in absorbed {
+            let other_end = self.intervals.remove(&key).expect("key was just listed");
+            end = end.max(other_end);
+        }
+        self.intervals.insert(start, end);
+    }
+
+    /// Removes all the values of `range` from the set.
+    pub fn remove(&mut self, range: &Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        // Trim an interval starting before the removed range, keeping what sticks out after it
+        if let Some((before_start, before_end)) = self.intervals.range(..&range.start).next_back() {
+            if *before_end > range.start {
+                let (before_start, before_end) = (before_start.clone(), before_end.clone());
+                self.intervals.insert(before_start, range.start.clone());
+                if before_end > range.end {
+                    self.intervals.insert(range.end.clone(), before_end);
+                }
+            }
+        }
+        let covered: Vec<T> = self
+            .intervals
+            .range(&range.start..&range.end)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in covered {
+            let end = self.intervals.remove(&key).expect("key was just listed");
+            if end > range.end {
+                self.intervals.insert(range.end.clone(), end);
+            }
+        }
+    }
+
+    /// Iterates over the disjoint intervals in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.intervals
+            .iter()
+            .map(|(start, end)| start.clone()..end.clone())
+    }
+
+    /// Returns the intervals of the set overlapping `range`, clipped to it.
+    pub fn overlapping(&self, range: &Range<T>) -> Vec<Range<T>> {
+        let mut clipped = Self::new();
+        if range.start < range.end {
+            let first = self
+                .interval_at(&range.start)
+                .map_or_else(|| range.start.clone(), |interval| interval.start);
+            for (start, end) in self.intervals.range(first..range.end.clone()) {
+                clipped.insert(
+                    start.clone().max(range.start.clone())..end.clone().min(range.end.clone()),
+                );
+            }
+        }
+        clipped.iter().collect()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range);
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.remove(&range);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Self::new();
+        for range in self.iter() {
+            for overlap in other.overlapping(&range) {
+                result.insert(overlap);
+            }
+        }
+        result
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    #[test]
+    fn merges_on_insert() {
+        let mut set: IntervalSet<u32> = [1..3, 5..7, 9..12].into_iter().collect();
+        set.insert(3..5);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..7, 9..12]);
+        set.insert(8..9);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..7, 8..12]);
+        set.insert(0..20);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..20]);
+        set.insert(4..4);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn splits_on_remove() {
+        let mut set: IntervalSet<u32> = [0..10, 12..20].into_iter().collect();
+        set.remove(&(3..5));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..3, 5..10, 12..20]);
+        set.remove(&(8..15));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0..3, 5..8, 15..20]);
+        assert!(set.contains(&2) && !set.contains(&3) && !set.contains(&20));
+        assert_eq!(set.interval_at(&6), Some(5..8));
+        assert_eq!(set.overlapping(&(2..16)), vec![2..3, 5..8, 15..16]);
+    }
+
+    #[test]
+    fn set_operations() {
+        // IPv4 ranges as integers
+        let allowed: IntervalSet<u32> = [0x0A00_0000..0x0B00_0000, 0xC0A8_0000..0xC0A9_0000]
+            .into_iter()
+            .collect();
+        let blocked: IntervalSet<u32> = [0x0A0A_0000..0x0A0B_0000, 0xC0A8_FF00..0xC0A9_1000]
+            .into_iter()
+            .collect();
+        let effective = allowed.difference(&blocked);
+        assert_eq!(
+            effective.iter().collect::<Vec<_>>(),
+            vec![
+                0x0A00_0000..0x0A0A_0000,
+                0x0A0B_0000..0x0B00_0000,
+                0xC0A8_0000..0xC0A8_FF00
+            ]
+        );
+        assert_eq!(
+            allowed.intersection(&blocked).iter().collect::<Vec<_>>(),
+            vec![0x0A0A_0000..0x0A0B_0000, 0xC0A8_FF00..0xC0A9_0000]
+        );
+        assert_eq!(effective.union(&blocked).len(), 2);
+    }
+
+    #[test]
+    fn matches_bitmap() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut set = IntervalSet::new();
+        let mut bitmap = [false; 200];
+        for _ in 0..2_000 {
+            let start = rng.random_range(0..200);
+            let end = rng.random_range(start..=200);
+            let add = rng.random_range(0..2) == 0;
+            if add {
+                set.insert(start..end);
+            } else {
+                set.remove(&(start..end));
+            }
+            bitmap[start..end].fill(add);
+        }
+        for (value, &present) in bitmap.iter().enumerate() {
+            assert_eq!(set.contains(&value), present);
+        }
+        let intervals: Vec<Range<usize>> = set.iter().collect();
+        assert!(intervals.windows(2).all(|pair| pair[0].end < pair[1].start));
+    }
+}
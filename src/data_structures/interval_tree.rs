@@ -0,0 +1,373 @@
+//! An augmented interval tree.
+//!
+//! Intervals are half-open ranges `[start, end)` stored in an AVL tree ordered by
+//! `(start, end)`, where each node also records the largest `end` in its subtree. A query only
+//! descends into a subtree if that maximum lies after the start of the query, and only into a
+//! right subtree if the node starts before the end of the query. Each of the `k` overlapping
+//! intervals costs at most one root-to-leaf path of visits, so reporting them takes
+//! `O(min(n, k log n))` time (`O(log n)` when there are none).
+//!
+//! References: [Wikipedia](https://en.wikipedia.org/wiki/Interval_tree#Augmented_tree)
+
+use std::cmp::{max, Ordering};
+use std::mem;
+use std::ops::Range;
+
+struct Node<T, V> {
+    range: Range<T>,
+    value: V,
+    /// The largest `end` of the intervals in this subtree.
+    max_end: T,
+    height: usize,
+    left: Option<Box<Node<T, V>>>,
+    right: Option<Box<Node<T, V>>>,
+}
+
+/// A map from half-open intervals to values, answering overlap queries.
+pub struct IntervalTree<T: Ord + Clone, V> {
+    root: Option<Box<Node<T, V>>>,
+    length: usize,
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        IntervalTree {
+            root: None,
+            length: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Inserts `value` for the interval `range`, returning the previous value of that exact interval.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn insert(&mut self, range: Range<T>, value: V) -> Option<V> {
+        assert!(range.start < range.end, "interval must not be empty");
+        let replaced = insert(&mut self.root, range, value);
+        if replaced.is_none() {
+            self.length += 1;
+        }
+        replaced
+    }
+
+    /// Removes the interval `range`, returning its value.
+    pub fn remove(&mut self, range: &Range<T>) -> Option<V> {
+        let removed = remove(&mut self.root, range);
+        if removed.is_some() {
+            self.length -= 1;
+        }
+        removed
+    }
+
+    pub fn get(&self, range: &Range<T>) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match cmp_ranges(range, &node.range) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            }
+        }
+        None
+    }
+
+    /// Returns the intervals containing `point`, ordered by start.
+    pub fn containing(&self, point: &T) -> Vec<(&Range<T>, &V)> {
+        let mut found = Vec::new();
+        collect_overlapping(&self.root, point, |start| start <= point, &mut found);
+        found
+    }
+
+    /// Returns the intervals overlapping `range`, ordered by start.
+    pub fn overlapping(&self, range: &Range<T>) -> Vec<(&Range<T>, &V)> {
+        let mut found = Vec::new();
+        if range.start < range.end {
+            // An interval overlaps `range` if it starts before its end and ends after its start
+            collect_overlapping(
+                &self.root,
+                &range.start,
+                |start| start < &range.end,
+                &mut found,
+            );
+        }
+        found
+    }
+
+    /// Iterates over the intervals ordered by `(start, end)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&Range<T>, &V)> {
+        let mut stack = Vec::new();
+        push_left(&self.root, &mut stack);
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            push_left(&node.right, &mut stack);
+            Some((&node.range, &node.value))
+        })
+    }
+}
+
+fn push_left<'a, T, V>(mut current: &'a Option<Box<Node<T, V>>>, stack: &mut Vec<&'a Node<T, V>>) {
+    while let Some(node) = current {
+        stack.push(node);
+        current = &node.left;
+    }
+}
+
+fn cmp_ranges<T: Ord>(a: &Range<T>, b: &Range<T>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+/// Collects the intervals of the subtree which end after `from` and whose start satisfies
+/// `starts_before`, which must hold for all the starts up to some point.
+fn collect_overlapping<'a, T: Ord, V>(
+    current: &'a Option<Box<Node<T, V>>>,
+    from: &T,
+    starts_before: impl Fn(&T) -> bool + Copy,
+    found: &mut Vec<(&'a Range<T>, &'a V)>,
+) {
+    let Some(node) = current else {
+        return;
+    };
+    if node.max_end <= *from {
+        // Every interval of the subtree ends before the query
+        return;
+    }
+    collect_overlapping(&node.left, from, starts_before, found);
+    if !starts_before(&node.range.start) {
+        // This node and its right subtree start too late
+        return;
+    }
+    if node.range.end > *from {
+        found.push((&node.range, &node.value));
+    }
+    collect_overlapping(&node.right, from, starts_before, found);
+}
+
+fn height<T, V>(tree: &Option<Box<Node<T, V>>>) -> usize {
+    tree.as_ref().map_or(0, |node| node.height)
+}
+
+/// Recomputes the height and maximum end of `node` from its children.
+fn update<T: Ord + Clone, V>(node: &mut Node<T, V>) {
+    node.height = 1 + max(height(&node.left), height(&node.right));
+    let mut max_end = node.range.end.clone();
+    for child in [&node.left, &node.right].into_iter().flatten() {
+        if child.max_end > max_end {
+            max_end = child.max_end.clone();
+        }
+    }
+    node.max_end = max_end;
+}
+
+fn rotate_right<T: Ord + Clone, V>(root: &mut Box<Node<T, V>>) {
+    let mut left = root.left.take().expect("rotation needs a left child");
+    root.left = left.right.take();
+    update(root);
+    mem::swap(root, &mut left);
+    root.right = Some(left);
+    update(root);
+}
+
+fn rotate_left<T: Ord + Clone, V>(root: &mut Box<Node<T, V>>) {
+    let mut right = root.right.take().expect("rotation needs a right child");
+    root.right = right.left.take();
+    update(root);
+    mem::swap(root, &mut right);
+    root.left = Some(right);
+    update(root);
+}
+
+/// Restores the AVL balance of `node` after one of its subtrees changed height by one.
+fn rebalance<T: Ord + Clone, V>(node: &mut Box<Node<T, V>>) {
+    update(node);
+    let (left, right) = (height(&node.left), height(&node.right));
+    if left > right + 1 {
+        let child = node.left.as_mut().expect("left subtree is higher");
+        if height(&child.right) > height(&child.left) {
+            rotate_left(child);
+        }
+        rotate_right(node);
+    } else if right > left + 1 {
+        let child = node.right.as_mut().expect("right subtree is higher");
+        if height(&child.left) > height(&child.right) {
+            rotate_right(child);
+        }
+        rotate_left(node);
+    }
+}
+
+fn insert<T: Ord + Clone, V>(
+    tree: &mut Option<Box<Node<T, V>>>,
+    range: Range<T>,
+    value: V,
+) -> Option<V> {
+    let Some(node) = tree else {
+        *tree = Some(Box::new(Node {
+            max_end: range.end.clone(),
+            range,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }));
+        return None;
+    };
+    let replaced = match cmp_ranges(&range, &node.range) {
+        Ordering::Equal => return Some(mem::replace(&mut node.value, value)),
+        Ordering::Less => insert(&mut node.left, range, value),
+        Ordering::Greater => insert(&mut node.right, range, value),
+    };
+    rebalance(node);
+    replaced
+}
+
+fn remove<T: Ord + Clone, V>(tree: &mut Option<Box<Node<T, V>>>, range: &Range<T>) -> Option<V> {
+    let node = tree.as_mut()?;
+    let removed = match cmp_ranges(range, &node.range) {
+        Ordering::Less => remove(&mut node.left, range),
+        Ordering::Greater => remove(&mut node.right, range),
+        Ordering::Equal => {
+            let mut node = tree.take().expect("node exists");
+            *tree = match (node.left.take(), node.right.take()) {
+                (None, None) => None,
+                (Some(child), None) | (None, Some(child)) => Some(child),
+                (Some(left), Some(right)) => {
+                    // Replace the node by the smallest interval of its right subtree
+                    let mut right = Some(right);
+                    let mut successor = take_min(&mut right);
+                    successor.left = Some(left);
+                    successor.right = right;
+                    rebalance(&mut successor);
+                    Some(successor)
+                }
+            };
+            return Some(node.value);
+        }
+    };
+    rebalance(node);
+    removed
+}
+
+/// Detaches the node with the smallest interval of a non-empty subtree.
+fn take_min<T: Ord + Clone, V>(tree: &mut Option<Box<Node<T, V>>>) -> Box<Node<T, V>> {
+    let node = tree.as_mut().expect("subtree is not empty");
+    if node.left.is_some() {
+        let min = take_min(&mut node.left);
+        rebalance(node);
+        min
+    } else {
+        let mut min = tree.take().expect("subtree is not empty");
+        *tree = min.right.take();
+        min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    fn is_balanced<T: Ord + Clone, V>(tree: &Option<Box<Node<T, V>>>) -> bool {
+        tree.as_ref().is_none_or(|node| {
+            height(&node.left).abs_diff(height(&node.right)) <= 1
+                && is_balanced(&node.left)
+                && is_balanced(&node.right)
+        })
+    }
+
+    #[test]
+    fn calendar() {
+        let mut calendar = IntervalTree::new();
+        calendar.insert(9..10, "standup");
+        calendar.insert(13..15, "review");
+        calendar.insert(14..16, "interview");
+        assert_eq!(calendar.insert(9..10, "daily"), Some("standup"));
+        assert_eq!(calendar.len(), 3);
+
+        let names = |found: Vec<(&Range<i32>, &&'static str)>| {
+            found.into_iter().map(|(_, name)| *name).collect::<Vec<_>>()
+        };
+        assert_eq!(names(calendar.containing(&14)), vec!["review", "interview"]);
+        assert_eq!(names(calendar.containing(&10)), Vec::<&str>::new());
+        assert_eq!(
+            names(calendar.overlapping(&(9..14))),
+            vec!["daily", "review"]
+        );
+        assert_eq!(names(calendar.overlapping(&(10..13))), Vec::<&str>::new());
+        assert_eq!(names(calendar.overlapping(&(12..12))), Vec::<&str>::new());
+
+        assert_eq!(calendar.remove(&(13..15)), Some("review"));
+        assert_eq!(calendar.remove(&(13..15)), None);
+        assert_eq!(calendar.get(&(14..16)), Some(&"interview"));
+        assert_eq!(
+            calendar
+                .iter()
+                .map(|(range, _)| range.clone())
+                .collect::<Vec<_>>(),
+            vec![9..10, 14..16]
+        );
+    }
+
+    #[test]
+    fn matches_naive_queries() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let mut tree = IntervalTree::new();
+        let mut naive: Vec<Range<u32>> = Vec::new();
+        for round in 0..3_000_u32 {
+            let start = rng.random_range(0..1_000);
+            let range = start..start + rng.random_range(1..50);
+            if rng.random_range(0..3) == 0 && !naive.is_empty() {
+                let victim = naive.swap_remove(rng.random_range(0..naive.len()));
+                assert_eq!(tree.remove(&victim), Some(()));
+            } else if !naive.contains(&range) {
+                naive.push(range.clone());
+                assert_eq!(tree.insert(range, ()), None);
+            }
+            if round % 10 == 0 {
+                let point = rng.random_range(0..1_050);
+                let query = point..point + rng.random_range(1..30);
+                let mut expected: Vec<Range<u32>> = naive
+                    .iter()
+                    .filter(|r| r.contains(&point))
+                    .cloned()
+                    .collect();
+                expected.sort_by(cmp_ranges);
+                let found: Vec<Range<u32>> = tree
+                    .containing(&point)
+                    .into_iter()
+                    .map(|(r, ())| r.clone())
+                    .collect();
+                assert_eq!(found, expected);
+
+                let mut expected: Vec<Range<u32>> = naive
+                    .iter()
+                    .filter(|r| r.start < query.end && query.start < r.end)
+                    .cloned()
+                    .collect();
+                expected.sort_by(cmp_ranges);
+                let found: Vec<Range<u32>> = tree
+                    .overlapping(&query)
+                    .into_iter()
+                    .map(|(r, ())| r.clone())
+                    .collect();
+                assert_eq!(found, expected);
+            }
+        }
+        assert_eq!(tree.len(), naive.len());
+        assert!(is_balanced(&tree.root));
+    }
+}
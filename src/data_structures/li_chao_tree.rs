@@ -0,0 +1,251 @@
+//! Li Chao tree: the minimum (or maximum) of a set of lines at a given point.
+//!
+//! The tree is a segment tree over an integer domain `[lo, hi)` where each node keeps one line:
+//! the one winning at the middle of its segment. When a line is added to a node, the line
+//! losing at the middle can only win on one side of it, so it is pushed down to that child
+//! only. Adding a line and querying a point both take `O(log(hi - lo))`; a line restricted to a
+//! sub-segment takes `O(log^2(hi - lo))`. Nodes are only allocated when needed, so the domain
+//! can be as wide as `i64` allows.
+//!
+//! This solves the "convex hull trick" problems without requiring sorted slopes or queries.
+//!
+//! References: [cp-algorithms](https://cp-algorithms.com/geometry/convex_hull_trick.html#li-chao-tree)
+
+/// The line `y = slope * x + intercept`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line {
+    pub slope: i64,
+    pub intercept: i64,
+}
+
+impl Line {
+    pub const fn new(slope: i64, intercept: i64) -> Self {
+        Line { slope, intercept }
+    }
+
+    /// Evaluates the line at `x`. Overflowing `i64` is the caller's responsibility.
+    pub const fn eval(&self, x: i64) -> i64 {
+        self.slope * x + self.intercept
+    }
+
+    const fn negate(self) -> Self {
+        Line::new(-self.slope, -self.intercept)
+    }
+}
+
+struct Node {
+    /// `None` until a line covering the whole segment of the node is pushed here.
+    line: Option<Line>,
+    children: [Option<usize>; 2],
+}
+
+pub struct LiChaoTree {
+    lo: i64,
+    hi: i64,
+    /// The nodes, the root first once a line was added.
+    nodes: Vec<Node>,
+    /// Maximum queries are minimum queries on the negated lines.
+    maximize: bool,
+}
+
+impl LiChaoTree {
+    /// Creates a tree answering minimum queries for `x` in `[lo, hi)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain is empty.
+    pub fn min(lo: i64, hi: i64) -> Self {
+        Self::with_direction(lo, hi, false)
+    }
+
+    /// Creates a tree answering maximum queries for `x` in `[lo, hi)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain is empty.
+    pub fn max(lo: i64, hi: i64) -> Self {
+        Self::with_direction(lo, hi, true)
+    }
+
+    fn with_direction(lo: i64, hi: i64, maximize: bool) -> Self {
+        assert!(lo < hi, "domain must not be empty");
+        LiChaoTree {
+            lo,
+            hi,
+            nodes: Vec::new(),
+            maximize,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a line over the whole domain.
+    pub fn add_line(&mut self, line: Line) {
+        self.add_segment(line, self.lo, self.hi);
+    }
+
+    /// Adds a line over `[from, to)`, clipped to the domain.
+    pub fn add_segment(&mut self, line: Line, from: i64, to: i64) {
+        let line = if self.maximize { line.negate() } else { line };
+        let (from, to) = (from.max(self.lo), to.min(self.hi));
+        if from >= to {
+            return;
+        }
+        if self.nodes.is_empty() {
+            self.nodes.push(Node {
+                line: None,
+                children: [None, None],
+            });
+        }
+        self.add(0, self.lo, self.hi, line, from, to);
+    }
+
+    /// Returns the minimum (or maximum) of the lines at `x`, or `None` if no line covers `x`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is outside the domain.
+    pub fn query(&self, x: i64) -> Option<i64> {
+        assert!((self.lo..self.hi).contains(&x), "x is outside the domain");
+        let mut best: Option<i64> = None;
+        let (mut lo, mut hi) = (self.lo, self.hi);
+        let mut current = (!self.nodes.is_empty()).then_some(0);
+        while let Some(index) = current {
+            let node = &self.nodes[index];
+            if let Some(line) = node.line {
+                let value = line.eval(x);
+                best = Some(best.map_or(value, |best| best.min(value)));
+            }
+            let mid = midpoint(lo, hi);
+            let side = usize::from(x >= mid);
+            (lo, hi) = if side == 0 { (lo, mid) } else { (mid, hi) };
+            current = node.children[side];
+        }
+        if self.maximize {
+            best.map(|value| -value)
+        } else {
+            best
+        }
+    }
+
+    fn add(&mut self, index: usize, lo: i64, hi: i64, mut line: Line, from: i64, to: i64) {
+        let mid = midpoint(lo, hi);
+        if from <= lo && hi <= to {
+            // The segment covers the node: keep the winner at the middle and push the loser
+            // towards the side where it may still win
+            let Some(current) = self.nodes[index].line else {
+                self.nodes[index].line = Some(line);
+                return;
+            };
+            if line.eval(mid) < current.eval(mid) {
+                self.nodes[index].line = Some(line);
+                line = current;
+            }
+            if hi - lo == 1 {
+                return;
+            }
+            let current = self.nodes[index].line.expect("node has a line");
+            if line.eval(lo) < current.eval(lo) {
+                self.add_child(index, 0, lo, mid, line, from, to);
+            } else if line.eval(hi - 1) < current.eval(hi - 1) {
+                self.add_child(index, 1, mid, hi, line, from, to);
+            }
+            return;
+        }
+        if from < mid {
+            self.add_child(index, 0, lo, mid, line, from, to);
+        }
+        if mid < to {
+            self.add_child(index, 1, mid, hi, line, from, to);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_child(
+        &mut self,
+        index: usize,
+        side: usize,
+        lo: i64,
+        hi: i64,
+        line: Line,
+        from: i64,
+        to: i64,
+    ) {
+        let child = match self.nodes[index].children[side] {
+            Some(child) => child,
+            None => {
+                self.nodes.push(Node {
+                    line: None,
+                    children: [None, None],
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[index].children[side] = Some(child);
+                child
+            }
+        };
+        self.add(child, lo, hi, line, from, to);
+    }
+}
+
+/// Returns the middle of `[lo, hi)` without overflowing.
+const fn midpoint(lo: i64, hi: i64) -> i64 {
+    lo + ((hi - lo) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    #[test]
+    fn min_and_max_of_lines() {
+        let mut min = LiChaoTree::min(-100, 100);
+        let mut max = LiChaoTree::max(-100, 100);
+        assert_eq!(min.query(0), None);
+        for line in [Line::new(2, 0), Line::new(-1, 3), Line::new(0, 1)] {
+            min.add_line(line);
+            max.add_line(line);
+        }
+        assert_eq!(min.query(-5), Some(-10));
+        assert_eq!(min.query(1), Some(1));
+        assert_eq!(min.query(10), Some(-7));
+        assert_eq!(max.query(10), Some(20));
+        assert_eq!(max.query(-10), Some(13));
+        assert!(!min.is_empty());
+    }
+
+    #[test]
+    fn matches_naive_with_segments() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let (lo, hi) = (-500, 500);
+        let mut tree = LiChaoTree::min(lo, hi);
+        let mut segments = Vec::new();
+        for _ in 0..300 {
+            let line = Line::new(rng.random_range(-50..50), rng.random_range(-10_000..10_000));
+            let from = rng.random_range(lo..hi);
+            let to = rng.random_range(from + 1..=hi);
+            tree.add_segment(line, from, to);
+            segments.push((line, from, to));
+        }
+        for x in lo..hi {
+            let expected = segments
+                .iter()
+                .filter(|(_, from, to)| (*from..*to).contains(&x))
+                .map(|(line, _, _)| line.eval(x))
+                .min();
+            assert_eq!(tree.query(x), expected);
+        }
+    }
+
+    #[test]
+    fn wide_domain() {
+        let mut tree = LiChaoTree::max(-1_000_000_000, 1_000_000_000);
+        tree.add_line(Line::new(3, -7));
+        tree.add_line(Line::new(-2, 5));
+        assert_eq!(tree.query(999_999_999), Some(2_999_999_990));
+        assert_eq!(tree.query(-1_000_000_000), Some(2_000_000_005));
+        assert!(tree.nodes.len() < 100);
+    }
+}
@@ -1,27 +1,48 @@
+//! A doubly linked list with cursors.
+//!
+//! Nodes are heap allocated and linked with raw pointers, but the public interface is safe:
+//! elements are reached by index, through double-ended iterators, or through cursors, which
+//! point at an element and can insert, remove or split the list around it in `O(1)`.
+
 use std::fmt::{self, Display, Formatter};
 use std::marker::PhantomData;
 use std::ptr::NonNull;
 
 pub struct Node<T> {
-    pub val: T,
-    pub next: Option<NonNull<Node<T>>>,
+    val: T,
+    next: Option<NonNull<Node<T>>>,
     prev: Option<NonNull<Node<T>>>,
 }
 
 impl<T> Node<T> {
-    fn new(t: T) -> Node<T> {
-        Node {
-            val: t,
-            prev: None,
-            next: None,
-        }
+    pub(super) const fn val(&self) -> &T {
+        &self.val
+    }
+
+    pub(super) fn val_mut(&mut self) -> &mut T {
+        &mut self.val
+    }
+
+    /// The node after this one, `None` at the tail.
+    pub(super) const fn next(&self) -> Option<NonNull<Self>> {
+        self.next
+    }
+
+    /// Points this node at `next`, e.g. to make a cycle.
+    ///
+    /// # Safety
+    ///
+    /// The list of the node must not be used afterwards, except to walk it.
+    #[cfg(test)]
+    pub(super) unsafe fn set_next(&mut self, next: Option<NonNull<Self>>) {
+        self.next = next;
     }
 }
 
 pub struct LinkedList<T> {
-    pub length: u32,
-    pub head: Option<NonNull<Node<T>>>,
-    pub tail: Option<NonNull<Node<T>>>,
+    length: usize,
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
     // Act like we own boxed nodes since we construct and leak them
     marker: PhantomData<Box<Node<T>>>,
 }
@@ -33,7 +54,7 @@ impl<T> Default for LinkedList<T> {
 }
 
 impl<T> LinkedList<T> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             length: 0,
             head: None,
@@ -42,166 +63,306 @@ impl<T> LinkedList<T> {
         }
     }
 
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
     pub fn insert_at_head(&mut self, obj: T) {
-        let mut node = Box::new(Node::new(obj));
-        node.next = self.head;
-        node.prev = None;
-        let node_ptr = NonNull::new(Box::into_raw(node));
-        match self.head {
-            None => self.tail = node_ptr,
-            Some(head_ptr) => unsafe { (*head_ptr.as_ptr()).prev = node_ptr },
-        }
-        self.head = node_ptr;
-        self.length += 1;
+        self.link_between(None, self.head, obj);
     }
 
     pub fn insert_at_tail(&mut self, obj: T) {
-        let mut node = Box::new(Node::new(obj));
-        node.next = None;
-        node.prev = self.tail;
-        let node_ptr = NonNull::new(Box::into_raw(node));
-        match self.tail {
-            None => self.head = node_ptr,
-            Some(tail_ptr) => unsafe { (*tail_ptr.as_ptr()).next = node_ptr },
-        }
-        self.tail = node_ptr;
-        self.length += 1;
+        self.link_between(self.tail, None, obj);
     }
 
-    pub fn insert_at_ith(&mut self, index: u32, obj: T) {
-        if self.length < index {
-            panic!("Index out of bounds");
-        }
+    /// Inserts `obj` so that it ends up at position `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert_at_ith(&mut self, index: usize, obj: T) {
+        assert!(index <= self.length, "Index out of bounds");
+        let next = self.node_at(index);
+        let prev = next.map_or(self.tail, |node| unsafe { (*node.as_ptr()).prev });
+        self.link_between(prev, next, obj);
+    }
 
-        if index == 0 || self.head.is_none() {
-            self.insert_at_head(obj);
-            return;
-        }
+    pub fn delete_head(&mut self) -> Option<T> {
+        // Safety: the head belongs to this list
+        self.head.map(|head| unsafe { self.unlink(head) })
+    }
 
-        if self.length == index {
-            self.insert_at_tail(obj);
-            return;
-        }
+    pub fn delete_tail(&mut self) -> Option<T> {
+        // Safety: the tail belongs to this list
+        self.tail.map(|tail| unsafe { self.unlink(tail) })
+    }
 
-        if let Some(mut ith_node) = self.head {
-            for _ in 0..index {
-                unsafe {
-                    match (*ith_node.as_ptr()).next {
-                        None => panic!("Index out of bounds"),
-                        Some(next_ptr) => ith_node = next_ptr,
-                    }
-                }
-            }
+    /// Removes and returns the element at position `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    pub fn delete_ith(&mut self, index: usize) -> Option<T> {
+        assert!(index < self.length, "Index out of bounds");
+        // Safety: the node was found in this list
+        self.node_at(index).map(|node| unsafe { self.unlink(node) })
+    }
 
-            let mut node = Box::new(Node::new(obj));
-            unsafe {
-                node.prev = (*ith_node.as_ptr()).prev;
-                node.next = Some(ith_node);
-                if let Some(p) = (*ith_node.as_ptr()).prev {
-                    let node_ptr = NonNull::new(Box::into_raw(node));
-                    println!("{:?}", (*p.as_ptr()).next);
-                    (*p.as_ptr()).next = node_ptr;
-                    (*ith_node.as_ptr()).prev = node_ptr;
-                    self.length += 1;
-                }
-            }
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.node_at(index)
+            .map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.node_at(index)
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.length,
+            marker: PhantomData,
         }
     }
 
-    pub fn delete_head(&mut self) -> Option<T> {
-        // Safety: head_ptr points to a leaked boxed node managed by this list
-        // We reassign pointers that pointed to the head node
-        if self.length == 0 {
-            return None;
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.length,
+            marker: PhantomData,
         }
+    }
 
-        self.head.map(|head_ptr| unsafe {
-            let old_head = Box::from_raw(head_ptr.as_ptr());
-            match old_head.next {
-                Some(mut next_ptr) => next_ptr.as_mut().prev = None,
-                None => self.tail = None,
+    /// Moves all the elements of `other` to the end of this list in `O(1)`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        let Some(mut tail) = self.tail else {
+            std::mem::swap(self, other);
+            return;
+        };
+        if let Some(mut other_head) = other.head.take() {
+            unsafe {
+                tail.as_mut().next = Some(other_head);
+                other_head.as_mut().prev = Some(tail);
             }
-            self.head = old_head.next;
-            self.length = self.length.checked_add_signed(-1).unwrap_or(0);
-            old_head.val
-        })
-        // None
+            self.tail = other.tail.take();
+            self.length += std::mem::take(&mut other.length);
+        }
     }
 
-    pub fn delete_tail(&mut self) -> Option<T> {
-        // Safety: tail_ptr points to a leaked boxed node managed by this list
-        // We reassign pointers that pointed to the tail node
-        self.tail.map(|tail_ptr| unsafe {
-            let old_tail = Box::from_raw(tail_ptr.as_ptr());
-            match old_tail.prev {
-                Some(mut prev) => prev.as_mut().next = None,
-                None => self.head = None,
-            }
-            self.tail = old_tail.prev;
-            self.length -= 1;
-            old_tail.val
-        })
+    /// Splits the list in two at `at`: this list keeps the elements before `at`, and the
+    /// elements from `at` on are returned.
+    ///
+    /// Relinking is `O(1)`, but reaching the node at `at` takes `O(min(at, len - at))`.
+    /// Use [`CursorMut::split_after`] to split at a known position in `O(1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.length, "Index out of bounds");
+        match at.checked_sub(1).and_then(|last| self.node_at(last)) {
+            // Safety: the node was found in this list
+            Some(node) => unsafe { self.split_after_node(node, at) },
+            None => std::mem::take(self),
+        }
     }
 
-    pub fn delete_ith(&mut self, index: u32) -> Option<T> {
-        if self.length <= index {
-            panic!("Index out of bounds");
+    /// Returns a cursor pointing at the first element, or at the "ghost" position if the list is empty.
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.head,
+            index: 0,
+            list: self,
         }
+    }
 
-        if index == 0 || self.head.is_none() {
-            return self.delete_head();
+    /// Returns a cursor pointing at the last element, or at the "ghost" position if the list is empty.
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.tail,
+            index: self.length.saturating_sub(1),
+            list: self,
         }
+    }
 
-        if self.length - 1 == index {
-            return self.delete_tail();
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
         }
+    }
 
-        if let Some(mut ith_node) = self.head {
-            for _ in 0..index {
-                unsafe {
-                    match (*ith_node.as_ptr()).next {
-                        None => panic!("Index out of bounds"),
-                        Some(next_ptr) => ith_node = next_ptr,
-                    }
-                }
-            }
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.length.saturating_sub(1),
+            list: self,
+        }
+    }
 
-            unsafe {
-                let old_ith = Box::from_raw(ith_node.as_ptr());
-                if let Some(mut prev) = old_ith.prev {
-                    prev.as_mut().next = old_ith.next;
+    /// Returns the node at `index`, walking from the closest end of the list.
+    fn node_at(&self, index: usize) -> Option<NonNull<Node<T>>> {
+        if index >= self.length {
+            return None;
+        }
+        unsafe {
+            if index < self.length / 2 {
+                let mut node = self.head?;
+                for _ in 0..index {
+                    node = (*node.as_ptr()).next?;
                 }
-                if let Some(mut next) = old_ith.next {
-                    next.as_mut().prev = old_ith.prev;
+                Some(node)
+            } else {
+                let mut node = self.tail?;
+                for _ in index + 1..self.length {
+                    node = (*node.as_ptr()).prev?;
                 }
+                Some(node)
+            }
+        }
+    }
 
-                self.length -= 1;
-                Some(old_ith.val)
+    /// Links a new node holding `val` between `prev` and `next`, which must be adjacent nodes of
+    /// this list (`None` standing for the ends of the list).
+    fn link_between(
+        &mut self,
+        prev: Option<NonNull<Node<T>>>,
+        next: Option<NonNull<Node<T>>>,
+        val: T,
+    ) -> NonNull<Node<T>> {
+        let node = NonNull::from(Box::leak(Box::new(Node { val, next, prev })));
+        // Safety: the neighbours belong to this list
+        unsafe {
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(node),
+                None => self.head = Some(node),
             }
-        } else {
-            None
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+        self.length += 1;
+        node
+    }
+
+    /// Unlinks `node` from the list, frees it and returns its value.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let node = Box::from_raw(node.as_ptr());
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.tail = node.prev,
         }
+        self.length -= 1;
+        node.val
     }
 
-    pub fn get(&self, index: i32) -> Option<&T> {
-        Self::get_ith_node(self.head, index).map(|ptr| unsafe { &(*ptr.as_ptr()).val })
+    /// Splits the list after `node`, the `kept`-th node of the list, and returns the nodes following it.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list, at position `kept - 1`.
+    unsafe fn split_after_node(&mut self, node: NonNull<Node<T>>, kept: usize) -> Self {
+        let mut rest = Self::new();
+        if let Some(next) = (*node.as_ptr()).next.take() {
+            (*next.as_ptr()).prev = None;
+            rest.head = Some(next);
+            rest.tail = self.tail;
+            rest.length = self.length - kept;
+        }
+        self.tail = Some(node);
+        self.length = kept;
+        rest
     }
 
-    fn get_ith_node(node: Option<NonNull<Node<T>>>, index: i32) -> Option<NonNull<Node<T>>> {
-        match node {
-            None => None,
-            Some(next_ptr) => match index {
-                0 => Some(next_ptr),
-                _ => Self::get_ith_node(unsafe { (*next_ptr.as_ptr()).next }, index - 1),
-            },
+    /// The first node of the list.
+    pub(super) const fn head_node(&self) -> Option<NonNull<Node<T>>> {
+        self.head
+    }
+
+    /// The last node of the list.
+    #[cfg(test)]
+    pub(super) const fn tail_node(&self) -> Option<NonNull<Node<T>>> {
+        self.tail
+    }
+
+    /// Inserts `val` at the front of the list, returning its node, which stays valid until it is removed.
+    pub(super) fn push_front_node(&mut self, val: T) -> NonNull<Node<T>> {
+        self.link_between(None, self.head, val)
+    }
+
+    /// Removes `node` from the list and returns its value.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list.
+    pub(super) unsafe fn remove_node(&mut self, node: NonNull<Node<T>>) -> T {
+        self.unlink(node)
+    }
+
+    /// Moves `node` to the front of the list in `O(1)`.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list.
+    pub(super) unsafe fn move_node_to_front(&mut self, node: NonNull<Node<T>>) {
+        if self.head == Some(node) {
+            return;
+        }
+        let (prev, next) = ((*node.as_ptr()).prev, (*node.as_ptr()).next);
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = next,
+            None => self.head = next,
         }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = prev,
+            None => self.tail = prev,
+        }
+        (*node.as_ptr()).prev = None;
+        (*node.as_ptr()).next = self.head;
+        match self.head {
+            Some(head) => (*head.as_ptr()).prev = Some(node),
+            None => self.tail = Some(node),
+        }
+        self.head = Some(node);
     }
 }
 
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
-        // Pop items until there are none left
-        while self.delete_head().is_some() {}
+        // Free exactly `length` nodes following the `next` links, without touching their
+        // neighbours, so that a list whose tail was linked back into it is freed correctly
+        let mut current = self.head;
+        for _ in 0..self.length {
+            if let Some(node) = current {
+                let node = unsafe { Box::from_raw(node.as_ptr()) };
+                current = node.next;
+            }
+        }
     }
 }
 
@@ -210,22 +371,352 @@ where
     T: Display,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.head {
-            Some(node) => write!(f, "{}", unsafe { node.as_ref() }),
-            None => Ok(()),
+        for (i, val) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{val}")?;
         }
+        Ok(())
     }
 }
 
-impl<T> Display for Node<T>
-where
-    T: Display,
-{
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.next {
-            Some(node) => write!(f, "{}, {}", self.val, unsafe { node.as_ref() }),
-            None => write!(f, "{}", self.val),
+/// A double-ended iterator over the elements of a [`LinkedList`].
+pub struct Iter<'a, T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &node.val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A double-ended iterator over mutable references to the elements of a [`LinkedList`].
+pub struct IterMut<'a, T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
         }
+        self.head.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &mut node.val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &mut node.val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// An owning double-ended iterator over the elements of a [`LinkedList`].
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.delete_head()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.length, Some(self.list.length))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.delete_tail()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert_at_tail(val);
+        }
+    }
+}
+
+/// A read-only cursor over a [`LinkedList`].
+///
+/// A cursor points at an element, or at a "ghost" position between the tail and the head,
+/// reached by moving past either end. Moving past the ghost wraps around to the other end.
+pub struct Cursor<'a, T> {
+    current: Option<NonNull<Node<T>>>,
+    /// Position of the current element, `len` for the ghost position.
+    index: usize,
+    list: &'a LinkedList<T>,
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// Returns the position of the current element, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn move_next(&mut self) {
+        (self.current, self.index) = match self.current {
+            None => (self.list.head, 0),
+            Some(node) => (unsafe { (*node.as_ptr()).next }, self.index + 1),
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        (self.current, self.index) = match self.current {
+            None => (self.list.tail, self.list.length.saturating_sub(1)),
+            Some(node) => match unsafe { (*node.as_ptr()).prev } {
+                None => (None, self.list.length),
+                prev => (prev, self.index - 1),
+            },
+        };
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = match self.current {
+            None => self.list.head,
+            Some(node) => unsafe { (*node.as_ptr()).next },
+        };
+        next.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = match self.current {
+            None => self.list.tail,
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+        };
+        prev.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+}
+
+/// A cursor over a [`LinkedList`] which can also edit the list around the current element.
+///
+/// It has the same ghost position semantics as [`Cursor`].
+pub struct CursorMut<'a, T> {
+    current: Option<NonNull<Node<T>>>,
+    /// Position of the current element, `len` for the ghost position.
+    index: usize,
+    list: &'a mut LinkedList<T>,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// Returns the position of the current element, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn move_next(&mut self) {
+        (self.current, self.index) = match self.current {
+            None => (self.list.head, 0),
+            Some(node) => (unsafe { (*node.as_ptr()).next }, self.index + 1),
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        (self.current, self.index) = match self.current {
+            None => (self.list.tail, self.list.length.saturating_sub(1)),
+            Some(node) => match unsafe { (*node.as_ptr()).prev } {
+                None => (None, self.list.length),
+                prev => (prev, self.index - 1),
+            },
+        };
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            None => self.list.head,
+            Some(node) => unsafe { (*node.as_ptr()).next },
+        };
+        next.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            None => self.list.tail,
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+        };
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    /// Inserts `val` after the current element, or at the front of the list at the ghost position.
+    pub fn insert_after(&mut self, val: T) {
+        match self.current {
+            None => {
+                self.list.insert_at_head(val);
+                self.index = self.list.length;
+            }
+            Some(node) => {
+                let next = unsafe { (*node.as_ptr()).next };
+                self.list.link_between(Some(node), next, val);
+            }
+        }
+    }
+
+    /// Inserts `val` before the current element, or at the back of the list at the ghost position.
+    pub fn insert_before(&mut self, val: T) {
+        match self.current {
+            None => {
+                self.list.insert_at_tail(val);
+                self.index = self.list.length;
+            }
+            Some(node) => {
+                let prev = unsafe { (*node.as_ptr()).prev };
+                self.list.link_between(prev, Some(node), val);
+                self.index += 1;
+            }
+        }
+    }
+
+    /// Removes the current element and returns it. The cursor then points at the next element
+    /// (or at the ghost position if the removed element was the last one).
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        self.current = unsafe { (*node.as_ptr()).next };
+        // Safety: the cursor only points at nodes of its list
+        Some(unsafe { self.list.unlink(node) })
+    }
+
+    /// Splits the list after the current element in `O(1)`, returning the elements following it.
+    /// At the ghost position, the whole list is returned.
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        match self.current {
+            // Safety: the cursor knows the position of its node
+            Some(node) => unsafe { self.list.split_after_node(node, self.index + 1) },
+            None => {
+                self.index = 0;
+                std::mem::take(self.list)
+            }
+        }
+    }
+
+    /// Splits the list before the current element in `O(1)`, returning the elements preceding it.
+    /// At the ghost position, the whole list is returned.
+    pub fn split_before(&mut self) -> LinkedList<T> {
+        let Some(node) = self.current else {
+            self.index = 0;
+            return std::mem::take(self.list);
+        };
+        let mut before = LinkedList::new();
+        if let Some(prev) = unsafe { (*node.as_ptr()).prev.take() } {
+            unsafe {
+                (*prev.as_ptr()).next = None;
+            }
+            before.head = self.list.head;
+            before.tail = Some(prev);
+            before.length = self.index;
+            self.list.head = Some(node);
+            self.list.length -= self.index;
+            self.index = 0;
+        }
+        before
     }
 }
 
@@ -500,6 +991,91 @@ mod tests {
         assert_eq!("B", *retrived_item.unwrap());
     }
 
+    #[test]
+    fn iterators_are_double_ended() {
+        let mut list: LinkedList<i32> = (1..=5).collect();
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            list.iter().rev().copied().collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+
+        for val in &mut list {
+            *val *= 10;
+        }
+        list.extend([60, 70]);
+        assert_eq!(list.to_string(), "10, 20, 30, 40, 50, 60, 70");
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(70));
+        assert_eq!(into_iter.collect::<Vec<_>>(), vec![10, 20, 30, 40, 50, 60]);
+    }
+
+    #[test]
+    fn append_and_split_off() {
+        let mut list: LinkedList<i32> = (0..3).collect();
+        let mut other: LinkedList<i32> = (3..6).collect();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.len(), 6);
+        assert_eq!(list.back(), Some(&5));
+
+        let mut tail = list.split_off(4);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(tail.iter().rev().copied().collect::<Vec<_>>(), vec![5, 4]);
+        let mut empty = LinkedList::new();
+        empty.append(&mut tail);
+        assert_eq!(empty.front(), Some(&4));
+        assert_eq!(list.split_off(0).len(), 4);
+        assert!(list.is_empty() && list.front().is_none());
+    }
+
+    #[test]
+    fn cursor_edits() {
+        let mut list: LinkedList<char> = "bd".chars().collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_before('a');
+        assert_eq!(cursor.index(), Some(1));
+        cursor.insert_after('c');
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 'c'));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.insert_before('e');
+        cursor.insert_after('_');
+        assert_eq!(cursor.peek_next(), Some(&mut '_'));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some('_'));
+        assert_eq!(cursor.current(), Some(&mut 'a'));
+        assert_eq!(list.iter().collect::<String>(), "abcde");
+
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(3));
+        let after = cursor.split_after();
+        assert_eq!(after.iter().collect::<String>(), "e");
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(before.iter().collect::<String>(), "abc");
+        assert_eq!(list.iter().collect::<String>(), "d");
+
+        let list: LinkedList<i32> = (0..3).collect();
+        let mut cursor = list.cursor_back();
+        assert_eq!(cursor.peek_prev(), Some(&1));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&0));
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&2)));
+    }
+
     #[test]
     #[should_panic(expected = "Index out of bounds")]
     fn delete_ith_panics_if_index_equals_length() {
@@ -0,0 +1,150 @@
+//! A least-recently-used cache.
+//!
+//! Entries live in a [`LinkedList`] ordered from the most to the least recently used, and a
+//! hash map indexes the list nodes by key, so lookups, insertions, promotions and evictions
+//! all run in `O(1)`.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ptr::NonNull;
+
+use super::linked_list::{LinkedList, Node};
+
+pub struct LruCache<K, V> {
+    capacity: usize,
+    index: HashMap<K, NonNull<Node<(K, V)>>>,
+    entries: LinkedList<(K, V)>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates a cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            index: HashMap::with_capacity(capacity),
+            entries: LinkedList::new(),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Returns the value of `key` and marks it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|val| &*val)
+    }
+
+    /// Returns the value of `key` mutably and marks it as the most recently used.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let node = *self.index.get(key)?;
+        // Safety: indexed nodes belong to `entries` until they are removed from both
+        unsafe {
+            self.entries.move_node_to_front(node);
+            Some(&mut (*node.as_ptr()).val_mut().1)
+        }
+    }
+
+    /// Returns the value of `key` without changing its recency.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let node = self.index.get(key)?;
+        Some(unsafe { &node.as_ref().val().1 })
+    }
+
+    /// Inserts `val` under `key` as the most recently used entry.
+    ///
+    /// Returns the entry that was pushed out: the previous entry for `key` if there was one,
+    /// otherwise the least recently used entry if the cache was full.
+    pub fn put(&mut self, key: K, val: V) -> Option<(K, V)> {
+        let replaced = match self.index.remove(&key) {
+            // Safety: indexed nodes belong to `entries`
+            Some(node) => Some(unsafe { self.entries.remove_node(node) }),
+            None if self.len() == self.capacity => {
+                self.entries.delete_tail().inspect(|(old, _)| {
+                    self.index.remove(old);
+                })
+            }
+            None => None,
+        };
+        let node = self.entries.push_front_node((key.clone(), val));
+        self.index.insert(key, node);
+        replaced
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let node = self.index.remove(key)?;
+        // Safety: indexed nodes belong to `entries`
+        Some(unsafe { self.entries.remove_node(node) }.1)
+    }
+
+    /// Iterates over the entries from the most to the least recently used.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, val)| (key, val))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.put(1, "one"), None);
+        assert_eq!(cache.put(2, "two"), None);
+        assert_eq!(cache.get(&1), Some(&"one"));
+        assert_eq!(cache.put(3, "three"), Some((2, "two")));
+        assert!(!cache.contains(&2));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.iter().collect::<Vec<_>>(),
+            vec![(&3, &"three"), (&1, &"one")]
+        );
+    }
+
+    #[test]
+    fn put_replaces_and_promotes() {
+        let mut cache = LruCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        assert_eq!(cache.put("a", 10), Some(("a", 1)));
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.put("d", 4), Some(("b", 2)));
+        assert_eq!(cache.peek(&"c"), Some(&3));
+        assert_eq!(cache.put("e", 5), Some(("c", 3)));
+    }
+
+    #[test]
+    fn get_mut_and_remove() {
+        let mut cache = LruCache::new(2);
+        cache.put('x', vec![1]);
+        cache.put('y', vec![2]);
+        cache.get_mut(&'x').unwrap().push(3);
+        assert_eq!(cache.remove(&'y'), Some(vec![2]));
+        assert_eq!(cache.remove(&'y'), None);
+        assert_eq!(cache.peek(&'x'), Some(&vec![1, 3]));
+        cache.put('z', vec![]);
+        cache.put('w', vec![]);
+        assert!(!cache.contains(&'x'));
+        assert_eq!(cache.capacity(), 2);
+        assert!(!cache.is_empty());
+    }
+}
@@ -1,47 +1,109 @@
+mod addressable_heap;
 mod avl_tree;
 mod b_tree;
 mod binary_search_tree;
+mod blocking_queue;
+mod cartesian_tree_rmq;
+mod concurrent_skip_list;
+mod dynamic_segment_tree;
 mod fenwick_tree;
+mod fenwick_tree_2d;
+mod fibonacci_heap;
 mod floyds_algorithm;
+mod generic_lazy_segment_tree;
 pub mod graph;
+pub mod hash_map;
 mod hash_table;
 mod heap;
+mod indexed_heap;
+mod interval_set;
+mod interval_tree;
 mod lazy_segment_tree;
+mod li_chao_tree;
 mod linked_list;
+mod lru_cache;
+pub mod monoid;
+mod monotonic_queue;
+mod pairing_heap;
 mod probabilistic;
 mod queue;
+mod radix_trie;
 mod range_minimum_query;
 mod rb_tree;
+mod ring_buffer;
+mod rollback_union_find;
 mod segment_tree;
+mod segment_tree_2d;
 mod segment_tree_recursive;
 mod skip_list;
+mod sparse_table;
+mod sparse_veb_tree;
+pub mod spatial;
 mod stack_using_singly_linked_list;
 mod treap;
 mod trie;
 mod union_find;
 mod veb_tree;
+mod weighted_union_find;
+mod x_fast_trie;
+mod y_fast_trie;
 
+pub use self::addressable_heap::{AddressableHeap, Handle, HeapError};
 pub use self::avl_tree::AVLTree;
 pub use self::b_tree::BTree;
 pub use self::binary_search_tree::BinarySearchTree;
-pub use self::fenwick_tree::FenwickTree;
+pub use self::blocking_queue::{BlockingQueue, BlockingQueueError};
+pub use self::cartesian_tree_rmq::CartesianTreeRmq;
+pub use self::concurrent_skip_list::ConcurrentSkipList;
+pub use self::dynamic_segment_tree::DynamicSegmentTree;
+pub use self::fenwick_tree::{
+    FenwickTree, FenwickTreeError, RangeFenwickTree, RangeUpdateFenwickTree,
+};
+pub use self::fenwick_tree_2d::FenwickTree2D;
+pub use self::fibonacci_heap::FibonacciHeap;
 pub use self::floyds_algorithm::{detect_cycle, has_cycle};
+pub use self::generic_lazy_segment_tree::GenericLazySegmentTree;
 pub use self::graph::DirectedGraph;
 pub use self::graph::UndirectedGraph;
+pub use self::hash_map::{ChainedHashMap, CuckooHashMap, OpenAddressingHashMap, RobinHoodHashMap};
 pub use self::hash_table::HashTable;
 pub use self::heap::Heap;
+pub use self::indexed_heap::IndexedHeap;
+pub use self::interval_set::IntervalSet;
+pub use self::interval_tree::IntervalTree;
 pub use self::lazy_segment_tree::LazySegmentTree;
-pub use self::linked_list::LinkedList;
+pub use self::li_chao_tree::{LiChaoTree, Line};
+pub use self::linked_list::{Cursor, CursorMut, LinkedList};
+pub use self::lru_cache::LruCache;
+pub use self::monotonic_queue::{sliding_window_max, sliding_window_min, MonotonicQueue};
+pub use self::pairing_heap::PairingHeap;
 pub use self::probabilistic::bloom_filter;
 pub use self::probabilistic::count_min_sketch;
+pub use self::probabilistic::counting_bloom_filter;
+pub use self::probabilistic::cuckoo_filter;
+pub use self::probabilistic::hyperloglog;
+pub use self::probabilistic::kll_sketch;
+pub use self::probabilistic::reservoir_sampling;
+pub use self::probabilistic::scalable_bloom_filter;
+pub use self::probabilistic::space_saving;
 pub use self::queue::Queue;
-pub use self::range_minimum_query::RangeMinimumQuery;
+pub use self::radix_trie::RadixTrie;
+pub use self::range_minimum_query::{RangeError, RangeMinimumQuery};
 pub use self::rb_tree::RBTree;
-pub use self::segment_tree::SegmentTree;
+pub use self::ring_buffer::{OverflowPolicy, RingBuffer, RingBufferError};
+pub use self::rollback_union_find::RollbackUnionFind;
+pub use self::segment_tree::{SegmentTree, SegmentTreeError};
+pub use self::segment_tree_2d::SegmentTree2D;
 pub use self::segment_tree_recursive::SegmentTree as SegmentTreeRecursive;
 pub use self::skip_list::SkipList;
+pub use self::sparse_table::{DisjointSparseTable, SparseTable};
+pub use self::sparse_veb_tree::SparseVebTree;
+pub use self::spatial::{KdPoint, KdTree, QuadTree, RTree};
 pub use self::stack_using_singly_linked_list::Stack;
 pub use self::treap::Treap;
 pub use self::trie::Trie;
-pub use self::union_find::UnionFind;
+pub use self::union_find::{DisjointSets, UnionFind};
 pub use self::veb_tree::VebTree;
+pub use self::weighted_union_find::{WeightedUnionFind, WeightedUnionFindError};
+pub use self::x_fast_trie::XFastTrie;
+pub use self::y_fast_trie::YFastTrie;
@@ -0,0 +1,491 @@
+//! Algebraic building blocks shared by the generic segment trees.
+//!
+//! A segment tree only needs two things from the values it stores: an associative
+//! way of combining two of them, and a neutral element for that combination.
+//! This is exactly a [monoid](https://en.wikipedia.org/wiki/Monoid).
+//!
+//! Lazy propagation additionally needs a family of *maps* (the updates) that act on
+//! those values. Maps must be composable, must have an identity, and applying a map
+//! to the combination of a segment must give the same result as combining the mapped
+//! elements of that segment.
+
+use std::marker::PhantomData;
+
+/// A set of values together with an associative operation and its identity element.
+///
+/// Implementations must guarantee:
+/// * `combine(&identity(), &x) == x` and `combine(&x, &identity()) == x`
+/// * `combine(&combine(&a, &b), &c) == combine(&a, &combine(&b, &c))`
+pub trait Monoid {
+    type Value: Clone;
+
+    fn identity() -> Self::Value;
+    fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// A monoid whose operation is idempotent: `combine(&x, &x) == x`.
+///
+/// Folding a range with such an operation may count elements several times, which lets a
+/// sparse table answer a query from two overlapping precomputed ranges.
+pub trait Idempotent: Monoid {}
+
+/// A family of maps acting on the values of the monoid `M`, used for lazy range updates.
+///
+/// Implementations must guarantee:
+/// * `apply(&identity_map(), &x, len) == x`
+/// * `apply(&compose(&f, &g), &x, len) == apply(&f, &apply(&g, &x, len), len)`
+/// * `apply(&f, &M::combine(&a, &b), la + lb) == M::combine(&apply(&f, &a, la), &apply(&f, &b, lb))`
+///
+/// `len` is the number of elements folded into `x`, which lets maps such as
+/// "add `v` to every element" update a sum in constant time. An empty segment has length 0
+/// and holds the identity of the monoid.
+pub trait Action<M: Monoid> {
+    type Map: Clone;
+
+    fn identity_map() -> Self::Map;
+    /// Returns the map equivalent to applying `inner` first and then `outer`.
+    fn compose(outer: &Self::Map, inner: &Self::Map) -> Self::Map;
+    fn apply(map: &Self::Map, value: &M::Value, len: usize) -> M::Value;
+}
+
+/// Combines `value` with itself `times` times in `O(log times)` monoid operations.
+pub fn repeat<M: Monoid>(value: &M::Value, mut times: u64) -> M::Value {
+    let mut result = M::identity();
+    let mut base = value.clone();
+    while times > 0 {
+        if times & 1 == 1 {
+            result = M::combine(&result, &base);
+        }
+        base = M::combine(&base, &base);
+        times >>= 1;
+    }
+    result
+}
+
+/// Primitive numeric types usable with the predefined monoids and actions of this module.
+pub trait Numeric:
+    Copy + PartialOrd + std::ops::Add<Output = Self> + std::ops::Mul<Output = Self> + std::fmt::Debug
+{
+    const ZERO: Self;
+    const ONE: Self;
+    /// The smallest representable value, used as the identity of [`MaxMonoid`].
+    const LOWEST: Self;
+    /// The largest representable value, used as the identity of [`MinMonoid`].
+    const HIGHEST: Self;
+
+    fn from_len(len: usize) -> Self;
+}
+
+macro_rules! impl_numeric {
+    ($lowest:ident, $highest:ident; $($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                const ZERO: Self = 0 as $t;
+                const ONE: Self = 1 as $t;
+                const LOWEST: Self = <$t>::$lowest;
+                const HIGHEST: Self = <$t>::$highest;
+
+                fn from_len(len: usize) -> Self {
+                    len as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric!(MIN, MAX; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_numeric!(NEG_INFINITY, INFINITY; f32, f64);
+
+fn partial_min<T: Numeric>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+fn partial_max<T: Numeric>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+/// Sum of the elements of a segment.
+pub struct SumMonoid<T>(PhantomData<T>);
+
+impl<T: Numeric> Monoid for SumMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::ZERO
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+/// Minimum of the elements of a segment.
+pub struct MinMonoid<T>(PhantomData<T>);
+
+impl<T: Numeric> Monoid for MinMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::HIGHEST
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        partial_min(*a, *b)
+    }
+}
+
+/// Maximum of the elements of a segment.
+pub struct MaxMonoid<T>(PhantomData<T>);
+
+impl<T: Numeric> Monoid for MaxMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::LOWEST
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        partial_max(*a, *b)
+    }
+}
+
+impl<T: Numeric> Idempotent for MinMonoid<T> {}
+impl<T: Numeric> Idempotent for MaxMonoid<T> {}
+
+/// Greatest common divisor of the elements of a segment, 0 being the identity.
+pub struct GcdMonoid<T>(PhantomData<T>);
+
+/// Bitwise and of the elements of a segment.
+pub struct BitAndMonoid<T>(PhantomData<T>);
+
+/// Bitwise or of the elements of a segment.
+pub struct BitOrMonoid<T>(PhantomData<T>);
+
+macro_rules! impl_integer_monoids {
+    ($($t:ty),*) => {
+        $(
+            impl Monoid for GcdMonoid<$t> {
+                type Value = $t;
+
+                fn identity() -> $t {
+                    0
+                }
+
+                fn combine(a: &$t, b: &$t) -> $t {
+                    let (mut a, mut b) = (*a, *b);
+                    while b != 0 {
+                        (a, b) = (b, a % b);
+                    }
+                    a
+                }
+            }
+
+            impl Monoid for BitAndMonoid<$t> {
+                type Value = $t;
+
+                fn identity() -> $t {
+                    !0
+                }
+
+                fn combine(a: &$t, b: &$t) -> $t {
+                    a & b
+                }
+            }
+
+            impl Monoid for BitOrMonoid<$t> {
+                type Value = $t;
+
+                fn identity() -> $t {
+                    0
+                }
+
+                fn combine(a: &$t, b: &$t) -> $t {
+                    a | b
+                }
+            }
+
+            impl Idempotent for GcdMonoid<$t> {}
+            impl Idempotent for BitAndMonoid<$t> {}
+            impl Idempotent for BitOrMonoid<$t> {}
+        )*
+    };
+}
+
+impl_integer_monoids!(u8, u16, u32, u64, u128, usize);
+
+/// Replaces every element of a segment by a given value. `None` is the identity map.
+pub struct RangeAssign<T>(PhantomData<T>);
+
+impl<T: Numeric> Action<SumMonoid<T>> for RangeAssign<T> {
+    type Map = Option<T>;
+
+    fn identity_map() -> Option<T> {
+        None
+    }
+
+    fn compose(outer: &Option<T>, inner: &Option<T>) -> Option<T> {
+        outer.or(*inner)
+    }
+
+    fn apply(map: &Option<T>, value: &T, len: usize) -> T {
+        map.map_or(*value, |v| v * T::from_len(len))
+    }
+}
+
+impl<T: Numeric> Action<MinMonoid<T>> for RangeAssign<T> {
+    type Map = Option<T>;
+
+    fn identity_map() -> Option<T> {
+        None
+    }
+
+    fn compose(outer: &Option<T>, inner: &Option<T>) -> Option<T> {
+        outer.or(*inner)
+    }
+
+    fn apply(map: &Option<T>, value: &T, _len: usize) -> T {
+        map.unwrap_or(*value)
+    }
+}
+
+impl<T: Numeric> Action<MaxMonoid<T>> for RangeAssign<T> {
+    type Map = Option<T>;
+
+    fn identity_map() -> Option<T> {
+        None
+    }
+
+    fn compose(outer: &Option<T>, inner: &Option<T>) -> Option<T> {
+        outer.or(*inner)
+    }
+
+    fn apply(map: &Option<T>, value: &T, _len: usize) -> T {
+        map.unwrap_or(*value)
+    }
+}
+
+/// Adds a given value to every element of a segment.
+pub struct RangeAdd<T>(PhantomData<T>);
+
+impl<T: Numeric> Action<SumMonoid<T>> for RangeAdd<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        *outer + *inner
+    }
+
+    fn apply(map: &T, value: &T, len: usize) -> T {
+        *value + *map * T::from_len(len)
+    }
+}
+
+impl<T: Numeric> Action<MinMonoid<T>> for RangeAdd<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        *outer + *inner
+    }
+
+    fn apply(map: &T, value: &T, len: usize) -> T {
+        // Empty segments hold the identity, which must stay untouched. Their length tells them
+        // apart from segments whose minimum happens to equal the identity.
+        if len == 0 {
+            *value
+        } else {
+            *value + *map
+        }
+    }
+}
+
+impl<T: Numeric> Action<MaxMonoid<T>> for RangeAdd<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::ZERO
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        *outer + *inner
+    }
+
+    fn apply(map: &T, value: &T, len: usize) -> T {
+        if len == 0 {
+            *value
+        } else {
+            *value + *map
+        }
+    }
+}
+
+/// Replaces every element `x` of a segment by `a * x + b`, the map being stored as `(a, b)`.
+pub struct RangeAffine<T>(PhantomData<T>);
+
+impl<T: Numeric> Action<SumMonoid<T>> for RangeAffine<T> {
+    type Map = (T, T);
+
+    fn identity_map() -> (T, T) {
+        (T::ONE, T::ZERO)
+    }
+
+    fn compose(outer: &(T, T), inner: &(T, T)) -> (T, T) {
+        (outer.0 * inner.0, outer.0 * inner.1 + outer.1)
+    }
+
+    fn apply(map: &(T, T), value: &T, len: usize) -> T {
+        map.0 * *value + map.1 * T::from_len(len)
+    }
+}
+
+/// Replaces every element `x` of a segment by `min(x, v)`.
+pub struct RangeChmin<T>(PhantomData<T>);
+
+impl<T: Numeric> Action<MinMonoid<T>> for RangeChmin<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::HIGHEST
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        partial_min(*outer, *inner)
+    }
+
+    fn apply(map: &T, value: &T, _len: usize) -> T {
+        partial_min(*map, *value)
+    }
+}
+
+impl<T: Numeric> Action<MaxMonoid<T>> for RangeChmin<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::HIGHEST
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        partial_min(*outer, *inner)
+    }
+
+    fn apply(map: &T, value: &T, _len: usize) -> T {
+        partial_min(*map, *value)
+    }
+}
+
+/// Replaces every element `x` of a segment by `max(x, v)`.
+pub struct RangeChmax<T>(PhantomData<T>);
+
+impl<T: Numeric> Action<MinMonoid<T>> for RangeChmax<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::LOWEST
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        partial_max(*outer, *inner)
+    }
+
+    fn apply(map: &T, value: &T, _len: usize) -> T {
+        partial_max(*map, *value)
+    }
+}
+
+impl<T: Numeric> Action<MaxMonoid<T>> for RangeChmax<T> {
+    type Map = T;
+
+    fn identity_map() -> T {
+        T::LOWEST
+    }
+
+    fn compose(outer: &T, inner: &T) -> T {
+        partial_max(*outer, *inner)
+    }
+
+    fn apply(map: &T, value: &T, _len: usize) -> T {
+        partial_max(*map, *value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeat() {
+        assert_eq!(repeat::<SumMonoid<i64>>(&3, 0), 0);
+        assert_eq!(repeat::<SumMonoid<i64>>(&3, 7), 21);
+        assert_eq!(repeat::<MinMonoid<i32>>(&-4, 1_000_000_000), -4);
+    }
+
+    #[test]
+    fn test_integer_monoids() {
+        assert_eq!(GcdMonoid::<u32>::combine(&12, &18), 6);
+        assert_eq!(
+            GcdMonoid::<u32>::combine(&GcdMonoid::<u32>::identity(), &7),
+            7
+        );
+        assert_eq!(
+            BitAndMonoid::<u8>::combine(&BitAndMonoid::<u8>::identity(), &0b1010),
+            0b1010
+        );
+        assert_eq!(BitOrMonoid::<u64>::combine(&0b1010, &0b0110), 0b1110);
+    }
+
+    #[test]
+    fn test_affine_composition() {
+        let f = (2, 3);
+        let g = (5, -1);
+        let fg = <RangeAffine<i64> as Action<SumMonoid<i64>>>::compose(&f, &g);
+        let direct = <RangeAffine<i64> as Action<SumMonoid<i64>>>::apply(
+            &f,
+            &<RangeAffine<i64> as Action<SumMonoid<i64>>>::apply(&g, &10, 4),
+            4,
+        );
+        assert_eq!(
+            <RangeAffine<i64> as Action<SumMonoid<i64>>>::apply(&fg, &10, 4),
+            direct
+        );
+    }
+
+    #[test]
+    fn test_add_keeps_identity() {
+        let highest = <MinMonoid<i32> as Monoid>::identity();
+        assert_eq!(
+            <RangeAdd<i32> as Action<MinMonoid<i32>>>::apply(&5, &highest, 0),
+            highest
+        );
+        let lowest = <MaxMonoid<i32> as Monoid>::identity();
+        assert_eq!(
+            <RangeAdd<i32> as Action<MaxMonoid<i32>>>::apply(&5, &lowest, 0),
+            lowest
+        );
+    }
+
+    #[test]
+    fn test_add_to_extreme_values() {
+        assert_eq!(
+            <RangeAdd<i64> as Action<MinMonoid<i64>>>::apply(&-1, &i64::MAX, 1),
+            i64::MAX - 1
+        );
+        assert_eq!(
+            <RangeAdd<i64> as Action<MaxMonoid<i64>>>::apply(&1, &i64::MIN, 3),
+            i64::MIN + 1
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! A FIFO queue that reports its minimum (or maximum) in `O(1)`.
+//!
+//! Alongside the queued values, the queue keeps a deque of candidates: the values that may still
+//! become the extremum once the values in front of them are popped. A pushed value discards the
+//! candidates it beats, so the candidates stay monotonic and the front one is the extremum.
+//! Every value enters and leaves the candidates at most once, so operations are amortized `O(1)`.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct MonotonicQueue<T> {
+    values: VecDeque<T>,
+    /// Candidates for the extremum, paired with their number in push order.
+    candidates: VecDeque<(usize, T)>,
+    /// Number of values pushed so far, and popped so far.
+    pushed: usize,
+    popped: usize,
+    /// The ordering a value must have against a candidate to discard it.
+    beats: Ordering,
+}
+
+impl<T: Ord + Clone> MonotonicQueue<T> {
+    /// Creates a queue reporting its minimum.
+    pub const fn min() -> Self {
+        Self::with_ordering(Ordering::Less)
+    }
+
+    /// Creates a queue reporting its maximum.
+    pub const fn max() -> Self {
+        Self::with_ordering(Ordering::Greater)
+    }
+
+    const fn with_ordering(beats: Ordering) -> Self {
+        Self {
+            values: VecDeque::new(),
+            candidates: VecDeque::new(),
+            pushed: 0,
+            popped: 0,
+            beats,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn push(&mut self, value: T) {
+        // Equal candidates are kept, so that popping one of them leaves the other
+        while self
+            .candidates
+            .back()
+            .is_some_and(|(_, candidate)| value.cmp(candidate) == self.beats)
+        {
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((self.pushed, value.clone()));
+        self.values.push_back(value);
+        self.pushed += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let value = self.values.pop_front()?;
+        if self
+            .candidates
+            .front()
+            .is_some_and(|&(id, _)| id == self.popped)
+        {
+            self.candidates.pop_front();
+        }
+        self.popped += 1;
+        Some(value)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.values.front()
+    }
+
+    /// Returns the minimum (or maximum) of the queued values.
+    pub fn extremum(&self) -> Option<&T> {
+        self.candidates.front().map(|(_, value)| value)
+    }
+}
+
+/// Returns the minimum of every window of `window` consecutive values, in `O(n)`.
+///
+/// # Panics
+///
+/// Panics if `window` is zero.
+pub fn sliding_window_min<T: Ord + Clone>(values: &[T], window: usize) -> Vec<T> {
+    sliding_window(MonotonicQueue::min(), values, window)
+}
+
+/// Returns the maximum of every window of `window` consecutive values, in `O(n)`.
+///
+/// # Panics
+///
+/// Panics if `window` is zero.
+pub fn sliding_window_max<T: Ord + Clone>(values: &[T], window: usize) -> Vec<T> {
+    sliding_window(MonotonicQueue::max(), values, window)
+}
+
+fn sliding_window<T: Ord + Clone>(
+    mut queue: MonotonicQueue<T>,
+    values: &[T],
+    window: usize,
+) -> Vec<T> {
+    assert!(window > 0, "window must be positive");
+    let mut extrema = Vec::with_capacity(values.len().saturating_sub(window - 1));
+    for value in values {
+        queue.push(value.clone());
+        if queue.len() > window {
+            queue.pop();
+        }
+        if queue.len() == window {
+            extrema.extend(queue.extremum().cloned());
+        }
+    }
+    extrema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_extremum_through_pops() {
+        let mut queue = MonotonicQueue::min();
+        for value in [5, 3, 4, 3, 6] {
+            queue.push(value);
+        }
+        assert_eq!(queue.extremum(), Some(&3));
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.extremum(), Some(&3));
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.extremum(), Some(&6));
+        assert_eq!(queue.front(), Some(&6));
+        assert_eq!(queue.len(), 1);
+        queue.pop();
+        assert!(queue.is_empty());
+        assert_eq!((queue.pop(), queue.extremum()), (None, None));
+    }
+
+    #[test]
+    fn sliding_windows() {
+        let values = [1, 3, -1, -3, 5, 3, 6, 7];
+        assert_eq!(sliding_window_max(&values, 3), vec![3, 3, 5, 5, 6, 7]);
+        assert_eq!(sliding_window_min(&values, 3), vec![-1, -3, -3, -3, 3, 3]);
+        assert_eq!(sliding_window_min(&values, 1), values.to_vec());
+        assert!(sliding_window_max(&values, 9).is_empty());
+    }
+
+    #[test]
+    fn matches_naive_windows() {
+        let mut seed = 7_u64;
+        let values: Vec<u64> = (0..200)
+            .map(|_| {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                seed >> 58
+            })
+            .collect();
+        for window in [2, 5, 17] {
+            let naive: Vec<u64> = values
+                .windows(window)
+                .map(|w| *w.iter().max().unwrap())
+                .collect();
+            assert_eq!(sliding_window_max(&values, window), naive);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! A pairing heap: a heap-ordered multiway tree, simple to implement and very fast in
+//! practice.
+//!
+//! `push`, `peek`, `decrease_key` and the linking step of `meld` are `O(1)`, and `pop`
+//! and `remove` are `O(log n)` amortized. Popping the root merges its subtrees with the
+//! classic two-pass pairing: first link the children pairwise from left to right, then
+//! link the resulting trees from right to left.
+
+use super::addressable_heap::{AddressableHeap, Arena, Handle, HeapError};
+
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Leftmost child.
+    child: usize,
+    /// Next sibling on the right.
+    sibling: usize,
+    /// Previous sibling, or parent for a leftmost child.
+    prev: usize,
+}
+
+pub struct PairingHeap<K: Ord, V> {
+    nodes: Arena<Node<K, V>>,
+    root: usize,
+}
+
+impl<K: Ord, V> PairingHeap<K, V> {
+    pub const fn new() -> Self {
+        PairingHeap {
+            nodes: Arena::new(),
+            root: NIL,
+        }
+    }
+
+    /// Moves every element of `other` into this heap.
+    ///
+    /// The two trees are linked in `O(1)`, but moving the nodes of `other` into the arena
+    /// of `self` costs `O(other.len())`, so the smaller heap should be melded into the
+    /// larger one. Handles obtained from `other` are invalidated.
+    pub fn meld(&mut self, other: PairingHeap<K, V>) {
+        if other.root == NIL {
+            return;
+        }
+        let mapping = self.nodes.absorb(other.nodes);
+        let remap = |link: usize| if link == NIL { NIL } else { mapping[link] };
+        for &index in mapping.iter().filter(|&&index| index != NIL) {
+            let node = &mut self.nodes[index];
+            node.child = remap(node.child);
+            node.sibling = remap(node.sibling);
+            node.prev = remap(node.prev);
+        }
+        self.root = self.link(self.root, remap(other.root));
+    }
+
+    /// Links two trees given by their roots, and returns the root of the result.
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        let (parent, child) = if self.nodes[b].key < self.nodes[a].key {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first_child = self.nodes[parent].child;
+        self.nodes[child].sibling = first_child;
+        self.nodes[child].prev = parent;
+        if first_child != NIL {
+            self.nodes[first_child].prev = child;
+        }
+        self.nodes[parent].child = child;
+        parent
+    }
+
+    /// Merges the list of siblings starting at `first` into a single tree with the
+    /// two-pass pairing strategy.
+    fn merge_pairs(&mut self, first: usize) -> usize {
+        let mut trees = Vec::new();
+        let mut current = first;
+        while current != NIL {
+            let next = self.nodes[current].sibling;
+            self.nodes[current].sibling = NIL;
+            self.nodes[current].prev = NIL;
+            trees.push(current);
+            current = next;
+        }
+        let paired: Vec<usize> = trees
+            .chunks(2)
+            .map(|pair| self.link(pair[0], pair.get(1).copied().unwrap_or(NIL)))
+            .collect();
+        paired
+            .into_iter()
+            .rev()
+            .fold(NIL, |merged, tree| self.link(tree, merged))
+    }
+
+    /// Detaches the subtree rooted at `node` from its parent and siblings.
+    fn cut(&mut self, node: usize) {
+        let (prev, sibling) = (self.nodes[node].prev, self.nodes[node].sibling);
+        if self.nodes[prev].child == node {
+            self.nodes[prev].child = sibling;
+        } else {
+            self.nodes[prev].sibling = sibling;
+        }
+        if sibling != NIL {
+            self.nodes[sibling].prev = prev;
+        }
+        self.nodes[node].prev = NIL;
+        self.nodes[node].sibling = NIL;
+    }
+}
+
+impl<K: Ord, V> Default for PairingHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> AddressableHeap<K, V> for PairingHeap<K, V> {
+    fn push(&mut self, key: K, value: V) -> Handle {
+        let index = self.nodes.insert(Node {
+            key,
+            value,
+            child: NIL,
+            sibling: NIL,
+            prev: NIL,
+        });
+        self.root = self.link(self.root, index);
+        self.nodes.handle(index)
+    }
+
+    fn peek(&self) -> Option<(&K, &V)> {
+        (self.root != NIL).then(|| {
+            let node = &self.nodes[self.root];
+            (&node.key, &node.value)
+        })
+    }
+
+    fn pop(&mut self) -> Option<(K, V)> {
+        if self.root == NIL {
+            return None;
+        }
+        let old_root = self.root;
+        self.root = self.merge_pairs(self.nodes[old_root].child);
+        let node = self.nodes.remove(old_root);
+        Some((node.key, node.value))
+    }
+
+    fn get(&self, handle: Handle) -> Option<(&K, &V)> {
+        self.nodes.resolve(handle).map(|index| {
+            let node = &self.nodes[index];
+            (&node.key, &node.value)
+        })
+    }
+
+    fn decrease_key(&mut self, handle: Handle, key: K) -> Result<(), HeapError> {
+        let index = self.nodes.resolve(handle).ok_or(HeapError::InvalidHandle)?;
+        if key > self.nodes[index].key {
+            return Err(HeapError::KeyIncreased);
+        }
+        self.nodes[index].key = key;
+        if index != self.root {
+            self.cut(index);
+            self.root = self.link(self.root, index);
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(K, V)> {
+        let index = self.nodes.resolve(handle)?;
+        if index == self.root {
+            return self.pop();
+        }
+        self.cut(index);
+        let subtree = self.merge_pairs(self.nodes[index].child);
+        self.root = self.link(self.root, subtree);
+        let node = self.nodes.remove(index);
+        Some((node.key, node.value))
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(heap: &mut PairingHeap<i32, u32>) -> Vec<i32> {
+        std::iter::from_fn(|| heap.pop())
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = PairingHeap::new();
+        assert_eq!(heap.pop(), None);
+        for key in [7, 3, 9, 1, 4, 4, -2] {
+            heap.push(key, 0);
+        }
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some((&-2, &0)));
+        assert_eq!(drain(&mut heap), vec![-2, 1, 3, 4, 4, 7, 9]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_decrease_key_and_remove() {
+        let mut heap = PairingHeap::new();
+        let handles: Vec<Handle> = (0..10).map(|i| heap.push(i * 10, i as u32)).collect();
+        assert_eq!(heap.pop(), Some((0, 0)));
+        assert_eq!(heap.decrease_key(handles[7], 5), Ok(()));
+        assert_eq!(heap.peek(), Some((&5, &7)));
+        assert_eq!(
+            heap.decrease_key(handles[3], 31),
+            Err(HeapError::KeyIncreased)
+        );
+        assert_eq!(
+            heap.decrease_key(handles[0], -1),
+            Err(HeapError::InvalidHandle)
+        );
+        assert_eq!(heap.remove(handles[4]), Some((40, 4)));
+        assert_eq!(heap.remove(handles[4]), None);
+        assert_eq!(heap.get(handles[9]), Some((&90, &9)));
+        assert_eq!(drain(&mut heap), vec![5, 10, 20, 30, 50, 60, 80, 90]);
+    }
+
+    #[test]
+    fn test_meld() {
+        let mut left = PairingHeap::new();
+        let mut right = PairingHeap::new();
+        for key in [5, 1, 9] {
+            left.push(key, 0);
+        }
+        let handle = right.push(8, 1);
+        right.push(2, 1);
+        right.pop();
+        right.push(0, 1);
+        assert_eq!(right.decrease_key(handle, 3), Ok(()));
+        left.meld(right);
+        assert_eq!(left.len(), 5);
+        assert_eq!(drain(&mut left), vec![0, 1, 3, 5, 9]);
+    }
+
+    #[test]
+    fn test_random_operations() {
+        let mut heap = PairingHeap::new();
+        let mut naive: Vec<(i32, Handle)> = Vec::new();
+        let mut seed = 12345u64;
+        for _ in 0..2000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let key = (seed >> 40) as i32 % 1000;
+            match seed >> 62 {
+                0 | 1 => naive.push((key, heap.push(key, 0))),
+                2 if !naive.is_empty() => {
+                    let i = (seed >> 20) as usize % naive.len();
+                    let lowered = naive[i].0 - key.abs() % 50;
+                    heap.decrease_key(naive[i].1, lowered).unwrap();
+                    naive[i].0 = lowered;
+                }
+                _ if !naive.is_empty() => {
+                    let i = (seed >> 20) as usize % naive.len();
+                    let (key, handle) = naive.swap_remove(i);
+                    assert_eq!(heap.remove(handle).map(|(k, _)| k), Some(key));
+                }
+                _ => {}
+            }
+            assert_eq!(
+                heap.peek().map(|(&k, _)| k),
+                naive.iter().map(|&(k, _)| k).min()
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use std::collections::hash_map::{DefaultHasher, RandomState};
-use std::hash::{BuildHasher, Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 /// A Bloom Filter <https://en.wikipedia.org/wiki/Bloom_filter> is a probabilistic data structure testing whether an element belongs to a set or not
 /// Therefore, its contract looks very close to the one of a set, for example a `HashSet`
@@ -104,7 +104,7 @@ impl<T: Hash> BloomFilter<T> for SingleBinaryBloomFilter {
 pub struct MultiBinaryBloomFilter {
     filter_size: usize,
     bytes: Vec<u8>,
-    hash_builders: Vec<RandomState>,
+    hash_count: usize,
 }
 
 impl MultiBinaryBloomFilter {
@@ -113,7 +113,7 @@ impl MultiBinaryBloomFilter {
         Self {
             filter_size,
             bytes: vec![0; bytes_count],
-            hash_builders: vec![RandomState::new(); hash_count],
+            hash_count,
         }
     }
 
@@ -121,55 +121,275 @@ impl MultiBinaryBloomFilter {
         estimated_count_of_items: usize,
         max_false_positive_probability: f64,
     ) -> Self {
-        // Check Wikipedia for these formulae
-        let optimal_filter_size = (-(estimated_count_of_items as f64)
-            * max_false_positive_probability.ln()
-            / (2.0_f64.ln().powi(2)))
-        .ceil() as usize;
-        let optimal_hash_count = ((optimal_filter_size as f64 / estimated_count_of_items as f64)
-            * 2.0_f64.ln())
-        .ceil() as usize;
-        Self::with_dimensions(optimal_filter_size, optimal_hash_count)
+        let (filter_size, hash_count) =
+            optimal_dimensions(estimated_count_of_items, max_false_positive_probability);
+        Self::with_dimensions(filter_size, hash_count)
+    }
+
+    /// The number of bits and of hash functions of the filter
+    pub(super) const fn dimensions(&self) -> (usize, usize) {
+        (self.filter_size, self.hash_count)
+    }
+
+    /// Two filters can only be combined if they have the same dimensions, so that an item sets the same bits in both
+    fn check_compatible(&self, other: &Self) -> Result<(), FilterError> {
+        if self.filter_size == other.filter_size && self.hash_count == other.hash_count {
+            Ok(())
+        } else {
+            Err(FilterError::IncompatibleFilters)
+        }
+    }
+
+    /// Turns `self` into a filter of the items of `self` **or** `other`: a bitwise OR of the two bit arrays
+    /// The result is exactly the filter we would get by inserting both sets of items
+    pub fn union(&mut self, other: &Self) -> Result<(), FilterError> {
+        self.check_compatible(other)?;
+        for (byte, other_byte) in self.bytes.iter_mut().zip(&other.bytes) {
+            *byte |= other_byte;
+        }
+        Ok(())
+    }
+
+    /// Turns `self` into a filter of the items of `self` **and** `other`: a bitwise AND of the two bit arrays
+    /// Items of the intersection are still guaranteed to be found, but the false positive rate can be higher than the one of a filter built from the intersection directly
+    pub fn intersection(&mut self, other: &Self) -> Result<(), FilterError> {
+        self.check_compatible(other)?;
+        for (byte, other_byte) in self.bytes.iter_mut().zip(&other.bytes) {
+            *byte &= other_byte;
+        }
+        Ok(())
+    }
+
+    /// Serializes the filter, so that it can be rebuilt elsewhere with `from_bytes`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![MULTI_BINARY_TAG];
+        put_u64(&mut buffer, self.filter_size as u64);
+        put_u64(&mut buffer, self.hash_count as u64);
+        buffer.extend_from_slice(&self.bytes);
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader::new(bytes, MULTI_BINARY_TAG)?;
+        let filter_size = reader.read_usize()?;
+        let hash_count = reader.read_usize()?;
+        // An empty filter or one without hash functions could not index its bits
+        if filter_size == 0 || hash_count == 0 {
+            return Err(FilterError::InvalidBytes);
+        }
+        let bytes = reader.read_bytes(filter_size.div_ceil(8))?.to_vec();
+        reader.finish()?;
+        Ok(Self {
+            filter_size,
+            bytes,
+            hash_count,
+        })
     }
 }
 
 impl<Item: Hash> BloomFilter<Item> for MultiBinaryBloomFilter {
     fn insert(&mut self, item: Item) {
-        for builder in &self.hash_builders {
-            let mut hasher = builder.build_hasher();
-            item.hash(&mut hasher);
-            let hash = builder.hash_one(&item);
-            let index = hash % self.filter_size as u64;
-            let byte_index = index as usize / 8; // this is this byte that we need to modify
-            let bit_index = (index % 8) as u8; // we cannot only OR with value 1 this time, since we have 8 bits
+        for index in bit_indices(&item, self.hash_count, self.filter_size) {
+            let byte_index = index / 8; // this is this byte that we need to modify
+            let bit_index = index % 8; // we cannot only OR with value 1 this time, since we have 8 bits
             self.bytes[byte_index] |= 1 << bit_index;
         }
     }
 
     fn contains(&self, item: &Item) -> bool {
-        for builder in &self.hash_builders {
-            let mut hasher = builder.build_hasher();
-            item.hash(&mut hasher);
-            let hash = builder.hash_one(item);
-            let index = hash % self.filter_size as u64;
-            let byte_index = index as usize / 8; // this is this byte that we need to modify
-            let bit_index = (index % 8) as u8; // we cannot only OR with value 1 this time, since we have 8 bits
-            if self.bytes[byte_index] & (1 << bit_index) == 0 {
-                return false;
-            }
+        bit_indices(item, self.hash_count, self.filter_size)
+            .all(|index| self.bytes[index / 8] & (1 << (index % 8)) != 0)
+    }
+}
+
+/// Errors that can occur when combining or deserializing filters
+#[derive(Debug, PartialEq, Eq)]
+pub enum FilterError {
+    /// The filters don't have the same dimensions, so their contents can't be combined
+    IncompatibleFilters,
+    /// The byte buffer doesn't hold a filter of the expected kind
+    InvalidBytes,
+    /// The filter has no room left for a new item
+    Full,
+}
+
+/// Computes the optimal `(filter_size, hash_count)` for a filter holding `estimated_count_of_items` items
+/// with a false positive probability of at most `max_false_positive_probability`
+pub(super) fn optimal_dimensions(
+    estimated_count_of_items: usize,
+    max_false_positive_probability: f64,
+) -> (usize, usize) {
+    // Check Wikipedia for these formulae
+    let optimal_filter_size = (-(estimated_count_of_items as f64)
+        * max_false_positive_probability.ln()
+        / (2.0_f64.ln().powi(2)))
+    .ceil() as usize;
+    let optimal_hash_count = ((optimal_filter_size as f64 / estimated_count_of_items as f64)
+        * 2.0_f64.ln())
+    .ceil() as usize;
+    (optimal_filter_size, optimal_hash_count)
+}
+
+/// The filters below must give the same answers on every machine they are shipped to,
+/// so they can't rely on a randomly seeded `RandomState`.
+/// Instead, they hash items with FNV-1a, followed by a final mix spreading the entropy over all the bits.
+/// The default `write_*` methods feed integers in native byte order and `usize` with its native width,
+/// so they are overridden to feed fixed-width little-endian bytes, `usize` being hashed as a `u64`
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write(&[i]);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.write_u8(i as u8);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        mix64(self.0)
+    }
+}
+
+/// The finalizer of SplitMix64: a bijection on `u64` where every input bit affects every output bit
+pub(super) const fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+pub(super) fn stable_hash<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = StableHasher(0xcbf2_9ce4_8422_2325);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Indices of the `hash_count` bits of `item` in a filter of `filter_size` bits
+///
+/// Rather than `hash_count` independent hash functions, we use double hashing: the i-th index is `h1 + i * h2`.
+/// It is known to give the same false positive rate, for the cost of a single hash
+pub(super) fn bit_indices<T: Hash + ?Sized>(
+    item: &T,
+    hash_count: usize,
+    filter_size: usize,
+) -> impl Iterator<Item = usize> {
+    let h1 = stable_hash(item);
+    let h2 = mix64(h1) | 1;
+    (0..hash_count as u64)
+        .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % filter_size as u64) as usize)
+}
+
+const MULTI_BINARY_TAG: u8 = b'B';
+
+/// Serialized filters are made of a tag byte identifying the kind of filter, then little-endian integers and raw bytes
+pub(super) fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(super) struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    /// Starts reading a buffer, checking that it begins with `tag`
+    pub(super) fn new(bytes: &'a [u8], tag: u8) -> Result<Self, FilterError> {
+        match bytes.split_first() {
+            Some((&first, rest)) if first == tag => Ok(ByteReader { bytes: rest }),
+            _ => Err(FilterError::InvalidBytes),
+        }
+    }
+
+    pub(super) fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], FilterError> {
+        if count > self.bytes.len() {
+            return Err(FilterError::InvalidBytes);
+        }
+        let (read, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(read)
+    }
+
+    pub(super) fn read_u64(&mut self) -> Result<u64, FilterError> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_le_bytes(
+            bytes.try_into().map_err(|_| FilterError::InvalidBytes)?,
+        ))
+    }
+
+    /// Reads a size, rejecting the ones which can't possibly fit in the remaining bytes to avoid huge allocations
+    pub(super) fn read_usize(&mut self) -> Result<usize, FilterError> {
+        usize::try_from(self.read_u64()?)
+            .ok()
+            .filter(|&size| size / 8 <= self.bytes.len())
+            .ok_or(FilterError::InvalidBytes)
+    }
+
+    /// Checks that the whole buffer was consumed
+    pub(super) fn finish(self) -> Result<(), FilterError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(FilterError::InvalidBytes)
         }
-        true
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::data_structures::probabilistic::bloom_filter::{
-        BasicBloomFilter, BloomFilter, MultiBinaryBloomFilter, SingleBinaryBloomFilter,
+        stable_hash, BasicBloomFilter, BloomFilter, FilterError, MultiBinaryBloomFilter,
+        SingleBinaryBloomFilter, StableHasher,
     };
     use quickcheck::{Arbitrary, Gen};
     use quickcheck_macros::quickcheck;
     use std::collections::HashSet;
+    use std::hash::Hasher;
 
     #[derive(Debug, Clone)]
     struct TestSet {
@@ -267,4 +487,83 @@ mod tests {
         let fp_rate = false_positives as f64 / tests as f64;
         assert!(fp_rate < 1.0); // This isn't really a test, but so that you have the `fp_rate` variable to print out, or evaluate
     }
+
+    #[test]
+    fn multi_binary_filters_union_and_intersection() {
+        let mut evens = MultiBinaryBloomFilter::from_estimate(1000, 0.01);
+        let mut odds = MultiBinaryBloomFilter::from_estimate(1000, 0.01);
+        let mut small = MultiBinaryBloomFilter::from_estimate(1000, 0.01);
+        for i in 0..1000 {
+            evens.insert(2 * i);
+            odds.insert(2 * i + 1);
+            if i < 100 {
+                small.insert(i);
+            }
+        }
+
+        let mut union = MultiBinaryBloomFilter::from_estimate(1000, 0.01);
+        union.union(&evens).unwrap();
+        union.union(&odds).unwrap();
+        assert!((0..2000).all(|i| union.contains(&i)));
+
+        evens.intersection(&small).unwrap();
+        assert!((0..100).step_by(2).all(|i| evens.contains(&i)));
+        let false_positives = (1000..3000).filter(|i| evens.contains(i)).count();
+        assert!(false_positives < 100);
+
+        let other_size = MultiBinaryBloomFilter::from_estimate(10, 0.01);
+        assert_eq!(
+            union.union(&other_size),
+            Err(FilterError::IncompatibleFilters)
+        );
+    }
+
+    #[test]
+    fn stable_hash_does_not_depend_on_the_platform() {
+        let hash_bytes = |bytes: &[u8]| {
+            let mut hasher = StableHasher(0xcbf2_9ce4_8422_2325);
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(stable_hash(&0x0102_0304_u32), hash_bytes(&[4, 3, 2, 1]));
+        assert_eq!(stable_hash(&-2_i16), hash_bytes(&[0xfe, 0xff]));
+        assert_eq!(stable_hash(&7_usize), stable_hash(&7_u64));
+        assert_eq!(stable_hash(&-7_isize), stable_hash(&-7_i64));
+        // Slices and strings hash their length as a `usize` first
+        assert_eq!(
+            stable_hash(&[1_u8, 2][..]),
+            hash_bytes(&[2, 0, 0, 0, 0, 0, 0, 0, 1, 2])
+        );
+    }
+
+    #[test]
+    fn multi_binary_filter_serialization() {
+        let mut filter = MultiBinaryBloomFilter::from_estimate(500, 0.01);
+        for word in ["batch", "job", "service"] {
+            filter.insert(word);
+        }
+        let bytes = filter.to_bytes();
+        let restored = MultiBinaryBloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        for word in ["batch", "job", "service"] {
+            assert!(restored.contains(&word));
+        }
+        assert!(!restored.contains(&"missing"));
+
+        assert_eq!(
+            MultiBinaryBloomFilter::from_bytes(&bytes[..bytes.len() - 1]).err(),
+            Some(FilterError::InvalidBytes)
+        );
+        assert_eq!(
+            MultiBinaryBloomFilter::from_bytes(b"X").err(),
+            Some(FilterError::InvalidBytes)
+        );
+        for (filter_size, hash_count) in [(0, 3), (64, 0)] {
+            let bytes = MultiBinaryBloomFilter::with_dimensions(filter_size, hash_count).to_bytes();
+            assert_eq!(
+                MultiBinaryBloomFilter::from_bytes(&bytes).err(),
+                Some(FilterError::InvalidBytes)
+            );
+        }
+    }
 }
@@ -1,6 +1,6 @@
-use std::collections::hash_map::RandomState;
+use super::bloom_filter::stable_hash;
 use std::fmt::{Debug, Formatter};
-use std::hash::{BuildHasher, Hash};
+use std::hash::Hash;
 
 /// A probabilistic data structure holding an approximate count for diverse items efficiently (using constant space)
 ///
@@ -88,10 +88,51 @@ pub trait CountMinSketch {
 ///
 /// This could potentially be overestimated if we have a huge number of entries and a lot of collisions.
 /// But an interesting property is that the count we return for "TEST" cannot be underestimated
+///
+/// The hash function of row `i` hashes the pair `(i, item)`. It doesn't depend on a random seed,
+/// so that two sketches with the same dimensions always map an item to the same counters and can be merged
+///
+/// Overestimation can be reduced with conservative update: when incrementing, only raise the counters
+/// of an item up to its new estimated count (its current minimum plus the increment) instead of adding to all of them.
+/// The counts remain upper bounds, but collisions inflate them less.
 pub struct HashCountMinSketch<Item: Hash, const WIDTH: usize, const DEPTH: usize> {
     phantom: std::marker::PhantomData<Item>, // just a marker for Item to be used
     counts: [[usize; WIDTH]; DEPTH],
-    hashers: [RandomState; DEPTH],
+    conservative: bool,
+}
+
+impl<Item: Hash, const WIDTH: usize, const DEPTH: usize> HashCountMinSketch<Item, WIDTH, DEPTH> {
+    /// Creates a sketch using conservative update
+    pub fn conservative() -> Self {
+        Self {
+            conservative: true,
+            ..Self::default()
+        }
+    }
+
+    /// Adds the counts of `other` to this sketch, as if every item of `other` had been counted here too
+    ///
+    /// Merging conservative sketches also gives upper bounds, but not necessarily the counts a single conservative sketch would have given
+    pub fn merge(&mut self, other: &Self) {
+        for (row, other_row) in self.counts.iter_mut().zip(&other.counts) {
+            for (count, other_count) in row.iter_mut().zip(other_row) {
+                *count += other_count;
+            }
+        }
+    }
+
+    fn estimate(&self, item: &Item) -> usize {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(row, counts)| counts[Self::column(row, item)])
+            .min()
+            .unwrap()
+    }
+
+    fn column(row: usize, item: &Item) -> usize {
+        (stable_hash(&(row, item)) % WIDTH as u64) as usize
+    }
 }
 
 impl<Item: Hash, const WIDTH: usize, const DEPTH: usize> Debug
@@ -106,12 +147,10 @@ impl<T: Hash, const WIDTH: usize, const DEPTH: usize> Default
     for HashCountMinSketch<T, WIDTH, DEPTH>
 {
     fn default() -> Self {
-        let hashers = std::array::from_fn(|_| RandomState::new());
-
         Self {
             phantom: std::marker::PhantomData,
             counts: [[0; WIDTH]; DEPTH],
-            hashers,
+            conservative: false,
         }
     }
 }
@@ -126,28 +165,21 @@ impl<Item: Hash, const WIDTH: usize, const DEPTH: usize> CountMinSketch
     }
 
     fn increment_by(&mut self, item: Self::Item, count: usize) {
-        for (row, r) in self.hashers.iter_mut().enumerate() {
-            let mut h = r.build_hasher();
-            item.hash(&mut h);
-            let hashed = r.hash_one(&item);
-            let col = (hashed % WIDTH as u64) as usize;
-            self.counts[row][col] += count;
+        if self.conservative {
+            let target = self.estimate(&item) + count;
+            for (row, counts) in self.counts.iter_mut().enumerate() {
+                let col = Self::column(row, &item);
+                counts[col] = counts[col].max(target);
+            }
+        } else {
+            for (row, counts) in self.counts.iter_mut().enumerate() {
+                counts[Self::column(row, &item)] += count;
+            }
         }
     }
 
     fn get_count(&self, item: Self::Item) -> usize {
-        self.hashers
-            .iter()
-            .enumerate()
-            .map(|(row, r)| {
-                let mut h = r.build_hasher();
-                item.hash(&mut h);
-                let hashed = r.hash_one(&item);
-                let col = (hashed % WIDTH as u64) as usize;
-                self.counts[row][col]
-            })
-            .min()
-            .unwrap()
+        self.estimate(&item)
     }
 }
 
@@ -244,4 +276,42 @@ mod tests {
             assert!(exact_ratio > 0.7); // the proof is quite hard, but this should be OK
         }
     }
+
+    #[test]
+    fn merge_adds_up_counts() {
+        let mut left: HashCountMinSketch<&str, 64, 4> = HashCountMinSketch::default();
+        let mut right: HashCountMinSketch<&str, 64, 4> = HashCountMinSketch::default();
+        left.increment_by("a", 3);
+        left.increment("b");
+        right.increment_by("a", 2);
+        right.increment_by("c", 7);
+        left.merge(&right);
+        assert!(left.get_count("a") >= 5);
+        assert!(left.get_count("b") >= 1);
+        assert!(left.get_count("c") >= 7);
+        assert_eq!(left.get_count("a").min(left.get_count("c")), 5);
+    }
+
+    #[test]
+    fn conservative_update_never_overestimates_more() {
+        let mut regular: HashCountMinSketch<u32, 20, 3> = HashCountMinSketch::default();
+        let mut conservative: HashCountMinSketch<u32, 20, 3> = HashCountMinSketch::conservative();
+        let mut exact = vec![0; 200];
+        for i in 0..2000u32 {
+            let item = (i * i + 7 * i) % 200;
+            regular.increment(item);
+            conservative.increment(item);
+            exact[item as usize] += 1;
+        }
+        let mut regular_error = 0;
+        let mut conservative_error = 0;
+        for (item, &count) in exact.iter().enumerate() {
+            let item = item as u32;
+            assert!(conservative.get_count(item) >= count);
+            assert!(conservative.get_count(item) <= regular.get_count(item));
+            regular_error += regular.get_count(item) - count;
+            conservative_error += conservative.get_count(item) - count;
+        }
+        assert!(conservative_error < regular_error);
+    }
 }
@@ -0,0 +1,181 @@
+use super::bloom_filter::{
+    bit_indices, optimal_dimensions, put_u64, BloomFilter, ByteReader, FilterError,
+};
+use std::hash::Hash;
+
+const COUNTING_TAG: u8 = b'C';
+
+/// A counting Bloom filter replaces every bit of a Bloom filter with a small counter
+///
+/// Inserting an item increments its counters instead of setting its bits, so removing it is just a matter of decrementing them back.
+/// As with a regular Bloom filter, a counter at 0 means that no inserted item maps to it: `contains` never returns false negatives,
+/// provided that only items which were inserted get removed.
+///
+/// Counters are bytes, which is far more than enough in practice: with optimal dimensions, the probability of a counter reaching 16 is already negligible.
+/// A counter that still overflows sticks at its maximum value, and is never decremented again, so that it can't cause a false negative
+pub struct CountingBloomFilter {
+    counters: Vec<u8>,
+    hash_count: usize,
+}
+
+impl CountingBloomFilter {
+    pub fn with_dimensions(filter_size: usize, hash_count: usize) -> Self {
+        Self {
+            counters: vec![0; filter_size],
+            hash_count,
+        }
+    }
+
+    pub fn from_estimate(
+        estimated_count_of_items: usize,
+        max_false_positive_probability: f64,
+    ) -> Self {
+        let (filter_size, hash_count) =
+            optimal_dimensions(estimated_count_of_items, max_false_positive_probability);
+        Self::with_dimensions(filter_size, hash_count)
+    }
+
+    /// Removes one occurrence of `item`, returning `false` if it was definitely not in the filter
+    ///
+    /// Removing an item which was never inserted, but is a false positive, corrupts the filter: it may then return false negatives
+    pub fn remove<Item: Hash>(&mut self, item: &Item) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        for index in bit_indices(item, self.hash_count, self.counters.len()) {
+            if self.counters[index] != u8::MAX {
+                self.counters[index] -= 1;
+            }
+        }
+        true
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), FilterError> {
+        if self.counters.len() == other.counters.len() && self.hash_count == other.hash_count {
+            Ok(())
+        } else {
+            Err(FilterError::IncompatibleFilters)
+        }
+    }
+
+    /// Turns `self` into a filter of the items of both filters, by adding up their counters
+    pub fn union(&mut self, other: &Self) -> Result<(), FilterError> {
+        self.check_compatible(other)?;
+        for (counter, other_counter) in self.counters.iter_mut().zip(&other.counters) {
+            *counter = counter.saturating_add(*other_counter);
+        }
+        Ok(())
+    }
+
+    /// Turns `self` into a filter of the items common to both filters, by keeping the smallest counters
+    pub fn intersection(&mut self, other: &Self) -> Result<(), FilterError> {
+        self.check_compatible(other)?;
+        for (counter, other_counter) in self.counters.iter_mut().zip(&other.counters) {
+            *counter = (*counter).min(*other_counter);
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![COUNTING_TAG];
+        put_u64(&mut buffer, self.counters.len() as u64);
+        put_u64(&mut buffer, self.hash_count as u64);
+        buffer.extend_from_slice(&self.counters);
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader::new(bytes, COUNTING_TAG)?;
+        let filter_size = reader.read_usize()?;
+        let hash_count = reader.read_usize()?;
+        if filter_size == 0 || hash_count == 0 {
+            return Err(FilterError::InvalidBytes);
+        }
+        let counters = reader.read_bytes(filter_size)?.to_vec();
+        reader.finish()?;
+        Ok(Self {
+            counters,
+            hash_count,
+        })
+    }
+}
+
+impl<Item: Hash> BloomFilter<Item> for CountingBloomFilter {
+    fn insert(&mut self, item: Item) {
+        for index in bit_indices(&item, self.hash_count, self.counters.len()) {
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+    }
+
+    fn contains(&self, item: &Item) -> bool {
+        bit_indices(item, self.hash_count, self.counters.len())
+            .all(|index| self.counters[index] > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_items_are_no_longer_found() {
+        let mut filter = CountingBloomFilter::from_estimate(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(i);
+        }
+        for i in (0..1000).step_by(2) {
+            assert!(filter.remove(&i));
+        }
+        assert!((1..1000).step_by(2).all(|i| filter.contains(&i)));
+        let still_found = (0..1000).step_by(2).filter(|i| filter.contains(i)).count();
+        assert!(still_found < 50, "{still_found} removed items still found");
+        assert!(!filter.remove(&-1) || filter.contains(&-1));
+
+        // An item inserted twice survives one removal
+        filter.insert(5000);
+        filter.insert(5000);
+        assert!(filter.remove(&5000));
+        assert!(filter.contains(&5000));
+    }
+
+    #[test]
+    fn union_intersection_and_serialization() {
+        let mut left = CountingBloomFilter::with_dimensions(2048, 5);
+        let mut right = CountingBloomFilter::with_dimensions(2048, 5);
+        for word in ["a", "b", "c"] {
+            left.insert(word);
+        }
+        for word in ["c", "d"] {
+            right.insert(word);
+        }
+
+        let mut both = CountingBloomFilter::from_bytes(&left.to_bytes()).unwrap();
+        both.intersection(&right).unwrap();
+        assert!(both.contains(&"c"));
+        assert!(!both.contains(&"a") && !both.contains(&"d"));
+
+        left.union(&right).unwrap();
+        assert!(["a", "b", "c", "d"].iter().all(|word| left.contains(word)));
+        // "c" was inserted in both filters, so it must be removed twice
+        assert!(left.remove(&"c"));
+        assert!(left.contains(&"c"));
+
+        let restored = CountingBloomFilter::from_bytes(&left.to_bytes()).unwrap();
+        assert!(["a", "b", "c", "d"]
+            .iter()
+            .all(|word| restored.contains(word)));
+        assert_eq!(
+            left.union(&CountingBloomFilter::with_dimensions(2048, 4)),
+            Err(FilterError::IncompatibleFilters)
+        );
+        assert_eq!(
+            CountingBloomFilter::from_bytes(&right.to_bytes()[..20]).err(),
+            Some(FilterError::InvalidBytes)
+        );
+        assert_eq!(
+            CountingBloomFilter::from_bytes(&CountingBloomFilter::with_dimensions(0, 5).to_bytes())
+                .err(),
+            Some(FilterError::InvalidBytes)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use super::bloom_filter::{mix64, put_u64, stable_hash, ByteReader, FilterError};
+use std::hash::Hash;
+
+const CUCKOO_TAG: u8 = b'K';
+/// Number of fingerprints per bucket
+const BUCKET_SIZE: usize = 4;
+/// Number of evictions after which the filter is considered full
+const MAX_KICKS: usize = 500;
+/// Fingerprint marking an empty slot
+const EMPTY: u16 = 0;
+
+/// A cuckoo filter <https://www.cs.cmu.edu/~dga/papers/cuckoo-conext2014.pdf> answers the same question as a Bloom filter,
+/// but also supports deletion, and uses less space than a Bloom filter for false positive rates below 3%.
+///
+/// Instead of setting bits, it stores a 16 bits fingerprint of each item, in one of two candidate buckets of 4 slots.
+/// The second bucket is computed from the first one and the fingerprint alone (`i2 = i1 ^ hash(fingerprint)`),
+/// so a fingerprint can be moved to its other bucket without knowing the item it came from.
+/// When both buckets are full, a random fingerprint is evicted to make room, and moves to its other bucket, possibly evicting another one, and so on (as in cuckoo hashing).
+///
+/// Unlike a Bloom filter, a cuckoo filter can be full: `insert` then fails
+pub struct CuckooFilter {
+    buckets: Vec<[u16; BUCKET_SIZE]>,
+    len: usize,
+    /// A fingerprint (and its bucket) left homeless by the last insertion, which then made the filter full
+    victim: Option<(usize, u16)>,
+}
+
+impl CuckooFilter {
+    /// Creates a filter able to hold about `capacity` items
+    pub fn with_capacity(capacity: usize) -> Self {
+        // Cuckoo filters with buckets of 4 can be filled up to ~95% of their slots
+        let buckets = (capacity * 100 / 95 / BUCKET_SIZE + 1).next_power_of_two();
+        Self {
+            buckets: vec![[EMPTY; BUCKET_SIZE]; buckets],
+            len: 0,
+            victim: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn mask(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    fn fingerprint_and_index<Item: Hash + ?Sized>(&self, item: &Item) -> (u16, usize) {
+        let hash = stable_hash(item);
+        let fingerprint = match (hash >> 48) as u16 {
+            EMPTY => 1,
+            fingerprint => fingerprint,
+        };
+        (fingerprint, hash as usize & self.mask())
+    }
+
+    fn alternate_index(&self, index: usize, fingerprint: u16) -> usize {
+        index ^ (mix64(u64::from(fingerprint)) as usize & self.mask())
+    }
+
+    /// Stores `fingerprint` in a free slot of bucket `index`, if any
+    fn place(&mut self, index: usize, fingerprint: u16) -> bool {
+        match self.buckets[index].iter_mut().find(|slot| **slot == EMPTY) {
+            Some(slot) => {
+                *slot = fingerprint;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts `item`, which may be inserted several times
+    ///
+    /// Returns `FilterError::Full` if there was no room left for it
+    pub fn insert<Item: Hash + ?Sized>(&mut self, item: &Item) -> Result<(), FilterError> {
+        if self.victim.is_some() {
+            return Err(FilterError::Full);
+        }
+        let (mut fingerprint, first) = self.fingerprint_and_index(item);
+        let second = self.alternate_index(first, fingerprint);
+        self.len += 1;
+        if self.place(first, fingerprint) || self.place(second, fingerprint) {
+            return Ok(());
+        }
+
+        let mut index = if fingerprint & 1 == 0 { first } else { second };
+        for kick in 0..MAX_KICKS {
+            // Pseudo-random, but deterministic, choice of the fingerprint to evict
+            let slot = (mix64(kick as u64 ^ u64::from(fingerprint)) % BUCKET_SIZE as u64) as usize;
+            std::mem::swap(&mut fingerprint, &mut self.buckets[index][slot]);
+            index = self.alternate_index(index, fingerprint);
+            if self.place(index, fingerprint) {
+                return Ok(());
+            }
+        }
+        // The item itself was stored, but another fingerprint is now homeless.
+        // Keeping it aside avoids a false negative, and marks the filter as full
+        self.victim = Some((index, fingerprint));
+        Ok(())
+    }
+
+    pub fn contains<Item: Hash + ?Sized>(&self, item: &Item) -> bool {
+        let (fingerprint, first) = self.fingerprint_and_index(item);
+        let second = self.alternate_index(first, fingerprint);
+        self.buckets[first].contains(&fingerprint)
+            || self.buckets[second].contains(&fingerprint)
+            || self.victim.is_some_and(|(index, victim)| {
+                victim == fingerprint && (index == first || index == second)
+            })
+    }
+
+    /// Removes one occurrence of `item`, returning `false` if it was definitely not in the filter
+    ///
+    /// As with a counting Bloom filter, only items which were inserted should be removed
+    pub fn remove<Item: Hash + ?Sized>(&mut self, item: &Item) -> bool {
+        let (fingerprint, first) = self.fingerprint_and_index(item);
+        let second = self.alternate_index(first, fingerprint);
+        if self.victim.is_some_and(|(index, victim)| {
+            victim == fingerprint && (index == first || index == second)
+        }) {
+            self.victim = None;
+            self.len -= 1;
+            return true;
+        }
+
+        let removed = [first, second].into_iter().any(|index| {
+            match self.buckets[index]
+                .iter_mut()
+                .find(|slot| **slot == fingerprint)
+            {
+                Some(slot) => {
+                    *slot = EMPTY;
+                    true
+                }
+                None => false,
+            }
+        });
+        if removed {
+            self.len -= 1;
+            // Give the homeless fingerprint a chance to use the slot just freed
+            if let Some((index, victim)) = self.victim {
+                let alternate = self.alternate_index(index, victim);
+                if self.place(index, victim) || self.place(alternate, victim) {
+                    self.victim = None;
+                }
+            }
+        }
+        removed
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![CUCKOO_TAG];
+        put_u64(&mut buffer, self.buckets.len() as u64);
+        let (victim_index, victim) = self.victim.unwrap_or((0, EMPTY));
+        put_u64(&mut buffer, victim_index as u64);
+        buffer.extend_from_slice(&victim.to_le_bytes());
+        for fingerprint in self.buckets.iter().flatten() {
+            buffer.extend_from_slice(&fingerprint.to_le_bytes());
+        }
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader::new(bytes, CUCKOO_TAG)?;
+        let bucket_count = reader.read_usize()?;
+        if !bucket_count.is_power_of_two() {
+            return Err(FilterError::InvalidBytes);
+        }
+        let victim_index = reader.read_usize()?;
+        let victim = u16::from_le_bytes([reader.read_bytes(1)?[0], reader.read_bytes(1)?[0]]);
+        let fingerprints_size = bucket_count
+            .checked_mul(BUCKET_SIZE * 2)
+            .ok_or(FilterError::InvalidBytes)?;
+        let fingerprints = reader.read_bytes(fingerprints_size)?;
+        reader.finish()?;
+        if victim != EMPTY && victim_index >= bucket_count {
+            return Err(FilterError::InvalidBytes);
+        }
+
+        let buckets: Vec<[u16; BUCKET_SIZE]> = fingerprints
+            .chunks_exact(BUCKET_SIZE * 2)
+            .map(|bucket| {
+                let mut slots = [EMPTY; BUCKET_SIZE];
+                for (slot, bytes) in slots.iter_mut().zip(bucket.chunks_exact(2)) {
+                    *slot = u16::from_le_bytes([bytes[0], bytes[1]]);
+                }
+                slots
+            })
+            .collect();
+        // Every stored fingerprint is an item, which keeps the length consistent with the slots
+        let len = buckets
+            .iter()
+            .flatten()
+            .filter(|&&slot| slot != EMPTY)
+            .count()
+            + usize::from(victim != EMPTY);
+        Ok(Self {
+            buckets,
+            len,
+            victim: (victim != EMPTY).then_some((victim_index, victim)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_contains_remove() {
+        let mut filter = CuckooFilter::with_capacity(10_000);
+        for i in 0..10_000 {
+            assert_eq!(filter.insert(&i), Ok(()));
+        }
+        assert_eq!(filter.len(), 10_000);
+        assert!((0..10_000).all(|i| filter.contains(&i)));
+        let false_positives = (10_000..110_000).filter(|i| filter.contains(i)).count();
+        // Expected rate: 2 * BUCKET_SIZE / 2^16, about 0.012%
+        assert!(false_positives < 100, "{false_positives} false positives");
+
+        for i in (0..10_000).step_by(2) {
+            assert!(filter.remove(&i));
+        }
+        assert_eq!(filter.len(), 5_000);
+        assert!((1..10_000).step_by(2).all(|i| filter.contains(&i)));
+        let still_found = (0..10_000)
+            .step_by(2)
+            .filter(|i| filter.contains(i))
+            .count();
+        assert!(still_found < 10);
+    }
+
+    #[test]
+    fn fills_up_without_false_negatives() {
+        let mut filter = CuckooFilter::with_capacity(100);
+        let mut inserted = 0;
+        while filter.insert(&inserted).is_ok() {
+            inserted += 1;
+        }
+        assert!(inserted >= 100, "only {inserted} items fit");
+        assert!((0..inserted).all(|i| filter.contains(&i)));
+        assert_eq!(filter.insert(&"one more"), Err(FilterError::Full));
+
+        // Removing the items also rehomes the evicted fingerprint
+        assert!((0..inserted).all(|i| filter.remove(&i)));
+        assert!(filter.is_empty());
+        assert!(filter.victim.is_none());
+        assert_eq!(filter.insert(&0), Ok(()));
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let mut filter = CuckooFilter::with_capacity(64);
+        for word in ["alpha", "beta", "gamma"] {
+            filter.insert(word).unwrap();
+        }
+        let bytes = filter.to_bytes();
+        let mut restored = CuckooFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.len(), 3);
+        assert!(restored.remove("beta"));
+        assert!(restored.contains("alpha") && !restored.contains("beta"));
+
+        assert_eq!(
+            CuckooFilter::from_bytes(&bytes[1..]).err(),
+            Some(FilterError::InvalidBytes)
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_bytes() {
+        // A power of two bucket count whose fingerprints don't fit in memory
+        let mut bytes = vec![CUCKOO_TAG];
+        put_u64(&mut bytes, 1 << 62);
+        put_u64(&mut bytes, 0);
+        bytes.extend_from_slice(&[0; 2]);
+        bytes.resize(bytes.len() + (1 << 16), 0);
+        assert_eq!(
+            CuckooFilter::from_bytes(&bytes).err(),
+            Some(FilterError::InvalidBytes)
+        );
+
+        // The length is recounted from the slots, so removing stays consistent
+        let mut filter = CuckooFilter::with_capacity(8);
+        filter.insert("only").unwrap();
+        let mut restored = CuckooFilter::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert!(restored.remove("only"));
+        assert!(restored.is_empty());
+        assert!(!restored.remove("only"));
+    }
+}
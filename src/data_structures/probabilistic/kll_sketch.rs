@@ -0,0 +1,221 @@
+/// A probabilistic data structure summarizing the distribution of a stream of ordered items in sub-linear space
+///
+/// Here is the definition of the different allowed operations on a `QuantileSketch`:
+///     * insert an item
+///     * estimate the rank of an item: the fraction of the items inserted which are smaller than or equal to it
+///     * estimate the q-quantile: an item whose rank is about `q` (`0.5` for the median)
+pub trait QuantileSketch {
+    type Item;
+
+    fn insert(&mut self, item: Self::Item);
+    fn rank(&self, item: &Self::Item) -> f64;
+    fn quantile(&self, q: f64) -> Option<Self::Item>;
+}
+
+/// Capacity ratio between a level and the level above it
+const DECAY: f64 = 2.0 / 3.0;
+/// Smallest capacity of a level
+const MIN_LEVEL_CAPACITY: usize = 2;
+
+/// The KLL sketch <https://arxiv.org/abs/1603.05346>
+///
+/// The sketch is a stack of levels (compactors). Items inserted are appended to level 0, where every item stands for itself.
+/// When a level is full, it is compacted: it is sorted, and one item out of two (either the odd or the even positions, at random) is promoted to the level above,
+/// where it stands for twice as many items. The rank of any value changes by at most one per compaction, and is unbiased thanks to the random choice.
+///
+/// The top level has a capacity of `k`, and the capacities decrease geometrically going down, so that the sketch holds `O(k)` items,
+/// while the lower levels, which are compacted often, hold items with a small weight.
+/// The rank error is about `1.65 / k` with high probability: 1% for the default `k = 200`
+pub struct KllSketch<T> {
+    k: usize,
+    levels: Vec<Vec<T>>,
+    count: usize,
+    /// State of the generator choosing which half of a level gets promoted
+    random_state: u64,
+}
+
+impl<T: PartialOrd + Clone> KllSketch<T> {
+    pub fn new() -> Self {
+        Self::with_k(200)
+    }
+
+    /// Creates a sketch whose top level holds `k` items. Larger `k` means more accurate ranks, but more space
+    pub fn with_k(k: usize) -> Self {
+        Self {
+            k: k.max(MIN_LEVEL_CAPACITY),
+            levels: vec![Vec::new()],
+            count: 0,
+            random_state: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Number of items inserted
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Number of items actually stored by the sketch
+    pub fn retained(&self) -> usize {
+        self.levels.iter().map(Vec::len).sum()
+    }
+
+    /// Merges `other` into this sketch, which then summarizes both streams
+    pub fn merge(&mut self, other: &Self) {
+        while self.levels.len() < other.levels.len() {
+            self.levels.push(Vec::new());
+        }
+        for (level, other_level) in self.levels.iter_mut().zip(&other.levels) {
+            level.extend_from_slice(other_level);
+        }
+        self.count += other.count;
+        self.compress();
+    }
+
+    fn level_capacity(&self, level: usize) -> usize {
+        let depth = self.levels.len() - 1 - level;
+        ((self.k as f64 * DECAY.powi(depth as i32)).ceil() as usize).max(MIN_LEVEL_CAPACITY)
+    }
+
+    fn total_capacity(&self) -> usize {
+        (0..self.levels.len())
+            .map(|level| self.level_capacity(level))
+            .sum()
+    }
+
+    fn random_bit(&mut self) -> usize {
+        // xorshift64
+        self.random_state ^= self.random_state << 13;
+        self.random_state ^= self.random_state >> 7;
+        self.random_state ^= self.random_state << 17;
+        (self.random_state & 1) as usize
+    }
+
+    /// Compacts full levels until the sketch fits in its capacity
+    fn compress(&mut self) {
+        while self.retained() > self.total_capacity() {
+            let level = (0..self.levels.len())
+                .find(|&level| self.levels[level].len() >= self.level_capacity(level))
+                .unwrap_or(0);
+            if level + 1 == self.levels.len() {
+                self.levels.push(Vec::new());
+            }
+
+            let mut items = std::mem::take(&mut self.levels[level]);
+            items.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            // An odd item out stays on its level
+            if items.len() % 2 == 1 {
+                let last = items.pop().expect("the level has an odd number of items");
+                self.levels[level].push(last);
+            }
+            let offset = self.random_bit();
+            let promoted = items.into_iter().skip(offset).step_by(2);
+            self.levels[level + 1].extend(promoted);
+        }
+    }
+
+    /// Returns the stored items with their weights, sorted by item
+    fn weighted_items(&self) -> Vec<(&T, usize)> {
+        let mut items: Vec<(&T, usize)> = self
+            .levels
+            .iter()
+            .enumerate()
+            .flat_map(|(level, items)| items.iter().map(move |item| (item, 1 << level)))
+            .collect();
+        items.sort_by(|a, b| a.0.partial_cmp(b.0).unwrap_or(std::cmp::Ordering::Equal));
+        items
+    }
+}
+
+impl<T: PartialOrd + Clone> Default for KllSketch<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone> QuantileSketch for KllSketch<T> {
+    type Item = T;
+
+    fn insert(&mut self, item: T) {
+        self.levels[0].push(item);
+        self.count += 1;
+        self.compress();
+    }
+
+    fn rank(&self, item: &T) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let weight: usize = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(level, items)| items.iter().filter(|stored| *stored <= item).count() << level)
+            .sum();
+        weight as f64 / self.count as f64
+    }
+
+    fn quantile(&self, q: f64) -> Option<T> {
+        let items = self.weighted_items();
+        let total: usize = items.iter().map(|(_, weight)| weight).sum();
+        let target = (q.clamp(0.0, 1.0) * total as f64).ceil() as usize;
+        let mut cumulative = 0;
+        for (item, weight) in &items {
+            cumulative += weight;
+            if cumulative >= target {
+                return Some((*item).clone());
+            }
+        }
+        items.last().map(|(item, _)| (*item).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A permutation of `0..n`, so that the items don't arrive in order
+    fn shuffled(n: u64) -> impl Iterator<Item = u64> {
+        (0..n).map(move |i| (i * 7_919) % n)
+    }
+
+    #[test]
+    fn quantiles_of_a_permutation() {
+        let n = 100_000;
+        let mut sketch = KllSketch::new();
+        for item in shuffled(n) {
+            sketch.insert(item);
+        }
+        assert_eq!(sketch.len(), n as usize);
+        assert!(
+            sketch.retained() < 1_000,
+            "{} items retained",
+            sketch.retained()
+        );
+        for q in [0.01, 0.1, 0.25, 0.5, 0.9, 0.99] {
+            let estimate = sketch.quantile(q).unwrap() as f64 / n as f64;
+            assert!((estimate - q).abs() < 0.02, "q = {q}: {estimate}");
+            let rank = sketch.rank(&((q * n as f64) as u64));
+            assert!((rank - q).abs() < 0.02, "rank of q = {q}: {rank}");
+        }
+        assert_eq!(KllSketch::<u64>::new().quantile(0.5), None);
+    }
+
+    #[test]
+    fn merged_sketches_summarize_both_streams() {
+        let mut low = KllSketch::with_k(100);
+        let mut high = KllSketch::with_k(100);
+        for item in shuffled(10_000) {
+            low.insert(item as f64);
+            high.insert(item as f64 + 10_000.0);
+        }
+        low.merge(&high);
+        assert_eq!(low.len(), 20_000);
+        let median = low.quantile(0.5).unwrap();
+        assert!((median - 10_000.0).abs() < 600.0, "median: {median}");
+        assert!((low.rank(&5_000.0) - 0.25).abs() < 0.03);
+    }
+}
@@ -1,2 +1,9 @@
 pub mod bloom_filter;
 pub mod count_min_sketch;
+pub mod counting_bloom_filter;
+pub mod cuckoo_filter;
+pub mod hyperloglog;
+pub mod kll_sketch;
+pub mod reservoir_sampling;
+pub mod scalable_bloom_filter;
+pub mod space_saving;
@@ -0,0 +1,94 @@
+use rand::RngExt;
+
+/// Keeps a uniform random sample of a stream of unknown length, in constant space
+///
+/// Here is the definition of the different allowed operations on a `StreamSampler`:
+///     * observe an item of the stream
+///     * retrieve the current sample
+pub trait StreamSampler {
+    type Item;
+
+    fn observe(&mut self, item: Self::Item);
+    fn sample(&self) -> &[Self::Item];
+}
+
+/// Reservoir sampling (Algorithm R) <https://en.wikipedia.org/wiki/Reservoir_sampling>
+///
+/// The first `capacity` items fill the reservoir. After that, the `n`-th item replaces a random item of the reservoir with probability `capacity / n`.
+/// By induction, after `n` items, every one of them is in the reservoir with the same probability `capacity / n`
+pub struct ReservoirSampler<Item, Rng: RngExt> {
+    capacity: usize,
+    reservoir: Vec<Item>,
+    seen: usize,
+    rng: Rng,
+}
+
+impl<Item, Rng: RngExt> ReservoirSampler<Item, Rng> {
+    pub fn new(capacity: usize, rng: Rng) -> Self {
+        Self {
+            capacity,
+            reservoir: Vec::with_capacity(capacity),
+            seen: 0,
+            rng,
+        }
+    }
+
+    /// Number of items observed so far
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+}
+
+impl<Item, Rng: RngExt> StreamSampler for ReservoirSampler<Item, Rng> {
+    type Item = Item;
+
+    fn observe(&mut self, item: Item) {
+        self.seen += 1;
+        if self.reservoir.len() < self.capacity {
+            self.reservoir.push(item);
+            return;
+        }
+        let index = self.rng.random_range(0..self.seen);
+        if index < self.capacity {
+            self.reservoir[index] = item;
+        }
+    }
+
+    fn sample(&self) -> &[Item] {
+        &self.reservoir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_streams_are_kept_entirely() {
+        let mut sampler = ReservoirSampler::new(10, rand::rng());
+        for i in 0..7 {
+            sampler.observe(i);
+        }
+        assert_eq!(sampler.sample(), &[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(sampler.seen(), 7);
+    }
+
+    #[test]
+    fn every_item_is_equally_likely() {
+        let mut hits = [0; 20];
+        for _ in 0..5_000 {
+            let mut sampler = ReservoirSampler::new(5, rand::rng());
+            for i in 0..20 {
+                sampler.observe(i);
+            }
+            assert_eq!(sampler.sample().len(), 5);
+            for &i in sampler.sample() {
+                hits[i] += 1;
+            }
+        }
+        // Each item is expected 1250 times, with a standard deviation of about 30
+        for count in hits {
+            assert!((1_050..1_450).contains(&count), "{hits:?}");
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use super::bloom_filter::{
+    optimal_dimensions, put_u64, BloomFilter, ByteReader, FilterError, MultiBinaryBloomFilter,
+};
+use std::hash::Hash;
+
+const SCALABLE_TAG: u8 = b'S';
+/// Each new slice can hold this many times more items than the previous one
+const GROWTH_FACTOR: usize = 2;
+/// Each new slice has a false positive probability this many times smaller than the previous one
+const TIGHTENING_RATIO: f64 = 0.5;
+
+struct Slice {
+    filter: MultiBinaryBloomFilter,
+    capacity: usize,
+    count: usize,
+}
+
+/// A Bloom filter has to be sized for the number of items it will hold: past this number, its false positive rate quickly degrades.
+/// A scalable Bloom filter <https://gsd.di.uminho.pt/members/cbm/ps/dbloom.pdf> doesn't need to know this number upfront.
+///
+/// It is a list of regular Bloom filters (slices), and items are only inserted into the last one.
+/// When the last slice is full, a new slice is added, `GROWTH_FACTOR` times bigger.
+/// An item may be found by any slice, so the false positive probabilities of the slices add up:
+/// making them decrease geometrically (`p0, p0 * r, p0 * r^2, ...`) bounds their sum by `p0 / (1 - r)`,
+/// which is kept below the target by picking `p0 = p * (1 - r)`
+pub struct ScalableBloomFilter {
+    initial_capacity: usize,
+    false_positive_probability: f64,
+    slices: Vec<Slice>,
+}
+
+impl ScalableBloomFilter {
+    pub fn new(initial_capacity: usize, max_false_positive_probability: f64) -> Self {
+        let mut filter = Self {
+            initial_capacity: initial_capacity.max(1),
+            false_positive_probability: max_false_positive_probability,
+            slices: Vec::new(),
+        };
+        filter.add_slice();
+        filter
+    }
+
+    /// The capacity and false positive probability of the `i`-th slice
+    fn slice_parameters(&self, i: usize) -> (usize, f64) {
+        let capacity = self
+            .initial_capacity
+            .saturating_mul(GROWTH_FACTOR.saturating_pow(i as u32));
+        let probability = self.false_positive_probability
+            * (1.0 - TIGHTENING_RATIO)
+            * TIGHTENING_RATIO.powi(i as i32);
+        (capacity, probability)
+    }
+
+    fn add_slice(&mut self) {
+        let (capacity, probability) = self.slice_parameters(self.slices.len());
+        self.slices.push(Slice {
+            filter: MultiBinaryBloomFilter::from_estimate(capacity, probability),
+            capacity,
+            count: 0,
+        });
+    }
+
+    /// Number of distinct items inserted, not counting the ones wrongly considered as already present
+    pub fn len(&self) -> usize {
+        self.slices.iter().map(|slice| slice.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn slice_count(&self) -> usize {
+        self.slices.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![SCALABLE_TAG];
+        put_u64(&mut buffer, self.initial_capacity as u64);
+        put_u64(&mut buffer, self.false_positive_probability.to_bits());
+        put_u64(&mut buffer, self.slices.len() as u64);
+        for slice in &self.slices {
+            let filter = slice.filter.to_bytes();
+            put_u64(&mut buffer, slice.capacity as u64);
+            put_u64(&mut buffer, slice.count as u64);
+            put_u64(&mut buffer, filter.len() as u64);
+            buffer.extend_from_slice(&filter);
+        }
+        buffer
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader::new(bytes, SCALABLE_TAG)?;
+        let initial_capacity = reader.read_u64()? as usize;
+        let false_positive_probability = f64::from_bits(reader.read_u64()?);
+        // `new` never builds filters without capacity, and slices need a probability strictly
+        // between 0 and 1 to get some bits and hash functions
+        if initial_capacity == 0
+            || !(false_positive_probability > 0.0 && false_positive_probability < 1.0)
+        {
+            return Err(FilterError::InvalidBytes);
+        }
+        let mut filter = Self {
+            initial_capacity,
+            false_positive_probability,
+            slices: Vec::new(),
+        };
+        let slice_count = reader.read_usize()?;
+        for i in 0..slice_count {
+            let capacity = reader.read_u64()? as usize;
+            let count = reader.read_u64()? as usize;
+            let filter_length = reader.read_usize()?;
+            let slice = MultiBinaryBloomFilter::from_bytes(reader.read_bytes(filter_length)?)?;
+            // Each slice must be the one `add_slice` would have built, which also bounds the
+            // capacity by the size of the buffer
+            let (expected_capacity, probability) = filter.slice_parameters(i);
+            if capacity != expected_capacity
+                || count > capacity
+                || slice.dimensions() != optimal_dimensions(capacity, probability)
+            {
+                return Err(FilterError::InvalidBytes);
+            }
+            filter.slices.push(Slice {
+                filter: slice,
+                capacity,
+                count,
+            });
+        }
+        reader.finish()?;
+        if filter.slices.is_empty() {
+            return Err(FilterError::InvalidBytes);
+        }
+        Ok(filter)
+    }
+}
+
+impl<Item: Hash> BloomFilter<Item> for ScalableBloomFilter {
+    fn insert(&mut self, item: Item) {
+        // Inserting an item already present would only fill the last slice faster
+        if self.contains(&item) {
+            return;
+        }
+        if self
+            .slices
+            .last()
+            .is_some_and(|slice| slice.count >= slice.capacity)
+        {
+            self.add_slice();
+        }
+        if let Some(slice) = self.slices.last_mut() {
+            slice.filter.insert(item);
+            slice.count += 1;
+        }
+    }
+
+    fn contains(&self, item: &Item) -> bool {
+        self.slices.iter().any(|slice| slice.filter.contains(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_false_positive_target_while_growing() {
+        let mut filter = ScalableBloomFilter::new(100, 0.01);
+        for i in 0..20_000 {
+            filter.insert(i);
+        }
+        assert!(filter.slice_count() > 5);
+        // Items wrongly found before their insertion are not counted
+        assert!(filter.len() <= 20_000 && filter.len() > 19_700);
+        assert!((0..20_000).all(|i| filter.contains(&i)));
+        // The target is 1%, leave some slack for the variance
+        let false_positives = (20_000..120_000).filter(|i| filter.contains(i)).count();
+        assert!(
+            false_positives < 1_500,
+            "{false_positives} false positives out of 100000"
+        );
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let mut filter = ScalableBloomFilter::new(4, 0.001);
+        for word in ["one", "two", "three", "four", "five", "six"] {
+            filter.insert(word);
+        }
+        assert_eq!(filter.slice_count(), 2);
+        let bytes = filter.to_bytes();
+        let mut restored = ScalableBloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.len(), 6);
+        assert!(restored.contains(&"four"));
+        restored.insert("seven");
+        assert!(restored.contains(&"seven"));
+
+        assert_eq!(
+            ScalableBloomFilter::from_bytes(&bytes[..bytes.len() - 3]).err(),
+            Some(FilterError::InvalidBytes)
+        );
+        assert_eq!(
+            ScalableBloomFilter::from_bytes(
+                &MultiBinaryBloomFilter::with_dimensions(8, 1).to_bytes()
+            )
+            .err(),
+            Some(FilterError::InvalidBytes)
+        );
+    }
+
+    #[test]
+    fn rejects_inconsistent_headers() {
+        let bytes = ScalableBloomFilter::new(4, 0.01).to_bytes();
+        let with_header = |capacity: u64, probability: f64| {
+            let mut corrupted = bytes.clone();
+            corrupted[1..9].copy_from_slice(&capacity.to_le_bytes());
+            corrupted[9..17].copy_from_slice(&probability.to_bits().to_le_bytes());
+            ScalableBloomFilter::from_bytes(&corrupted).err()
+        };
+        assert_eq!(with_header(4, 0.01), None);
+        for (capacity, probability) in [
+            (0, 0.01),
+            (4, f64::NAN),
+            (4, 0.0),
+            (4, 1.0),
+            (4, f64::INFINITY),
+            (1 << 60, 0.01),
+        ] {
+            assert_eq!(
+                with_header(capacity, probability),
+                Some(FilterError::InvalidBytes),
+                "{capacity} {probability}"
+            );
+        }
+
+        // A slice holding more items than its capacity
+        let mut corrupted = bytes.clone();
+        corrupted[33..41].copy_from_slice(&5_u64.to_le_bytes());
+        assert_eq!(
+            ScalableBloomFilter::from_bytes(&corrupted).err(),
+            Some(FilterError::InvalidBytes)
+        );
+    }
+}
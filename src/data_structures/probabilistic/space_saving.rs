@@ -0,0 +1,167 @@
+use super::count_min_sketch::CountMinSketch;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+/// The Space-Saving algorithm <https://www.cs.ucsb.edu/sites/default/files/documents/2005-23.pdf> tracks the most frequent items of a stream (heavy hitters)
+/// with a fixed number of counters.
+///
+/// While there are free counters, every new item gets its own, as in a frequency map.
+/// Once they are all taken, a new item replaces the item with the smallest count, and inherits its count plus one.
+/// This can only overestimate counts, by at most the count inherited (which is remembered as the error of the counter),
+/// and any item occurring more than `n / capacity` times in a stream of `n` items is guaranteed to be monitored.
+///
+/// Like a Count-min sketch, it answers approximate counts which are never underestimated, hence the implementation of `CountMinSketch`
+pub struct SpaceSaving<Item: Hash + Eq + Clone> {
+    capacity: usize,
+    /// Count and error of every monitored item
+    counters: HashMap<Item, (usize, usize)>,
+    /// The monitored items ordered by count, to find the smallest one quickly.
+    /// Items can't be compared, so they are identified by a sequence number
+    by_count: BTreeSet<(usize, u64)>,
+    ids: HashMap<Item, u64>,
+    items: HashMap<u64, Item>,
+    next_id: u64,
+}
+
+impl<Item: Hash + Eq + Clone> SpaceSaving<Item> {
+    /// Creates a tracker monitoring at most `capacity` items
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::with_capacity(capacity),
+            by_count: BTreeSet::new(),
+            ids: HashMap::with_capacity(capacity),
+            items: HashMap::with_capacity(capacity),
+            next_id: 0,
+        }
+    }
+
+    /// Returns the `k` items with the highest counts, in decreasing order of count, along with their count and maximum overestimation
+    pub fn top_k(&self, k: usize) -> Vec<(Item, usize, usize)> {
+        self.by_count
+            .iter()
+            .rev()
+            .take(k)
+            .map(|(count, id)| {
+                let item = &self.items[id];
+                (item.clone(), *count, self.counters[item].1)
+            })
+            .collect()
+    }
+
+    /// Returns the items which certainly occurred more than `threshold` times
+    pub fn heavy_hitters(&self, threshold: usize) -> Vec<Item> {
+        self.counters
+            .iter()
+            .filter(|(_, &(count, error))| count - error > threshold)
+            .map(|(item, _)| item.clone())
+            .collect()
+    }
+
+    fn min_count(&self) -> usize {
+        if self.counters.len() < self.capacity {
+            0
+        } else {
+            self.by_count.first().map_or(0, |&(count, _)| count)
+        }
+    }
+}
+
+impl<Item: Hash + Eq + Clone> CountMinSketch for SpaceSaving<Item> {
+    type Item = Item;
+
+    fn increment(&mut self, item: Self::Item) {
+        self.increment_by(item, 1);
+    }
+
+    fn increment_by(&mut self, item: Self::Item, count: usize) {
+        if let Some((current, _)) = self.counters.get_mut(&item) {
+            let id = self.ids[&item];
+            self.by_count.remove(&(*current, id));
+            *current += count;
+            self.by_count.insert((*current, id));
+            return;
+        }
+
+        // A new item inherits the count of the item it evicts
+        let error = if self.counters.len() == self.capacity {
+            let (min_count, evicted_id) = self
+                .by_count
+                .pop_first()
+                .expect("a full tracker monitors items");
+            let evicted = self
+                .items
+                .remove(&evicted_id)
+                .expect("monitored items have an id");
+            self.counters.remove(&evicted);
+            self.ids.remove(&evicted);
+            min_count
+        } else {
+            0
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.by_count.insert((error + count, id));
+        self.ids.insert(item.clone(), id);
+        self.items.insert(id, item.clone());
+        self.counters.insert(item, (error + count, error));
+    }
+
+    fn get_count(&self, item: Self::Item) -> usize {
+        self.counters
+            .get(&item)
+            .map_or_else(|| self.min_count(), |&(count, _)| count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_heavy_hitters() {
+        let mut tracker = SpaceSaving::with_capacity(10);
+        let mut n = 0;
+        for round in 0..1000 {
+            // "hot" occurs 300 times, "warm" 200 times, and 500 other items once
+            for item in ["hot", "hot", "hot", "warm", "warm"] {
+                if round % 5 == 0 {
+                    tracker.increment(item.to_string());
+                    n += 1;
+                }
+            }
+            tracker.increment(format!("cold-{round}"));
+            n += 1;
+        }
+        let top = tracker.top_k(2);
+        assert_eq!(top[0].0, "hot");
+        assert_eq!(top[1].0, "warm");
+        for (item, count, error) in top {
+            let exact = if item == "hot" { 600 } else { 400 };
+            assert!(count >= exact && count - error <= exact);
+            assert!(count - exact <= n / 10);
+        }
+        let mut heavy = tracker.heavy_hitters(n / 10);
+        heavy.sort();
+        assert_eq!(heavy, vec!["hot".to_string(), "warm".to_string()]);
+        assert!(tracker.get_count("cold-3".to_string()) <= n / 10);
+    }
+
+    #[test]
+    fn exact_while_not_full() {
+        let mut tracker = SpaceSaving::with_capacity(3);
+        tracker.increment_by('a', 5);
+        tracker.increment('b');
+        tracker.increment('a');
+        assert_eq!(tracker.get_count('a'), 6);
+        assert_eq!(tracker.get_count('b'), 1);
+        assert_eq!(tracker.get_count('z'), 0);
+        tracker.increment_by('c', 2);
+        tracker.increment('d'); // evicts 'b'
+        assert_eq!(
+            tracker.top_k(5),
+            vec![('a', 6, 0), ('d', 2, 1), ('c', 2, 0)]
+        );
+        assert_eq!(tracker.get_count('b'), 2);
+    }
+}
@@ -0,0 +1,376 @@
+//! This module provides a radix trie (also known as a Patricia or compressed trie).
+//! It offers the same operations as [`Trie`](super::Trie), but every chain of nodes
+//! having a single child and no value is collapsed into one node labelled with the whole
+//! chain. A radix trie storing `n` sequences therefore has at most `2n` nodes, whatever
+//! their length, which matters when storing many long keys such as routing prefixes.
+
+/// A node of the radix trie.
+#[derive(Debug)]
+struct Node<Key, Type> {
+    /// The keys on the edge leading to this node. Only the root has an empty label.
+    label: Vec<Key>,
+    /// The children of the node. Their labels start with distinct keys.
+    ///
+    /// A vector uses far less memory than a map, and the fan-out is bounded by the
+    /// alphabet size anyway.
+    children: Vec<Node<Key, Type>>,
+    /// The value associated with the sequence of keys ending at this node, if any.
+    value: Option<Type>,
+}
+
+impl<Key: Eq, Type> Node<Key, Type> {
+    const fn new(label: Vec<Key>, value: Option<Type>) -> Self {
+        Node {
+            label,
+            children: Vec::new(),
+            value,
+        }
+    }
+
+    /// Returns the index of the child whose label starts with `first`.
+    fn child_index(&self, first: &Key) -> Option<usize> {
+        self.children
+            .iter()
+            .position(|child| child.label.first() == Some(first))
+    }
+}
+
+/// Returns the length of the longest common prefix of `a` and `b`.
+fn common_prefix_len<Key: Eq>(a: &[Key], b: &[Key]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// A generic radix trie mapping sequences of keys to values.
+#[derive(Debug)]
+pub struct RadixTrie<Key: Eq, Type> {
+    /// The root node, which holds the value of the empty sequence.
+    root: Node<Key, Type>,
+    /// The number of sequences stored in the trie.
+    len: usize,
+}
+
+impl<Key: Eq, Type> RadixTrie<Key, Type> {
+    /// Creates a new, empty `RadixTrie`.
+    pub const fn new() -> Self {
+        Self {
+            root: Node::new(Vec::new(), None),
+            len: 0,
+        }
+    }
+
+    /// Inserts a value into the trie, associating it with a sequence of keys.
+    ///
+    /// An edge whose label only partially matches the sequence is split in two.
+    pub fn insert(&mut self, key: impl IntoIterator<Item = Key>, value: Type) {
+        let mut key: Vec<Key> = key.into_iter().collect();
+        let mut node = &mut self.root;
+        let mut pos = 0;
+        while pos < key.len() {
+            let Some(i) = node.child_index(&key[pos]) else {
+                node.children
+                    .push(Node::new(key.split_off(pos), Some(value)));
+                self.len += 1;
+                return;
+            };
+            let child = &mut node.children[i];
+            let common = common_prefix_len(&child.label, &key[pos..]);
+            if common < child.label.len() {
+                let tail = Node {
+                    label: child.label.split_off(common),
+                    children: std::mem::take(&mut child.children),
+                    value: child.value.take(),
+                };
+                child.children.push(tail);
+            }
+            pos += common;
+            node = child;
+        }
+        if node.value.replace(value).is_none() {
+            self.len += 1;
+        }
+    }
+
+    /// Finds the node at which `key` ends, if there is one.
+    fn find(&self, key: &[Key]) -> Option<&Node<Key, Type>> {
+        let mut node = &self.root;
+        let mut rest = key;
+        while let Some(first) = rest.first() {
+            node = &node.children[node.child_index(first)?];
+            rest = rest.strip_prefix(node.label.as_slice())?;
+        }
+        Some(node)
+    }
+
+    /// Retrieves a reference to the value associated with a sequence of keys, if it exists.
+    pub fn get(&self, key: impl IntoIterator<Item = Key>) -> Option<&Type> {
+        let key: Vec<Key> = key.into_iter().collect();
+        self.find(&key)?.value.as_ref()
+    }
+
+    /// Removes a sequence of keys from the trie, merging the nodes left with a single child.
+    ///
+    /// # Returns
+    /// The value that was associated with the sequence of keys, or `None` if it was not present.
+    pub fn remove(&mut self, key: impl IntoIterator<Item = Key>) -> Option<Type> {
+        let key: Vec<Key> = key.into_iter().collect();
+        let removed = Self::remove_from(&mut self.root, &key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_from(node: &mut Node<Key, Type>, key: &[Key]) -> Option<Type> {
+        let Some(first) = key.first() else {
+            return node.value.take();
+        };
+        let i = node.child_index(first)?;
+        let child = &mut node.children[i];
+        let rest = key.strip_prefix(child.label.as_slice())?;
+        let removed = Self::remove_from(child, rest)?;
+
+        if child.value.is_none() {
+            match child.children.len() {
+                0 => {
+                    node.children.swap_remove(i);
+                }
+                1 => {
+                    let grandchild = child.children.pop().expect("the child has one child");
+                    child.label.extend(grandchild.label);
+                    child.children = grandchild.children;
+                    child.value = grandchild.value;
+                }
+                _ => {}
+            }
+        }
+        Some(removed)
+    }
+
+    /// Iterates over all the sequences of keys starting with `prefix`, along with their values.
+    ///
+    /// The sequences are yielded in an arbitrary order.
+    pub fn iter_prefix(
+        &self,
+        prefix: impl IntoIterator<Item = Key>,
+    ) -> impl Iterator<Item = (Vec<Key>, &Type)>
+    where
+        Key: Clone,
+    {
+        let prefix: Vec<Key> = prefix.into_iter().collect();
+        let mut node = Some(&self.root);
+        let mut pos = 0;
+        while let Some(current) = node.filter(|_| pos < prefix.len()) {
+            // The prefix may end in the middle of the label of the next node
+            node = current
+                .child_index(&prefix[pos])
+                .map(|i| &current.children[i])
+                .filter(|child| {
+                    let common = common_prefix_len(&child.label, &prefix[pos..]);
+                    common == child.label.len() || pos + common == prefix.len()
+                });
+            if let Some(child) = node {
+                pos += child.label.len();
+            }
+        }
+
+        let mut stack: Vec<(Vec<Key>, &Node<Key, Type>)> = node
+            .map(|node| {
+                let mut path = prefix[..pos.min(prefix.len())].to_vec();
+                if pos > prefix.len() {
+                    let label = &node.label;
+                    path.extend_from_slice(&label[label.len() - (pos - prefix.len())..]);
+                }
+                (path, node)
+            })
+            .into_iter()
+            .collect();
+        std::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                for child in &node.children {
+                    let mut child_path = path.clone();
+                    child_path.extend_from_slice(&child.label);
+                    stack.push((child_path, child));
+                }
+                if let Some(value) = &node.value {
+                    return Some((path, value));
+                }
+            }
+            None
+        })
+    }
+
+    /// Finds the longest sequence stored in the trie which is a prefix of `key`.
+    ///
+    /// # Returns
+    /// The length of that prefix and its value, or `None` if no prefix of `key` is stored.
+    pub fn longest_prefix(&self, key: impl IntoIterator<Item = Key>) -> Option<(usize, &Type)> {
+        let key: Vec<Key> = key.into_iter().collect();
+        let mut node = &self.root;
+        let mut pos = 0;
+        let mut best = node.value.as_ref().map(|value| (0, value));
+        while let Some(first) = key.get(pos) {
+            let Some(i) = node.child_index(first) else {
+                break;
+            };
+            node = &node.children[i];
+            if !key[pos..].starts_with(&node.label) {
+                break;
+            }
+            pos += node.label.len();
+            if let Some(value) = &node.value {
+                best = Some((pos, value));
+            }
+        }
+        best
+    }
+
+    /// Returns the number of sequences of keys stored in the trie.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes of the trie, including the root.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(&node.children);
+        }
+        count
+    }
+}
+
+impl<Key: Eq, Type> Default for RadixTrie<Key, Type> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::Trie;
+
+    fn sorted_words(trie: &RadixTrie<char, usize>, prefix: &str) -> Vec<(String, usize)> {
+        let mut words: Vec<(String, usize)> = trie
+            .iter_prefix(prefix.chars())
+            .map(|(key, &value)| (key.into_iter().collect(), value))
+            .collect();
+        words.sort();
+        words
+    }
+
+    #[test]
+    fn test_insert_splits_edges() {
+        let mut trie = RadixTrie::new();
+        trie.insert("romane".chars(), 0);
+        assert_eq!(trie.node_count(), 2);
+        trie.insert("romanus".chars(), 1);
+        trie.insert("romulus".chars(), 2);
+        trie.insert("rom".chars(), 3);
+        trie.insert("rom".chars(), 4);
+        // root -> "rom" -> { "an" -> { "e", "us" }, "ulus" }
+        assert_eq!(trie.node_count(), 6);
+        assert_eq!(trie.len(), 4);
+        assert_eq!(trie.get("rom".chars()), Some(&4));
+        assert_eq!(trie.get("romanus".chars()), Some(&1));
+        assert_eq!(trie.get("roman".chars()), None);
+        assert_eq!(trie.get("romanes".chars()), None);
+        assert_eq!(trie.get("ro".chars()), None);
+    }
+
+    #[test]
+    fn test_remove_merges_nodes() {
+        let mut trie = RadixTrie::new();
+        trie.insert("test".chars(), 0);
+        trie.insert("team".chars(), 1);
+        trie.insert("toast".chars(), 2);
+        assert_eq!(trie.node_count(), 6);
+        assert_eq!(trie.remove("te".chars()), None);
+        assert_eq!(trie.remove("team".chars()), Some(1));
+        assert_eq!(trie.remove("team".chars()), None);
+        // "te" + "st" are merged back into "test"
+        assert_eq!(trie.node_count(), 4);
+        assert_eq!(trie.get("test".chars()), Some(&0));
+        assert_eq!(trie.remove("test".chars()), Some(0));
+        assert_eq!(trie.remove("toast".chars()), Some(2));
+        assert!(trie.is_empty());
+        assert_eq!(trie.node_count(), 1);
+    }
+
+    #[test]
+    fn test_iter_prefix_inside_label() {
+        let mut trie = RadixTrie::new();
+        for (i, word) in ["apple", "applet", "apply", "banana"].iter().enumerate() {
+            trie.insert(word.chars(), i);
+        }
+        let expected = vec![
+            ("apple".to_string(), 0),
+            ("applet".to_string(), 1),
+            ("apply".to_string(), 2),
+        ];
+        assert_eq!(sorted_words(&trie, "ap"), expected);
+        assert_eq!(sorted_words(&trie, "appl"), expected);
+        assert_eq!(sorted_words(&trie, "apple"), expected[..2].to_vec());
+        assert_eq!(sorted_words(&trie, "ba"), vec![("banana".to_string(), 3)]);
+        assert_eq!(sorted_words(&trie, "apples"), vec![]);
+        assert_eq!(sorted_words(&trie, "bx"), vec![]);
+        assert_eq!(sorted_words(&trie, "").len(), 4);
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut trie = RadixTrie::new();
+        trie.insert(vec![192, 168], "192.168/16");
+        trie.insert(vec![192, 168, 1], "192.168.1/24");
+        assert_eq!(
+            trie.longest_prefix(vec![192, 168, 1, 7]),
+            Some((3, &"192.168.1/24"))
+        );
+        assert_eq!(
+            trie.longest_prefix(vec![192, 168, 2, 7]),
+            Some((2, &"192.168/16"))
+        );
+        assert_eq!(trie.longest_prefix(vec![192, 169, 1, 7]), None);
+        assert_eq!(trie.longest_prefix(vec![192]), None);
+    }
+
+    #[test]
+    fn test_matches_trie() {
+        let mut radix = RadixTrie::new();
+        let mut trie = Trie::new();
+        let mut seed = 7u64;
+        for step in 0..3000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let word: Vec<u8> = (0..(seed >> 61) as usize)
+                .map(|i| b'a' + ((seed >> (4 * i + 8)) & 3) as u8)
+                .collect();
+            if (seed >> 40).is_multiple_of(3) {
+                assert_eq!(radix.remove(word.clone()), trie.remove(word.clone()));
+            } else {
+                radix.insert(word.clone(), step);
+                trie.insert(word.clone(), step);
+            }
+            assert_eq!(radix.get(word.clone()), trie.get(word.clone()));
+            assert_eq!(
+                radix.longest_prefix(word.clone()),
+                trie.longest_prefix(word.clone())
+            );
+            assert_eq!(radix.len(), trie.len());
+            assert!(radix.node_count() <= trie.node_count());
+            assert!(radix.node_count() <= 2 * radix.len() + 1);
+        }
+        for prefix in [vec![], vec![b'a'], vec![b'b', b'c'], vec![b'd', b'd', b'a']] {
+            let mut from_radix: Vec<_> = radix.iter_prefix(prefix.clone()).collect();
+            let mut from_trie: Vec<_> = trie.iter_prefix(prefix).collect();
+            from_radix.sort();
+            from_trie.sort();
+            assert_eq!(from_radix, from_trie);
+        }
+    }
+}
@@ -5,11 +5,13 @@
 //! of a given data after an initial preprocessing phase.
 //!
 //! The RMQ is particularly useful in scenarios requiring multiple queries on static data, as it
-//! allows querying in constant time after an O(n log(n)) preprocessing time.
+//! allows querying in constant time after an O(n log(n)) preprocessing time. It is a
+//! [`SparseTable`] over the [`MinMonoid`], which also answers the other idempotent operations.
 //!
 //! References: [Wikipedia](https://en.wikipedia.org/wiki/Range_minimum_query)
 
-use std::cmp::PartialOrd;
+use super::monoid::{MinMonoid, Numeric};
+use super::sparse_table::SparseTable;
 
 /// Custom error type for invalid range queries.
 #[derive(Debug, PartialEq, Eq)]
@@ -21,28 +23,23 @@ pub enum RangeError {
 }
 
 /// A data structure for efficiently answering range minimum queries on static data.
-pub struct RangeMinimumQuery<T: PartialOrd + Copy> {
-    /// The original input data on which range queries are performed.
-    data: Vec<T>,
-    /// The sparse table for storing preprocessed range minimum information. Each entry
-    /// contains the index of the minimum element in the range starting at `j` and having a length of `2^i`.
-    sparse_table: Vec<Vec<usize>>,
+pub struct RangeMinimumQuery<T: Numeric> {
+    table: SparseTable<MinMonoid<T>>,
 }
 
-impl<T: PartialOrd + Copy> RangeMinimumQuery<T> {
+impl<T: Numeric> RangeMinimumQuery<T> {
     /// Creates a new `RangeMinimumQuery` instance with the provided input data.
     ///
     /// # Arguments
     ///
-    /// * `input` - A slice of elements of type `T` that implement `PartialOrd` and `Copy`.
+    /// * `input` - A slice of numbers.
     ///
     /// # Returns
     ///
     /// A `RangeMinimumQuery` instance that can be used to perform range minimum queries.
     pub fn new(input: &[T]) -> RangeMinimumQuery<T> {
         RangeMinimumQuery {
-            data: input.to_vec(),
-            sparse_table: build_sparse_table(input),
+            table: SparseTable::new(input),
         }
     }
 
@@ -59,108 +56,30 @@ impl<T: PartialOrd + Copy> RangeMinimumQuery<T> {
     /// * `Err(RangeError)` - An error indicating the reason for failure, such as an invalid range
     ///   or indices out of bounds.
     pub fn get_range_min(&self, start: usize, end: usize) -> Result<T, RangeError> {
-        // Validate range
-        if start >= end {
-            return Err(RangeError::InvalidRange);
-        }
-        if start >= self.data.len() || end > self.data.len() {
-            return Err(RangeError::IndexOutOfBound);
-        }
-
-        // Calculate the log length and the index for the sparse table
-        let log_len = (end - start).ilog2() as usize;
-        let idx: usize = end - (1 << log_len);
-
-        // Retrieve the indices of the minimum values from the sparse table
-        let min_idx_start = self.sparse_table[log_len][start];
-        let min_idx_end = self.sparse_table[log_len][idx];
-
-        // Compare the values at the retrieved indices and return the minimum
-        if self.data[min_idx_start] < self.data[min_idx_end] {
-            Ok(self.data[min_idx_start])
-        } else {
-            Ok(self.data[min_idx_end])
-        }
+        self.table.query(start, end)
     }
 }
 
-/// Builds a sparse table for the provided data to support range minimum queries.
-///
-/// # Arguments
-///
-/// * `data` - A slice of elements of type `T` that implement `PartialOrd`.
-///
-/// # Returns
-///
-/// A 2D vector representing the sparse table, where each entry contains the index of the minimum
-/// element in the range defined by the starting index and the power of two lengths.
-fn build_sparse_table<T: PartialOrd>(data: &[T]) -> Vec<Vec<usize>> {
-    let mut sparse_table: Vec<Vec<usize>> = vec![(0..data.len()).collect()];
-    let len = data.len();
-
-    // Fill the sparse table
-    for log_len in 1..=len.ilog2() {
-        let mut row = Vec::new();
-        for idx in 0..=len - (1 << log_len) {
-            let min_idx_start = sparse_table[sparse_table.len() - 1][idx];
-            let min_idx_end = sparse_table[sparse_table.len() - 1][idx + (1 << (log_len - 1))];
-            if data[min_idx_start] < data[min_idx_end] {
-                row.push(min_idx_start);
-            } else {
-                row.push(min_idx_end);
-            }
-        }
-        sparse_table.push(row);
-    }
-
-    sparse_table
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    macro_rules! test_build_sparse_table {
-        ($($name:ident: $inputs:expr,)*) => {
-            $(
-                #[test]
-                fn $name() {
-                    let (data, expected) = $inputs;
-                    assert_eq!(build_sparse_table(&data), expected);
-                }
-            )*
+    #[test]
+    fn matches_a_naive_minimum() {
+        let data = [
+            20, 13, -13, 2, 3634, -2, 56, 3, 67, 8, 23, 0, -23, 1, 5, 85, 3, 24, 5, -10, 3, 4, 20,
+        ];
+        let rmq = RangeMinimumQuery::new(&data);
+        for start in 0..data.len() {
+            for end in start + 1..=data.len() {
+                assert_eq!(
+                    rmq.get_range_min(start, end),
+                    Ok(*data[start..end].iter().min().unwrap())
+                );
+            }
         }
     }
 
-    test_build_sparse_table! {
-        small: (
-            [1, 6, 3],
-            vec![
-                vec![0, 1, 2],
-                vec![0, 2]
-            ]
-        ),
-        medium: (
-            [1, 3, 6, 123, 7, 235, 3, -4, 6, 2],
-            vec![
-                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
-                vec![0, 1, 2, 4, 4, 6, 7, 7, 9],
-                vec![0, 1, 2, 6, 7, 7, 7],
-                vec![7, 7, 7]
-            ]
-        ),
-        large: (
-            [20, 13, -13, 2, 3634, -2, 56, 3, 67, 8, 23, 0, -23, 1, 5, 85, 3, 24, 5, -10, 3, 4, 20],
-            vec![
-                vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22],
-                vec![1, 2, 2, 3, 5, 5, 7, 7, 9, 9, 11, 12, 12, 13, 14, 16, 16, 18, 19, 19, 20, 21],
-                vec![2, 2, 2, 5, 5, 5, 7, 7, 11, 12, 12, 12, 12, 13, 16, 16, 19, 19, 19, 19],
-                vec![2, 2, 2, 5, 5, 12, 12, 12, 12, 12, 12, 12, 12, 19, 19, 19],
-                vec![12, 12, 12, 12, 12, 12, 12, 12]
-            ]
-        ),
-    }
-
     #[test]
     fn simple_query_tests() {
         let rmq = RangeMinimumQuery::new(&[1, 3, 6, 123, 7, 235, 3, -4, 6, 2]);
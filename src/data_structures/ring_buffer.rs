@@ -0,0 +1,311 @@
+//! A double-ended queue stored in a contiguous ring buffer.
+//!
+//! Elements live in a single vector whose slots are reused circularly: `head` is the slot of the
+//! front element and the others follow it, wrapping around the end of the vector. An unbounded
+//! buffer doubles its storage when it fills up; a bounded one keeps its capacity and either
+//! overwrites the element at the opposite end or rejects the new element, depending on its
+//! [`OverflowPolicy`].
+
+use std::ops::{Index, IndexMut};
+
+const MIN_CAPACITY: usize = 4;
+
+/// What a bounded [`RingBuffer`] does when an element is pushed while it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the element at the opposite end to make room.
+    Overwrite,
+    /// Refuse the new element.
+    Reject,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RingBufferError<T> {
+    /// The buffer is full and rejects new elements; the element is handed back.
+    Full(T),
+}
+
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    slots: Vec<Option<T>>,
+    head: usize,
+    len: usize,
+    /// `None` for a growable buffer.
+    policy: Option<OverflowPolicy>,
+}
+
+impl<T> Default for RingBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates an empty, growable buffer.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty, growable buffer with room for `capacity` elements before it reallocates.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Self::empty_slots(capacity.max(MIN_CAPACITY)),
+            head: 0,
+            len: 0,
+            policy: None,
+        }
+    }
+
+    /// Creates an empty buffer which never holds more than `capacity` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn bounded(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            slots: Self::empty_slots(capacity),
+            head: 0,
+            len: 0,
+            policy: Some(policy),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if a bounded buffer holds `capacity` elements. A growable buffer is never full.
+    pub fn is_full(&self) -> bool {
+        self.policy.is_some() && self.len == self.slots.len()
+    }
+
+    pub const fn policy(&self) -> Option<OverflowPolicy> {
+        self.policy
+    }
+
+    /// Pushes `value` at the back.
+    ///
+    /// Returns the front element if it was overwritten to make room, or an error if the buffer
+    /// is full and rejects new elements.
+    pub fn push_back(&mut self, value: T) -> Result<Option<T>, RingBufferError<T>> {
+        let (value, evicted) = self.make_room(value, Self::pop_front)?;
+        let slot = self.slot(self.len);
+        self.slots[slot] = Some(value);
+        self.len += 1;
+        Ok(evicted)
+    }
+
+    /// Pushes `value` at the front.
+    ///
+    /// Returns the back element if it was overwritten to make room, or an error if the buffer
+    /// is full and rejects new elements.
+    pub fn push_front(&mut self, value: T) -> Result<Option<T>, RingBufferError<T>> {
+        let (value, evicted) = self.make_room(value, Self::pop_back)?;
+        self.head = self.slot(self.slots.len() - 1);
+        self.slots[self.head] = Some(value);
+        self.len += 1;
+        Ok(evicted)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = self.slots[self.head].take();
+        self.head = self.slot(1);
+        self.len -= 1;
+        value
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        self.slots[slot].take()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns the element at position `index`, counting from the front.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.slots[self.slot(index)].as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        self.slots[slot].as_mut()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+        self.head = 0;
+    }
+
+    /// Iterates from the front to the back.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        (0..self.len).map(|index| &self[index])
+    }
+
+    /// Makes room for one more element, growing an unbounded buffer or applying the overflow
+    /// policy of a full bounded one, in which case `evict` removes the element at the opposite
+    /// end. Returns `value` together with the evicted element, if any.
+    fn make_room(
+        &mut self,
+        value: T,
+        evict: fn(&mut Self) -> Option<T>,
+    ) -> Result<(T, Option<T>), RingBufferError<T>> {
+        if self.len < self.slots.len() {
+            return Ok((value, None));
+        }
+        match self.policy {
+            None => {
+                self.grow();
+                Ok((value, None))
+            }
+            Some(OverflowPolicy::Reject) => Err(RingBufferError::Full(value)),
+            Some(OverflowPolicy::Overwrite) => Ok((value, evict(self))),
+        }
+    }
+
+    fn grow(&mut self) {
+        let mut slots = Self::empty_slots(self.slots.len() * 2);
+        for (index, slot) in slots.iter_mut().take(self.len).enumerate() {
+            let from = self.slot(index);
+            *slot = self.slots[from].take();
+        }
+        self.slots = slots;
+        self.head = 0;
+    }
+
+    /// Returns the slot of the element at position `index`, which may be past the back.
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % self.slots.len()
+    }
+
+    fn empty_slots(capacity: usize) -> Vec<Option<T>> {
+        std::iter::repeat_with(|| None).take(capacity).collect()
+    }
+}
+
+impl<T> Index<usize> for RingBuffer<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for RingBuffer<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("Index out of bounds")
+    }
+}
+
+impl<T> FromIterator<T> for RingBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        buffer.extend(iter);
+        buffer
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    /// Pushes every element at the back, following the overflow policy of a bounded buffer:
+    /// once a buffer rejecting new elements is full, the remaining elements are dropped.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            if self.push_back(value).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_and_wraps_around() {
+        let mut buffer = RingBuffer::new();
+        for i in 0..3 {
+            buffer.push_back(i).unwrap();
+        }
+        assert_eq!(buffer.pop_front(), Some(0));
+        for i in 3..10 {
+            buffer.push_back(i).unwrap();
+        }
+        buffer.push_front(-1).unwrap();
+        assert_eq!(buffer.len(), 10);
+        assert!(!buffer.is_full());
+        assert_eq!(
+            buffer.iter().copied().collect::<Vec<_>>(),
+            vec![-1, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+        assert_eq!(buffer.iter().next_back(), Some(&9));
+        assert_eq!((buffer.front(), buffer.back()), (Some(&-1), Some(&9)));
+        buffer[1] = 10;
+        assert_eq!(buffer.get(1), Some(&10));
+        assert_eq!(buffer.get(10), None);
+        assert_eq!(buffer.pop_back(), Some(9));
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(
+            (buffer.pop_front(), buffer.pop_back(), buffer.back()),
+            (None, None, None)
+        );
+    }
+
+    #[test]
+    fn bounded_overwrite() {
+        let mut buffer = RingBuffer::bounded(3, OverflowPolicy::Overwrite);
+        buffer.extend(1..=3);
+        assert!(buffer.is_full());
+        assert_eq!(buffer.push_back(4), Ok(Some(1)));
+        assert_eq!(buffer.push_front(0), Ok(Some(4)));
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), vec![0, 2, 3]);
+        assert_eq!(buffer.capacity(), 3);
+    }
+
+    #[test]
+    fn bounded_reject() {
+        let mut buffer = RingBuffer::bounded(2, OverflowPolicy::Reject);
+        assert_eq!(buffer.push_back('a'), Ok(None));
+        assert_eq!(buffer.push_front('b'), Ok(None));
+        assert_eq!(buffer.push_back('c'), Err(RingBufferError::Full('c')));
+        assert_eq!(buffer.pop_back(), Some('a'));
+        assert_eq!(buffer.push_back('c'), Ok(None));
+        assert_eq!(buffer.iter().collect::<String>(), "bc");
+        assert_eq!(buffer.policy(), Some(OverflowPolicy::Reject));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds")]
+    fn index_out_of_bounds() {
+        let buffer: RingBuffer<i32> = (0..3).collect();
+        assert_eq!(buffer[3], 3);
+    }
+}
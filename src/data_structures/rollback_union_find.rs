@@ -0,0 +1,172 @@
+//! A Union-Find whose unions can be undone.
+//!
+//! Path compression rewrites many parent links at once, so it can't be undone cheaply. Without
+//! it, union by size alone still keeps the trees logarithmic in height, so `find` is `O(log n)`,
+//! and every union changes a constant amount of state which is recorded in a history. Undoing
+//! the last unions restores the structure exactly, which is what offline divide-and-conquer
+//! algorithms need (e.g. dynamic connectivity over a segment tree of time intervals).
+
+use super::union_find::members;
+
+/// Index-keyed disjoint sets supporting checkpoints and rollback.
+#[derive(Debug, Default, Clone)]
+pub struct RollbackUnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    /// Circular lists of the members of each set, as in [`DisjointSets`](super::DisjointSets).
+    next: Vec<usize>,
+    count: usize,
+    /// The roots attached to another root by each successful union, in order.
+    history: Vec<usize>,
+}
+
+impl RollbackUnionFind {
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            next: (0..len).collect(),
+            count: len,
+            history: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the root of the set containing `element`.
+    pub fn find(&self, mut element: usize) -> usize {
+        while self.parent[element] != element {
+            element = self.parent[element];
+        }
+        element
+    }
+
+    /// Merges the sets containing `first` and `second`, returning `false` if they were already
+    /// in the same set. Only actual merges are recorded in the history.
+    pub fn union(&mut self, first: usize, second: usize) -> bool {
+        let (mut first_root, mut second_root) = (self.find(first), self.find(second));
+        if first_root == second_root {
+            return false;
+        }
+        if self.size[first_root] < self.size[second_root] {
+            std::mem::swap(&mut first_root, &mut second_root);
+        }
+        self.parent[second_root] = first_root;
+        self.size[first_root] += self.size[second_root];
+        self.next.swap(first_root, second_root);
+        self.count -= 1;
+        self.history.push(second_root);
+        true
+    }
+
+    pub fn is_same_set(&self, first: usize, second: usize) -> bool {
+        self.find(first) == self.find(second)
+    }
+
+    /// Returns the number of elements in the set containing `element`.
+    pub fn size_of(&self, element: usize) -> usize {
+        self.size[self.find(element)]
+    }
+
+    /// Iterates over the elements of the set containing `element`, starting with `element` itself.
+    pub fn members(&self, element: usize) -> impl Iterator<Item = usize> + '_ {
+        members(&self.next, element)
+    }
+
+    /// Returns a checkpoint of the current state, to be passed to [`rollback`](Self::rollback).
+    pub fn checkpoint(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes the last successful union, returning `false` if there was none.
+    pub fn undo(&mut self) -> bool {
+        let Some(child) = self.history.pop() else {
+            return false;
+        };
+        let root = self.parent[child];
+        self.parent[child] = child;
+        self.size[root] -= self.size[child];
+        self.next.swap(root, child);
+        self.count += 1;
+        true
+    }
+
+    /// Undoes all the unions made since `checkpoint` was taken.
+    pub fn rollback(&mut self, checkpoint: usize) {
+        while self.history.len() > checkpoint {
+            self.undo();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_members(uf: &RollbackUnionFind, element: usize) -> Vec<usize> {
+        let mut members: Vec<usize> = uf.members(element).collect();
+        members.sort_unstable();
+        members
+    }
+
+    #[test]
+    fn test_rollback_restores_state() {
+        let mut uf = RollbackUnionFind::new(6);
+        uf.union(0, 1);
+        uf.union(2, 3);
+        let checkpoint = uf.checkpoint();
+
+        assert!(uf.union(1, 3));
+        assert!(!uf.union(0, 2));
+        assert!(uf.union(4, 5));
+        assert_eq!(uf.count(), 2);
+        assert_eq!(uf.size_of(2), 4);
+        assert_eq!(sorted_members(&uf, 0), vec![0, 1, 2, 3]);
+
+        uf.rollback(checkpoint);
+        assert_eq!(uf.count(), 4);
+        assert!(!uf.is_same_set(1, 3));
+        assert!(uf.is_same_set(2, 3));
+        assert_eq!(uf.size_of(0), 2);
+        assert_eq!(sorted_members(&uf, 3), vec![2, 3]);
+        assert_eq!(sorted_members(&uf, 5), vec![5]);
+
+        assert!(uf.undo());
+        assert!(uf.undo());
+        assert!(!uf.undo());
+        assert_eq!(uf.count(), 6);
+    }
+
+    #[test]
+    fn test_offline_connectivity() {
+        // Nested checkpoints, as in a divide-and-conquer over time
+        let mut uf = RollbackUnionFind::new(5);
+        let outer = uf.checkpoint();
+        uf.union(0, 1);
+        let inner = uf.checkpoint();
+        uf.union(1, 2);
+        uf.union(3, 4);
+        assert_eq!(uf.count(), 2);
+        uf.rollback(inner);
+        assert_eq!(uf.count(), 4);
+        assert!(uf.is_same_set(0, 1));
+        uf.union(2, 4);
+        assert_eq!(uf.size_of(4), 2);
+        uf.rollback(outer);
+        assert_eq!(uf.count(), 5);
+        assert!((0..5).all(|i| uf.size_of(i) == 1));
+    }
+}
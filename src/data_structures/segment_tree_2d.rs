@@ -0,0 +1,211 @@
+//! A two-dimensional segment tree answering rectangle queries over a grid.
+//!
+//! Each row of the outer tree is itself an iterative segment tree over the columns,
+//! giving `O(log(rows) * log(cols))` point updates and rectangle queries in
+//! `O(rows * cols)` memory.
+//!
+//! Since the aggregates of several rectangles are combined out of order, the
+//! [`Monoid`] must be commutative (sum, min, max, gcd, xor, ...).
+
+use super::monoid::Monoid;
+use super::segment_tree::SegmentTreeError;
+use std::ops::Range;
+
+pub struct SegmentTree2D<M: Monoid> {
+    rows: usize,
+    cols: usize,
+    /// `(2 * rows) x (2 * cols)` matrix stored row by row.
+    nodes: Vec<M::Value>,
+}
+
+impl<M: Monoid> SegmentTree2D<M> {
+    /// Creates a `rows x cols` grid filled with the identity of the monoid.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        SegmentTree2D {
+            rows,
+            cols,
+            nodes: vec![M::identity(); 4 * rows * cols],
+        }
+    }
+
+    /// Builds the tree from a rectangular grid in `O(rows * cols)`.
+    ///
+    /// Returns `None` if the rows of `grid` don't all have the same length.
+    pub fn from_grid(grid: &[Vec<M::Value>]) -> Option<Self> {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, Vec::len);
+        if grid.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        let mut tree = Self::new(rows, cols);
+        for (r, row) in grid.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                let idx = tree.index(r + rows, c + cols);
+                tree.nodes[idx] = value.clone();
+            }
+            for c in (1..cols).rev() {
+                tree.pull_col(r + rows, c);
+            }
+        }
+        for r in (1..rows).rev() {
+            for c in 1..2 * cols {
+                tree.pull_row(r, c);
+            }
+        }
+        Some(tree)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Replaces the element at `(row, col)` by `val`.
+    pub fn set(&mut self, row: usize, col: usize, val: M::Value) -> Result<(), SegmentTreeError> {
+        if row >= self.rows || col >= self.cols {
+            return Err(SegmentTreeError::IndexOutOfBounds);
+        }
+        let (r, c) = (row + self.rows, col + self.cols);
+        let idx = self.index(r, c);
+        self.nodes[idx] = val;
+        let mut c_up = c / 2;
+        while c_up >= 1 {
+            self.pull_col(r, c_up);
+            c_up /= 2;
+        }
+        let mut r_up = r / 2;
+        while r_up >= 1 {
+            let mut c_up = c;
+            while c_up >= 1 {
+                self.pull_row(r_up, c_up);
+                c_up /= 2;
+            }
+            r_up /= 2;
+        }
+        Ok(())
+    }
+
+    /// Combines every element of the rectangle `row_range x col_range`.
+    pub fn query(
+        &self,
+        row_range: Range<usize>,
+        col_range: Range<usize>,
+    ) -> Result<M::Value, SegmentTreeError> {
+        if row_range.start > row_range.end
+            || row_range.end > self.rows
+            || col_range.start > col_range.end
+            || col_range.end > self.cols
+        {
+            return Err(SegmentTreeError::InvalidRange);
+        }
+        let mut result = M::identity();
+        let (mut top, mut bottom) = (row_range.start + self.rows, row_range.end + self.rows);
+        while top < bottom {
+            if top & 1 == 1 {
+                result = M::combine(&result, &self.query_row(top, &col_range));
+                top += 1;
+            }
+            if bottom & 1 == 1 {
+                bottom -= 1;
+                result = M::combine(&result, &self.query_row(bottom, &col_range));
+            }
+            top >>= 1;
+            bottom >>= 1;
+        }
+        Ok(result)
+    }
+
+    fn query_row(&self, r: usize, col_range: &Range<usize>) -> M::Value {
+        let mut result = M::identity();
+        let (mut left, mut right) = (col_range.start + self.cols, col_range.end + self.cols);
+        while left < right {
+            if left & 1 == 1 {
+                result = M::combine(&result, &self.nodes[self.index(r, left)]);
+                left += 1;
+            }
+            if right & 1 == 1 {
+                right -= 1;
+                result = M::combine(&result, &self.nodes[self.index(r, right)]);
+            }
+            left >>= 1;
+            right >>= 1;
+        }
+        result
+    }
+
+    fn index(&self, r: usize, c: usize) -> usize {
+        r * 2 * self.cols + c
+    }
+
+    fn pull_col(&mut self, r: usize, c: usize) {
+        let value = M::combine(
+            &self.nodes[self.index(r, 2 * c)],
+            &self.nodes[self.index(r, 2 * c + 1)],
+        );
+        let idx = self.index(r, c);
+        self.nodes[idx] = value;
+    }
+
+    fn pull_row(&mut self, r: usize, c: usize) {
+        let value = M::combine(
+            &self.nodes[self.index(2 * r, c)],
+            &self.nodes[self.index(2 * r + 1, c)],
+        );
+        let idx = self.index(r, c);
+        self.nodes[idx] = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::monoid::{MaxMonoid, SumMonoid};
+    use super::*;
+
+    #[test]
+    fn test_sum_grid() {
+        let grid = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        let mut tree = SegmentTree2D::<SumMonoid<i32>>::from_grid(&grid).unwrap();
+        assert_eq!(tree.query(0..3, 0..4), Ok(78));
+        assert_eq!(tree.query(1..3, 1..3), Ok(34));
+        assert_eq!(tree.query(0..1, 2..4), Ok(7));
+        assert_eq!(tree.query(2..2, 0..4), Ok(0));
+        assert_eq!(tree.set(1, 2, -3), Ok(()));
+        assert_eq!(tree.query(1..3, 1..3), Ok(24));
+        assert_eq!(tree.query(0..3, 0..4), Ok(68));
+        assert_eq!(tree.query(0..4, 0..1), Err(SegmentTreeError::InvalidRange));
+        assert_eq!(tree.set(0, 4, 1), Err(SegmentTreeError::IndexOutOfBounds));
+    }
+
+    #[test]
+    fn test_max_grid_matches_naive() {
+        let (rows, cols) = (5, 7);
+        let mut naive = vec![vec![0i64; cols]; rows];
+        let mut tree = SegmentTree2D::<MaxMonoid<i64>>::from_grid(&naive).unwrap();
+        let mut seed = 17i64;
+        for _ in 0..200 {
+            seed = (seed * 1_103_515_245 + 12_345) % 2_147_483_648;
+            let (r, c) = (
+                (seed % rows as i64) as usize,
+                (seed / 7 % cols as i64) as usize,
+            );
+            naive[r][c] = seed % 1000;
+            tree.set(r, c, seed % 1000).unwrap();
+            let (r0, c0) = ((seed / 11) as usize % rows, (seed / 13) as usize % cols);
+            let expected = naive[r0..]
+                .iter()
+                .flat_map(|row| row[c0..].iter())
+                .max()
+                .copied()
+                .unwrap();
+            assert_eq!(tree.query(r0..rows, c0..cols), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_ragged_grid() {
+        assert!(SegmentTree2D::<SumMonoid<i32>>::from_grid(&[vec![1, 2], vec![3]]).is_none());
+    }
+}
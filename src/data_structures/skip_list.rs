@@ -1,244 +1,316 @@
 use rand::random_range;
+use std::borrow::Borrow;
+use std::ops::{Bound, RangeBounds};
 use std::{cmp::Ordering, marker::PhantomData, ptr::null_mut};
 
 struct Node<K: Ord, V> {
     key: Option<K>,
     value: Option<V>,
     forward: Vec<*mut Node<K, V>>,
+    /// `width[i]` is the number of level-0 steps `forward[i]` spans. A null link spans to the
+    /// position right after the last node, so widths stay meaningful at every level.
+    width: Vec<usize>,
 }
 
 impl<K: Ord, V> Node<K, V> {
-    pub fn new() -> Self {
-        let mut forward = Vec::with_capacity(4);
-        forward.resize(4, null_mut());
+    pub fn new(capacity: usize) -> Self {
         Node {
             key: None,
             value: None,
-            forward,
+            forward: vec![null_mut(); capacity],
+            width: vec![1; capacity],
         }
     }
 
     pub fn make_node(capacity: usize, key: K, value: V) -> Self {
-        let mut new_node = Self::new();
+        let mut new_node = Self::new(capacity);
         new_node.key = Some(key);
         new_node.value = Some(value);
-        new_node.forward = Vec::<*mut Node<K, V>>::with_capacity(capacity);
-        new_node.forward.resize(capacity, null_mut());
         new_node
     }
+
+    /// Compares the key of this node with `key`; the header, which has no key, is the smallest.
+    fn cmp_key<Q>(&self, key: &Q) -> Ordering
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.key
+            .as_ref()
+            .map_or(Ordering::Less, |own| own.borrow().cmp(key))
+    }
 }
 
 /// A probabilistic data structure that maintains a sorted collection of key-value pairs.
 ///
 /// A skip list is a data structure that allows O(log n) search, insertion, and deletion
 /// on average by maintaining multiple levels of linked lists with probabilistic balancing.
+///
+/// Every link also records how many elements it skips over, which makes the list indexable:
+/// the k-th element and the rank of a key are found in O(log n) as well.
 pub struct SkipList<K: Ord, V> {
     header: *mut Node<K, V>,
     level: usize,
     max_level: usize,
+    len: usize,
     marker: PhantomData<Node<K, V>>,
 }
 
+/// The path of a search: for every level, the last node before the searched key and its position
+/// (the header being at position 0).
+struct SearchPath<K: Ord, V> {
+    update: Vec<*mut Node<K, V>>,
+    rank: Vec<usize>,
+}
+
 impl<K: Ord, V> SkipList<K, V> {
     pub fn new(max_level: usize) -> Self {
-        let mut node = Box::new(Node::<K, V>::new());
-        node.forward = Vec::with_capacity(max_level);
-        node.forward.resize(max_level, null_mut());
-
+        let max_level = max_level.max(1);
         SkipList {
-            header: Box::into_raw(node),
+            header: Box::into_raw(Box::new(Node::new(max_level))),
             level: 0,
             max_level,
+            len: 0,
             marker: PhantomData,
         }
     }
 
-    pub fn search(&self, searched_key: K) -> Option<&V> {
-        let mut x = self.header;
+    pub const fn len(&self) -> usize {
+        self.len
+    }
 
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn search<Q>(&self, searched_key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let x = self.find(searched_key);
+        unsafe { x.as_ref()?.value.as_ref() }
+    }
+
+    pub fn get_mut<Q>(&mut self, searched_key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let x = self.find(searched_key);
+        unsafe { x.as_mut()?.value.as_mut() }
+    }
+
+    pub fn contains_key<Q>(&self, searched_key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        !self.find(searched_key).is_null()
+    }
+
+    /// Inserts `new_value` under `searched_key`, returning the previous value of the key.
+    pub fn insert(&mut self, searched_key: K, new_value: V) -> Option<V> {
+        let path = self.search_path(&searched_key);
         unsafe {
-            'outer: for i in (0..self.level).rev() {
-                loop {
-                    let forward_i = (&*x).forward[i];
-                    if forward_i.is_null() {
-                        continue 'outer;
-                    }
+            let x = (&*path.update[0]).forward[0];
+            if !x.is_null() && (*x).cmp_key(&searched_key) == Ordering::Equal {
+                return (*x).value.replace(new_value);
+            }
 
-                    let forward_i_key = (*forward_i).key.as_ref();
-                    match forward_i_key.cmp(&Some(&searched_key)) {
-                        Ordering::Less => {
-                            x = forward_i;
-                        }
-                        _ => {
-                            break;
-                        }
-                    }
+            let v = random_value(self.max_level);
+            self.level = self.level.max(v);
+            let x = Box::into_raw(Box::new(Node::make_node(v, searched_key, new_value)));
+            let position = path.rank[0] + 1;
+            for i in 0..self.max_level {
+                let update_i = &mut *{ path.update[i] };
+                if i < v {
+                    // The new node takes over the part of the link after its position
+                    (&mut *x).forward[i] = update_i.forward[i];
+                    (&mut *x).width[i] = update_i.width[i] + path.rank[i] + 1 - position;
+                    update_i.forward[i] = x;
+                    update_i.width[i] = position - path.rank[i];
+                } else {
+                    update_i.width[i] += 1;
                 }
             }
+        }
+        self.len += 1;
+        None
+    }
 
-            x = (&*x).forward[0];
-            if x.is_null() {
+    /// Removes `searched_key`, returning its value.
+    pub fn remove<Q>(&mut self, searched_key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let path = self.search_path(searched_key);
+        unsafe {
+            let x = (&*path.update[0]).forward[0];
+            if x.is_null() || (*x).cmp_key(searched_key) != Ordering::Equal {
                 return None;
             }
-
-            match (*x).key.as_ref().cmp(&Some(&searched_key)) {
-                Ordering::Equal => {
-                    return (*x).value.as_ref();
-                }
-                _ => {
-                    return None;
+            let x = Box::from_raw(x);
+            for i in 0..self.max_level {
+                let update_i = &mut *{ path.update[i] };
+                if i < x.forward.len() {
+                    update_i.forward[i] = x.forward[i];
+                    update_i.width[i] += x.width[i] - 1;
+                } else {
+                    update_i.width[i] -= 1;
                 }
             }
+            while self.level > 0 && (&*self.header).forward[self.level - 1].is_null() {
+                self.level -= 1;
+            }
+            self.len -= 1;
+            x.value
         }
     }
 
-    pub fn insert(&mut self, searched_key: K, new_value: V) -> bool {
-        let mut update = Vec::<*mut Node<K, V>>::with_capacity(self.max_level);
-        update.resize(self.max_level, null_mut());
+    pub fn delete<Q>(&mut self, searched_key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove(searched_key).is_some()
+    }
 
+    /// Returns the `index`-th smallest entry, counting from 0.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        if index >= self.len {
+            return None;
+        }
+        let position = index + 1;
         let mut x = self.header;
-
+        let mut traversed = 0;
         unsafe {
             for i in (0..self.level).rev() {
-                loop {
-                    let x_forward_i = (&*x).forward[i];
-                    if x_forward_i.is_null() {
-                        break;
-                    }
-
-                    let x_forward_i_key = (*x_forward_i).key.as_ref();
-                    match x_forward_i_key.cmp(&Some(&searched_key)) {
-                        Ordering::Less => {
-                            x = x_forward_i;
-                        }
-                        _ => {
-                            break;
-                        }
-                    }
+                while !(&*x).forward[i].is_null() && traversed + (&*x).width[i] <= position {
+                    traversed += (&*x).width[i];
+                    x = (&*x).forward[i];
                 }
-                let update_i = &mut update[i];
-                *update_i = x;
             }
+            Self::entry(x)
+        }
+    }
 
-            let x_forward_i = (&*x).forward[0];
-            x = x_forward_i;
-            if x.is_null() || (*x).key.as_ref().cmp(&Some(&searched_key)) != Ordering::Equal {
-                let v = random_value(self.max_level);
-                if v > self.level {
-                    for update_i in update.iter_mut().take(v).skip(self.level) {
-                        *update_i = self.header;
-                    }
-                    self.level = v;
-                }
-                let new_node = Node::make_node(v, searched_key, new_value);
-                x = Box::into_raw(Box::new(new_node));
+    /// Returns the number of keys smaller than `key`, which is the index `key` has or would have.
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search_path(key).rank[0]
+    }
 
-                for (i, t) in update.iter_mut().enumerate().take(self.level) {
-                    let x_forward_i = (&mut *x).forward.get_mut(i);
-                    if x_forward_i.is_none() {
-                        break;
-                    }
-                    let x_forward_i = x_forward_i.unwrap();
-                    let update_i = t;
-                    let update_i_forward_i = &mut (&mut **update_i).forward[i];
-                    *x_forward_i = *update_i_forward_i;
-                    *update_i_forward_i = x;
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self)
+    }
+
+    /// Iterates in order over the entries whose keys lie in `range`.
+    pub fn range<'a, Q, R>(&'a self, range: R) -> impl Iterator<Item = (&'a K, &'a V)> + 'a
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'a,
+        R: RangeBounds<Q> + 'a,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(key) => self.search_path(key).update[0],
+            Bound::Excluded(key) => {
+                let before = self.search_path(key).update[0];
+                let next = unsafe { (&*before).forward[0] };
+                if !next.is_null() && unsafe { (*next).cmp_key(key) } == Ordering::Equal {
+                    next
+                } else {
+                    before
                 }
-                return true;
             }
-            (*x).value.replace(new_value);
-            return true;
+            Bound::Unbounded => self.header,
+        };
+        Iter {
+            current_node: start,
+            _marker: PhantomData,
         }
+        .take_while(move |(key, _)| match range.end_bound() {
+            Bound::Included(end) => (*key).borrow() <= end,
+            Bound::Excluded(end) => (*key).borrow() < end,
+            Bound::Unbounded => true,
+        })
     }
 
-    pub fn delete(&mut self, searched_key: K) -> bool {
-        let mut update = Vec::<*mut Node<K, V>>::with_capacity(self.max_level);
-        update.resize(self.max_level, null_mut());
-
+    /// Returns the node holding `key`, or null.
+    fn find<Q>(&self, key: &Q) -> *mut Node<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         let mut x = self.header;
-
         unsafe {
             for i in (0..self.level).rev() {
-                loop {
-                    let x_forward_i = (&*x).forward[i];
-                    if x_forward_i.is_null() {
+                while let Some(next) = (&*x).forward[i].as_ref() {
+                    if next.cmp_key(key) != Ordering::Less {
                         break;
                     }
-
-                    let x_forward_i_key = (*x_forward_i).key.as_ref();
-                    match x_forward_i_key.cmp(&Some(&searched_key)) {
-                        Ordering::Less => {
-                            x = x_forward_i;
-                        }
-                        _ => {
-                            break;
-                        }
-                    }
+                    x = (&*x).forward[i];
                 }
-                let update_i = &mut update[i];
-                *update_i = x;
             }
-
-            let x_forward_i = *((&*x).forward.first().unwrap());
-            x = x_forward_i;
-
-            if x.is_null() {
-                return false;
+            x = (&*x).forward[0];
+            if !x.is_null() && (*x).cmp_key(key) == Ordering::Equal {
+                x
+            } else {
+                null_mut()
             }
+        }
+    }
 
-            match (*x).key.as_ref().cmp(&Some(&searched_key)) {
-                Ordering::Equal => {
-                    for (i, update_i) in update.iter_mut().enumerate().take(self.level) {
-                        let update_i_forward_i = &mut (&mut **update_i).forward[i];
-                        if update_i_forward_i.is_null() {
-                            break;
-                        }
-
-                        let x_forward_i = (&mut *x).forward.get_mut(i);
-                        if x_forward_i.is_none() {
-                            break;
-                        }
-                        let x_forward_i = x_forward_i.unwrap();
-                        *update_i_forward_i = *x_forward_i;
-                    }
-
-                    let _v = Box::from_raw(x);
-
-                    loop {
-                        if self.level == 0 {
-                            break;
-                        }
-
-                        let header_forward_level = &(&*self.header).forward[self.level - 1];
-                        if header_forward_level.is_null() {
-                            self.level -= 1;
-                        } else {
-                            break;
-                        }
+    /// Finds the last node before `key` on every level, and their positions. Levels above the
+    /// current level of the list are filled with the header.
+    fn search_path<Q>(&self, key: &Q) -> SearchPath<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut update = vec![self.header; self.max_level];
+        let mut rank = vec![0; self.max_level];
+        let mut x = self.header;
+        let mut position = 0;
+        unsafe {
+            for i in (0..self.level).rev() {
+                while let Some(next) = (&*x).forward[i].as_ref() {
+                    if next.cmp_key(key) != Ordering::Less {
+                        break;
                     }
-                    return true;
-                }
-                _ => {
-                    return false;
+                    position += (&*x).width[i];
+                    x = (&*x).forward[i];
                 }
+                update[i] = x;
+                rank[i] = position;
             }
         }
+        SearchPath { update, rank }
     }
 
-    pub fn iter(&self) -> Iter<'_, K, V> {
-        Iter::new(self)
+    /// Returns the entry of a node, or `None` for the header.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list.
+    unsafe fn entry<'a>(node: *const Node<K, V>) -> Option<(&'a K, &'a V)> {
+        match (&(*node).key, &(*node).value) {
+            (Some(key), Some(value)) => Some((key, value)),
+            _ => None,
+        }
     }
 }
 
 impl<K: Ord, V> Drop for SkipList<K, V> {
     fn drop(&mut self) {
-        let mut node = unsafe { Box::from_raw(self.header) };
-        loop {
-            let node_forward_0 = node.forward.first().unwrap();
-            if node_forward_0.is_null() {
-                break;
-            }
-            node = unsafe { Box::from_raw(*node_forward_0) };
+        let mut node = self.header;
+        while !node.is_null() {
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.forward[0];
         }
     }
 }
@@ -274,17 +346,12 @@ impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
 
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
-            let forward_0 = (&*self.current_node).forward.first();
-            forward_0?;
-            let forward_0 = *forward_0.unwrap();
+            let forward_0 = (&*self.current_node).forward[0];
             if forward_0.is_null() {
                 return None;
             }
             self.current_node = forward_0;
-            return match ((*forward_0).key.as_ref(), (*forward_0).value.as_ref()) {
-                (Some(key), Some(value)) => Some((key, value)),
-                _ => None,
-            };
+            SkipList::entry(forward_0)
         }
     }
 }
@@ -374,4 +441,64 @@ mod test {
             assert_eq!(result, Some(&100));
         }
     }
+
+    #[test]
+    fn borrowed_lookups() {
+        let mut skip_list = super::SkipList::<String, usize>::new(8);
+        skip_list.insert("key".to_string(), 1);
+        assert_eq!(skip_list.search("key"), Some(&1));
+        *skip_list.get_mut("key").unwrap() += 1;
+        assert!(skip_list.contains_key("key"));
+        assert_eq!(skip_list.insert("key".to_string(), 5), Some(2));
+        assert_eq!(skip_list.remove("key"), Some(5));
+        assert!(skip_list.is_empty());
+    }
+
+    #[test]
+    fn indexing_and_rank() {
+        let mut skip_list = super::SkipList::<u32, u32>::new(6);
+        let mut seed = 11_u32;
+        let mut keys = std::collections::BTreeSet::new();
+        for _ in 0..400 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let key = (seed >> 16) % 300;
+            if seed.is_multiple_of(3) {
+                assert_eq!(skip_list.delete(&key), keys.remove(&key));
+            } else {
+                skip_list.insert(key, key * 2);
+                keys.insert(key);
+            }
+        }
+        assert_eq!(skip_list.len(), keys.len());
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(skip_list.get_index(index), Some((key, &(key * 2))));
+            assert_eq!(skip_list.rank(key), index);
+        }
+        assert_eq!(skip_list.get_index(keys.len()), None);
+        assert_eq!(skip_list.rank(&1_000), keys.len());
+    }
+
+    #[test]
+    fn range_queries() {
+        let mut skip_list = super::SkipList::<i32, char>::new(8);
+        for (key, value) in (0..10).zip('a'..) {
+            skip_list.insert(key * 2, value);
+        }
+        let keys =
+            |range: Vec<(&i32, &char)>| range.into_iter().map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(skip_list.range(3..8).collect()), vec![4, 6]);
+        assert_eq!(keys(skip_list.range(4..=8).collect()), vec![4, 6, 8]);
+        assert_eq!(keys(skip_list.range(15..).collect()), vec![16, 18]);
+        assert_eq!(keys(skip_list.range(..2).collect()), vec![0]);
+        assert_eq!(
+            keys(
+                skip_list
+                    .range((std::ops::Bound::Excluded(4), std::ops::Bound::Included(6)))
+                    .collect()
+            ),
+            vec![6]
+        );
+        assert_eq!(skip_list.range(..).count(), 10);
+        assert_eq!(skip_list.range(19..).count(), 0);
+    }
 }
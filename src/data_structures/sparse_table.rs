@@ -0,0 +1,214 @@
+//! Sparse tables: static range queries in O(1) after O(n log(n)) preprocessing.
+//!
+//! [`SparseTable`] stores the fold of every range whose length is a power of two, and answers a
+//! query by combining the two such ranges covering it. They usually overlap, so the operation
+//! must be idempotent (min, max, gcd, bitwise and/or).
+//!
+//! [`DisjointSparseTable`] works for any associative operation. At level `k`, the data is cut
+//! into blocks of `2^(k + 1)` elements, and the table stores the folds from each element to the
+//! middle of its block. Any range `[l, r]` straddles the middle of exactly one block, at the
+//! level of the highest bit in which `l` and `r` differ, and is the combination of the two
+//! disjoint folds stored there.
+//!
+//! References: [Sparse table](https://cp-algorithms.com/data_structures/sparse-table.html)
+
+use super::monoid::{Idempotent, Monoid};
+use super::range_minimum_query::RangeError;
+
+/// Answers range queries for an idempotent operation on static data.
+pub struct SparseTable<M: Idempotent> {
+    /// `table[k][i]` is the fold of the `2^k` elements starting at `i`.
+    table: Vec<Vec<M::Value>>,
+}
+
+impl<M: Idempotent> SparseTable<M> {
+    pub fn new(data: &[M::Value]) -> Self {
+        let mut table = vec![data.to_vec()];
+        for log_len in 1..=data.len().checked_ilog2().unwrap_or(0) as usize {
+            let previous = &table[log_len - 1];
+            let half = 1 << (log_len - 1);
+            let row = (0..=data.len() - (1 << log_len))
+                .map(|idx| M::combine(&previous[idx], &previous[idx + half]))
+                .collect();
+            table.push(row);
+        }
+        Self { table }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table[0].is_empty()
+    }
+
+    /// Returns the fold of the range [start, end).
+    pub fn query(&self, start: usize, end: usize) -> Result<M::Value, RangeError> {
+        check_range(start, end, self.len())?;
+        let log_len = (end - start).ilog2() as usize;
+        let row = &self.table[log_len];
+        Ok(M::combine(&row[start], &row[end - (1 << log_len)]))
+    }
+}
+
+/// Answers range queries for any associative operation on static data.
+pub struct DisjointSparseTable<M: Monoid> {
+    data: Vec<M::Value>,
+    /// `table[k][i]` is the fold from `i` to the middle of its block of `2^(k + 1)` elements:
+    /// up to the middle (excluded) in the first half, from the middle on in the second half.
+    table: Vec<Vec<M::Value>>,
+}
+
+impl<M: Monoid> DisjointSparseTable<M> {
+    pub fn new(data: &[M::Value]) -> Self {
+        let len = data.len();
+        let levels = len
+            .saturating_sub(1)
+            .checked_ilog2()
+            .map_or(0, |log| log as usize + 1);
+        let mut table = Vec::with_capacity(levels);
+        for level in 0..levels {
+            let half = 1 << level;
+            let mut row = data.to_vec();
+            for mid in (half..len).step_by(2 * half) {
+                for idx in (mid - half..mid - 1).rev() {
+                    row[idx] = M::combine(&data[idx], &row[idx + 1]);
+                }
+                for idx in mid + 1..(mid + half).min(len) {
+                    row[idx] = M::combine(&row[idx - 1], &data[idx]);
+                }
+            }
+            table.push(row);
+        }
+        Self {
+            data: data.to_vec(),
+            table,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the fold of the range [start, end).
+    pub fn query(&self, start: usize, end: usize) -> Result<M::Value, RangeError> {
+        check_range(start, end, self.len())?;
+        let last = end - 1;
+        if start == last {
+            return Ok(self.data[start].clone());
+        }
+        let row = &self.table[(start ^ last).ilog2() as usize];
+        Ok(M::combine(&row[start], &row[last]))
+    }
+}
+
+/// Checks that [start, end) is a non-empty range within data of length `len`.
+pub(super) fn check_range(start: usize, end: usize, len: usize) -> Result<(), RangeError> {
+    if start >= end {
+        return Err(RangeError::InvalidRange);
+    }
+    if start >= len || end > len {
+        return Err(RangeError::IndexOutOfBound);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::monoid::{
+        BitAndMonoid, BitOrMonoid, GcdMonoid, MaxMonoid, MinMonoid, SumMonoid,
+    };
+
+    fn naive<M: Monoid>(data: &[M::Value], start: usize, end: usize) -> M::Value {
+        data[start..end]
+            .iter()
+            .fold(M::identity(), |acc, value| M::combine(&acc, value))
+    }
+
+    #[test]
+    fn idempotent_operations() {
+        let data: Vec<u32> = vec![12, 18, 6, 9, 27, 4, 8, 16, 3, 30, 45];
+        let min = SparseTable::<MinMonoid<u32>>::new(&data);
+        let max = SparseTable::<MaxMonoid<u32>>::new(&data);
+        let gcd = SparseTable::<GcdMonoid<u32>>::new(&data);
+        let and = SparseTable::<BitAndMonoid<u32>>::new(&data);
+        let or = SparseTable::<BitOrMonoid<u32>>::new(&data);
+        for start in 0..data.len() {
+            for end in start + 1..=data.len() {
+                assert_eq!(
+                    min.query(start, end),
+                    Ok(naive::<MinMonoid<u32>>(&data, start, end))
+                );
+                assert_eq!(
+                    max.query(start, end),
+                    Ok(naive::<MaxMonoid<u32>>(&data, start, end))
+                );
+                assert_eq!(
+                    gcd.query(start, end),
+                    Ok(naive::<GcdMonoid<u32>>(&data, start, end))
+                );
+                assert_eq!(
+                    and.query(start, end),
+                    Ok(naive::<BitAndMonoid<u32>>(&data, start, end))
+                );
+                assert_eq!(
+                    or.query(start, end),
+                    Ok(naive::<BitOrMonoid<u32>>(&data, start, end))
+                );
+            }
+        }
+        assert_eq!(gcd.query(0, 3), Ok(6));
+        assert_eq!(min.query(3, 3), Err(RangeError::InvalidRange));
+        assert_eq!(max.query(5, 12), Err(RangeError::IndexOutOfBound));
+        assert_eq!(min.len(), data.len());
+    }
+
+    #[test]
+    fn disjoint_sums() {
+        for len in 0..40 {
+            let data: Vec<i64> = (0..len).map(|i| (i * 37 % 11) - 5).collect();
+            let table = DisjointSparseTable::<SumMonoid<i64>>::new(&data);
+            assert_eq!(table.len(), data.len());
+            for start in 0..data.len() {
+                for end in start + 1..=data.len() {
+                    assert_eq!(table.query(start, end), Ok(data[start..end].iter().sum()));
+                }
+            }
+            assert_eq!(
+                table.query(0, len as usize + 1),
+                Err(RangeError::IndexOutOfBound)
+            );
+        }
+    }
+
+    /// String concatenation is associative but neither commutative nor idempotent.
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(a: &String, b: &String) -> String {
+            format!("{a}{b}")
+        }
+    }
+
+    #[test]
+    fn disjoint_non_commutative() {
+        let data: Vec<String> = "disjoint".chars().map(String::from).collect();
+        let table = DisjointSparseTable::<Concat>::new(&data);
+        assert_eq!(table.query(0, 8).as_deref(), Ok("disjoint"));
+        assert_eq!(table.query(2, 7).as_deref(), Ok("sjoin"));
+        assert_eq!(table.query(5, 6).as_deref(), Ok("i"));
+        assert!(DisjointSparseTable::<Concat>::new(&[]).is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+//! A van Emde Boas tree over universes of up to 64 bits.
+//!
+//! It has the same recursive layout as [`VebTree`](super::VebTree): a value is split into a
+//! cluster number (its high bits) and an offset in the cluster (its low bits), a summary tree
+//! records the non-empty clusters, and the min of each tree is kept out of its clusters.
+//! Instead of allocating every cluster up front, clusters live in a hash map and only exist
+//! while they hold values, so the tree takes `O(n log log U)` space for `n` values and
+//! operations still run in `O(log log U)` expected time.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct SparseVebTree {
+    /// The tree stores values in `[0, 2^bits)`.
+    bits: u32,
+    min: Option<u64>,
+    max: Option<u64>,
+    len: usize,
+    /// The non-empty clusters, present only while there are some.
+    summary: Option<Box<SparseVebTree>>,
+    clusters: HashMap<u64, SparseVebTree>,
+}
+
+impl Default for SparseVebTree {
+    /// Creates a tree over all the `u64` values.
+    fn default() -> Self {
+        Self::new(u64::BITS)
+    }
+}
+
+impl SparseVebTree {
+    /// Creates an empty tree storing values in `[0, 2^bits)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `1 <= bits <= 64`.
+    pub fn new(bits: u32) -> Self {
+        assert!((1..=u64::BITS).contains(&bits), "bits must be in 1..=64");
+        Self {
+            bits,
+            min: None,
+            max: None,
+            len: 0,
+            summary: None,
+            clusters: HashMap::new(),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn min(&self) -> Option<u64> {
+        self.min
+    }
+
+    pub const fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        if self.min == Some(value) || self.max == Some(value) {
+            return true;
+        }
+        self.bits > 1
+            && self
+                .clusters
+                .get(&self.high(value))
+                .is_some_and(|cluster| cluster.contains(self.low(value)))
+    }
+
+    /// Inserts `value`, returning `false` if it was already in the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is outside the universe of the tree.
+    pub fn insert(&mut self, mut value: u64) -> bool {
+        assert!(
+            self.bits == u64::BITS || value >> self.bits == 0,
+            "value out of the universe"
+        );
+        let (Some(min), Some(max)) = (self.min, self.max) else {
+            self.min = Some(value);
+            self.max = Some(value);
+            self.len = 1;
+            return true;
+        };
+        if value == min || value == max {
+            return false;
+        }
+        if value < min {
+            // The new value becomes the min, and the old min goes into the clusters instead
+            self.min = Some(value);
+            value = min;
+        }
+        if self.bits > 1 {
+            let (high, low) = (self.high(value), self.low(value));
+            let low_bits = self.low_bits();
+            let cluster = self
+                .clusters
+                .entry(high)
+                .or_insert_with(|| Self::new(low_bits));
+            if cluster.is_empty() {
+                let high_bits = self.bits - low_bits;
+                self.summary
+                    .get_or_insert_with(|| Box::new(Self::new(high_bits)))
+                    .insert(high);
+            }
+            if !cluster.insert(low) {
+                return false;
+            }
+        }
+        self.max = Some(max.max(value));
+        self.len += 1;
+        true
+    }
+
+    /// Removes `value`, returning `false` if it was not in the tree.
+    pub fn remove(&mut self, value: u64) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.remove_present(value);
+        true
+    }
+
+    fn remove_present(&mut self, mut value: u64) {
+        self.len -= 1;
+        if self.min == self.max {
+            self.min = None;
+            self.max = None;
+            return;
+        }
+        if self.bits == 1 {
+            // The tree holds both 0 and 1, the other one remains
+            self.min = Some(1 - value);
+            self.max = self.min;
+            return;
+        }
+
+        let mut summary = self
+            .summary
+            .take()
+            .expect("a tree with two values has clusters");
+        if self.min == Some(value) {
+            // Pull the smallest clustered value up to replace the min, and remove it from its cluster
+            let first = summary.min.expect("summary is not empty");
+            value = self.index(
+                first,
+                self.clusters[&first].min.expect("clusters are not empty"),
+            );
+            self.min = Some(value);
+        }
+
+        let (high, low) = (self.high(value), self.low(value));
+        let cluster = self
+            .clusters
+            .get_mut(&high)
+            .expect("value is in its cluster");
+        cluster.remove_present(low);
+        if cluster.is_empty() {
+            self.clusters.remove(&high);
+            summary.remove_present(high);
+            if self.max == Some(value) {
+                self.max = match summary.max {
+                    Some(last) => self.clusters[&last].max.map(|max| self.index(last, max)),
+                    None => self.min,
+                };
+            }
+        } else if self.max == Some(value) {
+            self.max = cluster.max.map(|max| self.index(high, max));
+        }
+        if !summary.is_empty() {
+            self.summary = Some(summary);
+        }
+    }
+
+    /// Returns the smallest value greater than `value`.
+    pub fn succ(&self, value: u64) -> Option<u64> {
+        let (min, max) = (self.min?, self.max?);
+        if value < min {
+            return Some(min);
+        }
+        if value >= max {
+            return None;
+        }
+        if self.bits == 1 {
+            return Some(max);
+        }
+        let (high, low) = (self.high(value), self.low(value));
+        if let Some(cluster) = self.clusters.get(&high) {
+            if cluster.max.is_some_and(|cluster_max| low < cluster_max) {
+                return cluster.succ(low).map(|low| self.index(high, low));
+            }
+        }
+        let next = self.summary.as_ref()?.succ(high)?;
+        self.clusters[&next].min.map(|low| self.index(next, low))
+    }
+
+    /// Returns the largest value smaller than `value`.
+    pub fn pred(&self, value: u64) -> Option<u64> {
+        let (min, max) = (self.min?, self.max?);
+        if value > max {
+            return Some(max);
+        }
+        if value <= min {
+            return None;
+        }
+        if self.bits == 1 {
+            return Some(min);
+        }
+        let (high, low) = (self.high(value), self.low(value));
+        if let Some(cluster) = self.clusters.get(&high) {
+            if cluster.min.is_some_and(|cluster_min| low > cluster_min) {
+                return cluster.pred(low).map(|low| self.index(high, low));
+            }
+        }
+        // The min is not in any cluster, so it is the predecessor if no cluster before holds one
+        match self.summary.as_ref().and_then(|summary| summary.pred(high)) {
+            Some(previous) => self.clusters[&previous]
+                .max
+                .map(|low| self.index(previous, low)),
+            None => Some(min),
+        }
+    }
+
+    /// Iterates over the values in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        std::iter::successors(self.min, |&value| self.succ(value))
+    }
+
+    const fn low_bits(&self) -> u32 {
+        self.bits / 2
+    }
+
+    const fn high(&self, value: u64) -> u64 {
+        value >> self.low_bits()
+    }
+
+    const fn low(&self, value: u64) -> u64 {
+        value & ((1 << self.low_bits()) - 1)
+    }
+
+    const fn index(&self, high: u64, low: u64) -> u64 {
+        (high << self.low_bits()) | low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn sparse_64_bit_values() {
+        let mut tree = SparseVebTree::default();
+        for value in [u64::MAX, 0, 1 << 40, 12_345, u64::MAX - 1] {
+            assert!(tree.insert(value));
+        }
+        assert!(!tree.insert(12_345));
+        assert_eq!(tree.len(), 5);
+        assert_eq!((tree.min(), tree.max()), (Some(0), Some(u64::MAX)));
+        assert_eq!(tree.succ(12_345), Some(1 << 40));
+        assert_eq!(tree.pred(1 << 40), Some(12_345));
+        assert_eq!(tree.pred(u64::MAX), Some(u64::MAX - 1));
+        assert_eq!(tree.succ(u64::MAX), None);
+        assert_eq!(tree.pred(0), None);
+        assert!(tree.remove(u64::MAX));
+        assert!(!tree.remove(u64::MAX));
+        assert_eq!(tree.max(), Some(u64::MAX - 1));
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec![0, 12_345, 1 << 40, u64::MAX - 1]
+        );
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = StdRng::seed_from_u64(37);
+        for bits in [1, 5, 16, 64] {
+            let universe = if bits == 64 { 1_000 } else { 1_u64 << bits };
+            // Spread the values over the whole 64-bit universe
+            let scale = if bits == 64 { u64::MAX / universe } else { 1 };
+            let mut tree = SparseVebTree::new(bits);
+            let mut expected = BTreeSet::new();
+            for _ in 0..2_000 {
+                let value = rng.random_range(0..universe) * scale;
+                if rng.random_range(0..2) == 0 {
+                    assert_eq!(tree.insert(value), expected.insert(value));
+                } else {
+                    assert_eq!(tree.remove(value), expected.remove(&value));
+                }
+                assert_eq!(tree.len(), expected.len());
+                let probe = rng.random_range(0..universe) * scale;
+                assert_eq!(tree.contains(probe), expected.contains(&probe));
+                assert_eq!(
+                    tree.succ(probe),
+                    expected.range(probe + 1..).next().copied()
+                );
+                assert_eq!(
+                    tree.pred(probe),
+                    expected.range(..probe).next_back().copied()
+                );
+            }
+            assert!(tree.iter().eq(expected.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn releases_empty_clusters() {
+        let mut tree = SparseVebTree::default();
+        for value in (0..64).map(|shift| 1_u64 << shift) {
+            tree.insert(value);
+        }
+        for value in (0..64).map(|shift| 1_u64 << shift) {
+            assert!(tree.remove(value));
+        }
+        assert!(tree.is_empty());
+        assert!(tree.clusters.is_empty() && tree.summary.is_none());
+    }
+}
@@ -0,0 +1,474 @@
+//! A k-d tree: a binary space partitioning tree over points in `k` dimensions.
+//!
+//! Every node splits space along one axis, cycling through the axes with the depth of the node.
+//! Building the tree from a known set of points picks the median as the splitting node (with
+//! `select_nth_unstable`), which gives a balanced tree in `O(n log n)` time. Nearest-neighbour
+//! queries descend towards the target first and only visit the other side of a split if the
+//! splitting plane is closer than the current candidates, which takes `O(log n)` time on
+//! well-distributed points.
+//!
+//! References: [Wikipedia](https://en.wikipedia.org/wiki/K-d_tree)
+
+use crate::geometry::Point;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A point that can be stored in or used to query a [`KdTree`].
+pub trait KdPoint {
+    fn dimensions(&self) -> usize;
+
+    /// The coordinate of the point along `axis`, which is below [`KdPoint::dimensions`].
+    fn coordinate(&self, axis: usize) -> f64;
+}
+
+impl KdPoint for Point {
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        if axis == 0 {
+            self.x
+        } else {
+            self.y
+        }
+    }
+}
+
+impl KdPoint for [f64] {
+    fn dimensions(&self) -> usize {
+        self.len()
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        self[axis]
+    }
+}
+
+impl KdPoint for Vec<f64> {
+    fn dimensions(&self) -> usize {
+        self.len()
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        self[axis]
+    }
+}
+
+fn distance_squared<P: KdPoint + ?Sized, Q: KdPoint + ?Sized>(p: &P, q: &Q) -> f64 {
+    (0..p.dimensions())
+        .map(|axis| (p.coordinate(axis) - q.coordinate(axis)).powi(2))
+        .sum()
+}
+
+/// A point returned by a query, with its value and its distance to the query point.
+#[derive(Debug, PartialEq)]
+pub struct Neighbor<'a, P, V> {
+    pub point: &'a P,
+    pub value: &'a V,
+    pub distance: f64,
+}
+
+struct Node<P, V> {
+    point: P,
+    value: V,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A candidate of a k-nearest query, ordered by distance so that the heap keeps the farthest on top.
+struct Candidate {
+    distance_squared: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance_squared
+            .total_cmp(&other.distance_squared)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// A k-d tree mapping points to values. All points must have the same number of dimensions.
+pub struct KdTree<P: KdPoint, V = ()> {
+    /// The nodes, stored in an arena and linked by index.
+    nodes: Vec<Node<P, V>>,
+    root: Option<usize>,
+}
+
+impl<P: KdPoint, V> Default for KdTree<P, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: KdPoint, V> KdTree<P, V> {
+    pub fn new() -> Self {
+        KdTree {
+            nodes: Vec::new(),
+            root: None,
+        }
+    }
+
+    /// Builds a balanced tree from `points`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the points do not all have the same, non-zero, number of dimensions.
+    pub fn from_points(mut points: Vec<(P, V)>) -> Self {
+        if let Some((first, _)) = points.first() {
+            let dimensions = first.dimensions();
+            assert!(dimensions > 0, "points must have at least one dimension");
+            assert!(
+                points.iter().all(|(p, _)| p.dimensions() == dimensions),
+                "all points must have the same number of dimensions"
+            );
+        }
+        // Reorder the points so that every subtree is a contiguous slice with its root in the
+        // middle, then turn them into nodes in that order.
+        let mut links = vec![(0, None, None); points.len()];
+        let root = arrange(&mut points, 0, 0, &mut links);
+        let nodes = points
+            .into_iter()
+            .zip(links)
+            .map(|((point, value), (axis, left, right))| Node {
+                point,
+                value,
+                axis,
+                left,
+                right,
+            })
+            .collect();
+        KdTree { nodes, root }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The number of dimensions of the points in the tree, or `None` if it is empty.
+    pub fn dimensions(&self) -> Option<usize> {
+        self.nodes.first().map(|node| node.point.dimensions())
+    }
+
+    /// Inserts a point below the leaf it falls into. Insertions do not rebalance the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` does not have the same number of dimensions as the points in the tree.
+    pub fn insert(&mut self, point: P, value: V) {
+        self.check_dimensions(&point);
+        let index = self.nodes.len();
+        let mut axis = 0;
+        let mut link = &mut self.root;
+        while let Some(current) = *link {
+            let node = &self.nodes[current];
+            axis = (node.axis + 1) % point.dimensions();
+            let go_left = point.coordinate(node.axis) < node.point.coordinate(node.axis);
+            let node = &mut self.nodes[current];
+            link = if go_left {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        *link = Some(index);
+        self.nodes.push(Node {
+            point,
+            value,
+            axis,
+            left: None,
+            right: None,
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&P, &V)> {
+        self.nodes.iter().map(|node| (&node.point, &node.value))
+    }
+
+    /// The point closest to `target`, or `None` if the tree is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` does not have the same number of dimensions as the points in the tree.
+    pub fn nearest<Q: KdPoint + ?Sized>(&self, target: &Q) -> Option<Neighbor<'_, P, V>> {
+        self.k_nearest(target, 1).pop()
+    }
+
+    /// The `k` points closest to `target` (or all of them if there are fewer), by increasing
+    /// distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` does not have the same number of dimensions as the points in the tree.
+    pub fn k_nearest<Q: KdPoint + ?Sized>(&self, target: &Q, k: usize) -> Vec<Neighbor<'_, P, V>> {
+        self.check_dimensions(target);
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.k_nearest_from(self.root, target, k, &mut heap);
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|candidate| self.neighbor(candidate))
+            .collect()
+    }
+
+    /// The points whose distance to `target` is at most `radius`, by increasing distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` does not have the same number of dimensions as the points in the tree.
+    pub fn within_radius<Q: KdPoint + ?Sized>(
+        &self,
+        target: &Q,
+        radius: f64,
+    ) -> Vec<Neighbor<'_, P, V>> {
+        self.check_dimensions(target);
+        let radius_squared = radius * radius;
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let d = distance_squared(&node.point, target);
+            if d <= radius_squared {
+                found.push(Candidate {
+                    distance_squared: d,
+                    index,
+                });
+            }
+            let diff = target.coordinate(node.axis) - node.point.coordinate(node.axis);
+            // The left subtree lies strictly before the split, the right one at or after it.
+            if diff < 0.0 || diff * diff <= radius_squared {
+                stack.extend(node.left);
+            }
+            if diff >= 0.0 || diff * diff <= radius_squared {
+                stack.extend(node.right);
+            }
+        }
+        found.sort_unstable();
+        found
+            .into_iter()
+            .map(|candidate| self.neighbor(candidate))
+            .collect()
+    }
+
+    fn k_nearest_from<Q: KdPoint + ?Sized>(
+        &self,
+        index: Option<usize>,
+        target: &Q,
+        k: usize,
+        heap: &mut BinaryHeap<Candidate>,
+    ) {
+        let Some(index) = index else {
+            return;
+        };
+        let node = &self.nodes[index];
+        heap.push(Candidate {
+            distance_squared: distance_squared(&node.point, target),
+            index,
+        });
+        if heap.len() > k {
+            heap.pop();
+        }
+        let diff = target.coordinate(node.axis) - node.point.coordinate(node.axis);
+        let (near, far) = if diff < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        self.k_nearest_from(near, target, k, heap);
+        let worst = heap.peek().map_or(f64::INFINITY, |c| c.distance_squared);
+        if heap.len() < k || diff * diff <= worst {
+            self.k_nearest_from(far, target, k, heap);
+        }
+    }
+
+    fn neighbor(&self, candidate: Candidate) -> Neighbor<'_, P, V> {
+        let node = &self.nodes[candidate.index];
+        Neighbor {
+            point: &node.point,
+            value: &node.value,
+            distance: candidate.distance_squared.sqrt(),
+        }
+    }
+
+    fn check_dimensions<Q: KdPoint + ?Sized>(&self, point: &Q) {
+        let expected = self.dimensions().unwrap_or_else(|| point.dimensions());
+        assert!(
+            point.dimensions() == expected && expected > 0,
+            "expected a point with {expected} dimensions, got {}",
+            point.dimensions()
+        );
+    }
+}
+
+/// Arranges `points` so that the median along `axis` is in the middle, recursively, and records
+/// the axis and children of each position in `links`. Returns the index of the subtree root.
+fn arrange<P: KdPoint, V>(
+    points: &mut [(P, V)],
+    offset: usize,
+    axis: usize,
+    links: &mut [(usize, Option<usize>, Option<usize>)],
+) -> Option<usize> {
+    if points.is_empty() {
+        return None;
+    }
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |(a, _), (b, _)| {
+        a.coordinate(axis).total_cmp(&b.coordinate(axis))
+    });
+    // Equal coordinates may end up on both sides of the median; queries account for that by
+    // treating a difference of zero as lying on both sides.
+    let next = (axis + 1) % points[mid].0.dimensions();
+    let (left, rest) = points.split_at_mut(mid);
+    let left = arrange(left, offset, next, &mut links[..mid]);
+    let right = arrange(
+        &mut rest[1..],
+        offset + mid + 1,
+        next,
+        &mut links[mid + 1..],
+    );
+    links[mid] = (axis, left, right);
+    Some(offset + mid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    fn brute_force(points: &[Point], target: &Point) -> Vec<f64> {
+        let mut distances: Vec<f64> = points
+            .iter()
+            .map(|p| p.euclidean_distance(target))
+            .collect();
+        distances.sort_by(f64::total_cmp);
+        distances
+    }
+
+    #[test]
+    fn empty_tree() {
+        let tree: KdTree<Point> = KdTree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.dimensions(), None);
+        assert!(tree.nearest(&Point::new(0.0, 0.0)).is_none());
+        assert!(tree.k_nearest(&Point::new(0.0, 0.0), 3).is_empty());
+        assert!(tree.within_radius(&Point::new(0.0, 0.0), 1.0).is_empty());
+    }
+
+    #[test]
+    fn small_queries() {
+        let points = vec![
+            (Point::new(2.0, 3.0), 'a'),
+            (Point::new(5.0, 4.0), 'b'),
+            (Point::new(9.0, 6.0), 'c'),
+            (Point::new(4.0, 7.0), 'd'),
+            (Point::new(8.0, 1.0), 'e'),
+            (Point::new(7.0, 2.0), 'f'),
+        ];
+        let tree = KdTree::from_points(points);
+        assert_eq!(tree.len(), 6);
+
+        let nearest = tree.nearest(&Point::new(8.5, 1.0)).unwrap();
+        assert_eq!((nearest.value, nearest.distance), (&'e', 0.5));
+
+        let values: Vec<char> = tree
+            .k_nearest(&Point::new(6.0, 3.2), 3)
+            .into_iter()
+            .map(|n| *n.value)
+            .collect();
+        assert_eq!(values, vec!['b', 'f', 'e']);
+
+        let values: Vec<char> = tree
+            .within_radius(&Point::new(3.2, 5.0), 2.5)
+            .into_iter()
+            .map(|n| *n.value)
+            .collect();
+        assert_eq!(values, vec!['b', 'd', 'a']);
+        assert_eq!(tree.k_nearest(&Point::new(0.0, 0.0), 10).len(), 6);
+    }
+
+    #[test]
+    fn duplicate_coordinates() {
+        let points: Vec<(Point, usize)> = (0..20)
+            .map(|i| (Point::new(1.0, (i % 3) as f64), i))
+            .collect();
+        let tree = KdTree::from_points(points);
+        assert_eq!(tree.within_radius(&Point::new(1.0, 1.0), 0.0).len(), 7);
+        assert_eq!(tree.k_nearest(&Point::new(1.0, 2.0), 6)[5].distance, 0.0);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(40);
+        let points: Vec<Point> = (0..500)
+            .map(|_| Point::new(rng.random_range(-50.0..50.0), rng.random_range(-50.0..50.0)))
+            .collect();
+        let mut built = KdTree::from_points(points.iter().map(|p| (p.clone(), ())).collect());
+        let mut inserted = KdTree::new();
+        for p in &points {
+            inserted.insert(p.clone(), ());
+        }
+        let extra = Point::new(0.5, 0.5);
+        built.insert(extra.clone(), ());
+        inserted.insert(extra.clone(), ());
+        let mut all = points;
+        all.push(extra);
+
+        for _ in 0..50 {
+            let target = Point::new(rng.random_range(-60.0..60.0), rng.random_range(-60.0..60.0));
+            let expected = brute_force(&all, &target);
+            for tree in [&built, &inserted] {
+                let found: Vec<f64> = tree
+                    .k_nearest(&target, 10)
+                    .iter()
+                    .map(|n| n.distance)
+                    .collect();
+                assert_eq!(found, expected[..10]);
+                let within = tree.within_radius(&target, 15.0).len();
+                assert_eq!(within, expected.iter().filter(|&&d| d <= 15.0).count());
+            }
+        }
+    }
+
+    #[test]
+    fn higher_dimensions() {
+        let points: Vec<(Vec<f64>, u8)> = vec![
+            (vec![0.0, 0.0, 0.0], 0),
+            (vec![1.0, 1.0, 1.0], 1),
+            (vec![2.0, 0.0, 5.0], 2),
+        ];
+        let tree = KdTree::from_points(points);
+        assert_eq!(tree.dimensions(), Some(3));
+        let target: &[f64] = &[1.5, 0.5, 4.0];
+        assert_eq!(tree.nearest(target).unwrap().value, &2);
+    }
+
+    #[test]
+    #[should_panic(expected = "dimensions")]
+    fn mismatched_dimensions() {
+        let mut tree = KdTree::new();
+        tree.insert(vec![1.0, 2.0], ());
+        tree.insert(vec![1.0], ());
+    }
+}
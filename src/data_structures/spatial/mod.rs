@@ -0,0 +1,16 @@
+//! Spatial indexes over [`Point`](crate::geometry::Point)s and
+//! [`Rectangle`](crate::geometry::Rectangle)s of the plane:
+//!
+//! * [`KdTree`] answers nearest, k-nearest and radius queries, and also works on points with
+//!   more dimensions through the [`KdPoint`] trait.
+//! * [`QuadTree`] splits a fixed region into quadrants and answers range and radius queries.
+//! * [`RTree`] stores rectangles, can be bulk loaded with Sort-Tile-Recursive packing, and
+//!   answers intersection and point queries.
+
+mod kd_tree;
+mod quadtree;
+mod r_tree;
+
+pub use self::kd_tree::{KdPoint, KdTree, Neighbor};
+pub use self::quadtree::QuadTree;
+pub use self::r_tree::RTree;
@@ -0,0 +1,341 @@
+//! A point-region (PR) quadtree.
+//!
+//! The tree covers a fixed rectangle. Every leaf holds up to `capacity` points, and a leaf that
+//! overflows is split into four quadrants of equal size, each taking the points that fall into
+//! it. A point on the border between quadrants goes to the one on its right and above. Leaves
+//! at the maximal depth are never split, so that many copies of the same point cannot make the
+//! tree grow forever. Removing points merges quadrants back once they fit into a single leaf.
+//!
+//! References: [Wikipedia](https://en.wikipedia.org/wiki/Quadtree#Point-region_(PR)_quadtree)
+
+use crate::geometry::{Point, Rectangle};
+
+const MAX_DEPTH: usize = 24;
+
+enum Node<V> {
+    Leaf(Vec<(Point, V)>),
+    /// The quadrants, indexed by `right + 2 * top`.
+    Internal(Box<[Node<V>; 4]>),
+}
+
+/// A quadtree mapping points inside a fixed rectangle to values.
+pub struct QuadTree<V> {
+    root: Node<V>,
+    bounds: Rectangle,
+    capacity: usize,
+    length: usize,
+}
+
+impl<V> QuadTree<V> {
+    /// Creates a tree covering `bounds` whose leaves hold up to 4 points.
+    pub fn new(bounds: Rectangle) -> Self {
+        Self::with_capacity(bounds, 4)
+    }
+
+    /// Creates a tree covering `bounds` whose leaves hold up to `capacity` points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(bounds: Rectangle, capacity: usize) -> Self {
+        assert!(capacity > 0, "leaf capacity must be positive");
+        QuadTree {
+            root: Node::Leaf(Vec::new()),
+            bounds,
+            capacity,
+            length: 0,
+        }
+    }
+
+    pub fn bounds(&self) -> &Rectangle {
+        &self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Inserts a point, returning `false` (and dropping it) if it lies outside of the bounds.
+    pub fn insert(&mut self, point: Point, value: V) -> bool {
+        if !self.bounds.contains(&point) {
+            return false;
+        }
+        let mut bounds = self.bounds.clone();
+        let mut node = &mut self.root;
+        let mut depth = 0;
+        while let Node::Internal(children) = node {
+            let quadrant = quadrant(&bounds, &point);
+            bounds = child_bounds(&bounds, quadrant);
+            node = &mut children[quadrant];
+            depth += 1;
+        }
+        if let Node::Leaf(points) = node {
+            points.push((point, value));
+            if points.len() > self.capacity && depth < MAX_DEPTH {
+                split(node, &bounds);
+            }
+        }
+        self.length += 1;
+        true
+    }
+
+    /// Removes one of the points equal to `point`, returning its value.
+    pub fn remove(&mut self, point: &Point) -> Option<V> {
+        if !self.bounds.contains(point) {
+            return None;
+        }
+        let value = remove(&mut self.root, &self.bounds, point, self.capacity)?;
+        self.length -= 1;
+        Some(value)
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        self.query_range(&Rectangle::from_point(point))
+            .iter()
+            .any(|(p, _)| *p == point)
+    }
+
+    /// The points inside `range`, including its border.
+    pub fn query_range(&self, range: &Rectangle) -> Vec<(&Point, &V)> {
+        let mut found = Vec::new();
+        self.visit(range, |p, v| {
+            if range.contains(p) {
+                found.push((p, v));
+            }
+        });
+        found
+    }
+
+    /// The points whose distance to `center` is at most `radius`.
+    pub fn query_radius(&self, center: &Point, radius: f64) -> Vec<(&Point, &V)> {
+        let range = Rectangle::new(
+            Point::new(center.x - radius, center.y - radius),
+            Point::new(center.x + radius, center.y + radius),
+        );
+        let mut found = Vec::new();
+        self.visit(&range, |p, v| {
+            if p.euclidean_distance(center) <= radius {
+                found.push((p, v));
+            }
+        });
+        found
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Point, &V)> {
+        let mut stack = vec![&self.root];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                Node::Leaf(points) => return Some(points.iter()),
+                Node::Internal(children) => stack.extend(children.iter()),
+            }
+        })
+        .flatten()
+        .map(|(p, v)| (p, v))
+    }
+
+    /// Calls `f` on every point of the leaves whose quadrant intersects `range`.
+    fn visit<'a>(&'a self, range: &Rectangle, mut f: impl FnMut(&'a Point, &'a V)) {
+        let mut stack = vec![(&self.root, self.bounds.clone())];
+        while let Some((node, bounds)) = stack.pop() {
+            match node {
+                Node::Leaf(points) => points.iter().for_each(|(p, v)| f(p, v)),
+                Node::Internal(children) => {
+                    for (quadrant, child) in children.iter().enumerate() {
+                        let child_bounds = child_bounds(&bounds, quadrant);
+                        if child_bounds.intersects(range) {
+                            stack.push((child, child_bounds));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn quadrant(bounds: &Rectangle, point: &Point) -> usize {
+    let center = bounds.center();
+    usize::from(point.x >= center.x) + 2 * usize::from(point.y >= center.y)
+}
+
+fn child_bounds(bounds: &Rectangle, quadrant: usize) -> Rectangle {
+    let center = bounds.center();
+    let (min_x, max_x) = if quadrant.is_multiple_of(2) {
+        (bounds.min.x, center.x)
+    } else {
+        (center.x, bounds.max.x)
+    };
+    let (min_y, max_y) = if quadrant < 2 {
+        (bounds.min.y, center.y)
+    } else {
+        (center.y, bounds.max.y)
+    };
+    Rectangle::new(Point::new(min_x, min_y), Point::new(max_x, max_y))
+}
+
+/// Turns an overflowing leaf into an internal node, distributing its points.
+fn split<V>(node: &mut Node<V>, bounds: &Rectangle) {
+    let Node::Leaf(points) = std::mem::replace(node, Node::Leaf(Vec::new())) else {
+        return;
+    };
+    let mut children = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+    for (point, value) in points {
+        children[quadrant(bounds, &point)].push((point, value));
+    }
+    *node = Node::Internal(Box::new(children.map(Node::Leaf)));
+}
+
+fn remove<V>(node: &mut Node<V>, bounds: &Rectangle, point: &Point, capacity: usize) -> Option<V> {
+    match node {
+        Node::Leaf(points) => {
+            let index = points.iter().position(|(p, _)| p == point)?;
+            Some(points.swap_remove(index).1)
+        }
+        Node::Internal(children) => {
+            let quadrant = quadrant(bounds, point);
+            let child_bounds = child_bounds(bounds, quadrant);
+            let value = remove(&mut children[quadrant], &child_bounds, point, capacity)?;
+            merge(node, capacity);
+            Some(value)
+        }
+    }
+}
+
+/// Merges the quadrants of an internal node into a single leaf if they are leaves that fit.
+fn merge<V>(node: &mut Node<V>, capacity: usize) {
+    let Node::Internal(children) = node else {
+        return;
+    };
+    let mut total = 0;
+    for child in children.iter() {
+        match child {
+            Node::Leaf(points) => total += points.len(),
+            Node::Internal(_) => return,
+        }
+    }
+    if total > capacity {
+        return;
+    }
+    let Node::Internal(children) = std::mem::replace(node, Node::Leaf(Vec::new())) else {
+        return;
+    };
+    let points = children
+        .into_iter()
+        .flat_map(|child| match child {
+            Node::Leaf(points) => points,
+            Node::Internal(_) => Vec::new(),
+        })
+        .collect();
+    *node = Node::Leaf(points);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    fn square(size: f64) -> Rectangle {
+        Rectangle::new(Point::new(0.0, 0.0), Point::new(size, size))
+    }
+
+    #[test]
+    fn insert_and_query() {
+        let mut tree = QuadTree::new(square(10.0));
+        assert!(tree.is_empty());
+        for (i, (x, y)) in [
+            (1.0, 1.0),
+            (2.0, 8.0),
+            (5.0, 5.0),
+            (9.0, 9.0),
+            (10.0, 0.0),
+            (6.0, 4.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            assert!(tree.insert(Point::new(x, y), i));
+        }
+        assert!(!tree.insert(Point::new(10.5, 3.0), 99));
+        assert_eq!(tree.len(), 6);
+        assert!(tree.contains(&Point::new(5.0, 5.0)));
+        assert!(!tree.contains(&Point::new(5.0, 6.0)));
+
+        let range = Rectangle::new(Point::new(4.0, 0.0), Point::new(10.0, 5.0));
+        let mut found: Vec<usize> = tree
+            .query_range(&range)
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![2, 4, 5]);
+
+        let mut found: Vec<usize> = tree
+            .query_radius(&Point::new(5.5, 4.5), 1.0)
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![2, 5]);
+        assert_eq!(tree.iter().count(), 6);
+    }
+
+    #[test]
+    fn duplicates_do_not_split_forever() {
+        let mut tree = QuadTree::with_capacity(square(1.0), 2);
+        for i in 0..100 {
+            assert!(tree.insert(Point::new(0.25, 0.25), i));
+        }
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.query_radius(&Point::new(0.25, 0.25), 0.0).len(), 100);
+        for _ in 0..100 {
+            assert!(tree.remove(&Point::new(0.25, 0.25)).is_some());
+        }
+        assert!(tree.is_empty());
+        assert!(matches!(&tree.root, Node::Leaf(points) if points.is_empty()));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(40);
+        let mut tree = QuadTree::new(square(100.0));
+        let mut points = Vec::new();
+        for i in 0..1000 {
+            let p = Point::new(rng.random_range(0.0..100.0), rng.random_range(0.0..100.0));
+            tree.insert(p.clone(), i);
+            points.push((p, i));
+        }
+        for (p, i) in points.drain(..500) {
+            assert_eq!(tree.remove(&p), Some(i));
+        }
+        assert_eq!(tree.remove(&Point::new(-1.0, 0.0)), None);
+        assert_eq!(tree.len(), 500);
+
+        for _ in 0..50 {
+            let a = Point::new(rng.random_range(0.0..100.0), rng.random_range(0.0..100.0));
+            let b = Point::new(rng.random_range(0.0..100.0), rng.random_range(0.0..100.0));
+            let range = Rectangle::new(a.clone(), b);
+            let mut found: Vec<usize> = tree
+                .query_range(&range)
+                .into_iter()
+                .map(|(_, v)| *v)
+                .collect();
+            found.sort_unstable();
+            let expected: Vec<usize> = points
+                .iter()
+                .filter(|(p, _)| range.contains(p))
+                .map(|(_, i)| *i)
+                .collect();
+            assert_eq!(found, expected);
+
+            let found = tree.query_radius(&a, 10.0).len();
+            let expected = points
+                .iter()
+                .filter(|(p, _)| p.euclidean_distance(&a) <= 10.0)
+                .count();
+            assert_eq!(found, expected);
+        }
+    }
+}
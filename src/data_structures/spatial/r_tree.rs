@@ -0,0 +1,412 @@
+//! An R-tree: a balanced tree of rectangles for intersection and point queries.
+//!
+//! Every node stores up to `MAX_ENTRIES` entries together with their bounding rectangles, and an
+//! internal node covers the bounding rectangle of its children, so a query only descends into
+//! the children whose rectangle intersects it. Single insertions go to the child that needs the
+//! least enlargement and split overflowing nodes with Guttman's quadratic split.
+//!
+//! [`RTree::bulk_load`] builds a tree from known rectangles with Sort-Tile-Recursive (STR)
+//! packing: the rectangles are sorted by the `x` coordinate of their centers and cut into about
+//! `sqrt(n / MAX_ENTRIES)` vertical slices, each slice is sorted by `y` and cut into full nodes,
+//! and the same is repeated on the nodes until a single root remains. This gives nearly full
+//! nodes with little overlap.
+//!
+//! References:
+//! - [Wikipedia](https://en.wikipedia.org/wiki/R-tree)
+//! - Leutenegger, Lopez and Edgington, "STR: A simple and efficient algorithm for R-tree packing"
+
+use crate::geometry::{Point, Rectangle};
+
+const MAX_ENTRIES: usize = 16;
+const MIN_ENTRIES: usize = MAX_ENTRIES / 4;
+
+/// The entries of a node: values in a leaf, children in an internal node.
+type Entries<E> = Vec<(Rectangle, E)>;
+
+enum Node<V> {
+    Leaf(Entries<V>),
+    Internal(Entries<Node<V>>),
+}
+
+impl<V> Node<V> {
+    /// The bounding rectangle of a non-empty node.
+    fn bounds(&self) -> Rectangle {
+        match self {
+            Node::Leaf(entries) => bounding(entries),
+            Node::Internal(entries) => bounding(entries),
+        }
+    }
+}
+
+fn bounding<E>(entries: &[(Rectangle, E)]) -> Rectangle {
+    let (first, rest) = entries.split_first().expect("a node is never empty");
+    rest.iter()
+        .fold(first.0.clone(), |acc, (r, _)| acc.union(r))
+}
+
+/// How much `rect` grows when it is extended to cover `other`.
+fn enlargement(rect: &Rectangle, other: &Rectangle) -> f64 {
+    rect.union(other).area() - rect.area()
+}
+
+/// A collection of rectangles with values, answering intersection queries.
+pub struct RTree<V> {
+    root: Node<V>,
+    length: usize,
+}
+
+impl<V> Default for RTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> RTree<V> {
+    pub fn new() -> Self {
+        RTree {
+            root: Node::Leaf(Vec::new()),
+            length: 0,
+        }
+    }
+
+    /// Builds a tree from `entries` with Sort-Tile-Recursive packing.
+    pub fn bulk_load(entries: Vec<(Rectangle, V)>) -> Self {
+        let length = entries.len();
+        if length == 0 {
+            return Self::new();
+        }
+        let mut nodes: Entries<Node<V>> = pack(entries)
+            .into_iter()
+            .map(|entries| (bounding(&entries), Node::Leaf(entries)))
+            .collect();
+        while nodes.len() > 1 {
+            nodes = pack(nodes)
+                .into_iter()
+                .map(|entries| (bounding(&entries), Node::Internal(entries)))
+                .collect();
+        }
+        let root = nodes.pop().map_or(Node::Leaf(Vec::new()), |(_, node)| node);
+        RTree { root, length }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The number of levels of the tree, 1 if the root is a leaf.
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Node::Internal(entries) = node {
+            node = &entries[0].1;
+            height += 1;
+        }
+        height
+    }
+
+    pub fn insert(&mut self, rect: Rectangle, value: V) {
+        if let Some(sibling) = insert(&mut self.root, rect, value) {
+            let old = std::mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+            self.root = Node::Internal(vec![(old.bounds(), old), sibling]);
+        }
+        self.length += 1;
+    }
+
+    /// The entries whose rectangle intersects `range`, borders included.
+    pub fn search(&self, range: &Rectangle) -> Vec<(&Rectangle, &V)> {
+        let mut found = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => found.extend(
+                    entries
+                        .iter()
+                        .filter(|(r, _)| r.intersects(range))
+                        .map(|(r, v)| (r, v)),
+                ),
+                Node::Internal(entries) => stack.extend(
+                    entries
+                        .iter()
+                        .filter(|(r, _)| r.intersects(range))
+                        .map(|(_, child)| child),
+                ),
+            }
+        }
+        found
+    }
+
+    /// The entries whose rectangle contains `point`.
+    pub fn containing_point(&self, point: &Point) -> Vec<(&Rectangle, &V)> {
+        self.search(&Rectangle::from_point(point))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Rectangle, &V)> {
+        let mut stack = vec![&self.root];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                Node::Leaf(entries) => return Some(entries.iter()),
+                Node::Internal(entries) => stack.extend(entries.iter().map(|(_, child)| child)),
+            }
+        })
+        .flatten()
+        .map(|(r, v)| (r, v))
+    }
+}
+
+/// Sorts `entries` into groups of at most `MAX_ENTRIES`, tiling them by center `x`, then `y`.
+fn pack<E>(mut entries: Entries<E>) -> Vec<Entries<E>> {
+    let pages = entries.len().div_ceil(MAX_ENTRIES);
+    let slices = (pages as f64).sqrt().ceil() as usize;
+    let slice_size = slices * MAX_ENTRIES;
+    entries.sort_by(|(a, _), (b, _)| a.center().x.total_cmp(&b.center().x));
+    let mut groups = Vec::with_capacity(pages);
+    for mut slice in chunks(entries, slice_size) {
+        slice.sort_by(|(a, _), (b, _)| a.center().y.total_cmp(&b.center().y));
+        groups.extend(chunks(slice, MAX_ENTRIES));
+    }
+    groups
+}
+
+fn chunks<T>(items: Vec<T>, size: usize) -> Vec<Vec<T>> {
+    let mut items = items.into_iter().peekable();
+    let mut chunks = Vec::new();
+    while items.peek().is_some() {
+        chunks.push(items.by_ref().take(size).collect());
+    }
+    chunks
+}
+
+/// Inserts into the subtree of `node`, returning the new sibling of `node` if it was split.
+fn insert<V>(node: &mut Node<V>, rect: Rectangle, value: V) -> Option<(Rectangle, Node<V>)> {
+    match node {
+        Node::Leaf(entries) => {
+            entries.push((rect, value));
+            if entries.len() <= MAX_ENTRIES {
+                return None;
+            }
+            let (kept, moved) = quadratic_split(std::mem::take(entries));
+            *entries = kept;
+            Some((bounding(&moved), Node::Leaf(moved)))
+        }
+        Node::Internal(entries) => {
+            let best = entries
+                .iter()
+                .enumerate()
+                .min_by(|(_, (a, _)), (_, (b, _))| {
+                    enlargement(a, &rect)
+                        .total_cmp(&enlargement(b, &rect))
+                        .then(a.area().total_cmp(&b.area()))
+                })
+                .map(|(i, _)| i)
+                .expect("a node is never empty");
+            let (bounds, child) = &mut entries[best];
+            *bounds = bounds.union(&rect);
+            let sibling = insert(child, rect, value)?;
+            // The split moved entries out of the child, so its bounds may have shrunk.
+            *bounds = child.bounds();
+            entries.push(sibling);
+            if entries.len() <= MAX_ENTRIES {
+                return None;
+            }
+            let (kept, moved) = quadratic_split(std::mem::take(entries));
+            *entries = kept;
+            Some((bounding(&moved), Node::Internal(moved)))
+        }
+    }
+}
+
+/// Splits the entries of an overflowing node into two groups of at least `MIN_ENTRIES` each.
+fn quadratic_split<E>(entries: Entries<E>) -> (Entries<E>, Entries<E>) {
+    let n = entries.len();
+    let rects: Vec<&Rectangle> = entries.iter().map(|(r, _)| r).collect();
+
+    // Start with the pair that would waste the most area in a common group.
+    let mut seeds = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+    for i in 0..n {
+        for j in i + 1..n {
+            let waste = rects[i].union(rects[j]).area() - rects[i].area() - rects[j].area();
+            if waste > worst {
+                worst = waste;
+                seeds = (i, j);
+            }
+        }
+    }
+
+    let mut group = vec![None; n];
+    group[seeds.0] = Some(0);
+    group[seeds.1] = Some(1);
+    let mut bounds = [rects[seeds.0].clone(), rects[seeds.1].clone()];
+    let mut sizes = [1, 1];
+    let mut remaining = n - 2;
+    while remaining > 0 {
+        // If a group needs all the remaining entries to reach the minimum, give them to it.
+        if let Some(needy) = (0..2).find(|&g| sizes[g] + remaining == MIN_ENTRIES) {
+            for slot in group.iter_mut().filter(|slot| slot.is_none()) {
+                *slot = Some(needy);
+            }
+            sizes[needy] += remaining;
+            break;
+        }
+        // Otherwise assign the entry with the strongest preference for one of the groups.
+        let (next, _) = (0..n)
+            .filter(|&i| group[i].is_none())
+            .map(|i| {
+                let d0 = enlargement(&bounds[0], rects[i]);
+                let d1 = enlargement(&bounds[1], rects[i]);
+                (i, (d0 - d1).abs())
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect("entries remain");
+        let d0 = enlargement(&bounds[0], rects[next]);
+        let d1 = enlargement(&bounds[1], rects[next]);
+        let target = match d0.total_cmp(&d1) {
+            std::cmp::Ordering::Less => 0,
+            std::cmp::Ordering::Greater => 1,
+            std::cmp::Ordering::Equal => {
+                usize::from((bounds[1].area(), sizes[1]) < (bounds[0].area(), sizes[0]))
+            }
+        };
+        group[next] = Some(target);
+        bounds[target] = bounds[target].union(rects[next]);
+        sizes[target] += 1;
+        remaining -= 1;
+    }
+
+    let mut kept = Vec::with_capacity(sizes[0]);
+    let mut moved = Vec::with_capacity(sizes[1]);
+    for (entry, g) in entries.into_iter().zip(group) {
+        if g == Some(0) {
+            kept.push(entry);
+        } else {
+            moved.push(entry);
+        }
+    }
+    (kept, moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Rectangle {
+        Rectangle::new(Point::new(x0, y0), Point::new(x1, y1))
+    }
+
+    fn random_rects(rng: &mut StdRng, n: usize) -> Vec<(Rectangle, usize)> {
+        (0..n)
+            .map(|i| {
+                let x = rng.random_range(0.0..1000.0);
+                let y = rng.random_range(0.0..1000.0);
+                let w = rng.random_range(0.0..20.0);
+                let h = rng.random_range(0.0..20.0);
+                (rect(x, y, x + w, y + h), i)
+            })
+            .collect()
+    }
+
+    fn sorted(found: Vec<(&Rectangle, &usize)>) -> Vec<usize> {
+        let mut values: Vec<usize> = found.into_iter().map(|(_, v)| *v).collect();
+        values.sort_unstable();
+        values
+    }
+
+    /// Checks that all leaves are at the same depth and every node bound covers its children.
+    fn check_node<V>(node: &Node<V>, depth: usize, leaf_depth: &mut Option<usize>) {
+        match node {
+            Node::Leaf(entries) => {
+                assert!(entries.len() <= MAX_ENTRIES);
+                assert_eq!(*leaf_depth.get_or_insert(depth), depth);
+            }
+            Node::Internal(entries) => {
+                assert!(entries.len() <= MAX_ENTRIES);
+                for (bounds, child) in entries {
+                    assert_eq!(*bounds, child.bounds());
+                    check_node(child, depth + 1, leaf_depth);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_queries() {
+        let mut tree = RTree::new();
+        assert!(tree.is_empty());
+        tree.insert(rect(0.0, 0.0, 2.0, 2.0), 0);
+        tree.insert(rect(1.0, 1.0, 3.0, 3.0), 1);
+        tree.insert(rect(5.0, 5.0, 6.0, 6.0), 2);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(
+            sorted(tree.containing_point(&Point::new(1.5, 1.5))),
+            vec![0, 1]
+        );
+        assert_eq!(
+            sorted(tree.containing_point(&Point::new(2.5, 2.5))),
+            vec![1]
+        );
+        assert!(tree.containing_point(&Point::new(4.0, 4.0)).is_empty());
+        assert_eq!(
+            sorted(tree.search(&rect(2.0, 2.0, 5.0, 5.0))),
+            vec![0, 1, 2]
+        );
+        assert_eq!(tree.iter().count(), 3);
+    }
+
+    #[test]
+    fn bulk_load_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(40);
+        let entries = random_rects(&mut rng, 2000);
+        let tree = RTree::bulk_load(entries.clone());
+        assert_eq!(tree.len(), 2000);
+        // 2000 rectangles fill 125 leaves, which fit under 8 nodes below the root.
+        assert_eq!(tree.height(), 3);
+        check_node(&tree.root, 0, &mut None);
+
+        for _ in 0..50 {
+            let x = rng.random_range(0.0..1000.0);
+            let y = rng.random_range(0.0..1000.0);
+            let range = rect(x, y, x + 50.0, y + 50.0);
+            let expected: Vec<usize> = entries
+                .iter()
+                .filter(|(r, _)| r.intersects(&range))
+                .map(|(_, i)| *i)
+                .collect();
+            assert_eq!(sorted(tree.search(&range)), expected);
+        }
+        assert!(RTree::<u8>::bulk_load(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn insert_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let entries = random_rects(&mut rng, 1500);
+        let mut tree = RTree::new();
+        for (r, i) in entries.iter().cloned() {
+            tree.insert(r, i);
+        }
+        check_node(&tree.root, 0, &mut None);
+        assert!(tree.height() > 2);
+
+        let mut loaded = RTree::bulk_load(entries[..700].to_vec());
+        for (r, i) in entries[700..].iter().cloned() {
+            loaded.insert(r, i);
+        }
+        check_node(&loaded.root, 0, &mut None);
+
+        for _ in 0..50 {
+            let p = Point::new(rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0));
+            let expected: Vec<usize> = entries
+                .iter()
+                .filter(|(r, _)| r.contains(&p))
+                .map(|(_, i)| *i)
+                .collect();
+            assert_eq!(sorted(tree.containing_point(&p)), expected);
+            assert_eq!(sorted(loaded.containing_point(&p)), expected);
+        }
+    }
+}
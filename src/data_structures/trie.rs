@@ -23,8 +23,10 @@ where
     Key: Default + Eq + Hash,
     Type: Default,
 {
-    /// The root node of the Trie, which does not hold a value itself.
+    /// The root node of the Trie, which holds the value of the empty key.
     root: Node<Key, Type>,
+    /// The number of keys stored in the Trie.
+    len: usize,
 }
 
 impl<Key, Type> Trie<Key, Type>
@@ -39,6 +41,7 @@ where
     pub fn new() -> Self {
         Self {
             root: Node::default(),
+            len: 0,
         }
     }
 
@@ -55,7 +58,9 @@ where
         for c in key {
             node = node.children.entry(c).or_default();
         }
-        node.value = Some(value);
+        if node.value.replace(value).is_none() {
+            self.len += 1;
+        }
     }
 
     /// Retrieves a reference to the value associated with a sequence of keys, if it exists.
@@ -76,6 +81,106 @@ where
         }
         node.value.as_ref()
     }
+
+    /// Removes a sequence of keys from the Trie, pruning the nodes left without any value below them.
+    ///
+    /// # Returns
+    /// The value that was associated with the sequence of keys, or `None` if it was not present.
+    pub fn remove(&mut self, key: impl IntoIterator<Item = Key>) -> Option<Type> {
+        let removed = Self::remove_from(&mut self.root, &mut key.into_iter());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_from(
+        node: &mut Node<Key, Type>,
+        key: &mut impl Iterator<Item = Key>,
+    ) -> Option<Type> {
+        let Some(c) = key.next() else {
+            return node.value.take();
+        };
+        let child = node.children.get_mut(&c)?;
+        let removed = Self::remove_from(child, key);
+        if child.value.is_none() && child.children.is_empty() {
+            node.children.remove(&c);
+        }
+        removed
+    }
+
+    /// Iterates over all the sequences of keys starting with `prefix`, along with their values.
+    ///
+    /// The sequences are yielded in an arbitrary order.
+    pub fn iter_prefix(
+        &self,
+        prefix: impl IntoIterator<Item = Key>,
+    ) -> impl Iterator<Item = (Vec<Key>, &Type)>
+    where
+        Key: Clone,
+    {
+        let mut path = Vec::new();
+        let mut node = Some(&self.root);
+        for c in prefix {
+            node = node.and_then(|node| node.children.get(&c));
+            path.push(c);
+        }
+
+        let mut stack: Vec<(Vec<Key>, &Node<Key, Type>)> =
+            node.map(|node| (path, node)).into_iter().collect();
+        std::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                for (c, child) in &node.children {
+                    let mut child_path = path.clone();
+                    child_path.push(c.clone());
+                    stack.push((child_path, child));
+                }
+                if let Some(value) = &node.value {
+                    return Some((path, value));
+                }
+            }
+            None
+        })
+    }
+
+    /// Finds the longest sequence stored in the Trie which is a prefix of `key`.
+    ///
+    /// # Returns
+    /// The length of that prefix and its value, or `None` if no prefix of `key` is stored.
+    pub fn longest_prefix(&self, key: impl IntoIterator<Item = Key>) -> Option<(usize, &Type)> {
+        let mut node = &self.root;
+        let mut best = node.value.as_ref().map(|value| (0, value));
+        for (depth, c) in key.into_iter().enumerate() {
+            let Some(child) = node.children.get(&c) else {
+                break;
+            };
+            node = child;
+            if let Some(value) = &node.value {
+                best = Some((depth + 1, value));
+            }
+        }
+        best
+    }
+
+    /// Returns the number of sequences of keys stored in the Trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes of the Trie, including the root.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children.values());
+        }
+        count
+    }
 }
 
 #[cfg(test)]
@@ -152,4 +257,66 @@ mod tests {
         assert_eq!(trie.get("apple".chars()), Some(&10));
         assert_eq!(trie.get("applepie".chars()), None);
     }
+
+    #[test]
+    fn test_remove() {
+        let mut trie = Trie::new();
+        trie.insert("car".chars(), 1);
+        trie.insert("cart".chars(), 2);
+        trie.insert("cat".chars(), 3);
+        assert_eq!(trie.len(), 3);
+        assert_eq!(trie.node_count(), 6);
+
+        assert_eq!(trie.remove("ca".chars()), None);
+        assert_eq!(trie.remove("cart".chars()), Some(2));
+        assert_eq!(trie.remove("cart".chars()), None);
+        assert_eq!(trie.get("car".chars()), Some(&1));
+        assert_eq!(trie.node_count(), 5);
+
+        assert_eq!(trie.remove("car".chars()), Some(1));
+        assert_eq!(trie.remove("cat".chars()), Some(3));
+        assert!(trie.is_empty());
+        assert_eq!(trie.node_count(), 1);
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let mut trie = Trie::new();
+        for (i, word) in ["tea", "ten", "to", "inn", "te"].iter().enumerate() {
+            trie.insert(word.chars(), i);
+        }
+        let mut words: Vec<(String, usize)> = trie
+            .iter_prefix("te".chars())
+            .map(|(key, &value)| (key.into_iter().collect(), value))
+            .collect();
+        words.sort();
+        assert_eq!(
+            words,
+            vec![
+                ("te".to_string(), 4),
+                ("tea".to_string(), 0),
+                ("ten".to_string(), 1)
+            ]
+        );
+        assert_eq!(trie.iter_prefix("x".chars()).count(), 0);
+        assert_eq!(trie.iter_prefix("".chars()).count(), 5);
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut trie = Trie::new();
+        trie.insert(vec![10], "10/8");
+        trie.insert(vec![10, 1, 2], "10.1.2/24");
+        assert_eq!(
+            trie.longest_prefix(vec![10, 1, 2, 3]),
+            Some((3, &"10.1.2/24"))
+        );
+        assert_eq!(trie.longest_prefix(vec![10, 1, 3, 4]), Some((1, &"10/8")));
+        assert_eq!(trie.longest_prefix(vec![11, 0, 0, 1]), None);
+        trie.insert(vec![], "default");
+        assert_eq!(
+            trie.longest_prefix(vec![11, 0, 0, 1]),
+            Some((0, &"default"))
+        );
+    }
 }
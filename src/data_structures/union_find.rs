@@ -5,45 +5,169 @@
 //! It provides near-constant-time operations to add new sets, to find the
 //! representative of a set, and to merge sets.
 
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash, RandomState};
+
+/// Index-keyed disjoint sets over the elements `0..len`, using union by size and path compression.
+///
+/// Besides the parent links, every element points to the next element of its set, forming one
+/// circular list per set. Merging two sets just swaps the successors of their roots, which splices
+/// the two lists together in `O(1)`, and allows enumerating the members of a set in `O(size)`.
+#[derive(Debug, Default, Clone)]
+pub struct DisjointSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    next: Vec<usize>,
+    count: usize,
+}
+
+impl DisjointSets {
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            next: (0..len).collect(),
+            count: len,
+        }
+    }
+
+    /// Adds a new singleton set and returns its element.
+    pub fn push(&mut self) -> usize {
+        let element = self.parent.len();
+        self.parent.push(element);
+        self.size.push(1);
+        self.next.push(element);
+        self.count += 1;
+        element
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the root of the set containing `element`, compressing the path to it.
+    pub fn find(&mut self, element: usize) -> usize {
+        let root = self.root(element);
+        let mut current = element;
+        while current != root {
+            current = std::mem::replace(&mut self.parent[current], root);
+        }
+        root
+    }
+
+    /// Returns the root of the set containing `element`, without modifying the structure.
+    pub fn root(&self, mut element: usize) -> usize {
+        while self.parent[element] != element {
+            element = self.parent[element];
+        }
+        element
+    }
+
+    /// Merges the sets containing `first` and `second`, attaching the smaller set to the larger one.
+    ///
+    /// Returns `false` if they were already in the same set.
+    pub fn union(&mut self, first: usize, second: usize) -> bool {
+        let (mut first_root, mut second_root) = (self.find(first), self.find(second));
+        if first_root == second_root {
+            return false;
+        }
+        if self.size[first_root] < self.size[second_root] {
+            std::mem::swap(&mut first_root, &mut second_root);
+        }
+        self.parent[second_root] = first_root;
+        self.size[first_root] += self.size[second_root];
+        self.next.swap(first_root, second_root);
+        self.count -= 1;
+        true
+    }
+
+    pub fn is_same_set(&mut self, first: usize, second: usize) -> bool {
+        self.find(first) == self.find(second)
+    }
 
+    /// Returns the number of elements in the set containing `element`.
+    pub fn size_of(&self, element: usize) -> usize {
+        self.size[self.root(element)]
+    }
+
+    /// Iterates over the elements of the set containing `element`, starting with `element` itself.
+    pub fn members(&self, element: usize) -> impl Iterator<Item = usize> + '_ {
+        members(&self.next, element)
+    }
+
+    /// Iterates over the sets, each given as the list of its elements.
+    pub fn sets(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        (0..self.len())
+            .filter(|&element| self.parent[element] == element)
+            .map(|root| self.members(root).collect())
+    }
+}
+
+/// Walks the circular list of the set containing `element`.
+pub(super) fn members(next: &[usize], element: usize) -> impl Iterator<Item = usize> + '_ {
+    let mut current = Some(element);
+    std::iter::from_fn(move || {
+        let member = current?;
+        current = Some(next[member]).filter(|&following| following != element);
+        Some(member)
+    })
+}
+
+/// A Union-Find over arbitrary hashable items, mapping them to the elements of [`DisjointSets`].
+///
+/// Every item is stored once, at its index in the disjoint sets, and looked up through its hash.
 #[derive(Debug)]
 pub struct UnionFind<T: Debug + Eq + Hash> {
-    payloads: HashMap<T, usize>, // Maps values to their indices in the parent_links array.
-    parent_links: Vec<usize>,    // Holds the parent pointers; root elements are their own parents.
-    sizes: Vec<usize>,           // Holds the sizes of the sets.
-    count: usize,                // Number of disjoint sets.
+    items: Vec<T>,                     // Maps indices to their values.
+    indices: HashMap<u64, Vec<usize>>, // Maps hashes to the indices of the values having them.
+    hasher: RandomState,
+    sets: DisjointSets,
 }
 
 impl<T: Debug + Eq + Hash> UnionFind<T> {
     /// Creates an empty Union-Find structure with a specified capacity.
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            parent_links: Vec::with_capacity(capacity),
-            sizes: Vec::with_capacity(capacity),
-            payloads: HashMap::with_capacity(capacity),
-            count: 0,
+            items: Vec::with_capacity(capacity),
+            indices: HashMap::with_capacity(capacity),
+            hasher: RandomState::new(),
+            sets: DisjointSets::default(),
         }
     }
 
-    /// Inserts a new item (disjoint set) into the data structure.
+    /// Returns the index of the given value in the disjoint sets.
+    fn index_of(&self, value: &T) -> Option<usize> {
+        self.indices
+            .get(&self.hasher.hash_one(value))?
+            .iter()
+            .copied()
+            .find(|&index| self.items[index] == *value)
+    }
+
+    /// Inserts a new item (disjoint set) into the data structure. Inserting an item twice has no effect.
     pub fn insert(&mut self, item: T) {
-        let key = self.payloads.len();
-        self.parent_links.push(key);
-        self.sizes.push(1);
-        self.payloads.insert(item, key);
-        self.count += 1;
+        if self.index_of(&item).is_none() {
+            let hash = self.hasher.hash_one(&item);
+            self.indices.entry(hash).or_default().push(self.sets.push());
+            self.items.push(item);
+        }
     }
 
     /// Returns the root index of the set containing the given value, or `None` if it doesn't exist.
     pub fn find(&mut self, value: &T) -> Option<usize> {
-        self.payloads
-            .get(value)
-            .copied()
-            .map(|key| self.find_by_key(key))
+        self.index_of(value).map(|key| self.sets.find(key))
     }
 
     /// Unites the sets containing the two given values. Returns:
@@ -51,42 +175,9 @@ impl<T: Debug + Eq + Hash> UnionFind<T> {
     /// - `Some(true)` if two disjoint sets have been merged,
     /// - `Some(false)` if both elements were already in the same set.
     pub fn union(&mut self, first_item: &T, sec_item: &T) -> Option<bool> {
-        let (first_root, sec_root) = (self.find(first_item), self.find(sec_item));
-        match (first_root, sec_root) {
-            (Some(first_root), Some(sec_root)) => Some(self.union_by_key(first_root, sec_root)),
-            _ => None,
-        }
-    }
-
-    /// Finds the root of the set containing the element with the given index.
-    fn find_by_key(&mut self, key: usize) -> usize {
-        if self.parent_links[key] != key {
-            self.parent_links[key] = self.find_by_key(self.parent_links[key]);
-        }
-        self.parent_links[key]
-    }
-
-    /// Unites the sets containing the two elements identified by their indices.
-    fn union_by_key(&mut self, first_key: usize, sec_key: usize) -> bool {
-        let (first_root, sec_root) = (self.find_by_key(first_key), self.find_by_key(sec_key));
-
-        if first_root == sec_root {
-            return false;
-        }
-
-        match self.sizes[first_root].cmp(&self.sizes[sec_root]) {
-            Ordering::Less => {
-                self.parent_links[first_root] = sec_root;
-                self.sizes[sec_root] += self.sizes[first_root];
-            }
-            _ => {
-                self.parent_links[sec_root] = first_root;
-                self.sizes[first_root] += self.sizes[sec_root];
-            }
-        }
-
-        self.count -= 1;
-        true
+        let first_key = self.index_of(first_item)?;
+        let sec_key = self.index_of(sec_item)?;
+        Some(self.sets.union(first_key, sec_key))
     }
 
     /// Checks if two items belong to the same set.
@@ -96,18 +187,31 @@ impl<T: Debug + Eq + Hash> UnionFind<T> {
 
     /// Returns the number of disjoint sets.
     pub fn count(&self) -> usize {
-        self.count
+        self.sets.count()
+    }
+
+    /// Returns the size of the set containing the given value, or `None` if it doesn't exist.
+    pub fn size_of(&self, value: &T) -> Option<usize> {
+        self.index_of(value).map(|key| self.sets.size_of(key))
+    }
+
+    /// Returns the values in the same set as the given value (itself included), or `None` if it doesn't exist.
+    pub fn members(&self, value: &T) -> Option<Vec<&T>> {
+        let key = self.index_of(value)?;
+        Some(self.sets.members(key).map(|key| &self.items[key]).collect())
+    }
+
+    /// Iterates over the sets, each given as the list of its values.
+    pub fn sets(&self) -> impl Iterator<Item = Vec<&T>> + '_ {
+        self.sets
+            .sets()
+            .map(|set| set.into_iter().map(|key| &self.items[key]).collect())
     }
 }
 
 impl<T: Debug + Eq + Hash> Default for UnionFind<T> {
     fn default() -> Self {
-        Self {
-            parent_links: Vec::default(),
-            sizes: Vec::default(),
-            payloads: HashMap::default(),
-            count: 0,
-        }
+        Self::with_capacity(0)
     }
 }
 
@@ -225,4 +329,65 @@ mod tests {
 
         assert_eq!(None, uf.union(&0, &10));
     }
+
+    #[test]
+    fn test_sizes_members_and_sets() {
+        let mut uf = UnionFind::from_iter(["a", "b", "c", "d", "e"]);
+        uf.insert("a");
+        assert_eq!(uf.count(), 5);
+        uf.union(&"a", &"b");
+        uf.union(&"c", &"d");
+        uf.union(&"d", &"a");
+        assert_eq!(uf.size_of(&"b"), Some(4));
+        assert_eq!(uf.size_of(&"e"), Some(1));
+        assert_eq!(uf.size_of(&"z"), None);
+
+        let mut members = uf.members(&"c").unwrap();
+        members.sort();
+        assert_eq!(members, vec![&"a", &"b", &"c", &"d"]);
+        assert_eq!(uf.members(&"e"), Some(vec![&"e"]));
+
+        let mut sets: Vec<Vec<&&str>> = uf
+            .sets()
+            .map(|mut set| {
+                set.sort();
+                set
+            })
+            .collect();
+        sets.sort();
+        assert_eq!(sets, vec![vec![&"a", &"b", &"c", &"d"], vec![&"e"]]);
+    }
+
+    #[test]
+    fn test_items_without_clone() {
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Id(u32);
+
+        let mut uf: UnionFind<Id> = (0..4).map(Id).collect();
+        uf.insert(Id(2));
+        assert_eq!(uf.count(), 4);
+        assert_eq!(uf.union(&Id(0), &Id(3)), Some(true));
+        assert!(uf.is_same_set(&Id(3), &Id(0)));
+        assert_eq!(uf.members(&Id(0)).map(|members| members.len()), Some(2));
+        assert_eq!(uf.find(&Id(4)), None);
+    }
+
+    #[test]
+    fn test_disjoint_sets() {
+        let mut sets = DisjointSets::new(8);
+        for (a, b) in [(0, 1), (2, 3), (1, 3), (5, 6)] {
+            assert!(sets.union(a, b));
+        }
+        assert!(!sets.union(0, 2));
+        assert_eq!(sets.count(), 4);
+        assert_eq!(sets.size_of(3), 4);
+        assert!(sets.is_same_set(0, 3));
+        let mut members: Vec<usize> = sets.members(2).collect();
+        members.sort_unstable();
+        assert_eq!(members, vec![0, 1, 2, 3]);
+        assert_eq!(sets.members(7).collect::<Vec<_>>(), vec![7]);
+        assert_eq!(sets.push(), 8);
+        assert_eq!(sets.sets().map(|set| set.len()).sum::<usize>(), 9);
+        assert_eq!(sets.sets().count(), 5);
+    }
 }
@@ -1,6 +1,7 @@
 // This struct implements Van Emde Boas tree (VEB tree). It stores integers in range [0, U), where
-// O is any integer that is a power of 2. It supports operations such as insert, search,
-// predecessor, and successor in O(log(log(U))) time. The structure takes O(U) space.
+// O is any integer that is a power of 2. It supports operations such as insert, remove, search,
+// predecessor, and successor in O(log(log(U))) time. The structure takes O(U) space; see
+// `SparseVebTree` for 64-bit universes.
 pub struct VebTree {
     size: u32,
     child_size: u32, // Set to square root of size. Cache here to avoid recomputation.
@@ -85,7 +86,7 @@ impl VebTree {
         self.max = value;
     }
 
-    // Inserts value into the tree.
+    // Inserts value into the tree. Inserting a value already in the tree does nothing.
     pub fn insert(&mut self, mut value: u32) {
         assert!(value < self.size);
 
@@ -94,6 +95,12 @@ impl VebTree {
             return;
         }
 
+        if value == self.min || value == self.max {
+            // The min and max are only stored once. Any other value already in the tree is the
+            // min or max of some nested cluster, where the recursion stops.
+            return;
+        }
+
         if value < self.min {
             // If the new value is less than the current tree's min, set the min to the new value
             // and insert the old min.
@@ -123,6 +130,59 @@ impl VebTree {
         }
     }
 
+    // Removes value from the tree. Returns false if it was not in the tree.
+    pub fn remove(&mut self, value: u32) -> bool {
+        if value >= self.size || !self.search(value) {
+            return false;
+        }
+        self.remove_present(value);
+        true
+    }
+
+    fn remove_present(&mut self, mut value: u32) {
+        if self.min == self.max {
+            // Removing the only value empties the tree.
+            self.min = u32::MAX;
+            self.max = u32::MIN;
+            return;
+        }
+
+        if self.size == 2 {
+            // Base case. The tree holds both 0 and 1, the other one remains.
+            self.min = 1 - value;
+            self.max = self.min;
+            return;
+        }
+
+        let mut summary = self.summary.take().unwrap();
+        if value == self.min {
+            // The min is not stored in any cluster. Replace it with the smallest value of the
+            // clusters, which we then remove from its cluster instead.
+            let first = summary.min;
+            value = self.index(first, self.cluster[first as usize].min);
+            self.min = value;
+        }
+
+        let high = self.high(value);
+        let low = self.low(value);
+        self.cluster[high as usize].remove_present(low);
+        if self.cluster[high as usize].empty() {
+            // The cluster has no values left, so it no longer appears in the summary.
+            summary.remove_present(high);
+            if value == self.max {
+                self.max = if summary.empty() {
+                    self.min
+                } else {
+                    let last = summary.max;
+                    self.index(last, self.cluster[last as usize].max)
+                };
+            }
+        } else if value == self.max {
+            self.max = self.index(high, self.cluster[high as usize].max);
+        }
+        self.summary = Some(summary);
+    }
+
     // Returns the next greatest value(successor) in the tree after pred. Returns
     // `None` if there is no successor.
     pub fn succ(&self, pred: u32) -> Option<u32> {
@@ -264,6 +324,45 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_remove() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for size in [2, 16, 20, 256] {
+            let mut tree = VebTree::new(size);
+            let mut present = vec![false; size as usize];
+            for _ in 0..500 {
+                let value = rng.random_range(0..size);
+                if rng.random_range(0..3) == 0 {
+                    tree.insert(value);
+                    present[value as usize] = true;
+                } else {
+                    assert_eq!(tree.remove(value), present[value as usize]);
+                    present[value as usize] = false;
+                }
+                let expected: Vec<u32> = (0..size).filter(|&x| present[x as usize]).collect();
+                assert_eq!(tree.iter().collect::<Vec<_>>(), expected);
+                for pair in expected.windows(2) {
+                    assert_eq!(tree.pred(pair[1]), Some(pair[0]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_after_repeat_insert() {
+        let mut tree = VebTree::new(16);
+        tree.insert(3);
+        tree.insert(10);
+        tree.insert(10);
+        tree.insert(3);
+        assert!(tree.remove(3));
+        assert!(tree.remove(10));
+        assert!(tree.empty());
+        assert!(!tree.search(3) && !tree.search(10));
+        assert!(!tree.remove(10));
+        assert!(!tree.remove(100));
+    }
+
     #[test]
     fn test_empty() {
         test_veb_tree(16, Vec::new(), (0..16).collect());
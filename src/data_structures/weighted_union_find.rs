@@ -0,0 +1,161 @@
+//! A Union-Find tracking relative offsets ("potentials") between the elements of each set.
+//!
+//! Every element `x` has an unknown weight `w(x)`, and the structure records facts of the form
+//! `w(second) - w(first) = diff`. Each element stores the difference between its weight and the
+//! weight of its parent, so the difference between any two elements of a set is obtained by
+//! summing along their paths to the root. This answers queries like "how much heavier is `b` than
+//! `a`?" and detects contradicting facts, in near-constant time.
+
+use std::ops::{Add, Sub};
+
+/// Errors that can occur when recording a relation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum WeightedUnionFindError {
+    /// The relation contradicts the ones already recorded.
+    Contradiction,
+}
+
+/// Index-keyed disjoint sets with weights relative to the root of each set.
+#[derive(Debug, Clone)]
+pub struct WeightedUnionFind<W> {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    /// `w(x) - w(parent[x])`.
+    potential: Vec<W>,
+    count: usize,
+}
+
+impl<W> WeightedUnionFind<W>
+where
+    W: Copy + Default + PartialEq + Add<Output = W> + Sub<Output = W>,
+{
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            potential: vec![W::default(); len],
+            count: len,
+        }
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the root of the set containing `element` and `w(element) - w(root)`,
+    /// compressing the path to the root.
+    pub fn find(&mut self, element: usize) -> (usize, W) {
+        let mut path = Vec::new();
+        let mut root = element;
+        while self.parent[root] != root {
+            path.push(root);
+            root = self.parent[root];
+        }
+        // Going down from the root, the parent of each node already points to the root
+        for &node in path.iter().rev() {
+            let parent = self.parent[node];
+            if parent != root {
+                self.potential[node] = self.potential[node] + self.potential[parent];
+                self.parent[node] = root;
+            }
+        }
+        (root, self.potential[element])
+    }
+
+    /// Records that `w(second) - w(first) = diff`, merging their sets if needed.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if two sets have been merged, `Ok(false)` if the relation was already implied,
+    /// or `Err(WeightedUnionFindError::Contradiction)` if it contradicts the recorded ones.
+    pub fn union(
+        &mut self,
+        first: usize,
+        second: usize,
+        diff: W,
+    ) -> Result<bool, WeightedUnionFindError> {
+        let (first_root, first_potential) = self.find(first);
+        let (second_root, second_potential) = self.find(second);
+        if first_root == second_root {
+            return if second_potential - first_potential == diff {
+                Ok(false)
+            } else {
+                Err(WeightedUnionFindError::Contradiction)
+            };
+        }
+
+        // w(second_root) - w(first_root)
+        let roots_diff = diff + first_potential - second_potential;
+        if self.size[first_root] < self.size[second_root] {
+            self.parent[first_root] = second_root;
+            self.potential[first_root] = W::default() - roots_diff;
+            self.size[second_root] += self.size[first_root];
+        } else {
+            self.parent[second_root] = first_root;
+            self.potential[second_root] = roots_diff;
+            self.size[first_root] += self.size[second_root];
+        }
+        self.count -= 1;
+        Ok(true)
+    }
+
+    /// Returns `w(second) - w(first)`, or `None` if they are not in the same set.
+    pub fn diff(&mut self, first: usize, second: usize) -> Option<W> {
+        let (first_root, first_potential) = self.find(first);
+        let (second_root, second_potential) = self.find(second);
+        (first_root == second_root).then(|| second_potential - first_potential)
+    }
+
+    pub fn is_same_set(&mut self, first: usize, second: usize) -> bool {
+        self.find(first).0 == self.find(second).0
+    }
+
+    /// Returns the number of elements in the set containing `element`.
+    pub fn size_of(&mut self, element: usize) -> usize {
+        let root = self.find(element).0;
+        self.size[root]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_weights() {
+        let mut uf = WeightedUnionFind::new(6);
+        assert_eq!(uf.union(0, 1, 3), Ok(true)); // w1 = w0 + 3
+        assert_eq!(uf.union(2, 1, -2), Ok(true)); // w1 = w2 - 2
+        assert_eq!(uf.union(3, 4, 10), Ok(true));
+        assert_eq!(uf.union(2, 4, 1), Ok(true)); // w4 = w2 + 1
+        assert_eq!(uf.diff(0, 2), Some(5));
+        assert_eq!(uf.diff(3, 0), Some(4));
+        assert_eq!(uf.diff(0, 5), None);
+        assert_eq!(uf.union(0, 4, 6), Ok(false));
+        assert_eq!(
+            uf.union(0, 4, 7),
+            Err(WeightedUnionFindError::Contradiction)
+        );
+        assert_eq!(uf.count(), 2);
+        assert_eq!(uf.size_of(3), 5);
+        assert!(uf.is_same_set(1, 3));
+    }
+
+    #[test]
+    fn test_matches_absolute_weights() {
+        let n = 200;
+        let weights: Vec<i64> = (0..n).map(|i| (i * i * 31 + 7) % 1000 - 500).collect();
+        let mut uf = WeightedUnionFind::new(n as usize);
+        for i in 0..n as usize {
+            let j = (i * 37 + 11) % n as usize;
+            let diff = weights[j] - weights[i];
+            assert!(uf.union(i, j, diff).is_ok());
+            let k = (i * 13) % n as usize;
+            if let Some(diff) = uf.diff(k, i) {
+                assert_eq!(diff, weights[i] - weights[k]);
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! An x-fast trie over `u64` values.
+//!
+//! The trie is the binary trie of the bits of the stored values, with one hash map per depth
+//! holding the prefixes present at that depth. Since the prefixes of a value present in the trie
+//! form a chain, the longest one is found by binary search over the depths, with `O(log w)`
+//! hash lookups for `w = 64`. Each prefix node records the smallest and largest value below it,
+//! and the values form a sorted doubly linked list, so the predecessor and successor follow in
+//! `O(1)` from the deepest node. Updates touch every depth and take `O(w)`, and the trie takes
+//! `O(n w)` space; see [`YFastTrie`](super::YFastTrie) for `O(n)` space.
+
+use std::collections::HashMap;
+
+const BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, Copy)]
+struct Links {
+    prev: Option<u64>,
+    next: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct XFastTrie {
+    /// `levels[depth]` maps the prefixes of length `depth` to the smallest and largest values
+    /// below them, for `depth < 64`.
+    levels: Vec<HashMap<u64, (u64, u64)>>,
+    /// The values themselves, which are the prefixes of length 64, linked in sorted order.
+    leaves: HashMap<u64, Links>,
+}
+
+impl Default for XFastTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XFastTrie {
+    pub fn new() -> Self {
+        Self {
+            levels: vec![HashMap::new(); BITS],
+            leaves: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.leaves.contains_key(&value)
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.levels.first()?.get(&0).map(|&(min, _)| min)
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.levels.first()?.get(&0).map(|&(_, max)| max)
+    }
+
+    /// Returns the largest value smaller than `value`.
+    pub fn pred(&self, value: u64) -> Option<u64> {
+        if let Some(links) = self.leaves.get(&value) {
+            return links.prev;
+        }
+        let (depth, (min, max)) = self.deepest_node(value)?;
+        if next_bit(value, depth) == 1 {
+            // Only the 0 branch exists, so the whole subtree is below `value`
+            Some(max)
+        } else {
+            self.leaves[&min].prev
+        }
+    }
+
+    /// Returns the smallest value greater than `value`.
+    pub fn succ(&self, value: u64) -> Option<u64> {
+        if let Some(links) = self.leaves.get(&value) {
+            return links.next;
+        }
+        let (depth, (min, max)) = self.deepest_node(value)?;
+        if next_bit(value, depth) == 0 {
+            // Only the 1 branch exists, so the whole subtree is above `value`
+            Some(min)
+        } else {
+            self.leaves[&max].next
+        }
+    }
+
+    /// Inserts `value`, returning `false` if it was already in the trie.
+    pub fn insert(&mut self, value: u64) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+        let links = Links {
+            prev: self.pred(value),
+            next: self.succ(value),
+        };
+        if let Some(prev) = links.prev {
+            self.leaf_mut(prev).next = Some(value);
+        }
+        if let Some(next) = links.next {
+            self.leaf_mut(next).prev = Some(value);
+        }
+        self.leaves.insert(value, links);
+        for (depth, level) in self.levels.iter_mut().enumerate() {
+            let range = level.entry(prefix(value, depth)).or_insert((value, value));
+            range.0 = range.0.min(value);
+            range.1 = range.1.max(value);
+        }
+        true
+    }
+
+    /// Removes `value`, returning `false` if it was not in the trie.
+    pub fn remove(&mut self, value: u64) -> bool {
+        let Some(links) = self.leaves.remove(&value) else {
+            return false;
+        };
+        if let Some(prev) = links.prev {
+            self.leaf_mut(prev).next = links.next;
+        }
+        if let Some(next) = links.next {
+            self.leaf_mut(next).prev = links.prev;
+        }
+        // Rebuild the ranges bottom-up from the children of each prefix
+        for depth in (0..BITS).rev() {
+            let node = prefix(value, depth);
+            let children = [node << 1, (node << 1) | 1].map(|child| self.node(depth + 1, child));
+            let range = match children {
+                [Some((min, max)), None]
+                | [None, Some((min, max))]
+                | [Some((min, _)), Some((_, max))] => (min, max),
+                [None, None] => {
+                    self.levels[depth].remove(&node);
+                    continue;
+                }
+            };
+            self.levels[depth].insert(node, range);
+        }
+        true
+    }
+
+    /// Iterates over the values in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        std::iter::successors(self.min(), |value| self.leaves[value].next)
+    }
+
+    /// Returns the depth of the longest prefix of `value` in the trie, with its range, as long
+    /// as `value` itself is not in the trie.
+    fn deepest_node(&self, value: u64) -> Option<(usize, (u64, u64))> {
+        let mut range = *self.levels.first()?.get(&0)?;
+        // The prefix of length `low` is present, the one of length `high` is not
+        let (mut low, mut high) = (0, BITS);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            match self.levels[mid].get(&prefix(value, mid)) {
+                Some(&found) => {
+                    low = mid;
+                    range = found;
+                }
+                None => high = mid,
+            }
+        }
+        Some((low, range))
+    }
+
+    fn node(&self, depth: usize, node: u64) -> Option<(u64, u64)> {
+        if depth == BITS {
+            self.leaves.contains_key(&node).then_some((node, node))
+        } else {
+            self.levels[depth].get(&node).copied()
+        }
+    }
+
+    fn leaf_mut(&mut self, value: u64) -> &mut Links {
+        self.leaves
+            .get_mut(&value)
+            .expect("linked values are leaves")
+    }
+}
+
+/// Returns the first `depth` bits of `value`.
+const fn prefix(value: u64, depth: usize) -> u64 {
+    if depth == 0 {
+        0
+    } else {
+        value >> (BITS - depth)
+    }
+}
+
+/// Returns the bit of `value` following its prefix of length `depth`.
+const fn next_bit(value: u64, depth: usize) -> u64 {
+    (value >> (BITS - 1 - depth)) & 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn predecessor_queries() {
+        let mut trie = XFastTrie::new();
+        assert_eq!((trie.min(), trie.pred(5), trie.succ(5)), (None, None, None));
+        for value in [1_u64 << 63, 7, 1 << 20, u64::MAX] {
+            assert!(trie.insert(value));
+        }
+        assert!(!trie.insert(7));
+        assert_eq!(trie.len(), 4);
+        assert_eq!((trie.min(), trie.max()), (Some(7), Some(u64::MAX)));
+        assert_eq!(trie.pred(1 << 40), Some(1 << 20));
+        assert_eq!(trie.succ(1 << 40), Some(1 << 63));
+        assert_eq!(trie.succ(1 << 63), Some(u64::MAX));
+        assert_eq!(trie.pred(7), None);
+        assert_eq!(trie.succ(0), Some(7));
+        assert!(trie.remove(1 << 20));
+        assert!(!trie.remove(1 << 20));
+        assert_eq!(trie.pred(1 << 40), Some(7));
+        assert_eq!(trie.iter().collect::<Vec<_>>(), vec![7, 1 << 63, u64::MAX]);
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = StdRng::seed_from_u64(64);
+        let mut trie = XFastTrie::new();
+        let mut expected = BTreeSet::new();
+        for _ in 0..2_000 {
+            // Cluster the values a bit so that prefixes are shared
+            let value = rng.random_range(0..64_u64) << rng.random_range(0..58);
+            if rng.random_range(0..3) == 0 {
+                assert_eq!(trie.remove(value), expected.remove(&value));
+            } else {
+                assert_eq!(trie.insert(value), expected.insert(value));
+            }
+            let probe = rng.random::<u64>() >> rng.random_range(0..64);
+            assert_eq!(
+                trie.succ(probe),
+                expected.range(probe + 1..).next().copied()
+            );
+            assert_eq!(
+                trie.pred(probe),
+                expected.range(..probe).next_back().copied()
+            );
+        }
+        assert!(trie.iter().eq(expected.iter().copied()));
+        assert_eq!(trie.max(), expected.last().copied());
+    }
+}
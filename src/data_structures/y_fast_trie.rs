@@ -0,0 +1,258 @@
+//! A y-fast trie over `u64` values.
+//!
+//! The values are split into buckets of about `w = 64` consecutive values, each kept in a
+//! balanced search tree, and only one representative per bucket goes into an [`XFastTrie`].
+//! A bucket with representative `r` holds the values from `r` up to the next representative.
+//! Queries find the bucket with one predecessor query on the x-fast trie, in `O(log w)`, then
+//! search the bucket, in `O(log w)` as well. Buckets are split when they grow past `2w` values
+//! and merged with a neighbour below `w / 2`, so the x-fast trie, which takes `O(w)` space and
+//! update time per value, only holds `O(n / w)` representatives: the whole structure takes `O(n)`
+//! space and updates take `O(log w)` amortized time.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::x_fast_trie::XFastTrie;
+
+const BUCKET: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, Default)]
+pub struct YFastTrie {
+    representatives: XFastTrie,
+    buckets: HashMap<u64, BTreeSet<u64>>,
+    len: usize,
+}
+
+impl YFastTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.bucket_of(value)
+            .is_some_and(|representative| self.buckets[&representative].contains(&value))
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        let first = self.representatives.min()?;
+        self.buckets[&first].first().copied()
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        let last = self.representatives.max()?;
+        self.buckets[&last].last().copied()
+    }
+
+    /// Returns the largest value smaller than `value`.
+    pub fn pred(&self, value: u64) -> Option<u64> {
+        let representative = self.bucket_of(value)?;
+        if let Some(&pred) = self.buckets[&representative].range(..value).next_back() {
+            return Some(pred);
+        }
+        let previous = self.representatives.pred(representative)?;
+        self.buckets[&previous].last().copied()
+    }
+
+    /// Returns the smallest value greater than `value`.
+    pub fn succ(&self, value: u64) -> Option<u64> {
+        let next = match self.bucket_of(value) {
+            Some(representative) => {
+                let bucket = &self.buckets[&representative];
+                if let Some(&succ) = bucket.range(value.checked_add(1)?..).next() {
+                    return Some(succ);
+                }
+                self.representatives.succ(representative)?
+            }
+            None => self.representatives.min()?,
+        };
+        self.buckets[&next].first().copied()
+    }
+
+    /// Inserts `value`, returning `false` if it was already in the trie.
+    pub fn insert(&mut self, value: u64) -> bool {
+        let representative = match self.bucket_of(value) {
+            Some(representative) => representative,
+            None => {
+                // The value is below every bucket: it becomes the representative of the first
+                // bucket, or of a new one
+                if let Some(first) = self.representatives.min() {
+                    let bucket = self
+                        .buckets
+                        .remove(&first)
+                        .expect("representatives have buckets");
+                    self.representatives.remove(first);
+                    self.buckets.insert(value, bucket);
+                }
+                self.representatives.insert(value);
+                value
+            }
+        };
+        let bucket = self.buckets.entry(representative).or_default();
+        if !bucket.insert(value) {
+            return false;
+        }
+        self.len += 1;
+        if bucket.len() > 2 * BUCKET {
+            let middle = *bucket.iter().nth(BUCKET).expect("bucket is large enough");
+            let upper = bucket.split_off(&middle);
+            self.representatives.insert(middle);
+            self.buckets.insert(middle, upper);
+        }
+        true
+    }
+
+    /// Removes `value`, returning `false` if it was not in the trie.
+    pub fn remove(&mut self, value: u64) -> bool {
+        let Some(representative) = self.bucket_of(value) else {
+            return false;
+        };
+        let bucket = self
+            .buckets
+            .get_mut(&representative)
+            .expect("representatives have buckets");
+        if !bucket.remove(&value) {
+            return false;
+        }
+        self.len -= 1;
+        if bucket.len() < BUCKET / 2 {
+            self.rebalance(representative);
+        }
+        true
+    }
+
+    /// Iterates over the values in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.representatives
+            .iter()
+            .flat_map(|representative| self.buckets[&representative].iter().copied())
+    }
+
+    /// Returns the representative of the bucket `value` belongs in, if any bucket starts at or
+    /// below it.
+    fn bucket_of(&self, value: u64) -> Option<u64> {
+        if self.buckets.contains_key(&value) {
+            return Some(value);
+        }
+        self.representatives.pred(value)
+    }
+
+    /// Merges the small bucket of `representative` with a neighbour, splitting the result again
+    /// if it is too large, and drops empty buckets.
+    fn rebalance(&mut self, representative: u64) {
+        let (lower, upper) = match self.representatives.succ(representative) {
+            Some(next) => (representative, next),
+            None => match self.representatives.pred(representative) {
+                Some(previous) => (previous, representative),
+                None => {
+                    // The only bucket may shrink freely, and goes away with its last value
+                    if self.buckets[&representative].is_empty() {
+                        self.buckets.remove(&representative);
+                        self.representatives.remove(representative);
+                    }
+                    return;
+                }
+            },
+        };
+        let mut merged = self
+            .buckets
+            .remove(&upper)
+            .expect("representatives have buckets");
+        self.representatives.remove(upper);
+        let bucket = self
+            .buckets
+            .get_mut(&lower)
+            .expect("representatives have buckets");
+        bucket.append(&mut merged);
+        if bucket.len() > 2 * BUCKET {
+            let middle = *bucket
+                .iter()
+                .nth(bucket.len() / 2)
+                .expect("bucket is large enough");
+            let upper = bucket.split_off(&middle);
+            self.representatives.insert(middle);
+            self.buckets.insert(middle, upper);
+        } else if bucket.is_empty() {
+            self.buckets.remove(&lower);
+            self.representatives.remove(lower);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    #[test]
+    fn sparse_ids() {
+        let mut trie = YFastTrie::new();
+        let ids: Vec<u64> = (0..1_000_u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        for &id in &ids {
+            assert!(trie.insert(id));
+        }
+        assert!(!trie.insert(ids[10]));
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        assert_eq!(trie.len(), 1_000);
+        assert!(trie.iter().eq(sorted.iter().copied()));
+        assert_eq!(
+            (trie.min(), trie.max()),
+            (sorted.first().copied(), sorted.last().copied())
+        );
+        for pair in sorted.windows(2) {
+            assert_eq!(trie.succ(pair[0]), Some(pair[1]));
+            assert_eq!(trie.pred(pair[1]), Some(pair[0]));
+            if pair[1] - 1 > pair[0] {
+                assert_eq!(trie.pred(pair[1] - 1), Some(pair[0]));
+            }
+        }
+        // Buckets keep the number of representatives well below the number of values
+        assert!(trie.representatives.len() <= 1_000 / (BUCKET / 2));
+        for &id in &ids {
+            assert!(trie.remove(id));
+        }
+        assert!(trie.is_empty());
+        assert!(trie.buckets.is_empty() && trie.representatives.is_empty());
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = StdRng::seed_from_u64(99);
+        let mut trie = YFastTrie::new();
+        let mut expected = BTreeSet::new();
+        for round in 0..20_000 {
+            let value = rng.random_range(0..5_000_u64) * 1_000_003;
+            // Insert more than remove at first, then drain
+            if rng.random_range(0..10) < if round < 12_000 { 3 } else { 8 } {
+                assert_eq!(trie.remove(value), expected.remove(&value));
+            } else {
+                assert_eq!(trie.insert(value), expected.insert(value));
+            }
+            assert_eq!(trie.len(), expected.len());
+            let probe = rng.random_range(0..5_000_000_000_u64);
+            assert_eq!(trie.contains(probe), expected.contains(&probe));
+            assert_eq!(
+                trie.succ(probe),
+                expected.range(probe + 1..).next().copied()
+            );
+            assert_eq!(
+                trie.pred(probe),
+                expected.range(..probe).next_back().copied()
+            );
+        }
+        assert!(trie.iter().eq(expected.iter().copied()));
+        assert!(trie
+            .buckets
+            .values()
+            .all(|bucket| bucket.len() <= 2 * BUCKET));
+    }
+}
@@ -4,6 +4,7 @@ mod jarvis_scan;
 mod point;
 mod polygon_points;
 mod ramer_douglas_peucker;
+mod rectangle;
 mod segment;
 
 pub use self::closest_points::closest_points;
@@ -12,4 +13,5 @@ pub use self::jarvis_scan::jarvis_march;
 pub use self::point::Point;
 pub use self::polygon_points::lattice_points;
 pub use self::ramer_douglas_peucker::ramer_douglas_peucker;
+pub use self::rectangle::Rectangle;
 pub use self::segment::Segment;
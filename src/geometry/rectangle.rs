@@ -0,0 +1,100 @@
+use super::Point;
+
+// An axis-aligned rectangle, including its border.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rectangle {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rectangle {
+    // Builds the rectangle having a and b as opposite corners.
+    pub fn new(a: Point, b: Point) -> Rectangle {
+        Rectangle {
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    // The degenerate rectangle covering a single point.
+    pub fn from_point(p: &Point) -> Rectangle {
+        Rectangle {
+            min: p.clone(),
+            max: p.clone(),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(
+            f64::midpoint(self.min.x, self.max.x),
+            f64::midpoint(self.min.y, self.max.y),
+        )
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+
+    pub fn contains_rectangle(&self, other: &Rectangle) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    // The smallest rectangle covering both rectangles.
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        Rectangle {
+            min: Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    // The distance from p to the closest point of the rectangle, 0 if p is inside.
+    pub fn distance_to(&self, p: &Point) -> f64 {
+        let dx = (self.min.x - p.x).max(p.x - self.max.x).max(0.0);
+        let dy = (self.min.y - p.y).max(p.y - self.max.y).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectangle_operations() {
+        let r = Rectangle::new(Point::new(4.0, 3.0), Point::new(0.0, 1.0));
+        assert_eq!(r.min, Point::new(0.0, 1.0));
+        assert_eq!((r.width(), r.height(), r.area()), (4.0, 2.0, 8.0));
+        assert_eq!(r.center(), Point::new(2.0, 2.0));
+        assert!(r.contains(&Point::new(4.0, 1.0)));
+        assert!(!r.contains(&Point::new(4.1, 1.0)));
+
+        let other = Rectangle::new(Point::new(4.0, 3.0), Point::new(6.0, 6.0));
+        assert!(r.intersects(&other));
+        assert!(!r.intersects(&Rectangle::from_point(&Point::new(5.0, 0.0))));
+        let union = r.union(&other);
+        assert!(union.contains_rectangle(&r) && union.contains_rectangle(&other));
+        assert_eq!(union.area(), 30.0);
+
+        assert_eq!(r.distance_to(&Point::new(2.0, 2.0)), 0.0);
+        assert_eq!(r.distance_to(&Point::new(7.0, 7.0)), 5.0);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::data_structures::AddressableHeap;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Add;
 
@@ -51,9 +52,60 @@ pub fn dijkstra<V: Ord + Copy, E: Ord + Copy + Add<Output = E>>(
     ans
 }
 
+// same as `dijkstra`, but the priority queue is any addressable heap
+// instead of removing and reinserting a vertex when a shorter path to it is found, its key
+// is decreased in place, so the queue never holds more than V entries
+//
+// Time: O(E * logV) with a binary or pairing heap, O(E + V * logV) with a Fibonacci heap, whose
+// decrease_key is O(1) amortized.
+// Space: O(V).
+pub fn dijkstra_with_heap<V, E, H>(graph: &Graph<V, E>, start: V) -> BTreeMap<V, Option<(V, E)>>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E>,
+    H: AddressableHeap<(E, V), ()> + Default,
+{
+    let mut ans = BTreeMap::new();
+    let mut handles = BTreeMap::new();
+    let mut prio = H::default();
+
+    // start is the special case that doesn't have a predecessor
+    ans.insert(start, None);
+
+    for (new, weight) in &graph[&start] {
+        ans.insert(*new, Some((start, *weight)));
+        handles.insert(*new, prio.push((*weight, *new), ()));
+    }
+
+    while let Some(((path_weight, vertex), ())) = prio.pop() {
+        for (next, weight) in &graph[&vertex] {
+            let new_weight = path_weight + *weight;
+            match ans.get(next) {
+                // if ans[next] is a lower dist than the alternative one, we do nothing
+                Some(Some((_, dist_next))) if new_weight >= *dist_next => {}
+                // if ans[next] is None then next is start and so the distance won't be changed
+                Some(None) => {}
+                // the new path is shorter, either new was not in ans or it was farther
+                _ => {
+                    ans.insert(*next, Some((vertex, new_weight)));
+                    // the handle is stale if next was already popped, push it again then
+                    if handles.get(next).is_none_or(|&handle| {
+                        prio.decrease_key(handle, (new_weight, *next)).is_err()
+                    }) {
+                        handles.insert(*next, prio.push((new_weight, *next), ()));
+                    }
+                }
+            }
+        }
+    }
+
+    ans
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{dijkstra, Graph};
+    use super::{dijkstra, dijkstra_with_heap, Graph};
+    use crate::data_structures::{FibonacciHeap, IndexedHeap, PairingHeap};
     use std::collections::BTreeMap;
 
     fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
@@ -156,4 +208,33 @@ mod tests {
         dists_e.insert('b', Some(('c', 39)));
         assert_eq!(dijkstra(&graph, 'e'), dists_e);
     }
+
+    #[test]
+    fn with_heaps() {
+        let mut graph = BTreeMap::new();
+        for i in 0..60u32 {
+            add_edge(&mut graph, i, (i * 7 + 3) % 60, (i * 13) % 17 + 1);
+            add_edge(&mut graph, i, (i * 11 + 5) % 60, (i * 5) % 23 + 1);
+            add_edge(&mut graph, i, (i + 1) % 60, 40);
+        }
+        for start in [0, 17, 59] {
+            let expected = dijkstra(&graph, start);
+            assert_eq!(
+                dijkstra_with_heap::<_, _, IndexedHeap<_, _>>(&graph, start),
+                expected
+            );
+            assert_eq!(
+                dijkstra_with_heap::<_, _, IndexedHeap<_, _, 4>>(&graph, start),
+                expected
+            );
+            assert_eq!(
+                dijkstra_with_heap::<_, _, PairingHeap<_, _>>(&graph, start),
+                expected
+            );
+            assert_eq!(
+                dijkstra_with_heap::<_, _, FibonacciHeap<_, _>>(&graph, start),
+                expected
+            );
+        }
+    }
 }
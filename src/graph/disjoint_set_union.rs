@@ -2,21 +2,16 @@
 //! which is an efficient data structure for keeping track of a set of elements
 //! partitioned into disjoint (non-overlapping) subsets.
 
-/// Represents a node in the Disjoint Set Union (DSU) structure  which
-/// keep track of the parent-child relationships in the disjoint sets.
-pub struct DSUNode {
-    /// The index of the node's parent, or itself if it's the root.
-    parent: usize,
-    /// The size of the set rooted at this node, used for union by size.
-    size: usize,
-}
+use crate::data_structures::DisjointSets;
 
 /// Disjoint Set Union (Union-Find) data structure, particularly useful for
 /// managing dynamic connectivity problems such as determining
 /// if two elements are in the same subset or merging two subsets.
+///
+/// This is a thin wrapper around [`DisjointSets`], keeping the interface
+/// used by the graph algorithms.
 pub struct DisjointSetUnion {
-    /// List of DSU nodes where each element's parent and size are tracked.
-    nodes: Vec<DSUNode>,
+    sets: DisjointSets,
 }
 
 impl DisjointSetUnion {
@@ -30,15 +25,9 @@ impl DisjointSetUnion {
     ///
     /// A new instance of `DisjointSetUnion` with `n + 1` independent sets.
     pub fn new(num_elements: usize) -> DisjointSetUnion {
-        let mut nodes = Vec::with_capacity(num_elements + 1);
-        for idx in 0..=num_elements {
-            nodes.push(DSUNode {
-                parent: idx,
-                size: 1,
-            });
+        Self {
+            sets: DisjointSets::new(num_elements + 1),
         }
-
-        Self { nodes }
     }
 
     /// Finds the representative (root) of the set containing `element` with path compression.
@@ -54,10 +43,7 @@ impl DisjointSetUnion {
     ///
     /// The root representative of the set containing `element`.
     pub fn find_set(&mut self, element: usize) -> usize {
-        if element != self.nodes[element].parent {
-            self.nodes[element].parent = self.find_set(self.nodes[element].parent);
-        }
-        self.nodes[element].parent
+        self.sets.find(element)
     }
 
     /// Merges the sets containing `first_elem` and `sec_elem` using union by size.
@@ -73,23 +59,17 @@ impl DisjointSetUnion {
     ///
     /// The root of the merged set, or `usize::MAX` if both elements are already in the same set.
     pub fn merge(&mut self, first_elem: usize, sec_elem: usize) -> usize {
-        let mut first_root = self.find_set(first_elem);
-        let mut sec_root = self.find_set(sec_elem);
-
-        if first_root == sec_root {
+        if self.sets.union(first_elem, sec_elem) {
+            self.sets.find(first_elem)
+        } else {
             // Already in the same set, no merge required
-            return usize::MAX;
+            usize::MAX
         }
+    }
 
-        // Union by size: attach the smaller tree under the larger tree
-        if self.nodes[first_root].size < self.nodes[sec_root].size {
-            std::mem::swap(&mut first_root, &mut sec_root);
-        }
-
-        self.nodes[sec_root].parent = first_root;
-        self.nodes[first_root].size += self.nodes[sec_root].size;
-
-        first_root
+    /// Returns the number of elements in the set containing `element`.
+    pub fn size_of(&self, element: usize) -> usize {
+        self.sets.size_of(element)
     }
 }
 
@@ -7,6 +7,14 @@ manually or by using `src/graph/graph_enumeration.rs`
  Here we implement two different algorithms:
 - The online one is implemented using Sparse Table and has O(n.lg(n))
 time complexity and memory usage. It answers each query in O(lg(n)).
+Alternatively, `LowestCommonAncestorEulerTour` records the vertices in
+Euler tour order: the LCA of u and v is the shallowest vertex visited
+between u and v, which a Cartesian tree RMQ over the depths answers in
+O(1) after O(n) preprocessing. It is a separate type rather than a new
+backend of `LowestCommonAncestorOnline`, whose binary lifting tables are
+public fields that users may fill themselves, and it uses
+`CartesianTreeRmq` rather than `RangeMinimumQuery` because it needs the
+position of the minimum in the tour, not only the minimal depth.
 - The offline algorithm was discovered by Robert Tarjan. At first each
 query should be determined and saved. Then, vertices are visited in
 Depth First Search order and queries are answered using Disjoint
@@ -16,6 +24,7 @@ because alpha(n) < 5 for n < 10 ^ 600
  */
 
 use super::DisjointSetUnion;
+use crate::data_structures::CartesianTreeRmq;
 pub struct LowestCommonAncestorOnline {
     // Make members public to allow the user to fill them themself.
     pub parents_sparse_table: Vec<Vec<usize>>,
@@ -97,6 +106,68 @@ impl LowestCommonAncestorOnline {
     }
 }
 
+pub struct LowestCommonAncestorEulerTour {
+    // Vertices in the order they are visited, each vertex again after each of its children.
+    tour: Vec<usize>,
+    // The position of the first visit of each vertex in the tour.
+    first_visit: Vec<usize>,
+    // The depths along the tour.
+    depths: CartesianTreeRmq<usize>,
+}
+
+impl LowestCommonAncestorEulerTour {
+    pub fn new(root: usize, adj: &[Vec<usize>]) -> Self {
+        let mut tour = vec![root];
+        let mut depths = vec![0];
+        let mut first_visit = vec![usize::MAX; adj.len()];
+        first_visit[root] = 0;
+        // Iterative DFS: (vertex, parent, depth, index of the next neighbour to look at). The
+        // root has no parent, so none of its neighbours is skipped.
+        let mut stack = vec![(root, usize::MAX, 0, 0)];
+        while let Some(frame) = stack.last_mut() {
+            let (vertex, parent, depth, next) = *frame;
+            match adj[vertex][next..]
+                .iter()
+                .position(|&child| child != parent)
+            {
+                Some(offset) => {
+                    let child = adj[vertex][next + offset];
+                    frame.3 = next + offset + 1;
+                    first_visit[child] = tour.len();
+                    tour.push(child);
+                    depths.push(depth + 1);
+                    stack.push((child, vertex, depth + 1, 0));
+                }
+                None => {
+                    // Going back up visits the parent again
+                    stack.pop();
+                    if let Some(&(parent, _, parent_depth, _)) = stack.last() {
+                        tour.push(parent);
+                        depths.push(parent_depth);
+                    }
+                }
+            }
+        }
+        LowestCommonAncestorEulerTour {
+            tour,
+            first_visit,
+            depths: CartesianTreeRmq::new(depths),
+        }
+    }
+
+    pub fn get_ancestor(&self, v: usize, u: usize) -> usize {
+        let (mut start, mut end) = (self.first_visit[v], self.first_visit[u]);
+        if start > end {
+            std::mem::swap(&mut start, &mut end);
+        }
+        let shallowest = self
+            .depths
+            .min_index(start, end + 1)
+            .expect("both vertices are in the tour");
+        self.tour[shallowest]
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct LCAQuery {
     other: usize,
@@ -208,4 +279,43 @@ mod tests {
         offline_answers.sort_unstable_by(|a1, a2| a1.query_id.cmp(&a2.query_id));
         assert_eq!(offline_answers, online_answers);
     }
+
+    #[test]
+    fn euler_tour_matches_online() {
+        // A random tree where vertex i hangs below a smaller vertex
+        let num_verts = 300;
+        let mut tree: Vec<Vec<usize>> = vec![vec![]; num_verts + 1];
+        let mut seed = 42_usize;
+        for child in 2..=num_verts {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let parent = 1 + (seed >> 33) % (child - 1);
+            tree[parent].push(child);
+            tree[child].push(parent);
+        }
+        let mut online = LowestCommonAncestorOnline::new(num_verts);
+        online.fill_sparse_table(1, 0, 0, &tree);
+        let euler = LowestCommonAncestorEulerTour::new(1, &tree);
+        for v in 1..=num_verts {
+            for u in (1..=num_verts).step_by(7) {
+                assert_eq!(euler.get_ancestor(v, u), online.get_ancestor(v, u));
+            }
+        }
+        assert_eq!(euler.get_ancestor(5, 5), 5);
+        assert_eq!(euler.get_ancestor(1, 200), 1);
+    }
+
+    #[test]
+    fn euler_tour_root_next_to_vertex_zero() {
+        //     2
+        //    / \
+        //   0   3
+        //   |
+        //   1
+        let tree = vec![vec![2, 1], vec![0], vec![0, 3], vec![2]];
+        let euler = LowestCommonAncestorEulerTour::new(2, &tree);
+        assert_eq!(euler.get_ancestor(1, 3), 2);
+        assert_eq!(euler.get_ancestor(0, 1), 0);
+        assert_eq!(euler.get_ancestor(0, 3), 2);
+        assert_eq!(euler.get_ancestor(1, 1), 1);
+    }
 }
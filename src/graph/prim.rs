@@ -1,3 +1,4 @@
+use crate::data_structures::AddressableHeap;
 use std::cmp::Reverse;
 use std::collections::{BTreeMap, BinaryHeap};
 use std::ops::Add;
@@ -57,9 +58,59 @@ pub fn prim_with_start<V: Ord + Copy, E: Ord + Add + Copy>(
     mst
 }
 
+// same as `prim_with_start`, but the priority queue is any addressable heap
+// it holds at most one edge per vertex outside of the MST, the cheapest one found so far,
+// whose key is decreased in place when a cheaper edge is found
+//
+// Time: O(E * logV) with a binary or pairing heap, O(E + V * logV) with a Fibonacci heap.
+// Space: O(V).
+pub fn prim_with_heap<V, E, H>(graph: &Graph<V, E>, start: V) -> Graph<V, E>
+where
+    V: Ord + Copy,
+    E: Ord + Add + Copy,
+    H: AddressableHeap<(E, V, V), ()> + Default,
+{
+    // will contain the MST
+    let mut mst: Graph<V, E> = Graph::new();
+    // the elements are an edge: the cost, destination and source
+    let mut prio = H::default();
+    let mut handles = BTreeMap::new();
+
+    mst.insert(start, BTreeMap::new());
+
+    let mut relax = |prio: &mut H, mst: &Graph<V, E>, source: V| {
+        for (v, c) in &graph[&source] {
+            if mst.contains_key(v) {
+                continue;
+            }
+            let edge = (*c, *v, source);
+            match handles.get(v).and_then(|&handle| prio.get(handle)) {
+                // a cheaper edge already leads to v
+                Some((best, ())) if *best <= edge => {}
+                Some(_) => {
+                    prio.decrease_key(handles[v], edge)
+                        .expect("the new edge is cheaper");
+                }
+                None => {
+                    handles.insert(*v, prio.push(edge, ()));
+                }
+            }
+        }
+    };
+
+    relax(&mut prio, &mst, start);
+    while let Some(((dist, t, prev), ())) = prio.pop() {
+        add_edge(&mut mst, prev, t, dist);
+        relax(&mut prio, &mst, t);
+    }
+
+    mst
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{add_edge, prim, Graph};
+    use super::{add_edge, prim, prim_with_heap, Graph};
+    use crate::data_structures::{FibonacciHeap, IndexedHeap, PairingHeap};
     use std::collections::BTreeMap;
 
     #[test]
@@ -196,4 +247,31 @@ mod tests {
 
         assert_eq!(prim(&graph), ans);
     }
+
+    #[test]
+    fn with_heaps() {
+        let mut graph = BTreeMap::new();
+        for i in 0..50 {
+            add_edge(&mut graph, i, (i * 7 + 3) % 50, (i * 13) % 17);
+            add_edge(&mut graph, i, (i * 11 + 5) % 50, (i * 5) % 23);
+            add_edge(&mut graph, i, (i + 1) % 50, 30);
+        }
+        let expected = prim(&graph);
+        assert_eq!(
+            prim_with_heap::<_, _, IndexedHeap<_, _>>(&graph, 0),
+            expected
+        );
+        assert_eq!(
+            prim_with_heap::<_, _, IndexedHeap<_, _, 8>>(&graph, 0),
+            expected
+        );
+        assert_eq!(
+            prim_with_heap::<_, _, PairingHeap<_, _>>(&graph, 0),
+            expected
+        );
+        assert_eq!(
+            prim_with_heap::<_, _, FibonacciHeap<_, _>>(&graph, 0),
+            expected
+        );
+    }
 }
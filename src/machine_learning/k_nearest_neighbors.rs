@@ -1,19 +1,49 @@
-/// K-Nearest Neighbors (KNN) algorithm for classification.
-/// KNN is a simple, instance-based learning algorithm that classifies
-/// a data point based on the majority class of its k nearest neighbors.
+use crate::data_structures::KdTree;
 
-fn euclidean_distance(p1: &[f64], p2: &[f64]) -> f64 {
-    if p1.len() != p2.len() {
-        return f64::INFINITY;
+/// A KNN classifier that indexes its training data once and answers many queries.
+pub struct KNearestNeighbors {
+    tree: KdTree<Vec<f64>, f64>,
+}
+
+impl KNearestNeighbors {
+    /// Returns `None` if the training data is empty, or if its points do not all
+    /// have the same, non-zero, number of dimensions.
+    pub fn new(training_data: Vec<(Vec<f64>, f64)>) -> Option<Self> {
+        let dimensions = training_data.first()?.0.len();
+        if dimensions == 0
+            || training_data
+                .iter()
+                .any(|(features, _)| features.len() != dimensions)
+        {
+            return None;
+        }
+        Some(KNearestNeighbors {
+            tree: KdTree::from_points(training_data),
+        })
     }
 
-    p1.iter()
-        .zip(p2.iter())
-        .map(|(a, b)| (a - b).powi(2))
-        .sum::<f64>()
-        .sqrt()
+    /// Classifies `point` by the majority label of its `k` nearest training points.
+    /// Returns `None` if `k` is 0, exceeds the number of training points, or if
+    /// `point` does not have the dimensions of the training points.
+    pub fn predict(&self, point: &[f64], k: usize) -> Option<f64> {
+        if k == 0 || k > self.tree.len() || self.tree.dimensions() != Some(point.len()) {
+            return None;
+        }
+        majority_label(
+            self.tree
+                .k_nearest(point, k)
+                .into_iter()
+                .map(|neighbor| *neighbor.value),
+        )
+    }
 }
 
+/// K-Nearest Neighbors (KNN) algorithm for classification.
+/// KNN is a simple, instance-based learning algorithm that classifies
+/// a data point based on the majority class of its k nearest neighbors.
+///
+/// The neighbors are found with a k-d tree, so that a query does not need to
+/// look at every training point.
 pub fn k_nearest_neighbors(
     training_data: Vec<(Vec<f64>, f64)>,
     test_point: Vec<f64>,
@@ -23,24 +53,37 @@ pub fn k_nearest_neighbors(
         return None;
     }
 
-    let mut distances: Vec<(f64, f64)> = training_data
-        .iter()
-        .map(|(features, label)| (euclidean_distance(&test_point, features), *label))
-        .collect();
-
-    distances.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
-
-    let k_nearest = &distances[..k];
+    // Points with other dimensions than the test point are infinitely far away:
+    // they only count once all the comparable points have been used.
+    let (comparable, incomparable): (Vec<_>, Vec<_>) = training_data
+        .into_iter()
+        .partition(|(features, _)| features.len() == test_point.len());
+
+    let tree = KdTree::from_points(comparable);
+    let nearest = if test_point.is_empty() {
+        Vec::new()
+    } else {
+        tree.k_nearest(&test_point, k)
+    };
+    let labels = nearest
+        .into_iter()
+        .map(|neighbor| *neighbor.value)
+        .chain(incomparable.into_iter().map(|(_, label)| label))
+        .take(k);
+    majority_label(labels)
+}
 
+/// The most frequent label, preferring the one seen last among equally frequent labels.
+fn majority_label(labels: impl Iterator<Item = f64>) -> Option<f64> {
     let mut label_counts: Vec<(f64, usize)> = Vec::new();
-    for (_, label) in k_nearest {
+    for label in labels {
         let found = label_counts
             .iter_mut()
             .find(|(l, _)| (l - label).abs() < 1e-10);
         if let Some((_, count)) = found {
             *count += 1;
         } else {
-            label_counts.push((*label, 1));
+            label_counts.push((label, 1));
         }
     }
 
@@ -123,4 +166,25 @@ mod tests {
         let result = k_nearest_neighbors(training_data, test_point, 2);
         assert_eq!(result, Some(0.0));
     }
+
+    #[test]
+    fn test_classifier_reuses_training_data() {
+        let training_data = vec![
+            (vec![0.0, 0.0], 0.0),
+            (vec![1.0, 0.0], 0.0),
+            (vec![0.0, 1.0], 0.0),
+            (vec![5.0, 5.0], 1.0),
+            (vec![6.0, 5.0], 1.0),
+            (vec![5.0, 6.0], 1.0),
+        ];
+        let classifier = KNearestNeighbors::new(training_data).unwrap();
+        assert_eq!(classifier.predict(&[0.5, 0.5], 3), Some(0.0));
+        assert_eq!(classifier.predict(&[5.5, 5.5], 3), Some(1.0));
+        assert_eq!(classifier.predict(&[5.5, 5.5], 0), None);
+        assert_eq!(classifier.predict(&[5.5, 5.5], 7), None);
+        assert_eq!(classifier.predict(&[5.5], 1), None);
+
+        assert!(KNearestNeighbors::new(vec![]).is_none());
+        assert!(KNearestNeighbors::new(vec![(vec![1.0], 0.0), (vec![1.0, 2.0], 1.0)]).is_none());
+    }
 }
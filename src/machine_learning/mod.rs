@@ -15,7 +15,7 @@ mod support_vector_classifier;
 pub use self::cholesky::cholesky;
 pub use self::decision_tree::decision_tree;
 pub use self::k_means::k_means;
-pub use self::k_nearest_neighbors::k_nearest_neighbors;
+pub use self::k_nearest_neighbors::{k_nearest_neighbors, KNearestNeighbors};
 pub use self::linear_regression::linear_regression;
 pub use self::logistic_regression::logistic_regression;
 pub use self::loss_function::{
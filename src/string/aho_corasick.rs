@@ -1,87 +1,276 @@
-use std::cell::RefCell;
-use std::collections::BTreeMap;
-use std::collections::VecDeque;
-use std::rc::{Rc, Weak};
-
-#[derive(Default)]
-struct ACNode {
-    trans: BTreeMap<char, Rc<RefCell<ACNode>>>,
-    suffix: Weak<RefCell<ACNode>>, // the suffix(fail) link
-    lengths: Vec<usize>,           // lengths of matched patterns ended at this node
+// The Aho-Corasick algorithm finds all the occurrences of a set of patterns in a text in a single
+// pass. The patterns are stored in a trie, whose nodes are completed with failure links (the
+// longest proper suffix of a node that is also a node) into a finite automaton. Here the
+// automaton is a dense table with one transition per byte and state, so that scanning takes a
+// single table lookup per byte of the text, O(n + z) in total for z matches.
+// Source: https://en.wikipedia.org/wiki/Aho%E2%80%93Corasick_algorithm
+//
+// Besides all (overlapping) matches, the searcher can report non-overlapping matches with the
+// leftmost-first or leftmost-longest semantics: a match is reported as soon as no match can start
+// at or before its start any more, i.e. once the current state (the longest suffix of the text
+// read so far that starts some pattern) begins after it. This needs no backtracking over the
+// text, so it also works on a stream of chunks.
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// Which matches a search reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchKind {
+    /// Every occurrence of every pattern, by increasing end, and longest first for equal ends.
+    #[default]
+    Overlapping,
+    /// Non-overlapping matches, preferring the leftmost start and then the pattern given first.
+    LeftmostFirst,
+    /// Non-overlapping matches, preferring the leftmost start and then the longest pattern.
+    LeftmostLongest,
+}
+
+/// An occurrence of the pattern with index `pattern` at `start..end` in the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
 }
 
-#[derive(Default)]
+const ROOT: u32 = 0;
+
+/// An Aho-Corasick automaton over bytes.
 pub struct AhoCorasick {
-    root: Rc<RefCell<ACNode>>,
+    /// `transitions[256 * state + byte]` is the next state.
+    transitions: Vec<u32>,
+    /// The patterns ending at each state, excluding those reached by failure links.
+    outputs: Vec<Vec<usize>>,
+    /// The nearest state on the failure chain that has outputs.
+    output_links: Vec<Option<u32>>,
+    /// The length of the string spelled by each state.
+    depths: Vec<usize>,
+    pattern_lengths: Vec<usize>,
+    kind: MatchKind,
+}
+
+impl Default for AhoCorasick {
+    /// An automaton without patterns, which never matches.
+    fn default() -> Self {
+        Self::new::<&[u8]>(&[])
+    }
 }
 
 impl AhoCorasick {
-    pub fn new(words: &[&str]) -> Self {
-        let root = Rc::new(RefCell::new(ACNode::default()));
-        for word in words {
-            let mut cur = Rc::clone(&root);
-            for c in word.chars() {
-                cur = Rc::clone(Rc::clone(&cur).borrow_mut().trans.entry(c).or_default());
+    /// Builds an automaton reporting overlapping matches. Empty patterns never match.
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        Self::with_match_kind(patterns, MatchKind::Overlapping)
+    }
+
+    /// Builds an automaton reporting matches of the given kind. Empty patterns never match.
+    pub fn with_match_kind<P: AsRef<[u8]>>(patterns: &[P], kind: MatchKind) -> Self {
+        // Build the trie, where a missing transition is marked as going to the root.
+        let mut transitions = vec![ROOT; 256];
+        let mut outputs = vec![Vec::new()];
+        let mut depths = vec![0];
+        for (id, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
             }
-            cur.borrow_mut().lengths.push(word.len());
+            let mut state = ROOT;
+            for &b in pattern {
+                let slot = 256 * state as usize + usize::from(b);
+                if transitions[slot] == ROOT {
+                    transitions[slot] = u32::try_from(outputs.len()).expect("too many states");
+                    transitions.extend([ROOT; 256]);
+                    outputs.push(Vec::new());
+                    depths.push(depths[state as usize] + 1);
+                }
+                state = transitions[slot];
+            }
+            outputs[state as usize].push(id);
         }
-        Self::build_suffix(Rc::clone(&root));
-        Self { root }
-    }
-
-    fn build_suffix(root: Rc<RefCell<ACNode>>) {
-        let mut q = VecDeque::new();
-        q.push_back(Rc::clone(&root));
-        while let Some(parent) = q.pop_front() {
-            let parent = parent.borrow();
-            for (c, child) in &parent.trans {
-                q.push_back(Rc::clone(child));
-                let mut child = child.borrow_mut();
-                let mut suffix = parent.suffix.upgrade();
-                loop {
-                    match &suffix {
-                        None => {
-                            child.lengths.extend(root.borrow().lengths.clone());
-                            child.suffix = Rc::downgrade(&root);
-                            break;
-                        }
-                        Some(node) => {
-                            if node.borrow().trans.contains_key(c) {
-                                let node = &node.borrow().trans[c];
-                                child.lengths.extend(node.borrow().lengths.clone());
-                                child.suffix = Rc::downgrade(node);
-                                break;
-                            }
-                            suffix = suffix.unwrap().borrow().suffix.upgrade();
-                        }
-                    }
+
+        // Complete the transitions in breadth-first order, so that the failure state of every
+        // state is complete before the state itself.
+        let states = outputs.len();
+        let mut failures = vec![ROOT; states];
+        let mut output_links = vec![None; states];
+        let mut queue: VecDeque<u32> = (0..256)
+            .map(|b| transitions[b])
+            .filter(|&child| child != ROOT)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            let failure = failures[state as usize];
+            for b in 0..256 {
+                let slot = 256 * state as usize + b;
+                let fallback = transitions[256 * failure as usize + b];
+                let child = transitions[slot];
+                if child == ROOT {
+                    transitions[slot] = fallback;
+                } else {
+                    failures[child as usize] = fallback;
+                    output_links[child as usize] = if outputs[fallback as usize].is_empty() {
+                        output_links[fallback as usize]
+                    } else {
+                        Some(fallback)
+                    };
+                    queue.push_back(child);
                 }
             }
         }
+
+        AhoCorasick {
+            transitions,
+            outputs,
+            output_links,
+            depths,
+            pattern_lengths: patterns.iter().map(|p| p.as_ref().len()).collect(),
+            kind,
+        }
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    pub fn patterns_len(&self) -> usize {
+        self.pattern_lengths.len()
+    }
+
+    /// All the matches in `haystack`, according to the match kind.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<Match> {
+        let mut stream = self.stream();
+        let mut matches = stream.feed(haystack);
+        matches.extend(stream.finish());
+        matches
+    }
+
+    /// The first match reported in `haystack`, according to the match kind.
+    pub fn find(&self, haystack: &[u8]) -> Option<Match> {
+        let mut stream = self.stream();
+        let mut found = Vec::new();
+        for &b in haystack {
+            stream.step(b, &mut found);
+            if let Some(&first) = found.first() {
+                return Some(first);
+            }
+        }
+        stream.finish().first().copied()
     }
 
+    /// The matched substrings of `s`, in the order they are reported.
     pub fn search<'a>(&self, s: &'a str) -> Vec<&'a str> {
-        let mut ans = vec![];
-        let mut cur = Rc::clone(&self.root);
-        let mut position: usize = 0;
-        for c in s.chars() {
-            loop {
-                if let Some(child) = Rc::clone(&cur).borrow().trans.get(&c) {
-                    cur = Rc::clone(child);
-                    break;
-                }
-                let suffix = cur.borrow().suffix.clone();
-                match suffix.upgrade() {
-                    Some(node) => cur = node,
-                    None => break,
+        self.find_all(s.as_bytes())
+            .into_iter()
+            .map(|m| &s[m.start..m.end])
+            .collect()
+    }
+
+    /// Starts searching a text given in chunks.
+    pub fn stream(&self) -> StreamSearcher<'_> {
+        StreamSearcher {
+            automaton: self,
+            state: ROOT,
+            position: 0,
+            last_end: 0,
+            candidates: BTreeMap::new(),
+        }
+    }
+}
+
+/// Searches a text fed in consecutive chunks, reporting positions relative to the whole text.
+/// Matches may span several chunks.
+pub struct StreamSearcher<'a> {
+    automaton: &'a AhoCorasick,
+    state: u32,
+    /// The number of bytes read so far.
+    position: usize,
+    /// The end of the last reported match, for the leftmost kinds.
+    last_end: usize,
+    /// The preferred match at each start that may still be reported, for the leftmost kinds.
+    candidates: BTreeMap<usize, Match>,
+}
+
+impl StreamSearcher<'_> {
+    /// Reads the next chunk of the text, returning the matches that are now decided.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Match> {
+        let mut found = Vec::new();
+        for &b in chunk {
+            self.step(b, &mut found);
+        }
+        found
+    }
+
+    /// Ends the text, returning the matches that were still pending.
+    pub fn finish(&mut self) -> Vec<Match> {
+        let mut found = Vec::new();
+        self.commit(usize::MAX, &mut found);
+        found
+    }
+
+    /// The number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn step(&mut self, b: u8, found: &mut Vec<Match>) {
+        let ac = self.automaton;
+        self.state = ac.transitions[256 * self.state as usize + usize::from(b)];
+        self.position += 1;
+        let mut state = if ac.outputs[self.state as usize].is_empty() {
+            ac.output_links[self.state as usize]
+        } else {
+            Some(self.state)
+        };
+        while let Some(s) = state {
+            for &pattern in &ac.outputs[s as usize] {
+                let m = Match {
+                    pattern,
+                    start: self.position - ac.pattern_lengths[pattern],
+                    end: self.position,
+                };
+                if ac.kind == MatchKind::Overlapping {
+                    found.push(m);
+                } else if m.start >= self.last_end {
+                    self.candidates
+                        .entry(m.start)
+                        .and_modify(|best| {
+                            let preferred = match ac.kind {
+                                MatchKind::LeftmostLongest => m.end > best.end,
+                                _ => m.pattern < best.pattern,
+                            };
+                            if preferred {
+                                *best = m;
+                            }
+                        })
+                        .or_insert(m);
                 }
             }
-            position += c.len_utf8();
-            for &len in &cur.borrow().lengths {
-                ans.push(&s[position - len..position]);
+            state = ac.output_links[s as usize];
+        }
+        if ac.kind != MatchKind::Overlapping {
+            // Every match found from now on starts inside the current state or after it.
+            self.commit(self.position - ac.depths[self.state as usize], found);
+        }
+    }
+
+    /// Reports the preferred candidates starting before `horizon`, where no other match can
+    /// start any more. Only the front of the candidates is looked at, and every candidate is
+    /// removed once, so this takes O(log n) amortized time per candidate.
+    fn commit(&mut self, horizon: usize, found: &mut Vec<Match>) {
+        while let Some(entry) = self.candidates.first_entry() {
+            if *entry.key() >= horizon {
+                break;
+            }
+            let best = entry.remove();
+            found.push(best);
+            self.last_end = best.end;
+            // The candidates overlapping the reported match can't be reported any more.
+            while self
+                .candidates
+                .first_key_value()
+                .is_some_and(|(&start, _)| start < best.end)
+            {
+                self.candidates.pop_first();
             }
         }
-        ans
     }
 }
 
@@ -129,4 +318,133 @@ mod tests {
             ]
         );
     }
+
+    fn triples(matches: &[Match]) -> Vec<(usize, usize, usize)> {
+        matches
+            .iter()
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_match_positions() {
+        let ac = AhoCorasick::new(&["he", "she", "his", "hers"]);
+        let matches = ac.find_all(b"ushers");
+        assert_eq!(triples(&matches), [(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+        assert_eq!(ac.find(b"ushers"), Some(matches[0]));
+        assert_eq!(ac.find(b"xyz"), None);
+        assert_eq!(ac.patterns_len(), 4);
+    }
+
+    #[test]
+    fn test_match_kinds() {
+        let patterns = ["Sam", "Samwise", "wise", "amwi"];
+        let text = b"Samwise is wise";
+
+        let ac = AhoCorasick::with_match_kind(&patterns, MatchKind::LeftmostFirst);
+        assert_eq!(ac.match_kind(), MatchKind::LeftmostFirst);
+        assert_eq!(
+            triples(&ac.find_all(text)),
+            [(0, 0, 3), (2, 3, 7), (2, 11, 15)]
+        );
+
+        let ac = AhoCorasick::with_match_kind(&patterns, MatchKind::LeftmostLongest);
+        assert_eq!(triples(&ac.find_all(text)), [(1, 0, 7), (2, 11, 15)]);
+        assert_eq!(
+            ac.find(text),
+            Some(Match {
+                pattern: 1,
+                start: 0,
+                end: 7
+            })
+        );
+
+        let ac = AhoCorasick::new(&patterns);
+        assert_eq!(
+            triples(&ac.find_all(text)),
+            [(0, 0, 3), (3, 1, 5), (1, 0, 7), (2, 3, 7), (2, 11, 15)]
+        );
+    }
+
+    #[test]
+    fn test_leftmost_keeps_later_candidates() {
+        // "cd" is found before "ab" can be reported, and must not be lost.
+        let ac = AhoCorasick::with_match_kind(&["ab", "abcdef", "cd"], MatchKind::LeftmostLongest);
+        assert_eq!(triples(&ac.find_all(b"abcdx")), [(0, 0, 2), (2, 2, 4)]);
+        assert_eq!(triples(&ac.find_all(b"abcdefcd")), [(1, 0, 6), (2, 6, 8)]);
+    }
+
+    #[test]
+    fn test_leftmost_with_many_pending_candidates() {
+        // Every byte ends a match of each pattern, which all stay pending for 200 bytes.
+        let patterns: Vec<Vec<u8>> = (1..=200).map(|len| vec![b'a'; len]).collect();
+        let text = vec![b'a'; 2000];
+        let ac = AhoCorasick::with_match_kind(&patterns, MatchKind::LeftmostLongest);
+        let matches = ac.find_all(&text);
+        assert_eq!(matches.len(), 10);
+        assert!(matches
+            .iter()
+            .enumerate()
+            .all(|(i, m)| (m.pattern, m.start, m.end) == (199, 200 * i, 200 * (i + 1))));
+        let ac = AhoCorasick::with_match_kind(&patterns, MatchKind::LeftmostFirst);
+        assert_eq!(ac.find_all(&text).len(), 2000);
+    }
+
+    #[test]
+    fn test_streaming() {
+        let patterns = ["needle", "needles", "dle", "x"];
+        let text = b"haystack needles and needle in x hay";
+        for kind in [
+            MatchKind::Overlapping,
+            MatchKind::LeftmostFirst,
+            MatchKind::LeftmostLongest,
+        ] {
+            let ac = AhoCorasick::with_match_kind(&patterns, kind);
+            let expected = ac.find_all(text);
+            for chunk_size in [1, 2, 3, 5, 8] {
+                let mut stream = ac.stream();
+                let mut matches = Vec::new();
+                for chunk in text.chunks(chunk_size) {
+                    matches.extend(stream.feed(chunk));
+                }
+                assert_eq!(stream.position(), text.len());
+                matches.extend(stream.finish());
+                assert_eq!(matches, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_has_no_patterns() {
+        let ac = AhoCorasick::default();
+        assert_eq!(ac.patterns_len(), 0);
+        assert_eq!(ac.match_kind(), MatchKind::Overlapping);
+        assert!(ac.find_all(b"anything").is_empty());
+        assert_eq!(ac.find(b""), None);
+        assert!(ac.search("text").is_empty());
+    }
+
+    #[test]
+    fn test_matches_naive_search() {
+        let patterns: Vec<Vec<u8>> = (0..200_u32)
+            .map(|i| format!("{:b}", i * 7 + 3).into_bytes())
+            .chain([Vec::new()])
+            .collect();
+        let text: Vec<u8> = (0..2000_u32)
+            .map(|i| if (i * i + i / 3) % 5 < 2 { b'1' } else { b'0' })
+            .collect();
+        let ac = AhoCorasick::new(&patterns);
+        let mut expected = Vec::new();
+        for end in 1..=text.len() {
+            let mut at_end: Vec<(usize, usize, usize)> = patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| !p.is_empty() && text[..end].ends_with(p))
+                .map(|(id, p)| (id, end - p.len(), end))
+                .collect();
+            at_end.sort_by_key(|&(id, start, _)| (start, id));
+            expected.extend(at_end);
+        }
+        assert_eq!(triples(&ac.find_all(&text)), expected);
+    }
 }
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use super::unicode::{graphemes, is_letter, TextOptions};
+
 /// Custom error type representing an invalid character found in the input.
 #[derive(Debug, PartialEq)]
 pub enum AnagramError {
@@ -19,13 +21,33 @@ pub enum AnagramError {
 /// * `Ok(false)` if the strings are not anagrams.
 /// * `Err(AnagramError)` if either string contains non-alphabetic characters.
 pub fn check_anagram(s: &str, t: &str) -> Result<bool, AnagramError> {
-    let s_cleaned = clean_string(s)?;
-    let t_cleaned = clean_string(t)?;
+    check_anagram_with(s, t, TextOptions::default())
+}
 
-    Ok(char_count(&s_cleaned) == char_count(&t_cleaned))
+/// Checks if two strings are anagrams, ignoring spaces, after case folding and normalizing them
+/// as set in `options`. Letters are compared as grapheme clusters, so a letter with a
+/// combining accent counts as a single letter.
+///
+/// # Arguments
+///
+/// * `s` - First input string.
+/// * `t` - Second input string.
+/// * `options` - How both strings are prepared before the comparison.
+///
+/// # Returns
+///
+/// * `Ok(true)` if the strings are anagrams.
+/// * `Ok(false)` if the strings are not anagrams.
+/// * `Err(AnagramError)` if either string contains non-alphabetic characters.
+pub fn check_anagram_with(s: &str, t: &str, options: TextOptions) -> Result<bool, AnagramError> {
+    let (s, t) = (options.apply(s), options.apply(t));
+    let s_cleaned = clean_string(&s)?;
+    let t_cleaned = clean_string(&t)?;
+
+    Ok(grapheme_count(&s_cleaned) == grapheme_count(&t_cleaned))
 }
 
-/// Cleans the input string by removing spaces and converting to lowercase.
+/// Splits the prepared input string into its letters, removing spaces.
 /// Returns an error if any non-alphabetic character is found.
 ///
 /// # Arguments
@@ -34,14 +56,15 @@ pub fn check_anagram(s: &str, t: &str) -> Result<bool, AnagramError> {
 ///
 /// # Returns
 ///
-/// * `Ok(String)` containing the cleaned string (no spaces, lowercase).
+/// * `Ok(Vec<&str>)` containing the letters of the string as grapheme clusters.
 /// * `Err(AnagramError)` if the string contains non-alphabetic characters.
-fn clean_string(s: &str) -> Result<String, AnagramError> {
-    s.chars()
-        .filter(|c| !c.is_whitespace())
-        .map(|c| {
-            if c.is_alphabetic() {
-                Ok(c.to_ascii_lowercase())
+fn clean_string(s: &str) -> Result<Vec<&str>, AnagramError> {
+    graphemes(s)
+        .into_iter()
+        .filter(|g| !g.chars().all(char::is_whitespace))
+        .map(|g| {
+            if is_letter(g) {
+                Ok(g)
             } else {
                 Err(AnagramError::NonAlphabeticCharacter)
             }
@@ -49,19 +72,19 @@ fn clean_string(s: &str) -> Result<String, AnagramError> {
         .collect()
 }
 
-/// Computes the histogram of characters in a string.
+/// Computes the histogram of letters.
 ///
 /// # Arguments
 ///
-/// * `s` - Input string.
+/// * `letters` - The letters as grapheme clusters.
 ///
 /// # Returns
 ///
-/// * A `HashMap` where the keys are characters and values are their count.
-fn char_count(s: &str) -> HashMap<char, usize> {
+/// * A `HashMap` where the keys are letters and values are their count.
+fn grapheme_count<'a>(letters: &[&'a str]) -> HashMap<&'a str, usize> {
     let mut res = HashMap::new();
-    for c in s.chars() {
-        *res.entry(c).or_insert(0) += 1;
+    for &letter in letters {
+        *res.entry(letter).or_insert(0) += 1;
     }
     res
 }
@@ -107,5 +130,30 @@ mod tests {
         invalid_anagram_with_numeric_chars: ("test123", "321test", Err(AnagramError::NonAlphabeticCharacter)),
         invalid_anagram_with_symbols: ("check@anagram", "check@nagaram", Err(AnagramError::NonAlphabeticCharacter)),
         non_anagram_length_mismatch: ("abc", "abcd", Ok(false)),
+        unicode_case_insensitive: ("Éla", "alé", Ok(true)),
+        unicode_canonically_equivalent: ("été", "e\u{301}te\u{301}", Ok(true)),
+        unicode_case_folding: ("Straße", "strasse", Ok(true)),
+        unicode_non_latin: ("Ода", "ДАО", Ok(true)),
+    }
+
+    #[test]
+    fn anagram_with_options() {
+        use crate::string::{Normalization, TextOptions};
+
+        let exact = TextOptions::EXACT;
+        assert_eq!(check_anagram_with("Satan", "santa", exact), Ok(false));
+        assert_eq!(check_anagram_with("Satan", "natSa", exact), Ok(true));
+        // Without normalization, the composed and decomposed accents differ.
+        assert_eq!(check_anagram_with("é", "e\u{301}", exact), Ok(false));
+        let nfd = TextOptions {
+            case_fold: false,
+            normalization: Normalization::Nfd,
+        };
+        assert_eq!(check_anagram_with("é", "e\u{301}", nfd), Ok(true));
+        assert_eq!(
+            check_anagram_with("e\u{301}", "e", nfd),
+            Ok(false),
+            "an accented letter is a single letter"
+        );
     }
 }
@@ -6,84 +6,30 @@
     matches => ["orange", "oregano"]
 */
 
-use std::collections::HashMap;
-
-const END: char = '#';
-
-#[derive(Debug)]
-struct Trie(HashMap<char, Box<Trie>>);
-
-impl Trie {
-    fn new() -> Self {
-        Trie(HashMap::new())
-    }
-
-    fn insert(&mut self, text: &str) {
-        let mut trie = self;
-
-        for c in text.chars() {
-            trie = trie.0.entry(c).or_insert_with(|| Box::new(Trie::new()));
-        }
-
-        trie.0.insert(END, Box::new(Trie::new()));
-    }
-
-    fn find(&self, prefix: &str) -> Vec<String> {
-        let mut trie = self;
-
-        for c in prefix.chars() {
-            let char_trie = trie.0.get(&c);
-            if let Some(char_trie) = char_trie {
-                trie = char_trie;
-            } else {
-                return vec![];
-            }
-        }
-
-        Self::_elements(trie)
-            .iter()
-            .map(|s| prefix.to_owned() + s)
-            .collect()
-    }
-
-    fn _elements(map: &Trie) -> Vec<String> {
-        let mut results = vec![];
-
-        for (c, v) in map.0.iter() {
-            let mut sub_result = vec![];
-            if c == &END {
-                sub_result.push("".to_owned())
-            } else {
-                Self::_elements(v)
-                    .iter()
-                    .map(|s| sub_result.push(c.to_string() + s))
-                    .collect()
-            }
-
-            results.extend(sub_result)
-        }
-
-        results
-    }
-}
+use crate::data_structures::RadixTrie;
 
 pub struct Autocomplete {
-    trie: Trie,
+    trie: RadixTrie<char, ()>,
 }
 
 impl Autocomplete {
     fn new() -> Self {
-        Self { trie: Trie::new() }
+        Self {
+            trie: RadixTrie::new(),
+        }
     }
 
     pub fn insert_words<T: AsRef<str>>(&mut self, words: &[T]) {
         for word in words {
-            self.trie.insert(word.as_ref());
+            self.trie.insert(word.as_ref().chars(), ());
         }
     }
 
     pub fn find_words(&self, prefix: &str) -> Vec<String> {
-        self.trie.find(prefix)
+        self.trie
+            .iter_prefix(prefix.chars())
+            .map(|(word, ())| word.into_iter().collect())
+            .collect()
     }
 }
 
@@ -0,0 +1,267 @@
+//! A BK-tree (Burkhard-Keller tree) indexes words under a discrete metric to find all the
+//! words within some distance of a query, e.g. for spelling suggestions.
+//!
+//! Every child of a node is labelled with its distance to the node. Searching for the words
+//! within `k` of a query `q`, a node at distance `d` from `q` can only lead to matches through
+//! the children labelled `d - k..=d + k`, by the triangle inequality.
+//!
+//! The metric is pluggable ([`Levenshtein`] by default). Matches are ranked by distance, then
+//! by Jaro-Winkler similarity to the query, which prefers the words sharing a prefix with it.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::{jaro_winkler_distance, optimized_levenshtein_distance};
+
+/// An integer distance between words. It must satisfy the triangle inequality for a
+/// [`BkTree`] to find every match.
+pub trait Metric {
+    fn distance(&self, a: &str, b: &str) -> usize;
+}
+
+impl<F: Fn(&str, &str) -> usize> Metric for F {
+    fn distance(&self, a: &str, b: &str) -> usize {
+        self(a, b)
+    }
+}
+
+/// The Levenshtein distance between the characters of both words.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Levenshtein;
+
+impl Metric for Levenshtein {
+    fn distance(&self, a: &str, b: &str) -> usize {
+        optimized_levenshtein_distance(a, b)
+    }
+}
+
+/// A word found by an approximate search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApproximateMatch<'a> {
+    pub word: &'a str,
+    pub distance: usize,
+    /// The Jaro-Winkler similarity between the word and the query.
+    pub similarity: f64,
+}
+
+impl<'a> ApproximateMatch<'a> {
+    pub(super) fn new(word: &'a str, query: &str, distance: usize) -> Self {
+        Self {
+            word,
+            distance,
+            similarity: jaro_winkler_distance(word, query),
+        }
+    }
+}
+
+/// Sorts matches by distance, then by decreasing similarity, then alphabetically.
+pub(super) fn rank(matches: &mut [ApproximateMatch]) {
+    matches.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then_with(|| {
+                b.similarity
+                    .partial_cmp(&a.similarity)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| a.word.cmp(b.word))
+    });
+}
+
+struct Node {
+    word: String,
+    /// The children by distance to this node.
+    children: BTreeMap<usize, usize>,
+}
+
+pub struct BkTree<M = Levenshtein> {
+    nodes: Vec<Node>,
+    metric: M,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        Self::with_metric(Levenshtein)
+    }
+}
+
+impl Default for BkTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for BkTree {
+    fn from_iter<I: IntoIterator<Item = S>>(words: I) -> Self {
+        let mut tree = Self::new();
+        for word in words {
+            tree.insert(word.as_ref());
+        }
+        tree
+    }
+}
+
+impl<M: Metric> BkTree<M> {
+    pub fn with_metric(metric: M) -> Self {
+        Self {
+            nodes: Vec::new(),
+            metric,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a word, returning `false` if it was already present.
+    pub fn insert(&mut self, word: &str) -> bool {
+        if self.nodes.is_empty() {
+            self.nodes.push(Node {
+                word: word.to_string(),
+                children: BTreeMap::new(),
+            });
+            return true;
+        }
+        let mut current = 0;
+        loop {
+            let distance = self.metric.distance(&self.nodes[current].word, word);
+            if distance == 0 {
+                return false;
+            }
+            match self.nodes[current].children.get(&distance) {
+                Some(&child) => current = child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes[current].children.insert(distance, child);
+                    self.nodes.push(Node {
+                        word: word.to_string(),
+                        children: BTreeMap::new(),
+                    });
+                    return true;
+                }
+            }
+        }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        !self.search(word, 0).is_empty()
+    }
+
+    /// The words within `max_distance` of `query`, ranked by distance then similarity.
+    pub fn search(&self, query: &str, max_distance: usize) -> Vec<ApproximateMatch<'_>> {
+        let mut matches = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let distance = self.metric.distance(&node.word, query);
+            if distance <= max_distance {
+                matches.push(ApproximateMatch::new(&node.word, query, distance));
+            }
+            let range = distance.saturating_sub(max_distance)..=distance + max_distance;
+            stack.extend(node.children.range(range).map(|(_, &child)| child));
+        }
+        rank(&mut matches);
+        matches
+    }
+
+    /// The words in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(|node| node.word.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 10] = [
+        "book",
+        "books",
+        "cake",
+        "boo",
+        "boon",
+        "cook",
+        "cape",
+        "cart",
+        "hook",
+        "bookkeeper",
+    ];
+
+    fn words<'a>(matches: &[ApproximateMatch<'a>]) -> Vec<&'a str> {
+        matches.iter().map(|m| m.word).collect()
+    }
+
+    #[test]
+    fn search_matches_a_linear_scan() {
+        let tree: BkTree = WORDS.iter().collect();
+        assert_eq!(tree.len(), WORDS.len());
+        for query in ["bo", "book", "cale", "hoop", "xyz", ""] {
+            for k in 0..=3 {
+                let mut expected: Vec<&str> = WORDS
+                    .iter()
+                    .copied()
+                    .filter(|word| optimized_levenshtein_distance(word, query) <= k)
+                    .collect();
+                expected.sort_unstable();
+                let mut found = words(&tree.search(query, k));
+                found.sort_unstable();
+                assert_eq!(found, expected, "{query} within {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn ranked_suggestions() {
+        let tree: BkTree = WORDS.iter().collect();
+        let matches = tree.search("bok", 1);
+        let mut found = words(&matches);
+        found.sort_unstable();
+        assert_eq!(found, ["boo", "book"]);
+        assert!(matches.iter().all(|m| m.distance == 1));
+        // Equal distances are ordered by similarity to the query.
+        assert!(matches[0].similarity >= matches[1].similarity);
+        let matches = tree.search("cook", 2);
+        assert_eq!((matches[0].word, matches[0].distance), ("cook", 0));
+        assert!(matches
+            .windows(2)
+            .all(|pair| pair[0].distance <= pair[1].distance));
+    }
+
+    #[test]
+    fn insert_and_contains() {
+        let mut tree = BkTree::new();
+        assert!(tree.is_empty());
+        assert!(tree.search("a", 5).is_empty());
+        assert!(tree.insert("café"));
+        assert!(tree.insert("cafe"));
+        assert!(!tree.insert("café"));
+        assert_eq!(tree.len(), 2);
+        assert!(tree.contains("cafe"));
+        assert!(!tree.contains("caf"));
+        assert_eq!(tree.iter().collect::<Vec<_>>(), ["café", "cafe"]);
+    }
+
+    #[test]
+    fn custom_metric() {
+        // The Hamming distance, with the length difference counted as mismatches.
+        let hamming = |a: &str, b: &str| {
+            a.chars().zip(b.chars()).filter(|(x, y)| x != y).count()
+                + a.chars().count().abs_diff(b.chars().count())
+        };
+        let mut tree = BkTree::with_metric(hamming);
+        for word in ["karolin", "kathrin", "kerstin", "karolina"] {
+            tree.insert(word);
+        }
+        let matches = tree.search("karolin", 3);
+        assert_eq!(words(&matches[..2]), ["karolin", "karolina"]);
+        assert_eq!(matches.len(), 4);
+        assert_eq!(tree.search("karolin", 2).len(), 2);
+    }
+}
@@ -9,12 +9,12 @@ use std::collections::HashMap;
 /// This table stores the last occurrence of each character in the pattern.
 ///
 /// # Arguments
-/// * `pat` - The pattern as a slice of characters.
+/// * `pat` - The pattern as a slice of bytes.
 ///
 /// # Returns
 /// A `HashMap` where the keys are characters from the pattern and the values are their
 /// last known positions within the pattern.
-fn build_bad_char_table(pat: &[char]) -> HashMap<char, isize> {
+fn build_bad_char_table(pat: &[u8]) -> HashMap<u8, isize> {
     let mut bad_char_table = HashMap::new();
     for (i, &ch) in pat.iter().enumerate() {
         bad_char_table.insert(ch, i as isize);
@@ -38,8 +38,8 @@ fn calc_match_shift(
     shift: isize,
     pat_len: isize,
     text_len: isize,
-    bad_char_table: &HashMap<char, isize>,
-    text: &[char],
+    bad_char_table: &HashMap<u8, isize>,
+    text: &[u8],
 ) -> isize {
     if shift + pat_len >= text_len {
         return 1;
@@ -62,8 +62,8 @@ fn calc_match_shift(
 fn calc_mismatch_shift(
     mis_idx: isize,
     shift: isize,
-    text: &[char],
-    bad_char_table: &HashMap<char, isize>,
+    text: &[u8],
+    bad_char_table: &HashMap<u8, isize>,
 ) -> isize {
     let mis_ch = text[(shift + mis_idx) as usize];
     let bad_char_shift = bad_char_table.get(&mis_ch).unwrap_or(&-1);
@@ -81,7 +81,8 @@ fn calc_mismatch_shift(
 /// * `pat` - The pattern to search for as a string slice.
 ///
 /// # Returns
-/// A vector of starting indices where the pattern occurs in the text.
+/// A vector of the byte offsets in `text` where the pattern occurs. The search runs on the
+/// UTF-8 bytes, where a valid pattern can only match at char boundaries.
 pub fn boyer_moore_search(text: &str, pat: &str) -> Vec<usize> {
     let mut positions = Vec::new();
 
@@ -94,11 +95,10 @@ pub fn boyer_moore_search(text: &str, pat: &str) -> Vec<usize> {
     }
 
     // Convert text and pattern to character vectors for easier indexing
-    let pat: Vec<char> = pat.chars().collect();
-    let text: Vec<char> = text.chars().collect();
+    let (pat, text) = (pat.as_bytes(), text.as_bytes());
 
     // Build the bad character table for the pattern
-    let bad_char_table = build_bad_char_table(&pat);
+    let bad_char_table = build_bad_char_table(pat);
 
     let mut shift = 0;
 
@@ -114,10 +114,10 @@ pub fn boyer_moore_search(text: &str, pat: &str) -> Vec<usize> {
         // If we found a match (j < 0), record the position
         if j < 0 {
             positions.push(shift as usize);
-            shift += calc_match_shift(shift, pat_len, text_len, &bad_char_table, &text);
+            shift += calc_match_shift(shift, pat_len, text_len, &bad_char_table, text);
         } else {
             // If mismatch, calculate how far to shift based on the bad character rule
-            shift += calc_mismatch_shift(j, shift, &text, &bad_char_table);
+            shift += calc_mismatch_shift(j, shift, text, &bad_char_table);
         }
     }
 
@@ -157,5 +157,7 @@ mod tests {
         test_partial_overlap_no_match: ("ABCD", "ABCDE", vec![]),
         test_single_occurrence: ("XXXXXXXXXXXXXXXXXXPATTERNXXXXXXXXXXXXXXXXXX", "PATTERN", vec![18]),
         test_single_occurrence_with_noise: ("PATPATPATPATTERNPAT", "PATTERN", vec![9]),
+        test_unicode_byte_offsets: ("日本語の日本", "日本", vec![0, 12]),
+        test_unicode_text_ascii_pattern: ("naïve na", "na", vec![0, 7]),
     }
 }
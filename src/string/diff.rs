@@ -0,0 +1,969 @@
+//! Differences between two sequences, e.g. the lines or the words of two texts.
+//!
+//! [`myers_diff`] finds a shortest edit script with Myers' O(ND) greedy algorithm, which
+//! explores the diagonals of the edit graph by increasing number of edits `D`, following
+//! matching elements for free. It searches from both ends at once to split the problem in
+//! two around their meeting point, which only takes linear space. [`diff`] can also use two heuristics that often give diffs
+//! closer to what a human would write, at the cost of minimality:
+//!
+//! * patience diff only anchors on the elements that occur exactly once in both sequences,
+//!   keeping their longest increasing subsequence, and recurses between the anchors,
+//! * histogram diff anchors on the common elements that are the least frequent in the old
+//!   sequence, extended to the longest common run around them.
+//!
+//! Both fall back to Myers' algorithm where they find no anchor. The edit script can be
+//! grouped into [`Hunk`]s with some context, rendered in the unified format (and parsed back),
+//! shown side by side, and applied to the original sequence.
+//!
+//! References:
+//! - Myers, "An O(ND) Difference Algorithm and Its Variations"
+//! - [Patience diff](https://bramcohen.livejournal.com/73318.html)
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+/// A run of `len` elements kept, deleted from the old sequence or inserted from the new one.
+/// Both indices give the position of the run in each sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal {
+        old_index: usize,
+        new_index: usize,
+        len: usize,
+    },
+    Delete {
+        old_index: usize,
+        new_index: usize,
+        len: usize,
+    },
+    Insert {
+        old_index: usize,
+        new_index: usize,
+        len: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    Myers,
+    Patience,
+    Histogram,
+}
+
+/// A single element of an edit script, with its position in both sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+/// A shortest edit script from `old` to `new`, found with Myers' algorithm.
+pub fn myers_diff<T: Eq>(old: &[T], new: &[T]) -> Vec<DiffOp> {
+    let mut edits = Vec::new();
+    myers(old, new, 0, 0, &mut edits);
+    group(&edits)
+}
+
+/// An edit script from `old` to `new` found with `algorithm`.
+pub fn diff<T: Eq + Hash>(old: &[T], new: &[T], algorithm: DiffAlgorithm) -> Vec<DiffOp> {
+    let mut edits = Vec::new();
+    match algorithm {
+        DiffAlgorithm::Myers => myers(old, new, 0, 0, &mut edits),
+        DiffAlgorithm::Patience => patience(old, new, 0, 0, &mut edits),
+        DiffAlgorithm::Histogram => histogram(old, new, 0, 0, &mut edits),
+    }
+    group(&edits)
+}
+
+/// Groups consecutive edits of the same kind into runs, listing the deletions of every
+/// change before its insertions.
+fn group(edits: &[Edit]) -> Vec<DiffOp> {
+    let mut ops: Vec<DiffOp> = Vec::new();
+    let mut i = 0;
+    while i < edits.len() {
+        if let Edit::Equal(old_index, new_index) = edits[i] {
+            let len = edits[i..]
+                .iter()
+                .take_while(|edit| matches!(edit, Edit::Equal(..)))
+                .count();
+            ops.push(DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            });
+            i += len;
+            continue;
+        }
+        let change = edits[i..]
+            .iter()
+            .take_while(|edit| !matches!(edit, Edit::Equal(..)))
+            .count();
+        let (old_index, new_index) = match edits[i] {
+            Edit::Equal(o, n) | Edit::Delete(o, n) | Edit::Insert(o, n) => (o, n),
+        };
+        let deleted = edits[i..i + change]
+            .iter()
+            .filter(|edit| matches!(edit, Edit::Delete(..)))
+            .count();
+        if deleted > 0 {
+            ops.push(DiffOp::Delete {
+                old_index,
+                new_index,
+                len: deleted,
+            });
+        }
+        if change > deleted {
+            ops.push(DiffOp::Insert {
+                old_index: old_index + deleted,
+                new_index,
+                len: change - deleted,
+            });
+        }
+        i += change;
+    }
+    ops
+}
+
+/// Appends the edits turning `old` into `new`, which start at the given offsets of the whole
+/// sequences.
+///
+/// This is the linear space variant of the algorithm: the middle snake of a shortest path,
+/// found by searching from both corners at once, splits it into two paths with about half of
+/// the edits each, found recursively. It takes O((n + m) D) time and O(n + m) space.
+fn myers<T: Eq>(old: &[T], new: &[T], old_offset: usize, new_offset: usize, out: &mut Vec<Edit>) {
+    let (prefix, suffix) = trim(old, new, old_offset, new_offset, out);
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    let (old_start, new_start) = (old_offset + prefix, new_offset + prefix);
+    if old_middle.is_empty() {
+        out.extend((0..new_middle.len()).map(|j| Edit::Insert(old_start, new_start + j)));
+    } else if new_middle.is_empty() {
+        out.extend((0..old_middle.len()).map(|i| Edit::Delete(old_start + i, new_start)));
+    } else {
+        // Without a common prefix or suffix, a shortest path has at least two edits, so both
+        // halves have fewer of them.
+        let (x, y, len) = middle_snake(old_middle, new_middle);
+        myers(
+            &old_middle[..x],
+            &new_middle[..y],
+            old_start,
+            new_start,
+            out,
+        );
+        out.extend((0..len).map(|i| Edit::Equal(old_start + x + i, new_start + y + i)));
+        myers(
+            &old_middle[x + len..],
+            &new_middle[y + len..],
+            old_start + x + len,
+            new_start + y + len,
+            out,
+        );
+    }
+    push_suffix(old_offset + old.len(), new_offset + new.len(), suffix, out);
+}
+
+/// The middle snake of a shortest path from `(0, 0)` to `(n, m)`: the run of equal elements,
+/// possibly empty, where the searches from both corners meet. Returns its start and length.
+fn middle_snake<T: Eq>(old: &[T], new: &[T]) -> (usize, usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    // `forward[k + offset]` is the furthest x reached from (0, 0) on diagonal k = x - y, and
+    // `backward[k + offset]` the furthest distance travelled from (n, m) on the diagonal
+    // k = (n - x) - (m - y), i.e. delta - (x - y).
+    let offset = max + 1;
+    let mut forward = vec![0_isize; 2 * offset as usize + 1];
+    let mut backward = forward.clone();
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && forward[index - 1] < forward[index + 1]) {
+                forward[index + 1]
+            } else {
+                forward[index - 1] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[index] = x;
+            // With an odd delta, the paths meet while the forward one makes its last edit.
+            let reverse_k = delta - k;
+            if odd
+                && (1 - d..d).contains(&reverse_k)
+                && x + backward[(reverse_k + offset) as usize] >= n
+            {
+                return (start_x as usize, start_y as usize, (x - start_x) as usize);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && backward[index - 1] < backward[index + 1]) {
+                backward[index + 1]
+            } else {
+                backward[index - 1] + 1
+            };
+            let mut y = x - k;
+            let start_x = x;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[index] = x;
+            // With an even delta, they meet while the backward one makes its last edit.
+            let forward_k = delta - k;
+            if !odd
+                && (-d..=d).contains(&forward_k)
+                && x + forward[(forward_k + offset) as usize] >= n
+            {
+                return ((n - x) as usize, (m - y) as usize, (x - start_x) as usize);
+            }
+        }
+    }
+    unreachable!("the searches meet after at most n + m edits")
+}
+
+/// Splits off the common prefix and suffix, appending the prefix edits. Returns the lengths of
+/// both.
+fn trim<T: Eq>(
+    old: &[T],
+    new: &[T],
+    old_offset: usize,
+    new_offset: usize,
+    out: &mut Vec<Edit>,
+) -> (usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    out.extend((0..prefix).map(|i| Edit::Equal(old_offset + i, new_offset + i)));
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix, suffix)
+}
+
+fn push_suffix(old_end: usize, new_end: usize, suffix: usize, out: &mut Vec<Edit>) {
+    out.extend((0..suffix).map(|i| Edit::Equal(old_end - suffix + i, new_end - suffix + i)));
+}
+
+fn patience<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_offset: usize,
+    new_offset: usize,
+    out: &mut Vec<Edit>,
+) {
+    let (prefix, suffix) = trim(old, new, old_offset, new_offset, out);
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    let (old_start, new_start) = (old_offset + prefix, new_offset + prefix);
+
+    // The elements occurring once in each sequence, with their positions.
+    let mut counts: HashMap<&T, (usize, usize, usize, usize)> = HashMap::new();
+    for (i, element) in old_middle.iter().enumerate() {
+        let entry = counts.entry(element).or_insert((0, 0, 0, 0));
+        entry.0 += 1;
+        entry.1 = i;
+    }
+    for (j, element) in new_middle.iter().enumerate() {
+        if let Some(entry) = counts.get_mut(element) {
+            entry.2 += 1;
+            entry.3 = j;
+        }
+    }
+    let mut unique: Vec<(usize, usize)> = counts
+        .values()
+        .filter(|&&(old_count, _, new_count, _)| old_count == 1 && new_count == 1)
+        .map(|&(_, i, _, j)| (i, j))
+        .collect();
+    unique.sort_unstable();
+    let anchors = longest_increasing_subsequence(&unique);
+
+    if anchors.is_empty() {
+        myers(old_middle, new_middle, old_start, new_start, out);
+    } else {
+        let (mut i, mut j) = (0, 0);
+        for (anchor_i, anchor_j) in anchors {
+            patience(
+                &old_middle[i..anchor_i],
+                &new_middle[j..anchor_j],
+                old_start + i,
+                new_start + j,
+                out,
+            );
+            out.push(Edit::Equal(old_start + anchor_i, new_start + anchor_j));
+            (i, j) = (anchor_i + 1, anchor_j + 1);
+        }
+        patience(
+            &old_middle[i..],
+            &new_middle[j..],
+            old_start + i,
+            new_start + j,
+            out,
+        );
+    }
+    push_suffix(old_offset + old.len(), new_offset + new.len(), suffix, out);
+}
+
+/// The longest subsequence of `pairs` (sorted by their first element) whose second elements
+/// increase, found with patience sorting in O(n log n).
+fn longest_increasing_subsequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // `piles[p]` is the index of the pair with the smallest end of an increasing
+    // subsequence of length p + 1.
+    let mut piles: Vec<usize> = Vec::new();
+    let mut previous = vec![None; pairs.len()];
+    for (index, &(_, j)) in pairs.iter().enumerate() {
+        let pile = piles.partition_point(|&top| pairs[top].1 < j);
+        previous[index] = pile.checked_sub(1).map(|p| piles[p]);
+        if pile == piles.len() {
+            piles.push(index);
+        } else {
+            piles[pile] = index;
+        }
+    }
+    let mut sequence = Vec::with_capacity(piles.len());
+    let mut current = piles.last().copied();
+    while let Some(index) = current {
+        sequence.push(pairs[index]);
+        current = previous[index];
+    }
+    sequence.reverse();
+    sequence
+}
+
+/// Elements occurring more often than this in the old sequence are never used as anchors.
+const HISTOGRAM_MAX_OCCURRENCES: usize = 64;
+
+fn histogram<T: Eq + Hash>(
+    old: &[T],
+    new: &[T],
+    old_offset: usize,
+    new_offset: usize,
+    out: &mut Vec<Edit>,
+) {
+    let (prefix, suffix) = trim(old, new, old_offset, new_offset, out);
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    let (old_start, new_start) = (old_offset + prefix, new_offset + prefix);
+
+    let mut occurrences: HashMap<&T, Vec<usize>> = HashMap::new();
+    for (i, element) in old_middle.iter().enumerate() {
+        occurrences.entry(element).or_default().push(i);
+    }
+    // The best anchor: the rarest element, then the longest common run around it.
+    let mut best: Option<(usize, usize, usize, usize)> = None;
+    let mut j = 0;
+    while j < new_middle.len() {
+        let mut next_j = j + 1;
+        if let Some(positions) = occurrences.get(&new_middle[j]) {
+            let count = positions.len();
+            if count <= HISTOGRAM_MAX_OCCURRENCES {
+                for &i in positions {
+                    let before = old_middle[..i]
+                        .iter()
+                        .rev()
+                        .zip(new_middle[..j].iter().rev())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    let after = old_middle[i..]
+                        .iter()
+                        .zip(&new_middle[j..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    let len = before + after;
+                    let better = best.is_none_or(|(best_count, best_len, _, _)| {
+                        count < best_count || (count == best_count && len > best_len)
+                    });
+                    if better {
+                        best = Some((count, len, i - before, j - before));
+                    }
+                    // The elements of this run would only give the same run again.
+                    next_j = next_j.max(j + after);
+                }
+            }
+        }
+        j = next_j;
+    }
+
+    match best {
+        None => myers(old_middle, new_middle, old_start, new_start, out),
+        Some((_, len, i, j)) => {
+            histogram(
+                &old_middle[..i],
+                &new_middle[..j],
+                old_start,
+                new_start,
+                out,
+            );
+            out.extend((0..len).map(|k| Edit::Equal(old_start + i + k, new_start + j + k)));
+            histogram(
+                &old_middle[i + len..],
+                &new_middle[j + len..],
+                old_start + i + len,
+                new_start + j + len,
+                out,
+            );
+        }
+    }
+    push_suffix(old_offset + old.len(), new_offset + new.len(), suffix, out);
+}
+
+/// A line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine<T> {
+    Context(T),
+    Delete(T),
+    Insert(T),
+}
+
+/// A group of nearby changes with some surrounding context. Starts are 0-based indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk<T> {
+    pub old_start: usize,
+    pub new_start: usize,
+    pub lines: Vec<HunkLine<T>>,
+}
+
+impl<T> Hunk<T> {
+    pub fn old_len(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| !matches!(line, HunkLine::Insert(_)))
+            .count()
+    }
+
+    pub fn new_len(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| !matches!(line, HunkLine::Delete(_)))
+            .count()
+    }
+}
+
+/// Groups the changes of `ops` into hunks with up to `context` unchanged elements around
+/// them. Changes separated by at most `2 * context` unchanged elements share a hunk.
+pub fn hunks<T: Clone>(old: &[T], new: &[T], ops: &[DiffOp], context: usize) -> Vec<Hunk<T>> {
+    let mut edits = Vec::new();
+    for op in ops {
+        match *op {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => edits.extend((0..len).map(|k| Edit::Equal(old_index + k, new_index + k))),
+            DiffOp::Delete {
+                old_index,
+                new_index,
+                len,
+            } => edits.extend((0..len).map(|k| Edit::Delete(old_index + k, new_index))),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                len,
+            } => edits.extend((0..len).map(|k| Edit::Insert(old_index, new_index + k))),
+        }
+    }
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&i| !matches!(edits[i], Edit::Equal(..)))
+        .collect();
+
+    let mut hunks = Vec::new();
+    let mut c = 0;
+    while c < changes.len() {
+        let mut last = c;
+        while last + 1 < changes.len() && changes[last + 1] - changes[last] - 1 <= 2 * context {
+            last += 1;
+        }
+        let start = changes[c].saturating_sub(context);
+        let end = (changes[last] + context + 1).min(edits.len());
+        let (old_start, new_start) = match edits[start] {
+            Edit::Equal(o, n) | Edit::Delete(o, n) | Edit::Insert(o, n) => (o, n),
+        };
+        let lines = edits[start..end]
+            .iter()
+            .map(|edit| match *edit {
+                Edit::Equal(o, _) => HunkLine::Context(old[o].clone()),
+                Edit::Delete(o, _) => HunkLine::Delete(old[o].clone()),
+                Edit::Insert(_, n) => HunkLine::Insert(new[n].clone()),
+            })
+            .collect();
+        hunks.push(Hunk {
+            old_start,
+            new_start,
+            lines,
+        });
+        c = last + 1;
+    }
+    hunks
+}
+
+/// The line number printed in a hunk header: 1-based, or the line before for an empty range.
+fn header_line(start: usize, len: usize) -> usize {
+    if len == 0 {
+        start
+    } else {
+        start + 1
+    }
+}
+
+/// Renders hunks in the unified diff format, one element per line.
+pub fn unified_diff<T: Display>(hunks: &[Hunk<T>]) -> String {
+    let mut output = String::new();
+    for hunk in hunks {
+        let (old_len, new_len) = (hunk.old_len(), hunk.new_len());
+        writeln!(
+            output,
+            "@@ -{},{} +{},{} @@",
+            header_line(hunk.old_start, old_len),
+            old_len,
+            header_line(hunk.new_start, new_len),
+            new_len
+        )
+        .unwrap();
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(x) => writeln!(output, " {x}"),
+                HunkLine::Delete(x) => writeln!(output, "-{x}"),
+                HunkLine::Insert(x) => writeln!(output, "+{x}"),
+            }
+            .unwrap();
+        }
+    }
+    output
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PatchError {
+    /// A line of the unified diff (0-based) could not be parsed, or is missing at the end of
+    /// a truncated hunk.
+    InvalidLine(usize),
+    /// The hunk with the given index does not match the original sequence.
+    Mismatch(usize),
+}
+
+/// Parses the hunks of a unified diff. The line counts of the hunk headers tell where each
+/// hunk ends, and the lines between hunks are skipped: file headers (`--- a/x`, `+++ b/x`,
+/// `diff --git ...`), so the hunks of all the files of a patch are returned in order. The
+/// `\ No newline at end of file` markers are skipped too.
+pub fn parse_unified_diff(text: &str) -> Result<Vec<Hunk<String>>, PatchError> {
+    let mut hunks: Vec<Hunk<String>> = Vec::new();
+    // The numbers of old and new lines the last hunk still has to cover.
+    let (mut old_left, mut new_left) = (0, 0);
+    for (number, line) in text.lines().enumerate() {
+        if line.starts_with('\\') {
+            continue;
+        }
+        if old_left == 0 && new_left == 0 {
+            let Some(header) = line.strip_prefix("@@ ") else {
+                // File headers and other lines between hunks.
+                continue;
+            };
+            let parse = |range: Option<&str>, sign: char| -> Option<(usize, usize)> {
+                let range = range?.strip_prefix(sign)?;
+                let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+                let (start, len): (usize, usize) = (start.parse().ok()?, len.parse().ok()?);
+                if len == 0 {
+                    Some((start, len))
+                } else {
+                    Some((start.checked_sub(1)?, len))
+                }
+            };
+            let mut ranges = header.split(' ');
+            let old_range = parse(ranges.next(), '-');
+            let new_range = parse(ranges.next(), '+');
+            match (old_range, new_range) {
+                (Some((old_start, old_len)), Some((new_start, new_len))) => {
+                    hunks.push(Hunk {
+                        old_start,
+                        new_start,
+                        lines: Vec::new(),
+                    });
+                    (old_left, new_left) = (old_len, new_len);
+                }
+                _ => return Err(PatchError::InvalidLine(number)),
+            }
+            continue;
+        }
+        let content = line.get(1..).unwrap_or("").to_string();
+        let (hunk_line, old_used, new_used) = match line.chars().next() {
+            Some(' ') | None => (HunkLine::Context(content), 1, 1),
+            Some('-') => (HunkLine::Delete(content), 1, 0),
+            Some('+') => (HunkLine::Insert(content), 0, 1),
+            Some(_) => return Err(PatchError::InvalidLine(number)),
+        };
+        match (
+            old_left.checked_sub(old_used),
+            new_left.checked_sub(new_used),
+            hunks.last_mut(),
+        ) {
+            (Some(old), Some(new), Some(hunk)) => {
+                hunk.lines.push(hunk_line);
+                (old_left, new_left) = (old, new);
+            }
+            _ => return Err(PatchError::InvalidLine(number)),
+        }
+    }
+    if old_left > 0 || new_left > 0 {
+        return Err(PatchError::InvalidLine(text.lines().count()));
+    }
+    Ok(hunks)
+}
+
+/// Applies hunks (sorted and non-overlapping) to `original`, checking that their context and
+/// deleted elements match it.
+pub fn apply_patch<T: Eq + Clone>(original: &[T], hunks: &[Hunk<T>]) -> Result<Vec<T>, PatchError> {
+    let mut patched = Vec::with_capacity(original.len());
+    let mut position = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        if hunk.old_start < position
+            || hunk
+                .old_start
+                .checked_add(hunk.old_len())
+                .is_none_or(|end| end > original.len())
+        {
+            return Err(PatchError::Mismatch(index));
+        }
+        patched.extend_from_slice(&original[position..hunk.old_start]);
+        position = hunk.old_start;
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(x) | HunkLine::Delete(x) => {
+                    if original[position] != *x {
+                        return Err(PatchError::Mismatch(index));
+                    }
+                    if matches!(line, HunkLine::Context(_)) {
+                        patched.push(x.clone());
+                    }
+                    position += 1;
+                }
+                HunkLine::Insert(x) => patched.push(x.clone()),
+            }
+        }
+    }
+    patched.extend_from_slice(&original[position..]);
+    Ok(patched)
+}
+
+/// Shows both sequences in two columns, the old one truncated or padded to `width`
+/// characters. The gutter marks changed (`|`), deleted (`<`) and inserted (`>`) elements.
+pub fn side_by_side<T: Display>(old: &[T], new: &[T], ops: &[DiffOp], width: usize) -> String {
+    let mut output = String::new();
+    let mut row = |left: Option<&T>, marker: char, right: Option<&T>| {
+        let left: String = left.map_or(String::new(), |x| {
+            x.to_string().chars().take(width).collect()
+        });
+        let right = right.map_or(String::new(), ToString::to_string);
+        let line = format!("{left:<width$} {marker} {right}");
+        output.push_str(line.trim_end());
+        output.push('\n');
+    };
+    let mut i = 0;
+    while i < ops.len() {
+        match ops[i] {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => {
+                for k in 0..len {
+                    row(Some(&old[old_index + k]), ' ', Some(&new[new_index + k]));
+                }
+            }
+            DiffOp::Delete { old_index, len, .. } => {
+                // Pair the deletions with the insertions that replace them.
+                let (new_index, inserted) = match ops.get(i + 1) {
+                    Some(&DiffOp::Insert { new_index, len, .. }) => {
+                        i += 1;
+                        (new_index, len)
+                    }
+                    _ => (0, 0),
+                };
+                for k in 0..len.max(inserted) {
+                    let left = (k < len).then(|| &old[old_index + k]);
+                    let right = (k < inserted).then(|| &new[new_index + k]);
+                    let marker = match (left, right) {
+                        (Some(_), Some(_)) => '|',
+                        (Some(_), None) => '<',
+                        _ => '>',
+                    };
+                    row(left, marker, right);
+                }
+            }
+            DiffOp::Insert { new_index, len, .. } => {
+                for k in 0..len {
+                    row(None, '>', Some(&new[new_index + k]));
+                }
+            }
+        }
+        i += 1;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALGORITHMS: [DiffAlgorithm; 3] = [
+        DiffAlgorithm::Myers,
+        DiffAlgorithm::Patience,
+        DiffAlgorithm::Histogram,
+    ];
+
+    /// Rebuilds the new sequence from the old one and the edit script.
+    fn rebuild<T: Clone + Eq + std::fmt::Debug>(old: &[T], new: &[T], ops: &[DiffOp]) -> Vec<T> {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        for op in ops {
+            match *op {
+                DiffOp::Equal {
+                    old_index,
+                    new_index,
+                    len,
+                } => {
+                    assert_eq!((old_index, new_index), (i, j));
+                    assert_eq!(old[i..i + len], new[j..j + len]);
+                    result.extend_from_slice(&old[i..i + len]);
+                    (i, j) = (i + len, j + len);
+                }
+                DiffOp::Delete {
+                    old_index,
+                    new_index,
+                    len,
+                } => {
+                    assert_eq!((old_index, new_index), (i, j));
+                    i += len;
+                }
+                DiffOp::Insert {
+                    old_index,
+                    new_index,
+                    len,
+                } => {
+                    assert_eq!((old_index, new_index), (i, j));
+                    result.extend_from_slice(&new[j..j + len]);
+                    j += len;
+                }
+            }
+        }
+        assert_eq!((i, j), (old.len(), new.len()));
+        result
+    }
+
+    fn edit_count(ops: &[DiffOp]) -> usize {
+        ops.iter()
+            .map(|op| match *op {
+                DiffOp::Equal { .. } => 0,
+                DiffOp::Delete { len, .. } | DiffOp::Insert { len, .. } => len,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn myers_is_minimal() {
+        let (old, new) = (b"ABCABBA", b"CBABAC");
+        let ops = myers_diff(old, new);
+        assert_eq!(edit_count(&ops), 5);
+        assert_eq!(rebuild(old, new, &ops), new);
+        assert_eq!(myers_diff::<u8>(b"", b""), vec![]);
+        assert_eq!(
+            myers_diff(b"", b"ab"),
+            vec![DiffOp::Insert {
+                old_index: 0,
+                new_index: 0,
+                len: 2
+            }]
+        );
+        assert_eq!(
+            myers_diff(b"abc", b"abc"),
+            vec![DiffOp::Equal {
+                old_index: 0,
+                new_index: 0,
+                len: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn all_algorithms_rebuild_the_new_sequence() {
+        let mut state = 47_u64;
+        let mut random = |len: usize| -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    b"abcdef"[(state >> 33) as usize % 6]
+                })
+                .collect()
+        };
+        for (n, m) in [(0, 5), (5, 0), (10, 10), (50, 40), (200, 210)] {
+            let (old, new) = (random(n), random(m));
+            let minimal = edit_count(&myers_diff(&old, &new));
+            // The LCS length gives the minimal number of edits.
+            let lcs = {
+                let mut table = vec![vec![0; m + 1]; n + 1];
+                for i in 0..n {
+                    for j in 0..m {
+                        table[i + 1][j + 1] = if old[i] == new[j] {
+                            table[i][j] + 1
+                        } else {
+                            table[i][j + 1].max(table[i + 1][j])
+                        };
+                    }
+                }
+                table[n][m]
+            };
+            assert_eq!(minimal, n + m - 2 * lcs);
+            for algorithm in ALGORITHMS {
+                let ops = diff(&old, &new, algorithm);
+                assert_eq!(rebuild(&old, &new, &ops), new);
+                assert!(edit_count(&ops) >= minimal);
+            }
+        }
+    }
+
+    #[test]
+    fn patience_anchors_on_unique_lines() {
+        let old = ["fn a() {", "  one", "}", "", "fn b() {", "  two", "}"];
+        let new = ["fn b() {", "  two", "}", "", "fn a() {", "  one", "}"];
+        let ops = diff(&old, &new, DiffAlgorithm::Patience);
+        assert_eq!(rebuild(&old, &new, &ops), new);
+        // The unique lines "fn a() {" and "fn b() {" cannot both be kept; patience keeps the
+        // first increasing run of anchors.
+        assert_eq!(
+            ops[0],
+            DiffOp::Delete {
+                old_index: 0,
+                new_index: 0,
+                len: 4
+            }
+        );
+    }
+
+    #[test]
+    fn unified_format_round_trip() {
+        let old: Vec<&str> = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj".lines().collect();
+        let new: Vec<&str> = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk".lines().collect();
+        let ops = diff(&old, &new, DiffAlgorithm::Histogram);
+        let hunks = hunks(&old, &new, &ops, 2);
+        assert_eq!(hunks.len(), 2);
+        let text = unified_diff(&hunks);
+        assert_eq!(
+            text,
+            "@@ -1,4 +1,4 @@\n a\n-b\n+B\n c\n d\n@@ -9,2 +9,3 @@\n i\n j\n+k\n"
+        );
+        assert_eq!(apply_patch(&old, &hunks).unwrap(), new);
+
+        let parsed = parse_unified_diff(&format!("--- old\n+++ new\n{text}")).unwrap();
+        let old_owned: Vec<String> = old.iter().map(ToString::to_string).collect();
+        let new_owned: Vec<String> = new.iter().map(ToString::to_string).collect();
+        assert_eq!(apply_patch(&old_owned, &parsed).unwrap(), new_owned);
+
+        // With more context, both changes share one hunk.
+        assert_eq!(super::hunks(&old, &new, &ops, 4).len(), 1);
+        // Empty ranges refer to the line before them.
+        let hunks = super::hunks(&[] as &[&str], &["x"], &myers_diff(&[], &["x"]), 3);
+        assert_eq!(unified_diff(&hunks), "@@ -0,0 +1,1 @@\n+x\n");
+    }
+
+    #[test]
+    fn patch_errors() {
+        let old = ["a", "b", "c"];
+        let new = ["a", "x", "c"];
+        let hunks = hunks(&old, &new, &myers_diff(&old, &new), 1);
+        assert_eq!(
+            apply_patch(&["a", "y", "c"], &hunks),
+            Err(PatchError::Mismatch(0))
+        );
+        assert_eq!(apply_patch(&["a"], &hunks), Err(PatchError::Mismatch(0)));
+        assert_eq!(
+            parse_unified_diff("@@ -1,2 +1,2 @@\n a\n*b\n"),
+            Err(PatchError::InvalidLine(2))
+        );
+        assert_eq!(
+            parse_unified_diff("@@ -x +1 @@\n"),
+            Err(PatchError::InvalidLine(0))
+        );
+        // A hunk longer or shorter than its header says.
+        assert_eq!(
+            parse_unified_diff("@@ -1,1 +1,1 @@\n-a\n-b\n+c\n"),
+            Err(PatchError::InvalidLine(2))
+        );
+        assert_eq!(
+            parse_unified_diff("@@ -1,2 +1,2 @@\n a\n"),
+            Err(PatchError::InvalidLine(2))
+        );
+        let hunk = Hunk {
+            old_start: usize::MAX,
+            new_start: 0,
+            lines: vec![HunkLine::Delete("a")],
+        };
+        assert_eq!(apply_patch(&["a"], &[hunk]), Err(PatchError::Mismatch(0)));
+    }
+
+    #[test]
+    fn parse_multi_file_patches() {
+        // The deleted line `-- x` looks like a file header, only the counts tell it apart.
+        let patch = "\
+diff --git a/one b/one
+index 1234567..89abcde 100644
+--- a/one
++++ b/one
+@@ -1,2 +1,2 @@
+--- x
++y
+ z
+diff --git a/two b/two
+--- a/two
++++ b/two
+@@ -1 +1 @@
+-old
+\\ No newline at end of file
++new
+\\ No newline at end of file
+";
+        let hunks = parse_unified_diff(patch).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            hunks[0].lines,
+            [
+                HunkLine::Delete("-- x".to_string()),
+                HunkLine::Insert("y".to_string()),
+                HunkLine::Context("z".to_string()),
+            ]
+        );
+        let old = ["old".to_string()];
+        assert_eq!(apply_patch(&old, &hunks[1..]).unwrap(), ["new"]);
+    }
+
+    #[test]
+    fn side_by_side_view() {
+        let old = ["keep", "old", "gone"];
+        let new = ["keep", "new", "added", "more"];
+        let ops = myers_diff(&old, &new);
+        let view = side_by_side(&old, &new, &ops, 6);
+        assert_eq!(
+            view,
+            "keep     keep\nold    | new\ngone   | added\n       > more\n"
+        );
+        let words: Vec<&str> = "the quick brown fox".split(' ').collect();
+        let other: Vec<&str> = "the slow brown fox".split(' ').collect();
+        let view = side_by_side(&words, &other, &myers_diff(&words, &other), 5);
+        assert_eq!(
+            view,
+            "the     the\nquick | slow\nbrown   brown\nfox     fox\n"
+        );
+    }
+}
@@ -0,0 +1,607 @@
+//! The edit distance family: the cost of the cheapest sequence of single-symbol edits turning
+//! one sequence into another, and the edits themselves.
+//!
+//! All the functions work on slices of any comparable symbols, e.g. the `chars` of a string
+//! collected into a vector, or its bytes:
+//!
+//! * [`edit_script`] computes the Levenshtein alignment (insertions, deletions and
+//!   substitutions) under arbitrary per-symbol [`EditCosts`], and [`damerau_edit_script`] also
+//!   allows transpositions of adjacent symbols (the optimal string alignment distance).
+//! * [`edit_distance`] and [`damerau_levenshtein_distance`] only compute the unit-cost
+//!   distance, the latter allowing any number of edits between transposed symbols.
+//! * [`edit_distance_within`] decides whether the distance is at most `k` in O(k n) time by
+//!   only looking at a band of `2k + 1` diagonals.
+//! * [`myers_edit_distance`] computes the distance with Myers' bit-parallel algorithm, which
+//!   handles 64 cells of a column per machine word, for long sequences.
+//!
+//! References:
+//! - [Wikipedia](https://en.wikipedia.org/wiki/Levenshtein_distance)
+//! - [Wikipedia](https://en.wikipedia.org/wiki/Damerau%E2%80%93Levenshtein_distance)
+//! - Hyyrö, "A bit-vector algorithm for computing Levenshtein and Damerau edit distances"
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// One step of an edit script. Positions refer to the source and target sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditOp {
+    /// The symbols are equal and kept.
+    Keep {
+        source: usize,
+        target: usize,
+    },
+    Substitute {
+        source: usize,
+        target: usize,
+    },
+    /// Inserts the target symbol at `target`.
+    Insert {
+        target: usize,
+    },
+    /// Deletes the source symbol at `source`.
+    Delete {
+        source: usize,
+    },
+    /// Swaps the source symbols at `source` and `source + 1` into the target symbols at
+    /// `target` and `target + 1`.
+    Transpose {
+        source: usize,
+        target: usize,
+    },
+}
+
+/// An optimal edit script and its total cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+    pub distance: usize,
+    pub ops: Vec<EditOp>,
+}
+
+/// The costs of the edits. Keeping an equal symbol always costs nothing.
+pub trait EditCosts<T> {
+    fn insert(&self, symbol: &T) -> usize;
+    fn delete(&self, symbol: &T) -> usize;
+    fn substitute(&self, from: &T, to: &T) -> usize;
+
+    /// The cost of turning `first, second` into `second, first`. Defaults to the cost of a
+    /// single substitution.
+    fn transpose(&self, first: &T, second: &T) -> usize {
+        self.substitute(first, second)
+    }
+}
+
+/// Every edit costs 1.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnitCosts;
+
+impl<T> EditCosts<T> for UnitCosts {
+    fn insert(&self, _: &T) -> usize {
+        1
+    }
+
+    fn delete(&self, _: &T) -> usize {
+        1
+    }
+
+    fn substitute(&self, _: &T, _: &T) -> usize {
+        1
+    }
+}
+
+/// The Levenshtein alignment of `source` and `target` with the lowest total cost, in O(nm)
+/// time and space. Among equally cheap scripts, keeping and substituting are preferred over
+/// deleting, and deleting over inserting, from the end of the sequences.
+pub fn edit_script<T: Eq, C: EditCosts<T>>(source: &[T], target: &[T], costs: &C) -> Alignment {
+    align(source, target, costs, false)
+}
+
+/// Like [`edit_script`], but also allows swapping two adjacent symbols, as long as no other
+/// edit touches them (the optimal string alignment distance).
+pub fn damerau_edit_script<T: Eq, C: EditCosts<T>>(
+    source: &[T],
+    target: &[T],
+    costs: &C,
+) -> Alignment {
+    align(source, target, costs, true)
+}
+
+fn align<T: Eq, C: EditCosts<T>>(
+    source: &[T],
+    target: &[T],
+    costs: &C,
+    transpositions: bool,
+) -> Alignment {
+    let (n, m) = (source.len(), target.len());
+    let width = m + 1;
+    // `table[i * width + j]` is the distance between source[..i] and target[..j].
+    let mut table = vec![0; (n + 1) * width];
+    for j in 1..=m {
+        table[j] = table[j - 1] + costs.insert(&target[j - 1]);
+    }
+    for i in 1..=n {
+        table[i * width] = table[(i - 1) * width] + costs.delete(&source[i - 1]);
+        for j in 1..=m {
+            let (a, b) = (&source[i - 1], &target[j - 1]);
+            let diagonal = table[(i - 1) * width + j - 1];
+            let mut best = if a == b {
+                diagonal
+            } else {
+                diagonal + costs.substitute(a, b)
+            };
+            best = best.min(table[(i - 1) * width + j] + costs.delete(a));
+            best = best.min(table[i * width + j - 1] + costs.insert(b));
+            if transpositions && is_transposition(source, target, i, j) {
+                best = best.min(table[(i - 2) * width + j - 2] + costs.transpose(b, a));
+            }
+            table[i * width + j] = best;
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        let current = table[i * width + j];
+        if i > 0 && j > 0 {
+            let (a, b) = (&source[i - 1], &target[j - 1]);
+            let diagonal = table[(i - 1) * width + j - 1];
+            if a == b && current == diagonal {
+                ops.push(EditOp::Keep {
+                    source: i - 1,
+                    target: j - 1,
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+            if a != b && current == diagonal + costs.substitute(a, b) {
+                ops.push(EditOp::Substitute {
+                    source: i - 1,
+                    target: j - 1,
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+            if transpositions
+                && is_transposition(source, target, i, j)
+                && current == table[(i - 2) * width + j - 2] + costs.transpose(b, a)
+            {
+                ops.push(EditOp::Transpose {
+                    source: i - 2,
+                    target: j - 2,
+                });
+                i -= 2;
+                j -= 2;
+                continue;
+            }
+        }
+        if i > 0 && current == table[(i - 1) * width + j] + costs.delete(&source[i - 1]) {
+            ops.push(EditOp::Delete { source: i - 1 });
+            i -= 1;
+        } else {
+            ops.push(EditOp::Insert { target: j - 1 });
+            j -= 1;
+        }
+    }
+    ops.reverse();
+    Alignment {
+        distance: table[n * width + m],
+        ops,
+    }
+}
+
+/// Whether the last two symbols of `source[..i]` are those of `target[..j]` swapped.
+fn is_transposition<T: Eq>(source: &[T], target: &[T], i: usize, j: usize) -> bool {
+    i > 1
+        && j > 1
+        && source[i - 1] == target[j - 2]
+        && source[i - 2] == target[j - 1]
+        && source[i - 1] != source[i - 2]
+}
+
+/// The unit-cost Levenshtein distance, in O(nm) time and O(m) space.
+pub fn edit_distance<T: Eq>(source: &[T], target: &[T]) -> usize {
+    let mut row: Vec<usize> = (0..=target.len()).collect();
+    for (i, a) in source.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in target.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[target.len()]
+}
+
+/// The unrestricted Damerau-Levenshtein distance, where transposed symbols may also be edited
+/// in between (so "ca" to "abc" costs 2), computed with Lowrance and Wagner's algorithm in
+/// O(nm) time.
+pub fn damerau_levenshtein_distance<T: Eq + Hash>(source: &[T], target: &[T]) -> usize {
+    let (n, m) = (source.len(), target.len());
+    let infinity = n + m;
+    let width = m + 2;
+    // The table is shifted by one row and column, holding `infinity` as a border.
+    let mut table = vec![0; (n + 2) * width];
+    table[0] = infinity;
+    for i in 0..=n {
+        table[(i + 1) * width] = infinity;
+        table[(i + 1) * width + 1] = i;
+    }
+    for j in 0..=m {
+        table[j + 1] = infinity;
+        table[width + j + 1] = j;
+    }
+    // The last row where each symbol occurred in the source.
+    let mut last_row: HashMap<&T, usize> = HashMap::new();
+    for i in 1..=n {
+        // The last column of this row where the target matched the source symbol.
+        let mut last_match_column = 0;
+        for j in 1..=m {
+            let k = last_row.get(&target[j - 1]).copied().unwrap_or(0);
+            let l = last_match_column;
+            let cost = if source[i - 1] == target[j - 1] {
+                last_match_column = j;
+                0
+            } else {
+                1
+            };
+            table[(i + 1) * width + j + 1] = (table[i * width + j] + cost)
+                .min(table[(i + 1) * width + j] + 1)
+                .min(table[i * width + j + 1] + 1)
+                .min(table[k * width + l] + (i - k - 1) + 1 + (j - l - 1));
+        }
+        last_row.insert(&source[i - 1], i);
+    }
+    table[(n + 1) * width + m + 1]
+}
+
+/// The unit-cost Levenshtein distance if it is at most `k`, computed in O(k min(n, m)) time
+/// by ignoring the cells more than `k` diagonals away from the main one, which cannot lead to
+/// a distance of at most `k`.
+pub fn edit_distance_within<T: Eq>(source: &[T], target: &[T], k: usize) -> Option<usize> {
+    let (n, m) = (source.len(), target.len());
+    if n.abs_diff(m) > k {
+        return None;
+    }
+    // Values above `k` are all equivalent, so they are capped to keep the band arithmetic
+    // simple.
+    let cap = k + 1;
+    let mut previous = vec![cap; m + 1];
+    let mut current = vec![cap; m + 1];
+    for (j, cell) in previous.iter_mut().enumerate().take(k.min(m) + 1) {
+        *cell = j;
+    }
+    for i in 1..=n {
+        let low = i.saturating_sub(k).max(1);
+        let high = (i + k).min(m);
+        // Only the band and its two neighbours are written, so that a row costs O(k).
+        current[low - 1] = if low == 1 && i <= k { i } else { cap };
+        if high < m {
+            current[high + 1] = cap;
+        }
+        let mut row_min = current[low - 1];
+        for j in low..=high {
+            let substitution = previous[j - 1] + usize::from(source[i - 1] != target[j - 1]);
+            current[j] = substitution
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(cap);
+            row_min = row_min.min(current[j]);
+        }
+        if row_min > k {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[m]).filter(|&d| d <= k)
+}
+
+/// The unit-cost Levenshtein distance with Myers' bit-parallel algorithm, in O(n ⌈m / 64⌉)
+/// time where `m` is the length of `source`.
+///
+/// The columns of the dynamic programming table are encoded as bit vectors of their vertical
+/// differences (+1 or -1 from one cell to the one below), and a whole column is computed
+/// from the previous one with a few word operations per 64 cells.
+pub fn myers_edit_distance<T: Eq + Hash>(source: &[T], target: &[T]) -> usize {
+    let m = source.len();
+    if m == 0 {
+        return target.len();
+    }
+    let blocks = m.div_ceil(64);
+    // The positions of each symbol in the source, as one bit vector per block.
+    let mut peq: HashMap<&T, Vec<u64>> = HashMap::new();
+    for (i, symbol) in source.iter().enumerate() {
+        peq.entry(symbol).or_insert_with(|| vec![0; blocks])[i / 64] |= 1 << (i % 64);
+    }
+    let no_match = vec![0; blocks];
+    let last_bit = 1 << ((m - 1) % 64);
+
+    // Initially every vertical difference is +1 (the first column is 0, 1, 2, ...).
+    let mut positive = vec![!0_u64; blocks];
+    let mut negative = vec![0_u64; blocks];
+    let mut score = m;
+    for symbol in target {
+        let eq = peq.get(symbol).unwrap_or(&no_match);
+        // The horizontal difference entering the top of the first block: the first row is
+        // 0, 1, 2, ... so it is always +1.
+        let mut carry: i8 = 1;
+        for b in 0..blocks {
+            let high = if b + 1 == blocks { last_bit } else { 1 << 63 };
+            let (pv, mv) = (positive[b], negative[b]);
+            let mut eq = eq[b];
+            let xv = eq | mv;
+            if carry < 0 {
+                eq |= 1;
+            }
+            let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+            let mut ph = mv | !(xh | pv);
+            let mut mh = pv & xh;
+            let carry_out = if ph & high != 0 {
+                1
+            } else if mh & high != 0 {
+                -1
+            } else {
+                0
+            };
+            ph <<= 1;
+            mh <<= 1;
+            if carry < 0 {
+                mh |= 1;
+            } else if carry > 0 {
+                ph |= 1;
+            }
+            positive[b] = mh | !(xv | ph);
+            negative[b] = ph & xv;
+            carry = carry_out;
+        }
+        // The carry out of the last block is the change of the bottom cell.
+        score = score.wrapping_add_signed(isize::from(carry));
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    /// Applies an edit script, checking that it is consistent with both sequences.
+    fn apply<T: Eq + Clone>(source: &[T], target: &[T], ops: &[EditOp]) -> Vec<T> {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        for op in ops {
+            match *op {
+                EditOp::Keep {
+                    source: s,
+                    target: t,
+                } => {
+                    assert_eq!((s, t), (i, j));
+                    assert!(source[s] == target[t]);
+                    result.push(source[s].clone());
+                    (i, j) = (i + 1, j + 1);
+                }
+                EditOp::Substitute {
+                    source: s,
+                    target: t,
+                } => {
+                    assert_eq!((s, t), (i, j));
+                    result.push(target[t].clone());
+                    (i, j) = (i + 1, j + 1);
+                }
+                EditOp::Insert { target: t } => {
+                    assert_eq!(t, j);
+                    result.push(target[t].clone());
+                    j += 1;
+                }
+                EditOp::Delete { source: s } => {
+                    assert_eq!(s, i);
+                    i += 1;
+                }
+                EditOp::Transpose {
+                    source: s,
+                    target: t,
+                } => {
+                    assert_eq!((s, t), (i, j));
+                    result.push(source[s + 1].clone());
+                    result.push(source[s].clone());
+                    (i, j) = (i + 2, j + 2);
+                }
+            }
+        }
+        assert_eq!((i, j), (source.len(), target.len()));
+        result
+    }
+
+    const CASES: &[(&str, &str, usize)] = &[
+        ("", "", 0),
+        ("", "Rust", 4),
+        ("horse", "ros", 3),
+        ("tan", "elephant", 6),
+        ("execute", "intention", 8),
+        ("kitten", "sitting", 3),
+        ("ca", "abc", 3),
+        ("résumé", "resume", 2),
+    ];
+
+    #[test]
+    fn levenshtein_distances() {
+        for &(a, b, expected) in CASES {
+            let (a, b) = (chars(a), chars(b));
+            assert_eq!(edit_distance(&a, &b), expected);
+            assert_eq!(edit_distance(&b, &a), expected);
+            assert_eq!(myers_edit_distance(&a, &b), expected);
+            assert_eq!(myers_edit_distance(&b, &a), expected);
+            let alignment = edit_script(&a, &b, &UnitCosts);
+            assert_eq!(alignment.distance, expected);
+            assert_eq!(apply(&a, &b, &alignment.ops), b);
+            let edits = alignment
+                .ops
+                .iter()
+                .filter(|op| !matches!(op, EditOp::Keep { .. }))
+                .count();
+            assert_eq!(edits, expected);
+        }
+    }
+
+    #[test]
+    fn kitten_alignment() {
+        let alignment = edit_script(b"kitten", b"sitting", &UnitCosts);
+        assert_eq!(
+            alignment.ops,
+            vec![
+                EditOp::Substitute {
+                    source: 0,
+                    target: 0
+                },
+                EditOp::Keep {
+                    source: 1,
+                    target: 1
+                },
+                EditOp::Keep {
+                    source: 2,
+                    target: 2
+                },
+                EditOp::Keep {
+                    source: 3,
+                    target: 3
+                },
+                EditOp::Substitute {
+                    source: 4,
+                    target: 4
+                },
+                EditOp::Keep {
+                    source: 5,
+                    target: 5
+                },
+                EditOp::Insert { target: 6 },
+            ]
+        );
+    }
+
+    #[test]
+    fn damerau_distances() {
+        let cases = [
+            ("ca", "abc", 3, 2),
+            ("abcdef", "abdcef", 1, 1),
+            ("teh", "the", 1, 1),
+            ("abc", "abc", 0, 0),
+            ("", "ab", 2, 2),
+            ("a cat", "an act", 2, 2),
+        ];
+        for (a, b, restricted, unrestricted) in cases {
+            let (a, b) = (chars(a), chars(b));
+            let alignment = damerau_edit_script(&a, &b, &UnitCosts);
+            assert_eq!(alignment.distance, restricted);
+            assert_eq!(apply(&a, &b, &alignment.ops), b);
+            assert_eq!(damerau_levenshtein_distance(&a, &b), unrestricted);
+            assert_eq!(damerau_levenshtein_distance(&b, &a), unrestricted);
+        }
+        let alignment = damerau_edit_script(b"teh", b"the", &UnitCosts);
+        assert_eq!(
+            alignment.ops,
+            vec![
+                EditOp::Keep {
+                    source: 0,
+                    target: 0
+                },
+                EditOp::Transpose {
+                    source: 1,
+                    target: 1
+                },
+            ]
+        );
+    }
+
+    /// Substituting neighbours on a QWERTY row costs 1, other edits cost 3.
+    struct KeyboardCosts;
+
+    impl EditCosts<u8> for KeyboardCosts {
+        fn insert(&self, _: &u8) -> usize {
+            3
+        }
+
+        fn delete(&self, _: &u8) -> usize {
+            3
+        }
+
+        fn substitute(&self, from: &u8, to: &u8) -> usize {
+            let row = b"qwertyuiop";
+            match (
+                row.iter().position(|c| c == from),
+                row.iter().position(|c| c == to),
+            ) {
+                (Some(x), Some(y)) if x.abs_diff(y) == 1 => 1,
+                _ => 3,
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_costs() {
+        let alignment = edit_script(b"qeerty", b"wrerty", &KeyboardCosts);
+        assert_eq!(alignment.distance, 2);
+        // Deleting is cheaper than two far substitutions.
+        let alignment = edit_script(b"pat", b"at", &KeyboardCosts);
+        assert_eq!(alignment.distance, 3);
+        assert_eq!(alignment.ops[0], EditOp::Delete { source: 0 });
+        assert_eq!(apply(b"pat", b"at", &alignment.ops), b"at");
+        assert_eq!(edit_script(b"pot", b"pit", &KeyboardCosts).distance, 1);
+    }
+
+    #[test]
+    fn bounded_distance() {
+        for &(a, b, expected) in CASES {
+            let (a, b) = (chars(a), chars(b));
+            for k in 0..10 {
+                let within = edit_distance_within(&a, &b, k);
+                assert_eq!(within, Some(expected).filter(|&d| d <= k), "k = {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn random_sequences_agree() {
+        let mut state = 46_u64;
+        let mut random = |len: usize, alphabet: u64| -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    b'a' + ((state >> 33) % alphabet) as u8
+                })
+                .collect()
+        };
+        for (n, m) in [
+            (1, 1),
+            (10, 12),
+            (63, 64),
+            (64, 65),
+            (65, 30),
+            (130, 128),
+            (300, 290),
+        ] {
+            for alphabet in [2, 4, 26] {
+                let (a, b) = (random(n, alphabet), random(m, alphabet));
+                let expected = edit_distance(&a, &b);
+                assert_eq!(myers_edit_distance(&a, &b), expected);
+                assert_eq!(myers_edit_distance(&b, &a), expected);
+                let alignment = edit_script(&a, &b, &UnitCosts);
+                assert_eq!(alignment.distance, expected);
+                assert_eq!(apply(&a, &b, &alignment.ops), b);
+                assert_eq!(edit_distance_within(&a, &b, expected), Some(expected));
+                if expected > 0 {
+                    assert_eq!(edit_distance_within(&a, &b, expected - 1), None);
+                }
+                let damerau = damerau_edit_script(&a, &b, &UnitCosts);
+                assert_eq!(apply(&a, &b, &damerau.ops), b);
+                assert!(damerau_levenshtein_distance(&a, &b) <= damerau.distance);
+                assert!(damerau.distance <= expected);
+            }
+        }
+    }
+}
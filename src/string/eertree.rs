@@ -0,0 +1,314 @@
+//! An eertree (palindromic tree) stores every distinct palindromic substring of a sequence
+//! that grows one element at a time.
+//!
+//! Each node is a palindrome, with an edge labelled `c` to the palindrome `c P c`, and a
+//! suffix link to its longest proper palindromic suffix. Two roots of lengths -1 and 0 start
+//! the odd and even palindromes. Appending `c` finds, along the suffix links of the longest
+//! palindromic suffix, the longest palindrome `P` preceded by `c`: `c P c` is the new longest
+//! palindromic suffix, and the only palindrome that can be new. Appending takes amortized
+//! O(1) node visits, and the tree has at most `n + 2` nodes.
+//!
+//! Reference: Rubinchik and Shur, "EERTREE: An Efficient Data Structure for Processing
+//! Palindromes in Strings"
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+/// The imaginary root of length -1, whose children are the palindromes of odd length.
+const ODD_ROOT: usize = 0;
+/// The root of the empty palindrome, whose children are the palindromes of even length.
+const EVEN_ROOT: usize = 1;
+
+struct Node<T> {
+    len: isize,
+    /// The longest proper palindromic suffix.
+    link: usize,
+    edges: HashMap<T, usize>,
+    /// Where the palindrome first ends (exclusive).
+    first_end: usize,
+    /// The number of positions where this is the longest palindromic suffix.
+    suffix_count: usize,
+    /// The number of non-empty palindromic suffixes of this palindrome, itself included.
+    depth: usize,
+}
+
+impl<T> Node<T> {
+    fn new(len: isize, link: usize, first_end: usize, depth: usize) -> Self {
+        Self {
+            len,
+            link,
+            edges: HashMap::new(),
+            first_end,
+            suffix_count: 0,
+            depth,
+        }
+    }
+}
+
+/// A distinct palindromic substring with its number of occurrences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palindrome {
+    /// The first occurrence.
+    pub range: Range<usize>,
+    pub occurrences: usize,
+}
+
+pub struct Eertree<T> {
+    text: Vec<T>,
+    nodes: Vec<Node<T>>,
+    /// The longest palindromic suffix of every prefix of the text.
+    suffixes: Vec<usize>,
+    total: usize,
+}
+
+impl<T: Eq + Hash + Clone> Default for Eertree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Eq + Hash + Clone> FromIterator<T> for Eertree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for value in iter {
+            tree.push(value);
+        }
+        tree
+    }
+}
+
+impl<T: Eq + Hash + Clone> Eertree<T> {
+    pub fn new() -> Self {
+        Self {
+            text: Vec::new(),
+            nodes: vec![Node::new(-1, ODD_ROOT, 0, 0), Node::new(0, ODD_ROOT, 0, 0)],
+            suffixes: Vec::new(),
+            total: 0,
+        }
+    }
+
+    /// The length of the text.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn text(&self) -> &[T] {
+        &self.text
+    }
+
+    /// Follows the suffix links from `node` to the first palindrome preceded by the element
+    /// at `end`, whose extension ends at `end`.
+    fn extendable(&self, mut node: usize, end: usize) -> usize {
+        loop {
+            let before = end as isize - self.nodes[node].len - 1;
+            if before >= 0 && self.text[before as usize] == self.text[end] {
+                return node;
+            }
+            node = self.nodes[node].link;
+        }
+    }
+
+    /// Appends an element, returning whether it ends a palindrome that did not occur before.
+    pub fn push(&mut self, value: T) -> bool {
+        let end = self.text.len();
+        self.text.push(value);
+        let last = self.suffixes.last().copied().unwrap_or(EVEN_ROOT);
+        let parent = self.extendable(last, end);
+        let existing = self.nodes[parent].edges.get(&self.text[end]).copied();
+        let created = existing.is_none();
+        let node = existing.unwrap_or_else(|| {
+            let len = self.nodes[parent].len + 2;
+            let link = if len == 1 {
+                EVEN_ROOT
+            } else {
+                let link_parent = self.extendable(self.nodes[parent].link, end);
+                self.nodes[link_parent].edges[&self.text[end]]
+            };
+            let node = self.nodes.len();
+            let depth = self.nodes[link].depth + 1;
+            self.nodes.push(Node::new(len, link, end + 1, depth));
+            self.nodes[parent]
+                .edges
+                .insert(self.text[end].clone(), node);
+            node
+        });
+        self.nodes[node].suffix_count += 1;
+        self.total += self.nodes[node].depth;
+        self.suffixes.push(node);
+        created
+    }
+
+    /// The number of distinct non-empty palindromic substrings.
+    pub fn distinct_count(&self) -> usize {
+        self.nodes.len() - 2
+    }
+
+    /// The number of non-empty palindromic substrings, counted at every position.
+    pub fn total_count(&self) -> usize {
+        self.total
+    }
+
+    /// The longest palindromic suffix of the text.
+    pub fn longest_suffix(&self) -> Range<usize> {
+        self.suffixes.last().map_or(0..0, |&node| {
+            self.text.len() - self.nodes[node].len as usize..self.text.len()
+        })
+    }
+
+    /// The distinct palindromic substrings in order of first occurrence end, then length.
+    pub fn palindromes(&self) -> Vec<Palindrome> {
+        // Every occurrence of a palindrome ending somewhere is also an occurrence of its
+        // palindromic suffixes, and suffix links point to earlier nodes.
+        let mut occurrences: Vec<usize> = self.nodes.iter().map(|node| node.suffix_count).collect();
+        for node in (2..self.nodes.len()).rev() {
+            let link = self.nodes[node].link;
+            occurrences[link] += occurrences[node];
+        }
+        let mut palindromes: Vec<Palindrome> = (2..self.nodes.len())
+            .map(|index| {
+                let node = &self.nodes[index];
+                Palindrome {
+                    range: node.first_end - node.len as usize..node.first_end,
+                    occurrences: occurrences[index],
+                }
+            })
+            .collect();
+        palindromes.sort_by_key(|palindrome| (palindrome.range.end, palindrome.range.len()));
+        palindromes
+    }
+
+    fn find(&self, palindrome: &[T]) -> Option<usize> {
+        let len = palindrome.len();
+        if len == 0 || !palindrome.iter().eq(palindrome.iter().rev()) {
+            return None;
+        }
+        let root = if len % 2 == 1 { ODD_ROOT } else { EVEN_ROOT };
+        palindrome[len / 2..].iter().try_fold(root, |node, value| {
+            self.nodes[node].edges.get(value).copied()
+        })
+    }
+
+    pub fn contains(&self, palindrome: &[T]) -> bool {
+        self.find(palindrome).is_some()
+    }
+
+    /// The sorted start positions of the occurrences of a non-empty palindrome.
+    pub fn find_all(&self, palindrome: &[T]) -> Vec<usize> {
+        let Some(target) = self.find(palindrome) else {
+            return Vec::new();
+        };
+        // The palindrome ends at every position whose longest palindromic suffix has it as a
+        // suffix, i.e. lies in its subtree of the suffix link tree.
+        let mut children = vec![Vec::new(); self.nodes.len()];
+        for node in 2..self.nodes.len() {
+            children[self.nodes[node].link].push(node);
+        }
+        let mut in_subtree = vec![false; self.nodes.len()];
+        let mut stack = vec![target];
+        while let Some(node) = stack.pop() {
+            in_subtree[node] = true;
+            stack.extend(&children[node]);
+        }
+        self.suffixes
+            .iter()
+            .enumerate()
+            .filter(|&(_, &node)| in_subtree[node])
+            .map(|(end, _)| end + 1 - palindrome.len())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn naive_palindromes(text: &[u8]) -> (HashSet<&[u8]>, usize) {
+        let mut distinct = HashSet::new();
+        let mut total = 0;
+        for start in 0..text.len() {
+            for end in start + 1..=text.len() {
+                let s = &text[start..end];
+                if s.iter().eq(s.iter().rev()) {
+                    distinct.insert(s);
+                    total += 1;
+                }
+            }
+        }
+        (distinct, total)
+    }
+
+    #[test]
+    fn counts_match_a_naive_enumeration() {
+        let mut state = 49_u64;
+        let text: Vec<u8> = (0..200)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                b"abc"[(state >> 33) as usize % 3]
+            })
+            .collect();
+        let mut tree = Eertree::new();
+        for (i, &c) in text.iter().enumerate() {
+            let (before, _) = naive_palindromes(&text[..i]);
+            let created = tree.push(c);
+            let (distinct, total) = naive_palindromes(&text[..=i]);
+            assert_eq!(created, distinct.len() > before.len());
+            assert_eq!(tree.distinct_count(), distinct.len());
+            assert_eq!(tree.total_count(), total);
+        }
+        for palindrome in tree.palindromes() {
+            let s = &text[palindrome.range.clone()];
+            let positions = tree.find_all(s);
+            let expected: Vec<usize> = (0..=text.len() - s.len())
+                .filter(|&start| &text[start..start + s.len()] == s)
+                .collect();
+            assert_eq!(positions, expected);
+            assert_eq!(palindrome.occurrences, expected.len());
+            assert_eq!(palindrome.range.start, expected[0]);
+        }
+    }
+
+    #[test]
+    fn palindromes_of_a_word() {
+        let tree: Eertree<char> = "eertree".chars().collect();
+        let found: Vec<String> = tree
+            .palindromes()
+            .iter()
+            .map(|p| tree.text()[p.range.clone()].iter().collect())
+            .collect();
+        assert_eq!(found, ["e", "ee", "r", "t", "rtr", "ertre", "eertree"]);
+        assert_eq!(tree.distinct_count(), 7);
+        assert_eq!(tree.longest_suffix(), 0..7);
+        let e: Vec<char> = "e".chars().collect();
+        assert_eq!(tree.find_all(&e), [0, 1, 5, 6]);
+        let ee: Vec<char> = "ee".chars().collect();
+        assert!(tree.contains(&ee));
+        assert!(!tree.contains(&['e', 'r']));
+        assert!(tree.find_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn online_appends() {
+        let mut tree = Eertree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.longest_suffix(), 0..0);
+        assert!(tree.push("ab"));
+        assert!(tree.push("cd"));
+        assert!(tree.push("ef"));
+        assert!(!tree.push("ab"));
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.longest_suffix(), 3..4);
+        assert!(tree.push("ef"));
+        assert_eq!(tree.longest_suffix(), 2..5);
+        assert_eq!(tree.find_all(&["ab"]), [0, 3]);
+        assert_eq!(tree.find_all(&["ef", "ab", "ef"]), [2]);
+        assert_eq!(tree.total_count(), 6);
+    }
+}
@@ -0,0 +1,363 @@
+// An FM-index is a compressed full-text index built on the Burrows-Wheeler transform (BWT) of
+// the text. The BWT lists, for every suffix in sorted order, the symbol preceding it. Counting
+// the occurrences of a pattern then only needs `occ(c, i)`, the number of times `c` appears in
+// the first `i` symbols of the BWT: extending a match one symbol to the left maps the range of
+// matching suffixes to `C[c] + occ(c, start)..C[c] + occ(c, end)`, where `C[c]` counts the
+// symbols smaller than `c` (backward search).
+//
+// Here the BWT is stored in a wavelet matrix, which answers `occ` and random access in O(log σ)
+// with about one bit per bit of the text plus small rank directories. Positions are recovered
+// from a suffix array sampled at every `sample_rate`-th text position: walking the LF-mapping
+// from a row reaches a sampled suffix within `sample_rate` steps. Samples of the inverse suffix
+// array make it possible to extract any substring the same way.
+// Source: Ferragina and Manzini, "Opportunistic data structures with applications"
+
+use super::generate_suffix_array_sa_is;
+use std::ops::Range;
+
+/// A sequence of bits answering `rank` (the number of ones before a position) in O(1).
+struct BitVector {
+    words: Vec<u64>,
+    /// The number of ones in the words before each word.
+    ranks: Vec<usize>,
+}
+
+impl BitVector {
+    fn new(bits: impl ExactSizeIterator<Item = bool>) -> Self {
+        let mut words = vec![0_u64; bits.len().div_ceil(64)];
+        for (i, bit) in bits.enumerate() {
+            words[i / 64] |= u64::from(bit) << (i % 64);
+        }
+        let mut ranks = Vec::with_capacity(words.len() + 1);
+        let mut total = 0;
+        for word in &words {
+            ranks.push(total);
+            total += word.count_ones() as usize;
+        }
+        ranks.push(total);
+        BitVector { words, ranks }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    fn rank1(&self, i: usize) -> usize {
+        let (word, bit) = (i / 64, i % 64);
+        if bit == 0 {
+            return self.ranks[word];
+        }
+        self.ranks[word] + (self.words[word] << (64 - bit)).count_ones() as usize
+    }
+
+    fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+}
+
+/// A wavelet matrix over bytes: one bit vector per bit of the symbols, from the most
+/// significant one, where every level stably sorts the sequence by the bit of the level above.
+struct WaveletMatrix {
+    levels: Vec<BitVector>,
+    /// The number of zeros of each level.
+    zeros: Vec<usize>,
+}
+
+impl WaveletMatrix {
+    fn new(mut sequence: Vec<u8>) -> Self {
+        let mut levels = Vec::with_capacity(8);
+        let mut zeros = Vec::with_capacity(8);
+        for level in 0..8 {
+            let shift = 7 - level;
+            levels.push(BitVector::new(
+                sequence.iter().map(|&c| c >> shift & 1 == 1),
+            ));
+            let (mut next, ones): (Vec<u8>, Vec<u8>) =
+                sequence.iter().partition(|&&c| c >> shift & 1 == 0);
+            zeros.push(next.len());
+            next.extend(ones);
+            sequence = next;
+        }
+        WaveletMatrix { levels, zeros }
+    }
+
+    fn access(&self, mut i: usize) -> u8 {
+        let mut c = 0;
+        for (level, bits) in self.levels.iter().enumerate() {
+            if bits.get(i) {
+                c |= 1 << (7 - level);
+                i = self.zeros[level] + bits.rank1(i);
+            } else {
+                i = bits.rank0(i);
+            }
+        }
+        c
+    }
+
+    /// The number of occurrences of `c` in the first `i` symbols.
+    fn rank(&self, c: u8, i: usize) -> usize {
+        let (mut start, mut end) = (0, i);
+        for (level, bits) in self.levels.iter().enumerate() {
+            if c >> (7 - level) & 1 == 1 {
+                start = self.zeros[level] + bits.rank1(start);
+                end = self.zeros[level] + bits.rank1(end);
+            } else {
+                start = bits.rank0(start);
+                end = bits.rank0(end);
+            }
+        }
+        end - start
+    }
+}
+
+/// An FM-index of a byte string, counting, locating and extracting substrings without keeping
+/// the text itself.
+pub struct FmIndex {
+    /// The BWT of the text followed by a sentinel smaller than every byte. The sentinel itself
+    /// is stored as a 0 at row `sentinel_row`.
+    bwt: WaveletMatrix,
+    sentinel_row: usize,
+    /// `counts[c]` is the number of rows whose suffix starts with a symbol smaller than `c`.
+    counts: Vec<usize>,
+    sample_rate: usize,
+    /// Marks the rows whose suffix position is a multiple of `sample_rate`.
+    sampled_rows: BitVector,
+    /// The positions of the sampled rows, in row order.
+    suffix_samples: Vec<usize>,
+    /// The row of every text position that is a multiple of `sample_rate`.
+    row_samples: Vec<usize>,
+}
+
+impl FmIndex {
+    /// Builds the index with one suffix array sample every 32 text positions.
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_sample_rate(text, 32)
+    }
+
+    /// Builds the index, keeping one suffix array sample every `sample_rate` text positions:
+    /// larger rates take less memory but make `locate` and `extract` slower.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_rate` is zero.
+    pub fn with_sample_rate(text: &[u8], sample_rate: usize) -> Self {
+        assert!(sample_rate > 0, "sample rate must be positive");
+        let n = text.len();
+        // The suffix array of the text with its sentinel, whose suffix comes first.
+        let suffixes: Vec<usize> = std::iter::once(n)
+            .chain(generate_suffix_array_sa_is(text))
+            .collect();
+        let sentinel_row = suffixes.iter().position(|&i| i == 0).unwrap_or(0);
+        let bwt = suffixes
+            .iter()
+            .map(|&i| if i == 0 { 0 } else { text[i - 1] })
+            .collect();
+
+        let mut counts = vec![0; 257];
+        for &c in text {
+            counts[usize::from(c) + 1] += 1;
+        }
+        // The sentinel row comes before all the others.
+        counts[0] = 1;
+        for c in 1..counts.len() {
+            counts[c] += counts[c - 1];
+        }
+
+        let sampled_rows = BitVector::new(suffixes.iter().map(|&i| i % sample_rate == 0));
+        let suffix_samples = suffixes
+            .iter()
+            .copied()
+            .filter(|&i| i % sample_rate == 0)
+            .collect();
+        let mut row_samples = vec![0; n / sample_rate + 1];
+        for (row, &i) in suffixes.iter().enumerate() {
+            if i % sample_rate == 0 {
+                row_samples[i / sample_rate] = row;
+            }
+        }
+
+        FmIndex {
+            bwt: WaveletMatrix::new(bwt),
+            sentinel_row,
+            counts,
+            sample_rate,
+            sampled_rows,
+            suffix_samples,
+            row_samples,
+        }
+    }
+
+    /// The length of the indexed text.
+    pub fn len(&self) -> usize {
+        self.counts[256] - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of occurrences of `c` in the BWT rows before `row`, ignoring the sentinel.
+    fn occ(&self, c: u8, row: usize) -> usize {
+        let occ = self.bwt.rank(c, row);
+        if c == 0 && self.sentinel_row < row {
+            occ - 1
+        } else {
+            occ
+        }
+    }
+
+    /// The row of the suffix starting one position before the suffix of `row`, which must not
+    /// be the row of the whole text.
+    fn lf(&self, row: usize) -> (u8, usize) {
+        let c = self.bwt.access(row);
+        (c, self.counts[usize::from(c)] + self.occ(c, row))
+    }
+
+    /// The rows of the suffixes starting with `pattern`, found by backward search.
+    fn rows(&self, pattern: &[u8]) -> Range<usize> {
+        let (mut start, mut end) = (0, self.len() + 1);
+        for &c in pattern.iter().rev() {
+            let base = self.counts[usize::from(c)];
+            start = base + self.occ(c, start);
+            end = base + self.occ(c, end);
+            if start >= end {
+                return 0..0;
+            }
+        }
+        start..end
+    }
+
+    /// The number of occurrences of `pattern`, in O(m log σ) time.
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.rows(pattern).len()
+    }
+
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.count(pattern) > 0
+    }
+
+    /// The starting positions of all the occurrences of `pattern`, in increasing order. Each
+    /// one takes at most `sample_rate` LF steps to recover.
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions: Vec<usize> = self
+            .rows(pattern)
+            .map(|mut row| {
+                let mut steps = 0;
+                while !self.sampled_rows.get(row) {
+                    row = self.lf(row).1;
+                    steps += 1;
+                }
+                self.suffix_samples[self.sampled_rows.rank1(row)] + steps
+            })
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// The substring of the text in `range`, recovered by walking backwards from the next
+    /// sampled position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is not within the text.
+    pub fn extract(&self, range: Range<usize>) -> Vec<u8> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {range:?} out of bounds for a text of length {}",
+            self.len()
+        );
+        let sample = range.end.div_ceil(self.sample_rate);
+        let (mut position, mut row) = if sample < self.row_samples.len() {
+            (sample * self.sample_rate, self.row_samples[sample])
+        } else {
+            // The empty suffix at the end of the text always has the first row.
+            (self.len(), 0)
+        };
+        let mut extracted = Vec::with_capacity(range.len());
+        while position > range.start {
+            let (c, previous) = self.lf(row);
+            position -= 1;
+            row = previous;
+            if position < range.end {
+                extracted.push(c);
+            }
+        }
+        extracted.reverse();
+        extracted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_locate(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+        (0..=text.len().saturating_sub(pattern.len()))
+            .filter(|&i| text[i..].starts_with(pattern))
+            .collect()
+    }
+
+    #[test]
+    fn test_fm_index() {
+        let index = FmIndex::with_sample_rate(b"abracadabra", 4);
+        assert_eq!(index.len(), 11);
+        assert_eq!(index.count(b"abra"), 2);
+        assert_eq!(index.count(b"a"), 5);
+        assert_eq!(index.count(b"cad"), 1);
+        assert_eq!(index.count(b"abrac"), 1);
+        assert_eq!(index.count(b"x"), 0);
+        assert_eq!(index.count(b"abracadabras"), 0);
+        assert_eq!(index.count(b""), 12);
+        assert!(index.contains(b"dab"));
+        assert_eq!(index.locate(b"abra"), vec![0, 7]);
+        assert_eq!(index.locate(b"a"), vec![0, 3, 5, 7, 10]);
+        assert_eq!(index.locate(b"ra"), vec![2, 9]);
+        assert_eq!(index.extract(0..11), b"abracadabra");
+        assert_eq!(index.extract(3..6), b"aca");
+        assert_eq!(index.extract(11..11), b"");
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let index = FmIndex::new(b"");
+        assert!(index.is_empty());
+        assert_eq!(index.count(b"a"), 0);
+        assert_eq!(index.locate(b""), vec![0]);
+        assert_eq!(index.extract(0..0), b"");
+    }
+
+    #[test]
+    fn test_zero_bytes() {
+        let text = [0, 1, 0, 0, 2, 0];
+        let index = FmIndex::with_sample_rate(&text, 2);
+        assert_eq!(index.count(&[0]), 4);
+        assert_eq!(index.locate(&[0, 0]), vec![2]);
+        assert_eq!(index.extract(0..6), text);
+    }
+
+    #[test]
+    fn test_matches_naive_search() {
+        let mut state = 43_u64;
+        let text: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                b"ACGTN\n"[(state >> 33) as usize % 6]
+            })
+            .collect();
+        for rate in [1, 7, 64] {
+            let index = FmIndex::with_sample_rate(&text, rate);
+            for start in (0..19_990).step_by(997) {
+                for length in [1, 2, 5, 9] {
+                    let pattern = &text[start..start + length];
+                    let expected = naive_locate(&text, pattern);
+                    assert_eq!(index.count(pattern), expected.len());
+                    assert_eq!(index.locate(pattern), expected);
+                }
+                assert_eq!(index.extract(start..start + 10), &text[start..start + 10]);
+            }
+            assert_eq!(index.extract(0..text.len()), text);
+        }
+    }
+}
@@ -3,6 +3,8 @@
 
 use std::collections::HashMap;
 
+use super::unicode::{graphemes, is_letter, TextOptions};
+
 /// Enum representing possible errors that can occur while checking for isograms.
 #[derive(Debug, PartialEq, Eq)]
 pub enum IsogramError {
@@ -10,15 +12,16 @@ pub enum IsogramError {
     NonAlphabeticCharacter,
 }
 
-/// Counts the occurrences of each alphabetic character in a given string.
+/// Counts the occurrences of each letter in a given string.
 ///
-/// This function takes a string slice as input. It counts how many times each alphabetic character
-/// appears in the input string and returns a hashmap where the keys are characters and the values
-/// are their respective counts.
+/// This function takes a string slice as input. It splits the input into grapheme clusters,
+/// prepares every cluster with `options`, and returns a hashmap where the keys are the
+/// prepared letters and the values are their respective counts.
 ///
 /// # Arguments
 ///
-/// * `s` - A string slice that contains the input to count characters from.
+/// * `s` - A string slice that contains the input to count letters from.
+/// * `options` - How every letter is case folded and normalized.
 ///
 /// # Errors
 ///
@@ -26,19 +29,20 @@ pub enum IsogramError {
 ///
 /// # Note
 ///
-/// This function treats uppercase and lowercase letters as equivalent (case-insensitive).
-/// Spaces are ignored and do not affect the character count.
-fn count_letters(s: &str) -> Result<HashMap<char, usize>, IsogramError> {
+/// Letters are folded one at a time, so a letter folding to several characters (such as
+/// `ß` to `ss`) still counts as a single letter. Spaces are ignored and do not affect the
+/// letter count.
+fn count_letters(s: &str, options: TextOptions) -> Result<HashMap<String, usize>, IsogramError> {
     let mut letter_counts = HashMap::new();
 
-    for ch in s.to_ascii_lowercase().chars() {
-        if !ch.is_ascii_alphabetic() && !ch.is_whitespace() {
-            return Err(IsogramError::NonAlphabeticCharacter);
+    for grapheme in graphemes(s) {
+        if grapheme.chars().all(char::is_whitespace) {
+            continue;
         }
-
-        if ch.is_ascii_alphabetic() {
-            *letter_counts.entry(ch).or_insert(0) += 1;
+        if !is_letter(grapheme) {
+            return Err(IsogramError::NonAlphabeticCharacter);
         }
+        *letter_counts.entry(options.apply(grapheme)).or_insert(0) += 1;
     }
 
     Ok(letter_counts)
@@ -47,7 +51,7 @@ fn count_letters(s: &str) -> Result<HashMap<char, usize>, IsogramError> {
 /// Checks if the given input string is an isogram.
 ///
 /// This function takes a string slice as input. It counts the occurrences of each
-/// alphabetic character (ignoring case and spaces).
+/// letter (ignoring case and spaces, and treating canonically equivalent letters as equal).
 ///
 /// # Arguments
 ///
@@ -58,7 +62,23 @@ fn count_letters(s: &str) -> Result<HashMap<char, usize>, IsogramError> {
 /// - `Ok(true)` if all characters appear only once, or `Ok(false)` if any character appears more than once.
 /// - `Err(IsogramError::NonAlphabeticCharacter)` if the input contains any non-alphabetic characters.
 pub fn is_isogram(s: &str) -> Result<bool, IsogramError> {
-    let letter_counts = count_letters(s)?;
+    is_isogram_with(s, TextOptions::default())
+}
+
+/// Checks if the given input string is an isogram, comparing its letters after case folding
+/// and normalizing them as set in `options`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that contains the input to check for isogram properties.
+/// * `options` - How the letters are prepared before they are compared.
+///
+/// # Return
+///
+/// - `Ok(true)` if all letters appear only once, or `Ok(false)` if any letter appears more than once.
+/// - `Err(IsogramError::NonAlphabeticCharacter)` if the input contains any non-alphabetic characters.
+pub fn is_isogram_with(s: &str, options: TextOptions) -> Result<bool, IsogramError> {
+    let letter_counts = count_letters(s, options)?;
     Ok(letter_counts.values().all(|&count| count == 1))
 }
 
@@ -100,5 +120,20 @@ mod tests {
         isogram_single_character: ("a", Ok(true)),
         invalid_isogram_multiple_same_characters: ("aaaa", Ok(false)),
         invalid_isogram_with_symbols: ("abc@#$%", Err(IsogramError::NonAlphabeticCharacter)),
+        isogram_accented_letters: ("Éclat", Ok(true)),
+        isogram_german_sharp_s: ("Maß", Ok(true)),
+        isogram_greek: ("Σοφία", Ok(true)),
+        invalid_isogram_unicode_case: ("Été", Ok(false)),
+        invalid_isogram_canonically_equivalent: ("ée\u{301}", Ok(false)),
+        invalid_isogram_with_unicode_digit: ("abc٣", Err(IsogramError::NonAlphabeticCharacter)),
+    }
+
+    #[test]
+    fn isogram_with_options() {
+        use crate::string::TextOptions;
+
+        assert_eq!(is_isogram_with("Aa", TextOptions::EXACT), Ok(true));
+        assert_eq!(is_isogram_with("ée\u{301}", TextOptions::EXACT), Ok(true));
+        assert_eq!(is_isogram_with("Aa", TextOptions::default()), Ok(false));
     }
 }
@@ -4,7 +4,9 @@
 //! by some mapping relation to obtain the other string.
 use std::collections::HashMap;
 
-/// Determines whether two strings are isomorphic.
+use super::unicode::{graphemes, TextOptions};
+
+/// Determines whether two strings are isomorphic, comparing their characters exactly.
 ///
 /// # Arguments
 ///
@@ -15,14 +17,30 @@ use std::collections::HashMap;
 ///
 /// `true` if the strings are isomorphic, `false` otherwise.
 pub fn is_isomorphic(s: &str, t: &str) -> bool {
-    let s_chars: Vec<char> = s.chars().collect();
-    let t_chars: Vec<char> = t.chars().collect();
+    is_isomorphic_with(s, t, TextOptions::EXACT)
+}
+
+/// Determines whether two strings are isomorphic. The characters are grapheme clusters,
+/// case folded and normalized one at a time as set in `options`.
+///
+/// # Arguments
+///
+/// * `s` - The first string.
+/// * `t` - The second string.
+/// * `options` - How every character is prepared before the comparison.
+///
+/// # Returns
+///
+/// `true` if the strings are isomorphic, `false` otherwise.
+pub fn is_isomorphic_with(s: &str, t: &str, options: TextOptions) -> bool {
+    let s_chars: Vec<String> = graphemes(s).into_iter().map(|g| options.apply(g)).collect();
+    let t_chars: Vec<String> = graphemes(t).into_iter().map(|g| options.apply(g)).collect();
     if s_chars.len() != t_chars.len() {
         return false;
     }
     let mut s_to_t_map = HashMap::new();
     let mut t_to_s_map = HashMap::new();
-    for (s_char, t_char) in s_chars.into_iter().zip(t_chars) {
+    for (s_char, t_char) in s_chars.iter().zip(&t_chars) {
         if !check_mapping(&mut s_to_t_map, s_char, t_char)
             || !check_mapping(&mut t_to_s_map, t_char, s_char)
         {
@@ -43,8 +61,8 @@ pub fn is_isomorphic(s: &str, t: &str) -> bool {
 /// # Returns
 ///
 /// `true` if the mapping is consistent, `false` otherwise.
-fn check_mapping(map: &mut HashMap<char, char>, key: char, value: char) -> bool {
-    match map.get(&key) {
+fn check_mapping<'a>(map: &mut HashMap<&'a str, &'a str>, key: &'a str, value: &'a str) -> bool {
+    match map.get(key) {
         Some(&mapped_char) => mapped_char == value,
         None => {
             map.insert(key, value);
@@ -55,7 +73,9 @@ fn check_mapping(map: &mut HashMap<char, char>, key: char, value: char) -> bool
 
 #[cfg(test)]
 mod tests {
-    use super::is_isomorphic;
+    use super::{is_isomorphic, is_isomorphic_with};
+    use crate::string::{Normalization, TextOptions};
+
     macro_rules! test_is_isomorphic {
         ($($name:ident: $inputs:expr,)*) => {
         $(
@@ -80,4 +100,24 @@ mod tests {
         empty: ("", "", true),
         different_length: ("abc", "abcd", false),
     }
+
+    #[test]
+    fn grapheme_clusters_are_single_characters() {
+        assert!(is_isomorphic("e\u{301}b", "xy"));
+        assert!(!is_isomorphic("e\u{301}e", "xx"));
+        assert!(is_isomorphic("🇰🇷🇰🇷", "aa"));
+    }
+
+    #[test]
+    fn isomorphic_with_options() {
+        assert!(!is_isomorphic("Aa", "bb"));
+        assert!(is_isomorphic_with("Aa", "bb", TextOptions::default()));
+        let nfc = TextOptions {
+            case_fold: false,
+            normalization: Normalization::Nfc,
+        };
+        assert!(is_isomorphic("ée\u{301}", "xy"));
+        assert!(!is_isomorphic_with("ée\u{301}", "xy", nfc));
+        assert!(is_isomorphic_with("ée\u{301}", "xx", nfc));
+    }
 }
@@ -8,31 +8,31 @@ pub fn jaro_winkler_distance(str1: &str, str2: &str) -> f64 {
     if str1.is_empty() || str2.is_empty() {
         return 0.0;
     }
-    fn get_matched_characters(s1: &str, s2: &str) -> String {
-        let mut s2 = s2.to_string();
+    fn get_matched_characters(s1: &[char], s2: &[char]) -> Vec<char> {
+        let mut s2 = s2.to_vec();
         let mut matched: Vec<char> = Vec::new();
         let limit = std::cmp::min(s1.len(), s2.len()) / 2;
-        for (i, l) in s1.chars().enumerate() {
-            let left = std::cmp::max(0, i as i32 - limit as i32) as usize;
+        for (i, &l) in s1.iter().enumerate() {
+            let left = i.saturating_sub(limit);
             let right = std::cmp::min(i + limit + 1, s2.len());
-            if s2[left..right].contains(l) {
+            if left < right && s2[left..right].contains(&l) {
                 matched.push(l);
-                let a = &s2[0..s2.find(l).expect("this exists")];
-                let b = &s2[(s2.find(l).expect("this exists") + 1)..];
-                s2 = format!("{a} {b}");
+                let position = s2.iter().position(|&c| c == l).expect("this exists");
+                s2[position] = ' ';
             }
         }
-        matched.iter().collect::<String>()
+        matched
     }
 
-    let matching_1 = get_matched_characters(str1, str2);
-    let matching_2 = get_matched_characters(str2, str1);
+    let (str1, str2): (Vec<char>, Vec<char>) = (str1.chars().collect(), str2.chars().collect());
+    let matching_1 = get_matched_characters(&str1, &str2);
+    let matching_2 = get_matched_characters(&str2, &str1);
     let match_count = matching_1.len();
 
     // transposition
     let transpositions = {
         let mut count = 0;
-        for (c1, c2) in matching_1.chars().zip(matching_2.chars()) {
+        for (c1, c2) in matching_1.iter().zip(&matching_2) {
             if c1 != c2 {
                 count += 1;
             }
@@ -52,7 +52,7 @@ pub fn jaro_winkler_distance(str1: &str, str2: &str) -> f64 {
 
     let mut prefix_len = 0.0;
     let bound = std::cmp::min(std::cmp::min(str1.len(), str2.len()), 4);
-    for (c1, c2) in str1[..bound].chars().zip(str2[..bound].chars()) {
+    for (c1, c2) in str1[..bound].iter().zip(&str2[..bound]) {
         if c1 == c2 {
             prefix_len += 1.0;
         } else {
@@ -81,4 +81,15 @@ mod tests {
         let a = jaro_winkler_distance("hello world", "HeLLo W0rlD");
         assert_eq!(a, 0.6363636363636364);
     }
+
+    #[test]
+    fn test_jaro_winkler_distance_unicode() {
+        assert_eq!(jaro_winkler_distance("héllo", "héllo"), 1.0);
+        assert_eq!(
+            jaro_winkler_distance("héllo", "hello"),
+            jaro_winkler_distance("hxllo", "hello")
+        );
+        // A short second string used to make the matching window out of bounds.
+        assert!(jaro_winkler_distance("abcdefgh", "ab") > 0.0);
+    }
 }
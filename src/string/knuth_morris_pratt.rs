@@ -13,17 +13,18 @@
 ///
 /// # Returns
 ///
-/// A vector of starting indices where the pattern is found in the string. If the pattern or the
+/// A vector of the byte offsets in `string` where the pattern starts. If the pattern or the
 /// string is empty, an empty vector is returned.
+///
+/// The search runs on the UTF-8 bytes: a valid pattern can only match at char boundaries, so
+/// every offset can be used to slice `string`.
 pub fn knuth_morris_pratt(string: &str, pattern: &str) -> Vec<usize> {
     if string.is_empty() || pattern.is_empty() {
         return vec![];
     }
 
-    let text_chars = string.chars().collect::<Vec<char>>();
-    let pattern_chars = pattern.chars().collect::<Vec<char>>();
-    let partial_match_table = build_partial_match_table(&pattern_chars);
-    find_pattern(&text_chars, &pattern_chars, &partial_match_table)
+    let partial_match_table = build_partial_match_table(pattern.as_bytes());
+    find_pattern(string.as_bytes(), pattern.as_bytes(), &partial_match_table)
 }
 
 /// Builds the partial match table (also known as "prefix table") for the given pattern.
@@ -34,12 +35,12 @@ pub fn knuth_morris_pratt(string: &str, pattern: &str) -> Vec<usize> {
 ///
 /// # Arguments
 ///
-/// * `pattern_chars` - The pattern string as a slice of characters.
+/// * `pattern_chars` - The pattern string as a slice of bytes.
 ///
 /// # Returns
 ///
 /// A vector representing the partial match table.
-fn build_partial_match_table(pattern_chars: &[char]) -> Vec<usize> {
+fn build_partial_match_table(pattern_chars: &[u8]) -> Vec<usize> {
     let mut partial_match_table = vec![0];
     pattern_chars
         .iter()
@@ -66,16 +67,16 @@ fn build_partial_match_table(pattern_chars: &[char]) -> Vec<usize> {
 ///
 /// # Arguments
 ///
-/// * `text_chars` - The string to search within as a slice of characters.
-/// * `pattern_chars` - The pattern string to search for as a slice of characters.
+/// * `text_chars` - The string to search within as a slice of bytes.
+/// * `pattern_chars` - The pattern string to search for as a slice of bytes.
 /// * `partial_match_table` - The precomputed partial match table for the pattern.
 ///
 /// # Returns
 ///
-/// A vector of starting indices where the pattern is found in the string.
+/// A vector of the byte offsets where the pattern starts.
 fn find_pattern(
-    text_chars: &[char],
-    pattern_chars: &[char],
+    text_chars: &[u8],
+    pattern_chars: &[u8],
     partial_match_table: &[usize],
 ) -> Vec<usize> {
     let mut result_indices = vec![];
@@ -119,7 +120,8 @@ mod tests {
     test_knuth_morris_pratt! {
         each_letter_matches: ("aaa", "a", vec![0, 1, 2]),
         a_few_seperate_matches: ("abababa", "ab", vec![0, 2, 4]),
-        unicode: ("അഅഅ", "അ", vec![0, 1, 2]),
+        unicode: ("അഅഅ", "അ", vec![0, 3, 6]),
+        unicode_mixed_widths: ("aé日a日", "日", vec![3, 7]),
         unicode_no_match_but_similar_bytes: (
             &String::from_utf8(vec![224, 180, 133]).unwrap(),
             &String::from_utf8(vec![224, 180, 132]).unwrap(),
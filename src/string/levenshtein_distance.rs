@@ -88,9 +88,9 @@ pub fn naive_levenshtein_distance(string1: &str, string2: &str) -> usize {
 /// where n and m are lengths of `string1` and `string2`.
 pub fn optimized_levenshtein_distance(string1: &str, string2: &str) -> usize {
     if string1.is_empty() {
-        return string2.len();
+        return string2.chars().count();
     }
-    let l1 = string1.len();
+    let l1 = string1.chars().count();
     let mut prev_dist: Vec<usize> = (0..=l1).collect();
 
     for (row, c2) in string2.chars().enumerate() {
@@ -161,4 +161,11 @@ mod tests {
 
     levenshtein_distance_tests!(naive_levenshtein_distance);
     levenshtein_distance_tests!(optimized_levenshtein_distance);
+
+    #[test]
+    fn test_optimized_levenshtein_distance_counts_chars() {
+        assert_eq!(super::optimized_levenshtein_distance("héllo", "hello"), 1);
+        assert_eq!(super::optimized_levenshtein_distance("", "日本"), 2);
+        assert_eq!(super::optimized_levenshtein_distance("日本語", "日本"), 1);
+    }
 }
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 pub fn manacher(s: String) -> String {
     let l = s.len();
     if l <= 1 {
@@ -75,9 +77,94 @@ pub fn manacher(s: String) -> String {
     answer.replace('#', "")
 }
 
+/// The longest palindromes centered at every position of a sequence.
+///
+/// `odd[i]` is the largest `r` such that `s[i - r..=i + r]` is a palindrome, and `even[i]` the
+/// largest `r` such that `s[i - r..i + r]` is one (so `even[0]` is 0).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalindromeRadii {
+    pub odd: Vec<usize>,
+    pub even: Vec<usize>,
+}
+
+impl PalindromeRadii {
+    /// The leftmost longest palindromic substring.
+    pub fn longest(&self) -> Range<usize> {
+        let mut best = 0..0;
+        for (i, (&odd, &even)) in self.odd.iter().zip(&self.even).enumerate() {
+            if 2 * odd + 1 > best.len() {
+                best = i - odd..i + odd + 1;
+            }
+            if 2 * even > best.len() {
+                best = i - even..i + even;
+            }
+        }
+        best
+    }
+
+    /// Whether `s[range]` is a palindrome, in O(1).
+    pub fn is_palindrome(&self, range: Range<usize>) -> bool {
+        let half = range.len() / 2;
+        let center = range.start + half;
+        if range.is_empty() {
+            true
+        } else if range.len() % 2 == 1 {
+            self.odd[center] >= half
+        } else {
+            self.even[center] >= half
+        }
+    }
+
+    /// The number of non-empty palindromic substrings, counted at every position.
+    pub fn count(&self) -> usize {
+        self.odd.iter().map(|r| r + 1).sum::<usize>() + self.even.iter().sum::<usize>()
+    }
+}
+
+/// Computes the palindrome radii of `s` around every center in O(n) with Manacher's
+/// algorithm: the radius at a center inside the rightmost palindrome found so far starts from
+/// the radius at its mirror image.
+pub fn manacher_radii<T: Eq>(s: &[T]) -> PalindromeRadii {
+    let n = s.len();
+    let mut odd = vec![0; n];
+    let mut even = vec![0; n];
+    // The rightmost palindrome found so far is s[left..right].
+    let (mut left, mut right) = (0, 0);
+    for i in 0..n {
+        let mut r = if i < right {
+            odd[left + right - 1 - i].min(right - 1 - i)
+        } else {
+            0
+        };
+        while i > r && i + r + 1 < n && s[i - r - 1] == s[i + r + 1] {
+            r += 1;
+        }
+        odd[i] = r;
+        if i + r + 1 > right {
+            (left, right) = (i - r, i + r + 1);
+        }
+    }
+    let (mut left, mut right) = (0, 0);
+    for i in 0..n {
+        let mut r = if i < right {
+            even[left + right - i].min(right - i)
+        } else {
+            0
+        };
+        while i > r && i + r < n && s[i - r - 1] == s[i + r] {
+            r += 1;
+        }
+        even[i] = r;
+        if i + r > right {
+            (left, right) = (i - r, i + r);
+        }
+    }
+    PalindromeRadii { odd, even }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::manacher;
+    use super::*;
 
     #[test]
     fn get_longest_palindrome_by_manacher() {
@@ -88,4 +175,54 @@ mod tests {
         let ac_ans = manacher("ac".to_string());
         assert!(ac_ans == *"a" || ac_ans == *"c");
     }
+
+    fn naive_is_palindrome<T: Eq>(s: &[T]) -> bool {
+        s.iter().eq(s.iter().rev())
+    }
+
+    #[test]
+    fn radii_match_a_naive_check() {
+        let mut state = 49_u64;
+        for n in 0..40 {
+            let s: Vec<u8> = (0..n)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    b"ab"[(state >> 33) as usize % 2]
+                })
+                .collect();
+            let radii = manacher_radii(&s);
+            let mut count = 0;
+            let mut longest = 0;
+            for start in 0..=n {
+                for end in start..=n {
+                    let expected = naive_is_palindrome(&s[start..end]);
+                    assert_eq!(radii.is_palindrome(start..end), expected);
+                    if expected && end > start {
+                        count += 1;
+                        longest = longest.max(end - start);
+                    }
+                }
+            }
+            assert_eq!(radii.count(), count);
+            let range = radii.longest();
+            assert_eq!(range.len(), longest);
+            assert!(naive_is_palindrome(&s[range]));
+        }
+    }
+
+    #[test]
+    fn radii_over_any_sequence() {
+        let radii = manacher_radii(&[1, 2, 3, 2, 1, 1]);
+        assert_eq!(radii.odd, [0, 0, 2, 0, 0, 0]);
+        assert_eq!(radii.even, [0, 0, 0, 0, 0, 1]);
+        assert_eq!(radii.longest(), 0..5);
+
+        let words = ["red", "blue", "green", "blue", "red", "x"];
+        assert_eq!(manacher_radii(&words).longest(), 0..5);
+        let letters: Vec<char> = "wasitacaroracatisaw".chars().collect();
+        assert_eq!(manacher_radii(&letters).longest(), 0..19);
+        assert_eq!(manacher_radii::<u8>(&[]).longest(), 0..0);
+    }
 }
@@ -1,9 +1,14 @@
 mod aho_corasick;
 mod anagram;
 mod autocomplete_using_trie;
+mod bk_tree;
 mod boyer_moore_search;
 mod burrows_wheeler_transform;
+mod diff;
 mod duval_algorithm;
+mod edit_distance;
+mod eertree;
+mod fm_index;
 mod hamming_distance;
 mod isogram;
 mod isomorphism;
@@ -14,40 +19,59 @@ mod lipogram;
 mod manacher;
 mod palindrome;
 mod pangram;
+mod q_gram_index;
 mod rabin_karp;
+pub mod regex;
 mod reverse;
 mod run_length_encoding;
 mod shortest_palindrome;
 mod suffix_array;
 mod suffix_array_manber_myers;
+mod suffix_array_sa_is;
 mod suffix_tree;
+mod unicode;
 mod z_algorithm;
 
-pub use self::aho_corasick::AhoCorasick;
-pub use self::anagram::check_anagram;
+pub use self::aho_corasick::{AhoCorasick, Match, MatchKind, StreamSearcher};
+pub use self::anagram::{check_anagram, check_anagram_with};
 pub use self::autocomplete_using_trie::Autocomplete;
+pub use self::bk_tree::{ApproximateMatch, BkTree, Levenshtein, Metric};
 pub use self::boyer_moore_search::boyer_moore_search;
 pub use self::burrows_wheeler_transform::{
     burrows_wheeler_transform, inv_burrows_wheeler_transform,
 };
+pub use self::diff::{
+    apply_patch, diff, hunks, myers_diff, parse_unified_diff, side_by_side, unified_diff,
+    DiffAlgorithm, DiffOp, Hunk, HunkLine, PatchError,
+};
 pub use self::duval_algorithm::duval_algorithm;
+pub use self::edit_distance::{
+    damerau_edit_script, damerau_levenshtein_distance, edit_distance, edit_distance_within,
+    edit_script, myers_edit_distance, Alignment, EditCosts, EditOp, UnitCosts,
+};
+pub use self::eertree::{Eertree, Palindrome};
+pub use self::fm_index::FmIndex;
 pub use self::hamming_distance::hamming_distance;
-pub use self::isogram::is_isogram;
-pub use self::isomorphism::is_isomorphic;
+pub use self::isogram::{is_isogram, is_isogram_with};
+pub use self::isomorphism::{is_isomorphic, is_isomorphic_with};
 pub use self::jaro_winkler_distance::jaro_winkler_distance;
 pub use self::knuth_morris_pratt::knuth_morris_pratt;
 pub use self::levenshtein_distance::{naive_levenshtein_distance, optimized_levenshtein_distance};
 pub use self::lipogram::is_lipogram;
-pub use self::manacher::manacher;
-pub use self::palindrome::is_palindrome;
+pub use self::manacher::{manacher, manacher_radii, PalindromeRadii};
+pub use self::palindrome::{is_palindrome, is_palindrome_with};
 pub use self::pangram::is_pangram;
 pub use self::pangram::PangramStatus;
+pub use self::q_gram_index::QGramIndex;
 pub use self::rabin_karp::rabin_karp;
+pub use self::regex::{Dfa, Regex, RegexError};
 pub use self::reverse::reverse;
 pub use self::run_length_encoding::{run_length_decoding, run_length_encoding};
 pub use self::shortest_palindrome::shortest_palindrome;
-pub use self::suffix_array::generate_suffix_array;
+pub use self::suffix_array::{generate_suffix_array, lcp_array, LcpRmq, SuffixArray};
 pub use self::suffix_array_manber_myers::generate_suffix_array_manber_myers;
-pub use self::suffix_tree::{Node, SuffixTree};
+pub use self::suffix_array_sa_is::generate_suffix_array_sa_is;
+pub use self::suffix_tree::{GeneralizedSuffixTree, SuffixTree};
+pub use self::unicode::{char_index, graphemes, Normalization, TextOptions};
 pub use self::z_algorithm::match_pattern;
 pub use self::z_algorithm::z_array;
@@ -1,5 +1,7 @@
 //! A module for checking if a given string is a palindrome.
 
+use super::unicode::{graphemes, TextOptions};
+
 /// Checks if the given string is a palindrome.
 ///
 /// A palindrome is a sequence that reads the same backward as forward.
@@ -13,10 +15,28 @@
 ///
 /// * `true` if the string is a palindrome; otherwise, `false`.
 pub fn is_palindrome(s: &str) -> bool {
-    let mut chars = s
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .map(|c| c.to_ascii_lowercase());
+    is_palindrome_with(s, TextOptions::default())
+}
+
+/// Checks if the given string is a palindrome after case folding and normalizing it as set
+/// in `options`.
+///
+/// The string is read as grapheme clusters, so a letter keeps its combining accents when it
+/// is read backward. Clusters not starting with an alphanumeric character are ignored.
+///
+/// # Arguments
+///
+/// * `s` - A string slice that represents the input to be checked.
+/// * `options` - How the string is prepared before the comparison.
+///
+/// # Returns
+///
+/// * `true` if the string is a palindrome; otherwise, `false`.
+pub fn is_palindrome_with(s: &str, options: TextOptions) -> bool {
+    let prepared = options.apply(s);
+    let mut chars = graphemes(&prepared)
+        .into_iter()
+        .filter(|g| g.chars().next().is_some_and(char::is_alphanumeric));
 
     while let (Some(c1), Some(c2)) = (chars.next(), chars.next_back()) {
         if c1 != c2 {
@@ -70,5 +90,25 @@ mod tests {
         non_palindrome_simple: ("hello", false),
         non_palindrome_with_punctuation: ("hello!", false),
         non_palindrome_mixed_case: ("Hello, World", false),
+        accents_are_not_ignored: ("Ésope reste ici et se repose", false),
+        accented_palindrome: ("Été", true),
+        decomposed_accents_palindrome: ("e\u{301}te\u{301}", true),
+        misplaced_accent_non_palindrome: ("e\u{301}te", false),
+        cyrillic_palindrome: ("А роза упала на лапу Азора", true),
+    }
+
+    #[test]
+    fn palindrome_with_options() {
+        use crate::string::{Normalization, TextOptions};
+
+        assert!(!is_palindrome_with("Abba", TextOptions::EXACT));
+        assert!(is_palindrome_with("abba", TextOptions::EXACT));
+        // Composed and decomposed accents only match once normalized.
+        assert!(!is_palindrome_with("ée\u{301}", TextOptions::EXACT));
+        let nfd = TextOptions {
+            case_fold: false,
+            normalization: Normalization::Nfd,
+        };
+        assert!(is_palindrome_with("ée\u{301}", nfd));
     }
 }
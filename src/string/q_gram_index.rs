@@ -0,0 +1,200 @@
+//! A q-gram index finds the words within some Levenshtein distance of a query without
+//! comparing the query to every word.
+//!
+//! Each word is padded with `q - 1` markers on both sides and split into its `n + q - 1`
+//! overlapping q-grams (substrings of `q` characters), and an inverted index maps every
+//! q-gram to the words containing it. An edit touches at most `q` q-grams, so a word within
+//! distance `k` of the query shares at least `max(n, m) + q - 1 - k * q` of them (counted
+//! with multiplicity). Only the words reached through the postings of the query and passing
+//! this count filter and the length filter `|n - m| <= k` are verified with a banded edit
+//! distance. When the bound is not positive, a match may share no q-gram with the query, and
+//! every word passing the length filter is verified instead.
+//!
+//! Reference: Ukkonen, "Approximate string-matching with q-grams and maximal matches"
+
+use std::collections::HashMap;
+
+use super::bk_tree::{rank, ApproximateMatch};
+use super::edit_distance_within;
+
+/// A q-gram, with `None` for the padding markers.
+type QGram = Vec<Option<char>>;
+
+pub struct QGramIndex {
+    q: usize,
+    words: Vec<String>,
+    /// The id of every word.
+    ids: HashMap<String, usize>,
+    /// The length of every word in characters.
+    lengths: Vec<usize>,
+    /// The words containing every q-gram, with the number of occurrences.
+    postings: HashMap<QGram, Vec<(usize, usize)>>,
+}
+
+/// The padded q-grams of `word` with their number of occurrences.
+fn q_grams(word: &[char], q: usize) -> HashMap<QGram, usize> {
+    let padded: Vec<Option<char>> = std::iter::repeat_n(None, q - 1)
+        .chain(word.iter().copied().map(Some))
+        .chain(std::iter::repeat_n(None, q - 1))
+        .collect();
+    let mut counts = HashMap::new();
+    for gram in padded.windows(q) {
+        *counts.entry(gram.to_vec()).or_insert(0) += 1;
+    }
+    counts
+}
+
+impl QGramIndex {
+    /// An empty index over q-grams of `q` characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is 0.
+    pub fn new(q: usize) -> Self {
+        assert!(q > 0, "q-grams must have at least one character");
+        Self {
+            q,
+            words: Vec::new(),
+            ids: HashMap::new(),
+            lengths: Vec::new(),
+            postings: HashMap::new(),
+        }
+    }
+
+    pub fn from_words<S: AsRef<str>>(q: usize, words: &[S]) -> Self {
+        let mut index = Self::new(q);
+        for word in words {
+            index.insert(word.as_ref());
+        }
+        index
+    }
+
+    pub fn q(&self) -> usize {
+        self.q
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Adds a word, returning `false` if it was already present.
+    pub fn insert(&mut self, word: &str) -> bool {
+        if self.contains(word) {
+            return false;
+        }
+        let id = self.words.len();
+        let chars: Vec<char> = word.chars().collect();
+        for (gram, count) in q_grams(&chars, self.q) {
+            self.postings.entry(gram).or_default().push((id, count));
+        }
+        self.words.push(word.to_string());
+        self.ids.insert(word.to_string(), id);
+        self.lengths.push(chars.len());
+        true
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.ids.contains_key(word)
+    }
+
+    /// The words within Levenshtein distance `max_distance` of `query`, ranked by distance then
+    /// similarity.
+    pub fn search(&self, query: &str, max_distance: usize) -> Vec<ApproximateMatch<'_>> {
+        let query_chars: Vec<char> = query.chars().collect();
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for (gram, query_count) in q_grams(&query_chars, self.q) {
+            for &(id, count) in self.postings.get(&gram).map_or(&[][..], Vec::as_slice) {
+                *shared.entry(id).or_insert(0) += count.min(query_count);
+            }
+        }
+        // The words within the distance are longer than the query by at most `max_distance`,
+        // so they share at least this many q-grams with it.
+        let shortest_bound = (query_chars.len() + self.q - 1).checked_sub(max_distance * self.q);
+        let candidates: Vec<usize> = match shortest_bound {
+            Some(bound) if bound > 0 => shared.keys().copied().collect(),
+            _ => (0..self.words.len()).collect(),
+        };
+
+        let mut matches = Vec::new();
+        for id in candidates {
+            let length = self.lengths[id];
+            if length.abs_diff(query_chars.len()) > max_distance {
+                continue;
+            }
+            let grams = length.max(query_chars.len()) + self.q - 1;
+            if shared.get(&id).copied().unwrap_or(0) + max_distance * self.q < grams {
+                continue;
+            }
+            let word = &self.words[id];
+            let chars: Vec<char> = word.chars().collect();
+            if let Some(distance) = edit_distance_within(&chars, &query_chars, max_distance) {
+                matches.push(ApproximateMatch::new(word, query, distance));
+            }
+        }
+        rank(&mut matches);
+        matches
+    }
+
+    /// The words in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string::BkTree;
+
+    #[test]
+    fn matches_the_bk_tree() {
+        let words = [
+            "receive", "recipe", "deceive", "believe", "relieve", "review", "revive", "reserve",
+            "reverse", "recite", "receipt", "réceive", "",
+        ];
+        let tree: BkTree = words.iter().collect();
+        for q in 1..=3 {
+            let index = QGramIndex::from_words(q, &words);
+            assert_eq!(index.len(), words.len());
+            for query in ["recieve", "receive", "revese", "r", "", "xyz", "réceipt"] {
+                for k in 0..=3 {
+                    assert_eq!(index.search(query, k), tree.search(query, k), "{query} {k}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn did_you_mean() {
+        let index = QGramIndex::from_words(2, &["apple", "apply", "ample", "maple", "banana"]);
+        let matches = index.search("appel", 2);
+        assert_eq!(matches[0].word, "apple");
+        assert_eq!(matches[0].distance, 2);
+        assert!(matches.iter().all(|m| m.word != "banana"));
+        assert!(index.search("appel", 1).is_empty());
+    }
+
+    #[test]
+    fn insert_and_contains() {
+        let mut index = QGramIndex::new(3);
+        assert!(index.is_empty());
+        assert!(index.insert("aaaa"));
+        assert!(index.insert("aaa"));
+        assert!(!index.insert("aaaa"));
+        assert!(index.contains("aaa"));
+        assert!(!index.contains("aa"));
+        assert_eq!(index.q(), 3);
+        assert_eq!(index.iter().collect::<Vec<_>>(), ["aaaa", "aaa"]);
+        assert_eq!(index.search("aa", 1).len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_empty_q_grams() {
+        QGramIndex::new(0);
+    }
+}
@@ -12,12 +12,13 @@ const RADIX: usize = 256;
 /// * `pattern` - The substring pattern to search for.
 ///
 /// # Returns
-/// A vector of starting indices where the pattern is found.
+/// A vector of the byte offsets in `text` where the pattern is found.
 pub fn rabin_karp(text: &str, pattern: &str) -> Vec<usize> {
     if text.is_empty() || pattern.is_empty() || pattern.len() > text.len() {
         return vec![];
     }
 
+    let (text, pattern) = (text.as_bytes(), pattern.as_bytes());
     let pat_hash = compute_hash(pattern);
     let mut radix_pow = 1;
 
@@ -44,13 +45,13 @@ pub fn rabin_karp(text: &str, pattern: &str) -> Vec<usize> {
 /// Calculates the hash of a string using the Rabin-Karp formula.
 ///
 /// # Arguments
-/// * `s` - The string to calculate the hash for.
+/// * `s` - The bytes to calculate the hash for.
 ///
 /// # Returns
-/// The hash value of the string modulo `MOD`.
-fn compute_hash(s: &str) -> usize {
+/// The hash value of the bytes modulo `MOD`.
+fn compute_hash(s: &[u8]) -> usize {
     let mut hash_val = 0;
-    for &byte in s.as_bytes().iter() {
+    for &byte in s.iter() {
         hash_val = (hash_val * RADIX + byte as usize) % MOD;
     }
     hash_val
@@ -60,23 +61,23 @@ fn compute_hash(s: &str) -> usize {
 ///
 /// # Arguments
 /// * `s` - The full text where the search is performed.
-/// * `old_idx` - The index of the character that is leaving the window.
-/// * `new_idx` - The index of the new character entering the window.
+/// * `old_idx` - The index of the byte that is leaving the window.
+/// * `new_idx` - The index of the new byte entering the window.
 /// * `old_hash` - The hash of the previous substring.
 /// * `radix_pow` - The precomputed value of RADIX^(n-1) % MOD.
 ///
 /// # Returns
 /// The updated hash for the new substring.
 fn update_hash(
-    s: &str,
+    s: &[u8],
     old_idx: usize,
     new_idx: usize,
     old_hash: usize,
     radix_pow: usize,
 ) -> usize {
     let mut new_hash = old_hash;
-    let old_char = s.as_bytes()[old_idx] as usize;
-    let new_char = s.as_bytes()[new_idx] as usize;
+    let old_char = s[old_idx] as usize;
+    let new_char = s[new_idx] as usize;
     new_hash = (new_hash + MOD - (old_char * radix_pow % MOD)) % MOD;
     new_hash = (new_hash * RADIX + new_char) % MOD;
     new_hash
@@ -119,5 +120,7 @@ mod tests {
         special_characters: ("abc$def@ghi", "$def@", vec![3]),
         numeric_and_alphabetic_mix: ("abc123abc456", "123abc", vec![3]),
         case_sensitivity: ("AbcAbc", "abc", vec![]),
+        unicode_byte_offsets: ("日本語の日本", "日本", vec![0, 12]),
+        unicode_pattern_after_multibyte: ("é-e", "e", vec![3]),
     }
 }
@@ -0,0 +1,419 @@
+//! Parsing of patterns into an abstract syntax tree.
+
+use super::RegexError;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// The abstract syntax tree of a regular expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ast {
+    /// Matches the empty string.
+    Empty,
+    Literal(char),
+    /// Matches one character in any of the (sorted, disjoint) inclusive ranges.
+    Class(Vec<(char, char)>),
+    /// `^`: matches at the start of the text.
+    StartText,
+    /// `$`: matches at the end of the text.
+    EndText,
+    Concat(Vec<Ast>),
+    /// Matches any of the alternatives, preferring the first ones.
+    Alternation(Vec<Ast>),
+    Repetition {
+        ast: Box<Ast>,
+        kind: RepetitionKind,
+        greedy: bool,
+    },
+    /// A group, capturing with the given index (from 1) or non-capturing.
+    Group {
+        ast: Box<Ast>,
+        index: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepetitionKind {
+    /// `?`
+    ZeroOrOne,
+    /// `*`
+    ZeroOrMore,
+    /// `+`
+    OneOrMore,
+}
+
+/// Parses `pattern`, returning its syntax tree and its number of capture groups.
+///
+/// The syntax is a subset of the usual one: literals, `.` (any character but `\n`), classes
+/// (`[a-z_]`, `[^0-9]`), the escapes `\d`, `\w`, `\s` and their negations `\D`, `\W`, `\S`,
+/// `\n`, `\r`, `\t` and escaped punctuation, the anchors `^` and `$`, groups `(...)` and
+/// `(?:...)`, alternation `|` and the greedy or lazy (`*?`, `+?`, `??`) repetitions `*`, `+`
+/// and `?`.
+///
+/// Groups and repetitions may nest at most [`MAX_NESTING`] levels deep, counting every
+/// operator of a chain like `a**`, so that the tree can be compiled and dropped recursively.
+pub fn parse(pattern: &str) -> Result<(Ast, usize), RegexError> {
+    let mut parser = Parser {
+        chars: pattern.char_indices().peekable(),
+        groups: 0,
+        open_groups: 0,
+    };
+    let (ast, _) = parser.parse_alternation()?;
+    match parser.chars.next() {
+        None => Ok((ast, parser.groups)),
+        Some((position, _)) => Err(RegexError::UnmatchedParenthesis(position)),
+    }
+}
+
+/// The maximum number of nested groups and repetitions in a pattern.
+pub const MAX_NESTING: usize = 250;
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    groups: usize,
+    /// The number of groups enclosing the current position.
+    open_groups: usize,
+}
+
+/// A parsed subtree with its nesting height: the number of groups and repetitions on its
+/// longest branch.
+type Parsed = (Ast, usize);
+
+impl Parser<'_> {
+    fn next(&mut self) -> Result<(usize, char), RegexError> {
+        self.chars.next().ok_or(RegexError::UnexpectedEnd)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if(|&(_, next)| next == c).is_some()
+    }
+
+    fn parse_alternation(&mut self) -> Result<Parsed, RegexError> {
+        let (first, mut height) = self.parse_concat()?;
+        let mut alternatives = vec![first];
+        while self.eat('|') {
+            let (alternative, alternative_height) = self.parse_concat()?;
+            alternatives.push(alternative);
+            height = height.max(alternative_height);
+        }
+        let ast = if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Ast::Alternation(alternatives)
+        };
+        Ok((ast, height))
+    }
+
+    fn parse_concat(&mut self) -> Result<Parsed, RegexError> {
+        let mut items = Vec::new();
+        let mut height = 0;
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let (item, item_height) = self.parse_repetition()?;
+            items.push(item);
+            height = height.max(item_height);
+        }
+        let ast = match items.len() {
+            0 => Ast::Empty,
+            1 => items.remove(0),
+            _ => Ast::Concat(items),
+        };
+        Ok((ast, height))
+    }
+
+    fn parse_repetition(&mut self) -> Result<Parsed, RegexError> {
+        let (mut ast, mut height) = self.parse_atom()?;
+        loop {
+            let Some(&(position, _)) = self.chars.peek() else {
+                return Ok((ast, height));
+            };
+            let kind = if self.eat('*') {
+                RepetitionKind::ZeroOrMore
+            } else if self.eat('+') {
+                RepetitionKind::OneOrMore
+            } else if self.eat('?') {
+                RepetitionKind::ZeroOrOne
+            } else {
+                return Ok((ast, height));
+            };
+            height += 1;
+            if height > MAX_NESTING {
+                return Err(RegexError::NestingTooDeep(position));
+            }
+            let greedy = !self.eat('?');
+            ast = Ast::Repetition {
+                ast: Box::new(ast),
+                kind,
+                greedy,
+            };
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Parsed, RegexError> {
+        let (position, c) = self.next()?;
+        let ast = match c {
+            '(' => {
+                // Checked before parsing the group, to bound the recursion of the parser.
+                if self.open_groups == MAX_NESTING {
+                    return Err(RegexError::NestingTooDeep(position));
+                }
+                let index = if self.eat('?') {
+                    if !self.eat(':') {
+                        return Err(RegexError::UnsupportedGroup(position));
+                    }
+                    None
+                } else {
+                    self.groups += 1;
+                    Some(self.groups)
+                };
+                self.open_groups += 1;
+                let (ast, height) = self.parse_alternation()?;
+                self.open_groups -= 1;
+                if !self.eat(')') {
+                    return Err(RegexError::UnmatchedParenthesis(position));
+                }
+                if height == MAX_NESTING {
+                    return Err(RegexError::NestingTooDeep(position));
+                }
+                let group = Ast::Group {
+                    ast: Box::new(ast),
+                    index,
+                };
+                return Ok((group, height + 1));
+            }
+            '[' => self.parse_class()?,
+            '.' => Ast::Class(negate(&[('\n', '\n')])),
+            '^' => Ast::StartText,
+            '$' => Ast::EndText,
+            '*' | '+' | '?' => return Err(RegexError::NothingToRepeat(position)),
+            '\\' => match self.parse_escape()? {
+                Escape::Char(c) => Ast::Literal(c),
+                Escape::Class(ranges) => Ast::Class(ranges),
+            },
+            c => Ast::Literal(c),
+        };
+        Ok((ast, 0))
+    }
+
+    fn parse_escape(&mut self) -> Result<Escape, RegexError> {
+        let (position, c) = self.next()?;
+        let class = |ranges: &[(char, char)], negated: bool| {
+            Escape::Class(if negated {
+                negate(ranges)
+            } else {
+                ranges.to_vec()
+            })
+        };
+        Ok(match c {
+            'd' | 'D' => class(DIGIT, c == 'D'),
+            'w' | 'W' => class(WORD, c == 'W'),
+            's' | 'S' => class(SPACE, c == 'S'),
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            't' => Escape::Char('\t'),
+            c if c.is_ascii_punctuation() => Escape::Char(c),
+            _ => return Err(RegexError::InvalidEscape(position)),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Ast, RegexError> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let (position, c) = self.next()?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let start = match c {
+                '\\' => match self.parse_escape()? {
+                    Escape::Char(c) => c,
+                    Escape::Class(class) => {
+                        ranges.extend(class);
+                        continue;
+                    }
+                },
+                c => c,
+            };
+            // A '-' is a range unless it comes last.
+            let is_range = self.chars.peek().is_some_and(|&(_, c)| c == '-')
+                && self.chars.clone().nth(1).is_some_and(|(_, c)| c != ']');
+            if !is_range {
+                ranges.push((start, start));
+                continue;
+            }
+            self.next()?;
+            let end = match self.next()? {
+                (_, '\\') => match self.parse_escape()? {
+                    Escape::Char(c) => c,
+                    Escape::Class(_) => return Err(RegexError::InvalidRange(position)),
+                },
+                (_, c) => c,
+            };
+            if end < start {
+                return Err(RegexError::InvalidRange(position));
+            }
+            ranges.push((start, end));
+        }
+        let ranges = normalize(ranges);
+        Ok(Ast::Class(if negated { negate(&ranges) } else { ranges }))
+    }
+}
+
+enum Escape {
+    Char(char),
+    Class(Vec<(char, char)>),
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+/// Sorts ranges and merges the overlapping or adjacent ones.
+fn normalize(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(char, char)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if u32::from(start) <= u32::from(last.1) + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// The complement of sorted, disjoint ranges among all characters.
+fn negate(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut complement = Vec::new();
+    let mut next = Some('\0');
+    for &(start, end) in ranges {
+        if let Some(from) = next {
+            if from < start {
+                complement.push((from, previous_char(start)));
+            }
+        }
+        next = next_char(end);
+    }
+    if let Some(from) = next {
+        complement.push((from, char::MAX));
+    }
+    complement
+}
+
+/// The character after `c`, skipping the surrogate code points.
+pub(super) fn next_char(c: char) -> Option<char> {
+    match c {
+        '\u{D7FF}' => Some('\u{E000}'),
+        c => char::from_u32(u32::from(c) + 1),
+    }
+}
+
+fn previous_char(c: char) -> char {
+    match c {
+        '\u{E000}' => '\u{D7FF}',
+        c => char::from_u32(u32::from(c) - 1).unwrap_or('\0'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_structure() {
+        let (ast, groups) = parse("a(b|c)*?$").unwrap();
+        assert_eq!(groups, 1);
+        assert_eq!(
+            ast,
+            Ast::Concat(vec![
+                Ast::Literal('a'),
+                Ast::Repetition {
+                    ast: Box::new(Ast::Group {
+                        ast: Box::new(Ast::Alternation(vec![Ast::Literal('b'), Ast::Literal('c')])),
+                        index: Some(1),
+                    }),
+                    kind: RepetitionKind::ZeroOrMore,
+                    greedy: false,
+                },
+                Ast::EndText,
+            ])
+        );
+        assert_eq!(parse("").unwrap(), (Ast::Empty, 0));
+        assert_eq!(
+            parse("(?:x)|").unwrap().0,
+            Ast::Alternation(vec![
+                Ast::Group {
+                    ast: Box::new(Ast::Literal('x')),
+                    index: None
+                },
+                Ast::Empty
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_classes() {
+        assert_eq!(
+            parse("[a-cx\\d-]").unwrap().0,
+            Ast::Class(vec![('-', '-'), ('0', '9'), ('a', 'c'), ('x', 'x')])
+        );
+        assert_eq!(
+            parse("[]a]").unwrap().0,
+            Ast::Class(vec![(']', ']'), ('a', 'a')])
+        );
+        assert_eq!(
+            parse("[^b-y]").unwrap().0,
+            Ast::Class(vec![('\0', 'a'), ('z', char::MAX)])
+        );
+        assert_eq!(
+            parse("\\S").unwrap().0,
+            Ast::Class(vec![('\0', '\u{8}'), ('\u{E}', '\u{1F}'), ('!', char::MAX)])
+        );
+        assert_eq!(negate(&[('\0', '\u{D7FF}')]), vec![('\u{E000}', char::MAX)]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse("a(b"), Err(RegexError::UnmatchedParenthesis(1)));
+        assert_eq!(parse("ab)"), Err(RegexError::UnmatchedParenthesis(2)));
+        assert_eq!(parse("*a"), Err(RegexError::NothingToRepeat(0)));
+        assert_eq!(parse("a|+"), Err(RegexError::NothingToRepeat(2)));
+        assert_eq!(parse("\\q"), Err(RegexError::InvalidEscape(1)));
+        assert_eq!(parse("[z-a]"), Err(RegexError::InvalidRange(1)));
+        assert_eq!(parse("[ab"), Err(RegexError::UnexpectedEnd));
+        assert_eq!(parse("ab\\"), Err(RegexError::UnexpectedEnd));
+        assert_eq!(parse("(?=a)"), Err(RegexError::UnsupportedGroup(0)));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(parse(&nested(MAX_NESTING)).unwrap().1, MAX_NESTING);
+        assert_eq!(
+            parse(&nested(MAX_NESTING + 1)),
+            Err(RegexError::NestingTooDeep(MAX_NESTING))
+        );
+        assert_eq!(
+            parse(&nested(100_000)),
+            Err(RegexError::NestingTooDeep(MAX_NESTING))
+        );
+        assert!(parse(&format!("a{}", "*".repeat(MAX_NESTING))).is_ok());
+        assert_eq!(
+            parse(&format!("a{}", "*".repeat(100_000))),
+            Err(RegexError::NestingTooDeep(MAX_NESTING + 1))
+        );
+        // Repetitions count towards the nesting of the groups around them.
+        let inner = format!("a{}", "+".repeat(MAX_NESTING - 1));
+        assert!(parse(&format!("({inner})")).is_ok());
+        assert_eq!(
+            parse(&format!("(({inner}))")),
+            Err(RegexError::NestingTooDeep(0))
+        );
+        assert_eq!(
+            parse(&format!("({inner})?")),
+            Err(RegexError::NestingTooDeep(MAX_NESTING + 2))
+        );
+    }
+}
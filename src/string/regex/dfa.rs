@@ -0,0 +1,260 @@
+//! Subset construction of DFAs from the NFA program, either lazily while matching or eagerly,
+//! and DFA minimization.
+
+use super::ast::next_char;
+use super::nfa::{closure, Inst, Program};
+use super::RegexError;
+use std::collections::HashMap;
+
+/// A partition of all characters into intervals that no instruction of the program tells
+/// apart, so that a DFA only needs one transition per interval.
+#[derive(Debug, Clone)]
+pub(super) struct CharClasses {
+    /// Class `i` holds the characters in `[boundaries[i - 1], boundaries[i])`.
+    boundaries: Vec<char>,
+}
+
+impl CharClasses {
+    pub(super) fn new(program: &Program) -> Self {
+        let mut boundaries = Vec::new();
+        for inst in &program.insts {
+            let ranges = match inst {
+                Inst::Char(c) => vec![(*c, *c)],
+                Inst::Class(ranges) => ranges.clone(),
+                _ => continue,
+            };
+            for (start, end) in ranges {
+                boundaries.push(start);
+                boundaries.extend(next_char(end));
+            }
+        }
+        boundaries.retain(|&c| c != '\0');
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        CharClasses { boundaries }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.boundaries.len() + 1
+    }
+
+    pub(super) fn class_of(&self, c: char) -> usize {
+        self.boundaries.partition_point(|&b| b <= c)
+    }
+
+    fn representative(&self, class: usize) -> char {
+        class.checked_sub(1).map_or('\0', |i| self.boundaries[i])
+    }
+}
+
+/// A DFA state: a set of NFA states, and whether it is the state at the start of the text
+/// (where `^` holds).
+type Key = (Vec<usize>, bool);
+
+/// The NFA states reached from `pcs` by consuming a character of `class`.
+fn step(program: &Program, classes: &CharClasses, pcs: &[usize], class: usize) -> Vec<usize> {
+    let c = classes.representative(class);
+    pcs.iter()
+        .filter(|&&pc| program.insts[pc].matches(c))
+        .map(|&pc| pc + 1)
+        .collect()
+}
+
+/// Whether the program accepts when the text ends in the DFA state `key`.
+fn accepts_at_end(program: &Program, (pcs, at_start): &Key) -> bool {
+    closure(program, pcs, *at_start, true)
+        .iter()
+        .any(|&pc| program.insts[pc] == Inst::Match)
+}
+
+/// The most states a lazy DFA keeps before it starts over with an empty cache.
+const CACHE_LIMIT: usize = 4096;
+
+/// A DFA built on demand while searching, for an unanchored search: a new NFA thread starts
+/// at every position. Every character costs one table lookup once its transition is known,
+/// and at most one subset construction step (O(m) for a program of m instructions) before.
+pub(super) struct LazyDfa {
+    states: Vec<LazyState>,
+    /// `transitions[classes * state + class]`, once computed.
+    transitions: Vec<Option<usize>>,
+    index: HashMap<Key, usize>,
+}
+
+struct LazyState {
+    key: Key,
+    /// Whether a match ends here.
+    matching: bool,
+    matching_at_end: bool,
+}
+
+impl LazyDfa {
+    pub(super) fn new() -> Self {
+        LazyDfa {
+            states: Vec::new(),
+            transitions: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, program: &Program, classes: &CharClasses, key: Key) -> usize {
+        if let Some(&state) = self.index.get(&key) {
+            return state;
+        }
+        let state = self.states.len();
+        self.states.push(LazyState {
+            matching: key.0.iter().any(|&pc| program.insts[pc] == Inst::Match),
+            matching_at_end: accepts_at_end(program, &key),
+            key: key.clone(),
+        });
+        self.transitions
+            .extend(std::iter::repeat_n(None, classes.len()));
+        self.index.insert(key, state);
+        state
+    }
+
+    fn next(
+        &mut self,
+        program: &Program,
+        classes: &CharClasses,
+        state: usize,
+        class: usize,
+    ) -> usize {
+        let slot = classes.len() * state + class;
+        if let Some(next) = self.transitions[slot] {
+            return next;
+        }
+        let mut pcs = step(program, classes, &self.states[state].key.0, class);
+        pcs.push(0);
+        let key = (closure(program, &pcs, false, false), false);
+        if self.states.len() >= CACHE_LIMIT {
+            // Bound the memory use: start over, keeping only the state we go to.
+            *self = LazyDfa::new();
+            return self.add(program, classes, key);
+        }
+        let next = self.add(program, classes, key);
+        self.transitions[slot] = Some(next);
+        next
+    }
+
+    /// Whether some match of the program occurs in `text`.
+    pub(super) fn is_match(
+        &mut self,
+        program: &Program,
+        classes: &CharClasses,
+        text: &str,
+    ) -> bool {
+        let start = (closure(program, &[0], true, false), true);
+        let mut state = self.add(program, classes, start);
+        for c in text.chars() {
+            if self.states[state].matching {
+                return true;
+            }
+            state = self.next(program, classes, state, classes.class_of(c));
+        }
+        self.states[state].matching || self.states[state].matching_at_end
+    }
+}
+
+/// A complete deterministic automaton recognizing the texts that the pattern matches
+/// entirely (as if it was surrounded by `^` and `$`).
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    classes: CharClasses,
+    /// `transitions[classes * state + class]`.
+    transitions: Vec<usize>,
+    accepting: Vec<bool>,
+    start: usize,
+}
+
+impl Dfa {
+    /// Builds the DFA of `program` by subset construction.
+    pub(super) fn new(program: &Program, max_states: usize) -> Result<Self, RegexError> {
+        let classes = CharClasses::new(program);
+        let start = (closure(program, &[0], true, false), true);
+        let mut index = HashMap::from([(start.clone(), 0)]);
+        let mut keys = vec![start];
+        let mut transitions = Vec::new();
+        let mut state = 0;
+        while state < keys.len() {
+            for class in 0..classes.len() {
+                let pcs = step(program, &classes, &keys[state].0, class);
+                let key = (closure(program, &pcs, false, false), false);
+                let next = *index.entry(key).or_insert_with_key(|key| {
+                    keys.push(key.clone());
+                    keys.len() - 1
+                });
+                if keys.len() > max_states {
+                    return Err(RegexError::TooManyStates);
+                }
+                transitions.push(next);
+            }
+            state += 1;
+        }
+        Ok(Dfa {
+            accepting: keys
+                .iter()
+                .map(|key| accepts_at_end(program, key))
+                .collect(),
+            classes,
+            transitions,
+            start: 0,
+        })
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.accepting.len()
+    }
+
+    /// Whether the whole `text` matches.
+    pub fn is_match(&self, text: &str) -> bool {
+        let width = self.classes.len();
+        let end = text.chars().fold(self.start, |state, c| {
+            self.transitions[width * state + self.classes.class_of(c)]
+        });
+        self.accepting[end]
+    }
+
+    /// The equivalent DFA with the fewest states, found by Moore's partition refinement:
+    /// starting from the accepting and the other states, states are split apart as long as
+    /// some character leads them into different blocks.
+    pub fn minimize(&self) -> Dfa {
+        let width = self.classes.len();
+        let n = self.state_count();
+        let mut block: Vec<usize> = self.accepting.iter().map(|&a| usize::from(a)).collect();
+        let mut blocks = block.iter().max().map_or(0, |&b| b + 1);
+        loop {
+            let mut ids = HashMap::new();
+            let refined: Vec<usize> = (0..n)
+                .map(|state| {
+                    let signature: Vec<usize> = std::iter::once(block[state])
+                        .chain((0..width).map(|c| block[self.transitions[width * state + c]]))
+                        .collect();
+                    let next_id = ids.len();
+                    *ids.entry(signature).or_insert(next_id)
+                })
+                .collect();
+            let refined_blocks = ids.len();
+            block = refined;
+            if refined_blocks == blocks {
+                break;
+            }
+            blocks = refined_blocks;
+        }
+
+        let mut transitions = vec![0; blocks * width];
+        let mut accepting = vec![false; blocks];
+        for state in 0..n {
+            let b = block[state];
+            accepting[b] = self.accepting[state];
+            for c in 0..width {
+                transitions[width * b + c] = block[self.transitions[width * state + c]];
+            }
+        }
+        Dfa {
+            classes: self.classes.clone(),
+            transitions,
+            accepting,
+            start: block[self.start],
+        }
+    }
+}
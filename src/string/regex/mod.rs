@@ -0,0 +1,325 @@
+//! A regular expression engine with linear-time matching.
+//!
+//! A pattern goes through the classic pipeline:
+//!
+//! 1. it is parsed into an [`Ast`] (see [`parse`] for the supported syntax),
+//! 2. the tree is compiled into an NFA with Thompson's construction: one small fragment per
+//!    node, glued with epsilon transitions,
+//! 3. [`Regex::is_match`] runs a lazy DFA: the subset construction is done on the fly, one
+//!    transition at a time, and cached, so each character of the text costs at most one
+//!    construction step and usually a single table lookup,
+//! 4. [`Regex::find`] and [`Regex::captures`] simulate the NFA with Pike's VM, which tracks
+//!    the capture positions of at most one thread per NFA state.
+//!
+//! Both never backtrack, so matching takes O(n m) time in the worst case for a text of
+//! length n and a pattern of size m, whatever the pattern, and so does [`Regex::find_iter`]
+//! for all the matches. [`Regex::to_dfa`] builds the full DFA of the pattern, which
+//! [`Dfa::minimize`] reduces to its minimal equivalent.
+//!
+//! Matches follow the leftmost-first semantics of backtracking engines: among the matches
+//! starting leftmost, the one preferred by the order of alternatives and the greediness of
+//! repetitions wins. Positions are byte offsets in the text.
+//!
+//! References:
+//! - [Thompson's construction](https://en.wikipedia.org/wiki/Thompson%27s_construction)
+//! - Russ Cox, [Regular Expression Matching: the Virtual Machine Approach](https://swtch.com/~rsc/regexp/regexp2.html)
+//! - [DFA minimization](https://en.wikipedia.org/wiki/DFA_minimization)
+
+mod ast;
+mod dfa;
+mod nfa;
+
+pub use self::ast::{parse, Ast, RepetitionKind, MAX_NESTING};
+pub use self::dfa::Dfa;
+
+use self::dfa::{CharClasses, LazyDfa};
+use self::nfa::{compile, pike_vm, Liveness, Program};
+use std::ops::Range;
+use std::sync::Mutex;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RegexError {
+    /// The pattern ends inside an escape, a class or a group.
+    UnexpectedEnd,
+    /// A parenthesis at the given byte offset of the pattern is not matched.
+    UnmatchedParenthesis(usize),
+    /// A repetition operator at the given offset follows nothing.
+    NothingToRepeat(usize),
+    /// An unknown escape sequence at the given offset.
+    InvalidEscape(usize),
+    /// A class range at the given offset is reversed or bounded by a class.
+    InvalidRange(usize),
+    /// A group with an unsupported `(?` flag at the given offset.
+    UnsupportedGroup(usize),
+    /// The group or repetition at the given offset nests deeper than
+    /// [`MAX_NESTING`] levels.
+    NestingTooDeep(usize),
+    /// The DFA would have more states than allowed.
+    TooManyStates,
+}
+
+/// A compiled regular expression.
+pub struct Regex {
+    ast: Ast,
+    program: Program,
+    classes: CharClasses,
+    groups: usize,
+    lazy_dfa: Mutex<LazyDfa>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let (ast, groups) = parse(pattern)?;
+        let program = compile(&ast, groups);
+        Ok(Regex {
+            classes: CharClasses::new(&program),
+            ast,
+            program,
+            groups,
+            lazy_dfa: Mutex::new(LazyDfa::new()),
+        })
+    }
+
+    pub fn ast(&self) -> &Ast {
+        &self.ast
+    }
+
+    /// The number of capture groups, not counting the whole match.
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    /// Whether the pattern matches somewhere in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        // A poisoned cache is still consistent: every update leaves it in a valid state.
+        let mut lazy_dfa = self
+            .lazy_dfa
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        lazy_dfa.is_match(&self.program, &self.classes, text)
+    }
+
+    /// The leftmost match in `text`.
+    pub fn find(&self, text: &str) -> Option<Range<usize>> {
+        self.find_at(text, 0, None)
+    }
+
+    fn find_at(&self, text: &str, start: usize, live: Option<&Liveness>) -> Option<Range<usize>> {
+        let slots = pike_vm(&self.program, text, start, live)?;
+        Some(slots[0]?..slots[1]?)
+    }
+
+    /// The successive non-overlapping matches in `text`. After an empty match, the search
+    /// resumes one character further.
+    ///
+    /// Every match is found by a new run of Pike's VM from the end of the previous one. A run
+    /// only stops once the threads preferred to its match have died, which can be far past the
+    /// end of the match (e.g. `\w+x|\w` on a long word), and the next run would scan that part
+    /// of the text again. So a backward pass first finds the states from which the rest of the
+    /// text has no match, and the runs drop their threads: the remaining ones are bound to
+    /// match, and every run stops at the end of its match. The text is read about twice, in
+    /// O(n m) time, with O(n m) bits of memory.
+    pub fn find_iter(&self, text: &str) -> Vec<Range<usize>> {
+        let live = Liveness::new(&self.program, text);
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start <= text.len() {
+            let Some(found) = self.find_at(text, start, Some(&live)) else {
+                break;
+            };
+            start = if found.is_empty() {
+                found.end + text[found.end..].chars().next().map_or(1, char::len_utf8)
+            } else {
+                found.end
+            };
+            matches.push(found);
+        }
+        matches
+    }
+
+    /// The positions of the leftmost match (at index 0) and of its capture groups (from index
+    /// 1), `None` for the groups that did not participate in the match.
+    pub fn captures(&self, text: &str) -> Option<Vec<Option<Range<usize>>>> {
+        let slots = pike_vm(&self.program, text, 0, None)?;
+        Some(
+            slots
+                .chunks(2)
+                .map(|pair| Some(pair[0]?..pair[1]?))
+                .collect(),
+        )
+    }
+
+    /// Builds the complete DFA recognizing the texts the pattern matches entirely, failing if
+    /// it has more than `max_states` states (subset construction can be exponential).
+    pub fn to_dfa(&self, max_states: usize) -> Result<Dfa, RegexError> {
+        Dfa::new(&self.program, max_states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strs<'a>(text: &'a str, ranges: &[Range<usize>]) -> Vec<&'a str> {
+        ranges.iter().map(|r| &text[r.clone()]).collect()
+    }
+
+    #[test]
+    fn is_match() {
+        let cases = [
+            ("abc", "xxabcxx", true),
+            ("abc", "abxc", false),
+            ("^abc", "abcd", true),
+            ("^abc", "xabc", false),
+            ("abc$", "xabc", true),
+            ("abc$", "abcx", false),
+            ("^$", "", true),
+            ("^$", "a", false),
+            ("a|b*c", "bbbc", true),
+            ("colou?r", "the color", true),
+            ("colou?r", "the colr", false),
+            ("[0-9]+-[0-9]+", "call 555-1234", true),
+            ("\\d\\d:\\d\\d", "at 9:30", false),
+            ("^\\w+@\\w+\\.com$", "me@example.com", true),
+            ("^\\w+@\\w+\\.com$", "me@example.org", false),
+            ("a.c", "a\nc", false),
+            ("[^a-z]", "abc", false),
+            ("[^a-z]", "abC", true),
+            ("", "", true),
+            ("x*", "yyy", true),
+            ("é+", "café", true),
+        ];
+        for (pattern, text, expected) in cases {
+            let regex = Regex::new(pattern).unwrap();
+            assert_eq!(regex.is_match(text), expected, "{pattern} on {text:?}");
+            assert_eq!(
+                regex.find(text).is_some(),
+                expected,
+                "{pattern} on {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn find_leftmost_first() {
+        let regex = Regex::new("a+|b").unwrap();
+        assert_eq!(regex.find("xbaaab"), Some(1..2));
+        let regex = Regex::new("a+?").unwrap();
+        assert_eq!(regex.find("xaaa"), Some(1..2));
+        let regex = Regex::new("ab|abcd").unwrap();
+        assert_eq!(regex.find("abcd"), Some(0..2));
+        let regex = Regex::new("[a-z]+").unwrap();
+        let text = "hello, wörld 42 times";
+        assert_eq!(
+            strs(text, &regex.find_iter(text)),
+            ["hello", "w", "rld", "times"]
+        );
+        let regex = Regex::new("x*").unwrap();
+        assert_eq!(regex.find_iter("axxb"), vec![0..0, 1..3, 3..3, 4..4]);
+        // Without pruning, every run would look for an `x` up to the end of the text.
+        let regex = Regex::new("\\w+x|\\w").unwrap();
+        let text = "a".repeat(200);
+        let matches = regex.find_iter(&text);
+        assert_eq!(matches.len(), 200);
+        assert!(matches.iter().enumerate().all(|(i, m)| *m == (i..i + 1)));
+    }
+
+    #[test]
+    fn captures() {
+        let regex = Regex::new("(\\w+)@(\\w+)(\\.(com|org))?").unwrap();
+        assert_eq!(regex.groups(), 4);
+        assert_eq!(
+            regex.captures("mail me@example now"),
+            Some(vec![Some(5..15), Some(5..7), Some(8..15), None, None])
+        );
+        assert_eq!(
+            regex.captures("me@host.org"),
+            Some(vec![
+                Some(0..11),
+                Some(0..2),
+                Some(3..7),
+                Some(7..11),
+                Some(8..11)
+            ])
+        );
+        assert_eq!(regex.captures("nothing here"), None);
+
+        // The last iteration of a repeated group is captured.
+        let regex = Regex::new("(?:(a)|(b))+").unwrap();
+        assert_eq!(
+            regex.captures("abab"),
+            Some(vec![Some(0..4), Some(2..3), Some(3..4)])
+        );
+        let regex = Regex::new("(a*)*").unwrap();
+        assert_eq!(regex.captures("b").unwrap()[0], Some(0..0));
+    }
+
+    #[test]
+    fn dfa_and_minimization() {
+        let regex = Regex::new("(a|b)*abb").unwrap();
+        let dfa = regex.to_dfa(100).unwrap();
+        let minimal = dfa.minimize();
+        // The textbook example: the minimal DFA has 4 states, plus a dead state for the
+        // characters other than 'a' and 'b'.
+        assert_eq!(minimal.state_count(), 5);
+        assert!(minimal.state_count() <= dfa.state_count());
+        for text in ["abb", "aabb", "babb", "ab", "abba", "", "abbabb", "xabb"] {
+            let expected = text.ends_with("abb") && text.chars().all(|c| c == 'a' || c == 'b');
+            assert_eq!(dfa.is_match(text), expected, "{text}");
+            assert_eq!(minimal.is_match(text), expected, "{text}");
+        }
+
+        let dfa = Regex::new("^a|b$").unwrap().to_dfa(100).unwrap().minimize();
+        assert!(dfa.is_match("a") && dfa.is_match("b") && !dfa.is_match("ab"));
+
+        // (a|b)*a(a|b){k} needs 2^k DFA states.
+        let pattern = format!("(a|b)*a{}", "(a|b)".repeat(12));
+        let regex = Regex::new(&pattern).unwrap();
+        assert_eq!(regex.to_dfa(1000).unwrap_err(), RegexError::TooManyStates);
+        assert!(regex.is_match(&"ab".repeat(100)));
+    }
+
+    #[test]
+    fn linear_time_on_pathological_patterns() {
+        // Catastrophic for backtracking engines: (a?){n}a{n} on a^n.
+        let n = 30;
+        let pattern = format!("{}{}", "a?".repeat(n), "a".repeat(n));
+        let regex = Regex::new(&pattern).unwrap();
+        let text = "a".repeat(n);
+        assert!(regex.is_match(&text));
+        assert_eq!(regex.find(&text), Some(0..n));
+
+        let regex = Regex::new("(x+x+)+y").unwrap();
+        let text = "x".repeat(5000);
+        assert!(!regex.is_match(&text));
+        assert_eq!(regex.find(&text), None);
+
+        // Quadratic if every match rescans the rest of the text.
+        let regex = Regex::new("\\w+x|\\w").unwrap();
+        let text = "a".repeat(50_000);
+        assert_eq!(regex.find_iter(&text).len(), 50_000);
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert_eq!(
+            Regex::new("(ab").err(),
+            Some(RegexError::UnmatchedParenthesis(0))
+        );
+        assert_eq!(Regex::new("a**").err(), None);
+        assert_eq!(Regex::new("+").err(), Some(RegexError::NothingToRepeat(0)));
+        let deep = format!("{}a{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(matches!(
+            Regex::new(&deep).err(),
+            Some(RegexError::NestingTooDeep(_))
+        ));
+        let stars = format!("a{}", "*".repeat(100_000));
+        assert!(matches!(
+            Regex::new(&stars).err(),
+            Some(RegexError::NestingTooDeep(_))
+        ));
+        let deepest = format!("{}a{}", "(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+        let regex = Regex::new(&deepest).unwrap();
+        assert_eq!(regex.captures("xa").unwrap()[MAX_NESTING], Some(1..2));
+    }
+}
@@ -0,0 +1,463 @@
+//! Thompson's construction of an NFA from the syntax tree, and its simulation with captures.
+
+use super::ast::{Ast, RepetitionKind};
+
+/// An instruction of the NFA program. Every instruction is a state; `Split` and `Jmp` are
+/// epsilon transitions, and the first branch of a `Split` has priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Inst {
+    Char(char),
+    Class(Vec<(char, char)>),
+    Split(usize, usize),
+    Jmp(usize),
+    /// Records the current position in a capture slot.
+    Save(usize),
+    StartText,
+    EndText,
+    Match,
+}
+
+impl Inst {
+    pub(super) fn matches(&self, c: char) -> bool {
+        match self {
+            Inst::Char(expected) => *expected == c,
+            Inst::Class(ranges) => ranges
+                .binary_search_by(|&(start, end)| {
+                    if end < c {
+                        std::cmp::Ordering::Less
+                    } else if start > c {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                })
+                .is_ok(),
+            _ => false,
+        }
+    }
+}
+
+pub(super) struct Program {
+    pub(super) insts: Vec<Inst>,
+    /// The number of capture slots: two per group, including the whole match as group 0.
+    pub(super) slots: usize,
+}
+
+/// Compiles `ast` (with `groups` capture groups) into a program whose first instruction is
+/// the start state.
+pub(super) fn compile(ast: &Ast, groups: usize) -> Program {
+    let mut insts = vec![Inst::Save(0)];
+    emit(ast, &mut insts);
+    insts.push(Inst::Save(1));
+    insts.push(Inst::Match);
+    Program {
+        insts,
+        slots: 2 * (groups + 1),
+    }
+}
+
+/// Appends the fragment of `ast`, which continues with the next instruction appended.
+fn emit(ast: &Ast, insts: &mut Vec<Inst>) {
+    match ast {
+        Ast::Empty => {}
+        Ast::Literal(c) => insts.push(Inst::Char(*c)),
+        Ast::Class(ranges) => insts.push(Inst::Class(ranges.clone())),
+        Ast::StartText => insts.push(Inst::StartText),
+        Ast::EndText => insts.push(Inst::EndText),
+        Ast::Concat(items) => items.iter().for_each(|item| emit(item, insts)),
+        Ast::Alternation(alternatives) => {
+            let mut jumps = Vec::new();
+            for (i, alternative) in alternatives.iter().enumerate() {
+                if i + 1 == alternatives.len() {
+                    emit(alternative, insts);
+                    break;
+                }
+                let split = insts.len();
+                insts.push(Inst::Split(split + 1, 0));
+                emit(alternative, insts);
+                jumps.push(insts.len());
+                insts.push(Inst::Jmp(0));
+                insts[split] = Inst::Split(split + 1, insts.len());
+            }
+            let end = insts.len();
+            for jump in jumps {
+                insts[jump] = Inst::Jmp(end);
+            }
+        }
+        Ast::Repetition { ast, kind, greedy } => {
+            let branches = |body: usize, exit: usize| {
+                if *greedy {
+                    Inst::Split(body, exit)
+                } else {
+                    Inst::Split(exit, body)
+                }
+            };
+            let start = insts.len();
+            match kind {
+                RepetitionKind::ZeroOrOne => {
+                    insts.push(Inst::Jmp(0));
+                    emit(ast, insts);
+                    insts[start] = branches(start + 1, insts.len());
+                }
+                RepetitionKind::ZeroOrMore => {
+                    insts.push(Inst::Jmp(0));
+                    emit(ast, insts);
+                    insts.push(Inst::Jmp(start));
+                    insts[start] = branches(start + 1, insts.len());
+                }
+                RepetitionKind::OneOrMore => {
+                    emit(ast, insts);
+                    let split = insts.len();
+                    insts.push(branches(start, split + 1));
+                }
+            }
+        }
+        Ast::Group { ast, index } => match index {
+            Some(index) => {
+                insts.push(Inst::Save(2 * index));
+                emit(ast, insts);
+                insts.push(Inst::Save(2 * index + 1));
+            }
+            None => emit(ast, insts),
+        },
+    }
+}
+
+/// A set of instruction indices with O(1) insertion, membership and clearing, which keeps
+/// the insertion order (the priority of the threads).
+struct SparseSet {
+    dense: Vec<usize>,
+    sparse: Vec<usize>,
+}
+
+impl SparseSet {
+    fn new(capacity: usize) -> Self {
+        SparseSet {
+            dense: Vec::with_capacity(capacity),
+            sparse: vec![0; capacity],
+        }
+    }
+
+    fn contains(&self, value: usize) -> bool {
+        let i = self.sparse[value];
+        i < self.dense.len() && self.dense[i] == value
+    }
+
+    fn insert(&mut self, value: usize) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+        self.sparse[value] = self.dense.len();
+        self.dense.push(value);
+        true
+    }
+
+    fn clear(&mut self) {
+        self.dense.clear();
+    }
+}
+
+type Slots = Vec<Option<usize>>;
+
+/// The threads of the Pike VM at one position: the instructions waiting for the next
+/// character, by priority, with their capture slots.
+struct Threads {
+    set: SparseSet,
+    slots: Vec<Slots>,
+}
+
+impl Threads {
+    fn new(program: &Program) -> Self {
+        Threads {
+            set: SparseSet::new(program.insts.len()),
+            slots: vec![vec![None; program.slots]; program.insts.len()],
+        }
+    }
+}
+
+/// For every position of a text, the states from which the rest of the text has a match,
+/// whatever the priorities. The threads in the other states never match, so Pike's VM can
+/// drop them.
+pub(super) struct Liveness {
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl Liveness {
+    /// Computes the live states backwards from the end of `text`, in O(n m) time: a state is
+    /// live if it matches, consumes the next character towards a live state, or has an epsilon
+    /// transition (whose assertion holds) to a live state.
+    pub(super) fn new(program: &Program, text: &str) -> Self {
+        let words = program.insts.len().div_ceil(64);
+        let mut liveness = Liveness {
+            words,
+            bits: vec![0; words * (text.len() + 1)],
+        };
+        let mut predecessors = vec![Vec::new(); program.insts.len()];
+        for (pc, inst) in program.insts.iter().enumerate() {
+            match *inst {
+                Inst::Jmp(target) => predecessors[target].push(pc),
+                Inst::Split(first, second) => {
+                    predecessors[first].push(pc);
+                    predecessors[second].push(pc);
+                }
+                Inst::Save(_) | Inst::StartText | Inst::EndText => predecessors[pc + 1].push(pc),
+                Inst::Char(_) | Inst::Class(_) | Inst::Match => {}
+            }
+        }
+        let positions = text
+            .char_indices()
+            .map(|(i, c)| (i, Some(c)))
+            .chain(std::iter::once((text.len(), None)));
+        let mut stack = Vec::new();
+        for (position, next) in positions.rev() {
+            for (pc, inst) in program.insts.iter().enumerate() {
+                let live = match inst {
+                    Inst::Match => true,
+                    Inst::Char(_) | Inst::Class(_) => next.is_some_and(|c| {
+                        inst.matches(c) && liveness.contains(position + c.len_utf8(), pc + 1)
+                    }),
+                    _ => false,
+                };
+                if live {
+                    stack.push(pc);
+                }
+            }
+            while let Some(pc) = stack.pop() {
+                if liveness.contains(position, pc) {
+                    continue;
+                }
+                liveness.bits[position * words + pc / 64] |= 1 << (pc % 64);
+                for &predecessor in &predecessors[pc] {
+                    let holds = match program.insts[predecessor] {
+                        Inst::StartText => position == 0,
+                        Inst::EndText => position == text.len(),
+                        _ => true,
+                    };
+                    if holds {
+                        stack.push(predecessor);
+                    }
+                }
+            }
+        }
+        liveness
+    }
+
+    fn contains(&self, position: usize, pc: usize) -> bool {
+        self.bits[position * self.words + pc / 64] & (1 << (pc % 64)) != 0
+    }
+}
+
+/// Runs the program on `text` from byte offset `start` with Pike's VM, which follows all the
+/// NFA states at once, at most one thread per state, so it takes O(n m) time. Returns the
+/// capture slots of the leftmost match, preferring the branches and repetitions in the
+/// priority order of the pattern (as a backtracking engine would).
+///
+/// The run goes on until the threads preferred to the match die, possibly far past its end.
+/// Given the `live` states of `text`, the threads that can't match are dropped, so the run
+/// stops right after the end of its match.
+pub(super) fn pike_vm(
+    program: &Program,
+    text: &str,
+    start: usize,
+    live: Option<&Liveness>,
+) -> Option<Slots> {
+    let mut current = Threads::new(program);
+    let mut next = Threads::new(program);
+    let mut stack = Vec::new();
+    let mut matched = None;
+    let mut chars = text[start..].char_indices().map(|(i, c)| (start + i, c));
+    let mut position = start;
+    loop {
+        if matched.is_none() {
+            // Start a new thread at this position, with the lowest priority.
+            let slots = vec![None; program.slots];
+            add_thread(
+                program,
+                &mut current,
+                &mut stack,
+                0,
+                position,
+                text.len(),
+                slots,
+                live,
+            );
+        }
+        // Dropping the dead threads can leave none before a match, which may still start later.
+        if matched.is_some() && current.set.dense.is_empty() {
+            break;
+        }
+        let step = chars.next();
+        let next_position = step.map_or(position, |(i, c)| i + c.len_utf8());
+        for i in 0..current.set.dense.len() {
+            let pc = current.set.dense[i];
+            match &program.insts[pc] {
+                Inst::Match => {
+                    matched = Some(current.slots[pc].clone());
+                    // The remaining threads have a lower priority.
+                    break;
+                }
+                inst => {
+                    if let Some((_, c)) = step {
+                        if inst.matches(c) {
+                            let slots = current.slots[pc].clone();
+                            add_thread(
+                                program,
+                                &mut next,
+                                &mut stack,
+                                pc + 1,
+                                next_position,
+                                text.len(),
+                                slots,
+                                live,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        if step.is_none() {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.set.clear();
+        position = next_position;
+    }
+    matched
+}
+
+/// Adds the thread at `pc` and everything reachable from it by epsilon transitions, in
+/// priority order, skipping the instructions that already have a thread or are not `live`.
+#[allow(clippy::too_many_arguments)]
+fn add_thread(
+    program: &Program,
+    threads: &mut Threads,
+    stack: &mut Vec<(usize, Slots)>,
+    pc: usize,
+    position: usize,
+    text_len: usize,
+    slots: Slots,
+    live: Option<&Liveness>,
+) {
+    stack.push((pc, slots));
+    while let Some((pc, mut slots)) = stack.pop() {
+        if live.is_some_and(|live| !live.contains(position, pc)) || !threads.set.insert(pc) {
+            continue;
+        }
+        match program.insts[pc] {
+            Inst::Jmp(target) => stack.push((target, slots)),
+            Inst::Split(first, second) => {
+                stack.push((second, slots.clone()));
+                stack.push((first, slots));
+            }
+            Inst::Save(slot) => {
+                slots[slot] = Some(position);
+                stack.push((pc + 1, slots));
+            }
+            Inst::StartText => {
+                if position == 0 {
+                    stack.push((pc + 1, slots));
+                }
+            }
+            Inst::EndText => {
+                if position == text_len {
+                    stack.push((pc + 1, slots));
+                }
+            }
+            Inst::Char(_) | Inst::Class(_) | Inst::Match => threads.slots[pc] = slots,
+        }
+    }
+}
+
+/// The consuming and matching states reachable from `pcs` by epsilon transitions, sorted.
+/// Assertions are followed if they hold: the start of the text if `at_start`, and the end of
+/// the text if `at_end`.
+pub(super) fn closure(
+    program: &Program,
+    pcs: &[usize],
+    at_start: bool,
+    at_end: bool,
+) -> Vec<usize> {
+    let mut seen = SparseSet::new(program.insts.len());
+    let mut stack: Vec<usize> = pcs.iter().rev().copied().collect();
+    let mut states = Vec::new();
+    while let Some(pc) = stack.pop() {
+        if !seen.insert(pc) {
+            continue;
+        }
+        match program.insts[pc] {
+            Inst::Jmp(target) => stack.push(target),
+            Inst::Split(first, second) => {
+                stack.push(second);
+                stack.push(first);
+            }
+            Inst::Save(_) => stack.push(pc + 1),
+            Inst::StartText => {
+                if at_start {
+                    stack.push(pc + 1);
+                }
+            }
+            Inst::EndText => {
+                if at_end {
+                    stack.push(pc + 1);
+                } else {
+                    // Kept so that reaching the end later can still follow it.
+                    states.push(pc);
+                }
+            }
+            Inst::Char(_) | Inst::Class(_) | Inst::Match => states.push(pc),
+        }
+    }
+    states.sort_unstable();
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ast::parse;
+    use super::*;
+
+    #[test]
+    fn thompson_construction() {
+        let (ast, groups) = parse("a|b*").unwrap();
+        let program = compile(&ast, groups);
+        assert_eq!(
+            program.insts,
+            vec![
+                Inst::Save(0),
+                Inst::Split(2, 4),
+                Inst::Char('a'),
+                Inst::Jmp(7),
+                Inst::Split(5, 7),
+                Inst::Char('b'),
+                Inst::Jmp(4),
+                Inst::Save(1),
+                Inst::Match,
+            ]
+        );
+        assert_eq!(closure(&program, &[0], true, false), vec![2, 5, 8]);
+    }
+
+    #[test]
+    fn dropping_dead_threads_keeps_the_matches() {
+        for (pattern, text) in [
+            ("\\w+x|\\w", "aaaxaa"),
+            ("(a|ab)(c|bcd)(d*)", "xabcdabcd"),
+            ("^a|b$|c", "abcab"),
+            ("(a*)*b", "aacaab"),
+            ("x*", "axxb"),
+            ("é+|ü", "aééüb"),
+        ] {
+            let (ast, groups) = parse(pattern).unwrap();
+            let program = compile(&ast, groups);
+            let live = Liveness::new(&program, text);
+            for (start, _) in text.char_indices() {
+                assert_eq!(
+                    pike_vm(&program, text, start, Some(&live)),
+                    pike_vm(&program, text, start, None),
+                    "{pattern} on {text:?} from {start}"
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,10 @@
+use unicode_segmentation::UnicodeSegmentation;
+
 /// Reverses the given string.
 ///
+/// The string is reversed by extended grapheme clusters, so combining accents stay on their
+/// letter and multi-codepoint emoji (flags, skin tones, ZWJ sequences) are kept whole.
+///
 /// # Arguments
 ///
 /// * `text` - A string slice that holds the string to be reversed.
@@ -8,7 +13,7 @@
 ///
 /// * A new `String` that is the reverse of the input string.
 pub fn reverse(text: &str) -> String {
-    text.chars().rev().collect()
+    text.graphemes(true).rev().collect()
 }
 
 #[cfg(test)]
@@ -36,5 +41,9 @@ mod tests {
         test_leading_trailing_spaces: ("  hello  ", "  olleh  "),
         test_unicode_characters: ("你好", "好你"),
         test_mixed_content: ("a1b2c3!", "!3c2b1a"),
+        test_combining_accents: ("noe\u{308}l", "le\u{308}on"),
+        test_flag_emoji: ("🇰🇷🇯🇵", "🇯🇵🇰🇷"),
+        test_zwj_sequence: ("a👩‍👩‍👧b", "b👩‍👩‍👧a"),
+        test_crlf: ("a\r\nb", "b\r\na"),
     }
 }
@@ -2,6 +2,8 @@
 // It is a data structure used in, among others, full-text indices, data-compression algorithms,
 // and the field of bibliometrics. Source: https://en.wikipedia.org/wiki/Suffix_array
 
+use super::generate_suffix_array_sa_is;
+use crate::data_structures::RangeMinimumQuery;
 use std::cmp::Ordering;
 
 #[derive(Clone)]
@@ -83,6 +85,154 @@ pub fn generate_suffix_array(txt: &str) -> Vec<usize> {
     suffix_arr
 }
 
+/// Computes the LCP array of `text` with Kasai's algorithm in O(n): `lcp[i]` is the length of
+/// the longest common prefix of the suffixes at ranks `i - 1` and `i`, and `lcp[0]` is 0.
+pub fn lcp_array(text: &[u8], suffix_array: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let mut rank = vec![0; n];
+    for (r, &i) in suffix_array.iter().enumerate() {
+        rank[i] = r;
+    }
+    let mut lcp = vec![0; n];
+    // The common prefix with the previous suffix shrinks by at most one from a suffix to the next.
+    let mut h: usize = 0;
+    for i in 0..n {
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let j = suffix_array[rank[i] - 1];
+        while i + h < n && j + h < n && text[i + h] == text[j + h] {
+            h += 1;
+        }
+        lcp[rank[i]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+/// A suffix array of a byte string together with its LCP array, built in linear time with SA-IS
+/// and Kasai's algorithm.
+pub struct SuffixArray {
+    text: Vec<u8>,
+    suffixes: Vec<usize>,
+    lcp: Vec<usize>,
+}
+
+impl SuffixArray {
+    pub fn new(text: &[u8]) -> Self {
+        let suffixes = generate_suffix_array_sa_is(text);
+        let lcp = lcp_array(text, &suffixes);
+        SuffixArray {
+            text: text.to_vec(),
+            suffixes,
+            lcp,
+        }
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// The starting positions of the suffixes, in increasing order of the suffixes.
+    pub fn suffixes(&self) -> &[usize] {
+        &self.suffixes
+    }
+
+    /// The LCP array, see [`lcp_array`].
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    /// The starting positions of all the occurrences of `pattern`, in increasing order. They are
+    /// found by binary search in O(m log n) time.
+    pub fn find_all(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions = self.suffixes[self.matching_ranks(pattern)].to_vec();
+        positions.sort_unstable();
+        positions
+    }
+
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.matching_ranks(pattern).len()
+    }
+
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        !self.matching_ranks(pattern).is_empty()
+    }
+
+    /// The ranks of the suffixes starting with `pattern`, which are contiguous.
+    fn matching_ranks(&self, pattern: &[u8]) -> std::ops::Range<usize> {
+        let text = &self.text;
+        let start = self.suffixes.partition_point(|&i| &text[i..] < pattern);
+        let end = self.suffixes.partition_point(|&i| {
+            let suffix = &text[i..];
+            &suffix[..suffix.len().min(pattern.len())] <= pattern
+        });
+        start..end
+    }
+
+    /// The number of distinct non-empty substrings: every suffix contributes its prefixes that
+    /// are not shared with the previous suffix.
+    pub fn distinct_substrings(&self) -> usize {
+        let n = self.text.len();
+        n * (n + 1) / 2 - self.lcp.iter().sum::<usize>()
+    }
+
+    /// The longest substring occurring at least twice (possibly overlapping). Among several, the
+    /// smallest one is returned; the result is empty if no symbol repeats.
+    pub fn longest_repeated_substring(&self) -> &[u8] {
+        let best = (0..self.lcp.len()).max_by_key(|&r| (self.lcp[r], std::cmp::Reverse(r)));
+        best.map_or(&[], |r| {
+            let start = self.suffixes[r];
+            &self.text[start..start + self.lcp[r]]
+        })
+    }
+}
+
+/// Answers the length of the longest common prefix of any two suffixes in O(1), as the minimum
+/// of the LCP array between their ranks.
+pub struct LcpRmq {
+    rank: Vec<usize>,
+    rmq: Option<RangeMinimumQuery<usize>>,
+}
+
+impl LcpRmq {
+    pub fn new(suffix_array: &SuffixArray) -> Self {
+        let mut rank = vec![0; suffix_array.suffixes.len()];
+        for (r, &i) in suffix_array.suffixes.iter().enumerate() {
+            rank[i] = r;
+        }
+        let rmq = (!suffix_array.lcp.is_empty()).then(|| RangeMinimumQuery::new(&suffix_array.lcp));
+        LcpRmq { rank, rmq }
+    }
+
+    /// The length of the longest common prefix of the suffixes starting at `i` and `j`. A
+    /// position equal to the length of the text denotes the empty suffix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is larger than the length of the text.
+    pub fn lcp(&self, i: usize, j: usize) -> usize {
+        let n = self.rank.len();
+        assert!(i <= n && j <= n, "suffix position out of bounds");
+        if i == n || j == n {
+            return 0;
+        }
+        if i == j {
+            return n - i;
+        }
+        let (low, high) = if self.rank[i] < self.rank[j] {
+            (self.rank[i], self.rank[j])
+        } else {
+            (self.rank[j], self.rank[i])
+        };
+        self.rmq
+            .as_ref()
+            .and_then(|rmq| rmq.get_range_min(low + 1, high + 1).ok())
+            .unwrap_or(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,4 +242,61 @@ mod tests {
         let a = generate_suffix_array("banana");
         assert_eq!(a, vec![5, 3, 1, 0, 4, 2]);
     }
+
+    #[test]
+    fn test_lcp_array() {
+        let suffixes = generate_suffix_array_sa_is(b"banana");
+        assert_eq!(lcp_array(b"banana", &suffixes), vec![0, 1, 3, 0, 0, 2]);
+        assert_eq!(lcp_array(b"", &[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_suffix_array_queries() {
+        let sa = SuffixArray::new(b"mississippi");
+        assert_eq!(sa.suffixes(), &[10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+        assert_eq!(sa.find_all(b"ssi"), vec![2, 5]);
+        assert_eq!(sa.find_all(b"i"), vec![1, 4, 7, 10]);
+        assert_eq!(sa.find_all(b"issippix"), Vec::<usize>::new());
+        assert_eq!(sa.count(b"p"), 2);
+        assert!(sa.contains(b"sip"));
+        assert!(!sa.contains(b"spi"));
+        assert_eq!(sa.count(b""), 11);
+        assert_eq!(sa.longest_repeated_substring(), b"issi");
+
+        let sa = SuffixArray::new(b"abab");
+        // a, b, ab, ba, aba, bab, abab
+        assert_eq!(sa.distinct_substrings(), 7);
+        assert_eq!(SuffixArray::new(b"").distinct_substrings(), 0);
+        assert_eq!(SuffixArray::new(b"abc").longest_repeated_substring(), b"");
+    }
+
+    #[test]
+    fn test_distinct_substrings_matches_naive() {
+        let text = b"abracadabra_abracadabra";
+        let mut naive = std::collections::HashSet::new();
+        for i in 0..text.len() {
+            for j in i + 1..=text.len() {
+                naive.insert(&text[i..j]);
+            }
+        }
+        assert_eq!(SuffixArray::new(text).distinct_substrings(), naive.len());
+    }
+
+    #[test]
+    fn test_lcp_rmq() {
+        let text = b"abracadabra";
+        let sa = SuffixArray::new(text);
+        let rmq = LcpRmq::new(&sa);
+        for i in 0..=text.len() {
+            for j in 0..=text.len() {
+                let naive = text[i..]
+                    .iter()
+                    .zip(&text[j..])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                assert_eq!(rmq.lcp(i, j), naive);
+            }
+        }
+        assert_eq!(LcpRmq::new(&SuffixArray::new(b"")).lcp(0, 0), 0);
+    }
 }
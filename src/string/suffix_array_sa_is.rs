@@ -0,0 +1,169 @@
+// SA-IS builds the suffix array of a string in linear time by induced sorting.
+//
+// Every suffix is classified as S-type (smaller than the next suffix) or L-type (larger), and
+// the leftmost S-type suffixes of each run (LMS suffixes) are sorted first: once their order is
+// known, a left-to-right scan places all the L-type suffixes in their buckets and a right-to-left
+// scan places all the S-type ones. The LMS suffixes themselves are sorted by naming the LMS
+// substrings and, if some names repeat, sorting the suffixes of the shorter string of names
+// recursively. The reduced string is at most half as long, so the whole algorithm is O(n).
+// Source: Nong, Zhang and Chan, "Linear Suffix Array Construction by Almost Pure Induced-Sorting"
+
+const EMPTY: usize = usize::MAX;
+
+pub fn generate_suffix_array_sa_is(text: &[u8]) -> Vec<usize> {
+    // Shift the bytes by one to make room for a sentinel smaller than all of them.
+    let mut s: Vec<usize> = text.iter().map(|&b| usize::from(b) + 1).collect();
+    s.push(0);
+    let mut suffix_array = sa_is(&s, 257);
+    // The sentinel suffix always comes first.
+    suffix_array.remove(0);
+    suffix_array
+}
+
+/// Sorts the suffixes of `s`, whose symbols are below `alphabet` and whose last symbol is a
+/// unique 0.
+fn sa_is(s: &[usize], alphabet: usize) -> Vec<usize> {
+    let n = s.len();
+    if n == 1 {
+        return vec![0];
+    }
+    let mut is_s = vec![false; n];
+    is_s[n - 1] = true;
+    for i in (0..n - 1).rev() {
+        is_s[i] = s[i] < s[i + 1] || (s[i] == s[i + 1] && is_s[i + 1]);
+    }
+    let is_lms = |i: usize| i > 0 && is_s[i] && !is_s[i - 1];
+
+    let mut bucket_starts = vec![0; alphabet];
+    for &c in s {
+        bucket_starts[c] += 1;
+    }
+    let mut sum = 0;
+    for start in bucket_starts.iter_mut() {
+        let count = *start;
+        *start = sum;
+        sum += count;
+    }
+    let bucket_ends: Vec<usize> = (0..alphabet)
+        .map(|c| bucket_starts.get(c + 1).copied().unwrap_or(n))
+        .collect();
+
+    // Places the LMS suffixes in the order given, then induces the order of all the others.
+    let induce = |lms_order: &[usize]| {
+        let mut sa = vec![EMPTY; n];
+        let mut ends = bucket_ends.clone();
+        for &i in lms_order.iter().rev() {
+            ends[s[i]] -= 1;
+            sa[ends[s[i]]] = i;
+        }
+        let mut starts = bucket_starts.clone();
+        for j in 0..n {
+            let i = sa[j];
+            if i != EMPTY && i > 0 && !is_s[i - 1] {
+                sa[starts[s[i - 1]]] = i - 1;
+                starts[s[i - 1]] += 1;
+            }
+        }
+        let mut ends = bucket_ends.clone();
+        for j in (0..n).rev() {
+            let i = sa[j];
+            if i != EMPTY && i > 0 && is_s[i - 1] {
+                ends[s[i - 1]] -= 1;
+                sa[ends[s[i - 1]]] = i - 1;
+            }
+        }
+        sa
+    };
+
+    let lms: Vec<usize> = (1..n).filter(|&i| is_lms(i)).collect();
+    let sa = induce(&lms);
+
+    // Name the LMS substrings (from one LMS position to the next) in sorted order, giving equal
+    // substrings equal names.
+    let lms_equal = |a: usize, b: usize| {
+        if a == n - 1 || b == n - 1 {
+            return a == b;
+        }
+        let mut j = 0;
+        loop {
+            if s[a + j] != s[b + j] || is_s[a + j] != is_s[b + j] {
+                return false;
+            }
+            if j > 0 && (is_lms(a + j) || is_lms(b + j)) {
+                return is_lms(a + j) && is_lms(b + j);
+            }
+            j += 1;
+        }
+    };
+    let mut names = vec![EMPTY; n];
+    let mut name = 0;
+    let mut previous: Option<usize> = None;
+    for &i in sa.iter().filter(|&&i| is_lms(i)) {
+        if previous.is_some_and(|p| !lms_equal(p, i)) {
+            name += 1;
+        }
+        names[i] = name;
+        previous = Some(i);
+    }
+    let reduced: Vec<usize> = lms.iter().map(|&i| names[i]).collect();
+
+    let reduced_order = if name + 1 == reduced.len() {
+        // All the names are distinct, so they already give the order.
+        let mut order = vec![0; reduced.len()];
+        for (i, &c) in reduced.iter().enumerate() {
+            order[c] = i;
+        }
+        order
+    } else {
+        sa_is(&reduced, name + 1)
+    };
+    let sorted_lms: Vec<usize> = reduced_order.into_iter().map(|i| lms[i]).collect();
+    induce(&sorted_lms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_suffix_array(text: &[u8]) -> Vec<usize> {
+        let mut suffixes: Vec<usize> = (0..text.len()).collect();
+        suffixes.sort_by_key(|&i| &text[i..]);
+        suffixes
+    }
+
+    #[test]
+    fn test_sa_is() {
+        assert_eq!(
+            generate_suffix_array_sa_is(b"banana"),
+            vec![5, 3, 1, 0, 4, 2]
+        );
+        assert_eq!(generate_suffix_array_sa_is(b""), Vec::<usize>::new());
+        assert_eq!(generate_suffix_array_sa_is(b"a"), vec![0]);
+        assert_eq!(generate_suffix_array_sa_is(b"aaaa"), vec![3, 2, 1, 0]);
+        assert_eq!(
+            generate_suffix_array_sa_is(b"mmiissiissiippii"),
+            naive_suffix_array(b"mmiissiissiippii")
+        );
+    }
+
+    #[test]
+    fn test_sa_is_matches_naive() {
+        let mut state = 42_u64;
+        for length in [2, 3, 10, 100, 1000, 5000] {
+            for alphabet in [2, 4, 26, 256] {
+                let text: Vec<u8> = (0..length)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        ((state >> 33) % alphabet) as u8
+                    })
+                    .collect();
+                assert_eq!(
+                    generate_suffix_array_sa_is(&text),
+                    naive_suffix_array(&text)
+                );
+            }
+        }
+    }
+}
@@ -2,93 +2,362 @@
 // is a compressed trie containing all the suffixes of the given text as their keys and positions
 // in the text as their values. Suffix trees allow particularly fast implementations of many
 // important string operations. Source: https://en.wikipedia.org/wiki/Suffix_tree
+//
+// The tree is built with Ukkonen's algorithm, which adds the symbols of the text one at a time
+// and keeps an "active point" (the longest suffix that is still implicit in the tree) together
+// with suffix links, so that the whole construction takes O(n log σ) time, where σ is the size of
+// the alphabet (children are kept in ordered maps). Edges store ranges of the text instead of
+// copies of it, so the tree takes O(n) space.
+// Source: https://en.wikipedia.org/wiki/Ukkonen%27s_algorithm
+//
+// A generalized suffix tree indexes several texts at once by building the tree of their
+// concatenation, where each text is followed by its own unique terminator.
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Node {
-    pub sub: String,    // substring of input string
-    pub ch: Vec<usize>, // vector of child nodes
+use std::collections::BTreeMap;
+
+/// A symbol of the indexed sequence: either a symbol of a text, or the terminator of text `i`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Symbol<T> {
+    Char(T),
+    End(usize),
+}
+
+struct Node<T> {
+    /// The edge from the parent is labeled by `symbols[start..end]`.
+    start: usize,
+    /// `None` for leaves, whose edge extends to the end of the sequence.
+    end: Option<usize>,
+    suffix_link: usize,
+    children: BTreeMap<Symbol<T>, usize>,
+}
+
+const ROOT: usize = 0;
+
+/// The suffix tree of a sequence of symbols whose last symbol is unique.
+struct Tree<T> {
+    symbols: Vec<Symbol<T>>,
+    nodes: Vec<Node<T>>,
 }
 
-impl Node {
-    fn new(sub: String, children: Vec<usize>) -> Self {
-        Node {
-            sub,
-            ch: children.to_vec(),
+impl<T: Ord + Clone> Tree<T> {
+    fn new(symbols: Vec<Symbol<T>>) -> Self {
+        let mut tree = Tree {
+            symbols,
+            nodes: Vec::new(),
+        };
+        tree.add_node(0, Some(0));
+        tree.build();
+        tree
+    }
+
+    fn add_node(&mut self, start: usize, end: Option<usize>) -> usize {
+        self.nodes.push(Node {
+            start,
+            end,
+            suffix_link: ROOT,
+            children: BTreeMap::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    fn edge_end(&self, node: usize) -> usize {
+        self.nodes[node].end.unwrap_or(self.symbols.len())
+    }
+
+    fn edge_length(&self, node: usize, position: usize) -> usize {
+        self.nodes[node].end.unwrap_or(position + 1) - self.nodes[node].start
+    }
+
+    fn build(&mut self) {
+        let mut active_node = ROOT;
+        let mut active_edge = 0;
+        let mut active_length = 0;
+        // The number of suffixes that still have to be inserted explicitly.
+        let mut remainder = 0;
+        for position in 0..self.symbols.len() {
+            remainder += 1;
+            // The last internal node created in this phase, waiting for its suffix link.
+            let mut needs_link: Option<usize> = None;
+            while remainder > 0 {
+                if active_length == 0 {
+                    active_edge = position;
+                }
+                let first = self.symbols[active_edge].clone();
+                match self.nodes[active_node].children.get(&first).copied() {
+                    None => {
+                        let leaf = self.add_node(position, None);
+                        self.nodes[active_node].children.insert(first, leaf);
+                        if let Some(node) = needs_link.replace(active_node) {
+                            self.nodes[node].suffix_link = active_node;
+                        }
+                    }
+                    Some(next) => {
+                        let length = self.edge_length(next, position);
+                        if active_length >= length {
+                            // Walk down: the active point lies below `next`.
+                            active_edge += length;
+                            active_length -= length;
+                            active_node = next;
+                            continue;
+                        }
+                        let start = self.nodes[next].start;
+                        if self.symbols[start + active_length] == self.symbols[position] {
+                            // The suffix is already in the tree: it stays implicit until the
+                            // next phase.
+                            active_length += 1;
+                            if let Some(node) = needs_link.replace(active_node) {
+                                self.nodes[node].suffix_link = active_node;
+                            }
+                            break;
+                        }
+                        let split = self.add_node(start, Some(start + active_length));
+                        self.nodes[active_node].children.insert(first, split);
+                        let leaf = self.add_node(position, None);
+                        let branch = self.symbols[position].clone();
+                        self.nodes[split].children.insert(branch, leaf);
+                        self.nodes[next].start += active_length;
+                        let rest = self.symbols[start + active_length].clone();
+                        self.nodes[split].children.insert(rest, next);
+                        if let Some(node) = needs_link.replace(split) {
+                            self.nodes[node].suffix_link = split;
+                        }
+                    }
+                }
+                remainder -= 1;
+                if active_node == ROOT && active_length > 0 {
+                    active_length -= 1;
+                    active_edge = position + 1 - remainder;
+                } else {
+                    active_node = self.nodes[active_node].suffix_link;
+                }
+            }
+        }
+    }
+
+    /// Follows `pattern` from the root, returning the highest node whose path starts with it
+    /// and the length of that path, or `None` if `pattern` does not occur.
+    fn locate(&self, pattern: &[T]) -> Option<(usize, usize)> {
+        let mut node = ROOT;
+        let mut depth = 0;
+        while depth < pattern.len() {
+            let first = Symbol::Char(pattern[depth].clone());
+            let child = *self.nodes[node].children.get(&first)?;
+            let start = self.nodes[child].start;
+            let end = self.edge_end(child);
+            let length = (end - start).min(pattern.len() - depth);
+            let matches = self.symbols[start..start + length]
+                .iter()
+                .zip(&pattern[depth..])
+                .all(|(symbol, c)| *symbol == Symbol::Char(c.clone()));
+            if !matches {
+                return None;
+            }
+            depth += end - start;
+            node = child;
+        }
+        Some((node, depth))
+    }
+
+    /// The starting positions (in the sequence) of the suffixes below `node`, whose path has
+    /// `depth` symbols.
+    fn suffixes_below(&self, node: usize, depth: usize) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![(node, depth)];
+        while let Some((node, depth)) = stack.pop() {
+            if self.nodes[node].end.is_none() {
+                // Leaves extend to the end of the sequence.
+                found.push(self.symbols.len() - depth);
+                continue;
+            }
+            for &child in self.nodes[node].children.values() {
+                let length = self.edge_end(child) - self.nodes[child].start;
+                stack.push((child, depth + length));
+            }
         }
+        found.sort_unstable();
+        found
     }
-    pub fn empty() -> Self {
-        Node {
-            sub: "".to_string(),
-            ch: vec![],
+
+    /// Visits the internal nodes (other than the root) with the number of symbols on their path.
+    fn internal_nodes(&self) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let mut stack = vec![(ROOT, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            for &child in self.nodes[node].children.values() {
+                if let Some(end) = self.nodes[child].end {
+                    let child_depth = depth + end - self.nodes[child].start;
+                    found.push((child, child_depth));
+                    stack.push((child, child_depth));
+                }
+            }
         }
+        found
+    }
+
+    /// The symbols on the path from the root to the internal node `node`, which has `depth`.
+    fn path_label(&self, node: usize, depth: usize) -> Vec<T> {
+        let end = self.edge_end(node);
+        self.symbols[end - depth..end]
+            .iter()
+            .filter_map(|symbol| match symbol {
+                Symbol::Char(c) => Some(c.clone()),
+                Symbol::End(_) => None,
+            })
+            .collect()
     }
 }
 
-pub struct SuffixTree {
-    pub nodes: Vec<Node>,
+/// A suffix tree of a single text, answering substring queries in time proportional to the
+/// length of the pattern (plus the number of occurrences reported).
+pub struct SuffixTree<T = u8> {
+    tree: Tree<T>,
 }
 
-impl SuffixTree {
-    pub fn new(s: &str) -> Self {
-        let mut suf_tree = SuffixTree {
-            nodes: vec![Node::empty()],
-        };
-        for i in 0..s.len() {
-            let (_, substr) = s.split_at(i);
-            suf_tree.add_suffix(substr);
+impl<T: Ord + Clone> SuffixTree<T> {
+    pub fn new(text: &[T]) -> Self {
+        let symbols = text
+            .iter()
+            .cloned()
+            .map(Symbol::Char)
+            .chain(std::iter::once(Symbol::End(0)))
+            .collect();
+        SuffixTree {
+            tree: Tree::new(symbols),
         }
-        suf_tree
-    }
-    fn add_suffix(&mut self, suf: &str) {
-        let mut n = 0;
-        let mut i = 0;
-        while i < suf.len() {
-            let b = suf.chars().nth(i);
-            let mut x2 = 0;
-            let mut n2: usize;
-            loop {
-                let children = &self.nodes[n].ch;
-                if children.len() == x2 {
-                    n2 = self.nodes.len();
-                    self.nodes.push(Node::new(
-                        {
-                            let (_, sub) = suf.split_at(i);
-                            sub.to_string()
-                        },
-                        vec![],
-                    ));
-                    self.nodes[n].ch.push(n2);
-                    return;
-                }
-                n2 = children[x2];
-                if self.nodes[n2].sub.chars().next() == b {
-                    break;
-                }
-                x2 += 1;
+    }
+
+    /// The length of the indexed text.
+    pub fn len(&self) -> usize {
+        self.tree.symbols.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        self.tree.locate(pattern).is_some()
+    }
+
+    /// The starting positions of all the occurrences of `pattern` in the text, in increasing
+    /// order. The empty pattern occurs at every position, including the end of the text.
+    pub fn find_all(&self, pattern: &[T]) -> Vec<usize> {
+        self.tree
+            .locate(pattern)
+            .map_or_else(Vec::new, |(node, depth)| {
+                self.tree.suffixes_below(node, depth)
+            })
+    }
+
+    /// The longest substring occurring at least twice (possibly overlapping), or an empty
+    /// vector if there is none. The first such substring in symbol order is returned.
+    pub fn longest_repeated_substring(&self) -> Vec<T> {
+        self.tree
+            .internal_nodes()
+            .into_iter()
+            .min_by_key(|&(node, depth)| (std::cmp::Reverse(depth), node))
+            .map_or_else(Vec::new, |(node, depth)| self.tree.path_label(node, depth))
+    }
+}
+
+/// A suffix tree indexing several texts at once.
+pub struct GeneralizedSuffixTree<T = u8> {
+    tree: Tree<T>,
+    /// The position of each text in the indexed sequence.
+    offsets: Vec<usize>,
+}
+
+impl<T: Ord + Clone> GeneralizedSuffixTree<T> {
+    pub fn new(texts: &[&[T]]) -> Self {
+        let mut symbols = Vec::with_capacity(texts.iter().map(|text| text.len() + 1).sum());
+        let mut offsets = Vec::with_capacity(texts.len());
+        for (i, text) in texts.iter().enumerate() {
+            offsets.push(symbols.len());
+            symbols.extend(text.iter().cloned().map(Symbol::Char));
+            symbols.push(Symbol::End(i));
+        }
+        GeneralizedSuffixTree {
+            tree: Tree::new(symbols),
+            offsets,
+        }
+    }
+
+    /// The number of indexed texts.
+    pub fn texts(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        self.tree.locate(pattern).is_some()
+    }
+
+    /// The occurrences of `pattern` as `(text index, starting position in that text)` pairs,
+    /// in increasing order.
+    pub fn find_all(&self, pattern: &[T]) -> Vec<(usize, usize)> {
+        if self.offsets.is_empty() {
+            return Vec::new();
+        }
+        self.tree
+            .locate(pattern)
+            .map_or_else(Vec::new, |(node, depth)| {
+                self.tree
+                    .suffixes_below(node, depth)
+                    .into_iter()
+                    .map(|position| self.text_position(position))
+                    .collect()
+            })
+    }
+
+    /// The longest substring common to all the texts, or an empty vector if there is none.
+    pub fn longest_common_substring(&self) -> Vec<T> {
+        let texts = self.offsets.len();
+        if texts == 0 {
+            return Vec::new();
+        }
+        // For every node, the set of texts having a suffix below it, as a bitset.
+        let words = texts.div_ceil(64);
+        let mut sets = vec![vec![0_u64; words]; self.tree.nodes.len()];
+        let mut order = Vec::with_capacity(self.tree.nodes.len());
+        let mut stack = vec![(ROOT, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            order.push((node, depth));
+            for &child in self.tree.nodes[node].children.values() {
+                let length = self.tree.edge_end(child) - self.tree.nodes[child].start;
+                stack.push((child, depth + length));
             }
-            let sub2 = self.nodes[n2].sub.clone();
-            let mut j = 0;
-            while j < sub2.len() {
-                if suf.chars().nth(i + j) != sub2.chars().nth(j) {
-                    let n3 = n2;
-                    n2 = self.nodes.len();
-                    self.nodes.push(Node::new(
-                        {
-                            let (sub, _) = sub2.split_at(j);
-                            sub.to_string()
-                        },
-                        vec![n3],
-                    ));
-                    let (_, temp_sub) = sub2.split_at(j);
-                    self.nodes[n3].sub = temp_sub.to_string();
-                    self.nodes[n].ch[x2] = n2;
-                    break;
+        }
+        let mut best: Option<(usize, usize)> = None;
+        // Children come after their parent in `order`, so a reverse scan sees them first.
+        for &(node, depth) in order.iter().rev() {
+            let current = &self.tree.nodes[node];
+            if current.end.is_none() {
+                let (text, _) = self.text_position(self.tree.symbols.len() - depth);
+                sets[node][text / 64] |= 1 << (text % 64);
+                continue;
+            }
+            let mut set = vec![0_u64; words];
+            for &child in current.children.values() {
+                for (word, child_word) in set.iter_mut().zip(&sets[child]) {
+                    *word |= child_word;
                 }
-                j += 1;
             }
-            i += j;
-            n = n2;
+            let complete = set
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>()
+                == texts;
+            if complete && node != ROOT && best.is_none_or(|(_, best_depth)| depth > best_depth) {
+                best = Some((node, depth));
+            }
+            sets[node] = set;
         }
+        best.map_or_else(Vec::new, |(node, depth)| self.tree.path_label(node, depth))
+    }
+
+    /// Maps a position of the indexed sequence to a text and a position in that text.
+    fn text_position(&self, position: usize) -> (usize, usize) {
+        let text = self.offsets.partition_point(|&offset| offset <= position) - 1;
+        (text, position - self.offsets[text])
     }
 }
 
@@ -96,57 +365,109 @@ impl SuffixTree {
 mod tests {
     use super::*;
 
+    fn naive_find_all(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+        (0..=text.len().saturating_sub(pattern.len()))
+            .filter(|&i| text[i..].starts_with(pattern))
+            .collect()
+    }
+
     #[test]
     fn test_suffix_tree() {
-        let suf_tree = SuffixTree::new("banana$");
+        let tree = SuffixTree::new(b"banana");
+        assert_eq!(tree.len(), 6);
+        // The root, the internal nodes of "a", "ana", "na" and one leaf per suffix of "banana$".
+        assert_eq!(tree.tree.nodes.len(), 1 + 3 + 7);
+        assert!(tree.contains(b"nan"));
+        assert!(tree.contains(b"banana"));
+        assert!(!tree.contains(b"bananas"));
+        assert!(!tree.contains(b"nb"));
+        assert_eq!(tree.find_all(b"ana"), vec![1, 3]);
+        assert_eq!(tree.find_all(b"a"), vec![1, 3, 5]);
+        assert_eq!(tree.find_all(b"x"), Vec::<usize>::new());
+        assert_eq!(tree.find_all(b""), (0..=6).collect::<Vec<_>>());
+        assert_eq!(tree.longest_repeated_substring(), b"ana");
+    }
+
+    #[test]
+    fn test_empty_and_unique() {
+        let tree = SuffixTree::<u8>::new(b"");
+        assert!(tree.is_empty());
+        assert!(tree.contains(b""));
+        assert!(!tree.contains(b"a"));
+        assert_eq!(tree.longest_repeated_substring(), Vec::<u8>::new());
         assert_eq!(
-            suf_tree.nodes,
-            vec![
-                Node {
-                    sub: "".to_string(),
-                    ch: vec![1, 8, 6, 10]
-                },
-                Node {
-                    sub: "banana$".to_string(),
-                    ch: vec![]
-                },
-                Node {
-                    sub: "na$".to_string(),
-                    ch: vec![]
-                },
-                Node {
-                    sub: "na$".to_string(),
-                    ch: vec![]
-                },
-                Node {
-                    sub: "na".to_string(),
-                    ch: vec![2, 5]
-                },
-                Node {
-                    sub: "$".to_string(),
-                    ch: vec![]
-                },
-                Node {
-                    sub: "na".to_string(),
-                    ch: vec![3, 7]
-                },
-                Node {
-                    sub: "$".to_string(),
-                    ch: vec![]
-                },
-                Node {
-                    sub: "a".to_string(),
-                    ch: vec![4, 9]
-                },
-                Node {
-                    sub: "$".to_string(),
-                    ch: vec![]
-                },
-                Node {
-                    sub: "$".to_string(),
-                    ch: vec![]
-                }
-            ]
+            SuffixTree::new(b"abcd").longest_repeated_substring(),
+            Vec::<u8>::new()
         );
+        assert_eq!(
+            SuffixTree::new(b"aaaa").longest_repeated_substring(),
+            b"aaa"
+        );
+    }
+
+    #[test]
+    fn test_generic_symbols() {
+        let words = ["to", "be", "or", "not", "to", "be"];
+        let tree = SuffixTree::new(&words);
+        assert_eq!(tree.find_all(&["to", "be"]), vec![0, 4]);
+        assert_eq!(tree.longest_repeated_substring(), vec!["to", "be"]);
+    }
+
+    #[test]
+    fn test_matches_naive_search() {
+        let mut state = 40_u64;
+        let text: Vec<u8> = (0..3000)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                b"ACGT"[(state >> 62) as usize]
+            })
+            .collect();
+        let tree = SuffixTree::new(&text);
+        // Every suffix ends in its own leaf, and every internal node has at least two children.
+        let leaves = tree.tree.nodes.iter().filter(|n| n.end.is_none()).count();
+        assert_eq!(leaves, text.len() + 1);
+        assert!(tree.tree.nodes.len() <= 2 * leaves);
+        for start in (0..2990).step_by(37) {
+            for length in [1, 3, 6, 10] {
+                let pattern = &text[start..start + length];
+                assert_eq!(tree.find_all(pattern), naive_find_all(&text, pattern));
+            }
+        }
+        let repeated = tree.longest_repeated_substring();
+        assert!(naive_find_all(&text, &repeated).len() >= 2);
+        let longer_repeats = (0..text.len() - repeated.len())
+            .filter(|&i| naive_find_all(&text, &text[i..=i + repeated.len()]).len() >= 2)
+            .count();
+        assert_eq!(longer_repeats, 0);
+    }
+
+    #[test]
+    fn test_long_input() {
+        let text: Vec<u8> = b"GATTACA".iter().cycle().take(200_000).copied().collect();
+        let tree = SuffixTree::new(&text);
+        assert_eq!(tree.find_all(b"TACAGA"), naive_find_all(&text, b"TACAGA"));
+        assert_eq!(tree.longest_repeated_substring().len(), 200_000 - 7);
+    }
+
+    #[test]
+    fn test_generalized_suffix_tree() {
+        let texts: [&[u8]; 3] = [b"xabxac", b"abcabxabcd", b"babxba"];
+        let tree = GeneralizedSuffixTree::new(&texts);
+        assert_eq!(tree.texts(), 3);
+        assert_eq!(tree.find_all(b"abx"), vec![(0, 1), (1, 3), (2, 1)]);
+        assert_eq!(tree.find_all(b"c"), vec![(0, 5), (1, 2), (1, 8)]);
+        assert!(!tree.contains(b"xabxb"));
+        assert_eq!(tree.longest_common_substring(), b"abx");
+
+        let tree = GeneralizedSuffixTree::new(&[b"GATTACA".as_slice(), b"TAGACCA", b"ATACA"]);
+        // "AC", "CA" and "TA" are all common to the three texts.
+        assert_eq!(tree.longest_common_substring().len(), 2);
+        let tree = GeneralizedSuffixTree::new(&[b"abc".as_slice(), b"def"]);
+        assert_eq!(tree.longest_common_substring(), Vec::<u8>::new());
+        let tree = GeneralizedSuffixTree::<u8>::new(&[]);
+        assert_eq!(tree.longest_common_substring(), Vec::<u8>::new());
+        assert!(tree.find_all(b"a").is_empty());
     }
 }
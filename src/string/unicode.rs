@@ -0,0 +1,133 @@
+//! Unicode conventions shared by the string utilities.
+//!
+//! Positions: the functions searching a `&str` (`knuth_morris_pratt`, `boyer_moore_search`,
+//! `rabin_karp`, `AhoCorasick`, `Regex`, ...) report byte offsets into it. These are always char
+//! boundaries, so they can be used to slice the text; [`char_index`] converts them to char
+//! positions when needed.
+//!
+//! Comparisons: the letter-based checks (`check_anagram`, `is_isogram`, `is_isomorphic`,
+//! `is_palindrome`) have `_with` variants taking [`TextOptions`]. The text is first case folded
+//! and normalized as requested, then compared by extended grapheme clusters (user-perceived
+//! characters such as `"e\u{301}"` or a flag emoji) instead of chars.
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A Unicode normalization form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Keep the text as it is.
+    None,
+    /// Canonical composition: `"e\u{301}"` becomes `"é"`.
+    Nfc,
+    /// Canonical decomposition: `"é"` becomes `"e\u{301}"`.
+    Nfd,
+}
+
+/// How text is prepared before its characters are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextOptions {
+    /// Apply full Unicode case folding, e.g. `"Straße"` and `"STRASSE"` fold to `"strasse"`.
+    pub case_fold: bool,
+    pub normalization: Normalization,
+}
+
+impl TextOptions {
+    /// Compares the text exactly as it is.
+    pub const EXACT: Self = Self {
+        case_fold: false,
+        normalization: Normalization::None,
+    };
+
+    /// Returns the prepared text.
+    pub fn apply(&self, text: &str) -> String {
+        let folded = match (self.case_fold, self.normalization) {
+            (false, _) => text.to_string(),
+            (true, Normalization::None) => caseless::default_case_fold_str(text),
+            // Folding the decomposed text makes canonically equivalent strings fold alike.
+            (true, _) => caseless::default_case_fold_str(&text.nfd().collect::<String>()),
+        };
+        match self.normalization {
+            Normalization::None => folded,
+            Normalization::Nfc => folded.nfc().collect(),
+            Normalization::Nfd => folded.nfd().collect(),
+        }
+    }
+}
+
+impl Default for TextOptions {
+    /// Case-insensitive comparison of canonically equivalent text.
+    fn default() -> Self {
+        Self {
+            case_fold: true,
+            normalization: Normalization::Nfc,
+        }
+    }
+}
+
+/// Splits the text into extended grapheme clusters.
+pub fn graphemes(text: &str) -> Vec<&str> {
+    text.graphemes(true).collect()
+}
+
+/// Whether a grapheme cluster is a letter, possibly with combining marks.
+pub(super) fn is_letter(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphabetic)
+}
+
+/// The char position of a byte offset of `text`.
+///
+/// # Panics
+///
+/// Panics if `byte_offset` is not a char boundary of `text`.
+pub fn char_index(text: &str, byte_offset: usize) -> usize {
+    text[..byte_offset].chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options() {
+        let composed = "Café";
+        let decomposed = "Cafe\u{301}";
+        assert_ne!(composed, decomposed);
+        assert_eq!(TextOptions::EXACT.apply(decomposed), decomposed);
+        let nfc = TextOptions {
+            case_fold: false,
+            normalization: Normalization::Nfc,
+        };
+        assert_eq!(nfc.apply(decomposed), composed);
+        let nfd = TextOptions {
+            normalization: Normalization::Nfd,
+            ..nfc
+        };
+        assert_eq!(nfd.apply(composed), decomposed);
+        let options = TextOptions::default();
+        assert_eq!(options.apply(composed), "café");
+        assert_eq!(options.apply(decomposed), "café");
+        assert_eq!(options.apply("Straße"), options.apply("STRASSE"));
+        assert_eq!(options.apply("ΣΊΣΥΦΟΣ"), options.apply("σίσυφος"));
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        assert_eq!(graphemes("e\u{301}a"), ["e\u{301}", "a"]);
+        assert_eq!(graphemes("🇰🇷!"), ["🇰🇷", "!"]);
+        assert_eq!(graphemes("\r\n"), ["\r\n"]);
+        assert!(graphemes("").is_empty());
+        assert!(is_letter("e\u{301}"));
+        assert!(!is_letter("1"));
+        assert!(!is_letter(""));
+    }
+
+    #[test]
+    fn byte_offsets_to_char_positions() {
+        let text = "aé日b";
+        assert_eq!(char_index(text, 0), 0);
+        assert_eq!(char_index(text, 3), 2);
+        assert_eq!(char_index(text, 6), 3);
+        assert_eq!(char_index(text, text.len()), 4);
+    }
+}